@@ -0,0 +1,230 @@
+use nih_plug::util;
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::Fft;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Bins below this level are drawn at the floor of the display.
+pub const FLOOR_DB: f32 = -96.0;
+/// How far, in dB, the displayed spectrum falls per published frame.
+const FALL_DB: f32 = 1.5;
+/// Loudest partials listed by the tuner.
+pub const MAX_PARTIALS: usize = 8;
+/// Peaks below this level are not listed as partials.
+const PARTIAL_FLOOR_DB: f32 = -80.0;
+/// Shortest and longest time the waveform display spans, in milliseconds.
+pub const MIN_SCOPE_MS: u16 = 50;
+pub const MAX_SCOPE_MS: u16 = 5000;
+/// Columns of the waveform history, each the lowest and highest of its samples.
+pub const SCOPE_POINTS: usize = 1024;
+
+/// Where in the signal chain the analyzer listens.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum AnalyzerTap {
+    /// The padded input, before any processing.
+    #[default]
+    Input,
+    /// The harmonizer's output before it is mixed with the dry signal.
+    Wet,
+    /// What leaves the plugin.
+    Output,
+}
+
+impl AnalyzerTap {
+    pub const ALL: [AnalyzerTap; 3] = [AnalyzerTap::Input, AnalyzerTap::Wet, AnalyzerTap::Output];
+
+    pub fn from_index(index: u8) -> Self {
+        Self::ALL.get(index as usize).copied().unwrap_or_default()
+    }
+
+    pub fn index(self) -> u8 {
+        self as u8
+    }
+
+    #[cfg(feature = "editor")]
+    pub fn label(self) -> &'static str {
+        match self {
+            AnalyzerTap::Input => "Input",
+            AnalyzerTap::Wet => "Wet",
+            AnalyzerTap::Output => "Output",
+        }
+    }
+}
+
+/// A peak in the analyzed spectrum.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Partial {
+    /// Frequency in Hz, interpolated between the bins.
+    pub freq: f32,
+    pub level_db: f32,
+}
+
+/// The latest analyzer frame, read by the editor.
+#[derive(Clone)]
+pub struct AnalyzerData {
+    pub tap: AnalyzerTap,
+    /// Level per bin in dBFS, with a slow fall so short peaks stay visible.
+    pub spectrum_db: Vec<f32>,
+    /// Lowest and highest sample of every column of the waveform history, oldest first.
+    pub scope: Vec<[f32; 2]>,
+    /// Time the waveform history spans, in seconds. At least the requested window, as every
+    /// column covers a whole number of samples.
+    pub scope_span: f32,
+    /// The frame's loudest peaks, loudest first.
+    pub partials: Vec<Partial>,
+    pub sample_rate: f32,
+}
+
+impl AnalyzerData {
+    pub fn new(fft_size: usize) -> Self {
+        Self {
+            tap: AnalyzerTap::default(),
+            spectrum_db: vec![FLOOR_DB; fft_size / 2],
+            scope: vec![[0.0; 2]; SCOPE_POINTS],
+            scope_span: 0.0,
+            // One more than listed, so inserting before truncating never allocates
+            partials: Vec::with_capacity(MAX_PARTIALS + 1),
+            sample_rate: 44100.0,
+        }
+    }
+}
+
+/// Collects the mono sum of the selected tap and publishes its spectrum once per hop.
+pub struct Analyzer {
+    ring: VecDeque<f32>,
+    scratch: Vec<Complex<f32>>,
+    /// Level per bin of the latest frame in dBFS, without the fall.
+    levels_db: Vec<f32>,
+    scope: VecDeque<[f32; 2]>,
+    /// Samples per scope column, and the lowest and highest sample of the column being filled.
+    column_len: usize,
+    column_fill: usize,
+    column: [f32; 2],
+}
+
+impl Analyzer {
+    pub fn new(fft_size: usize) -> Self {
+        Self {
+            ring: VecDeque::from(vec![0.0; fft_size]),
+            scratch: vec![Complex::zero(); fft_size],
+            levels_db: vec![FLOOR_DB; fft_size / 2],
+            scope: VecDeque::from(vec![[0.0; 2]; SCOPE_POINTS]),
+            column_len: 1,
+            column_fill: 0,
+            column: [f32::INFINITY, f32::NEG_INFINITY],
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.ring.iter_mut().for_each(|x| *x = 0.0);
+        self.clear_scope();
+    }
+
+    /// Makes the waveform history span at least `window_ms`, starting it over when that changes
+    /// its resolution.
+    pub fn set_scope_window(&mut self, window_ms: u16, sample_rate: f32) {
+        let window_ms = window_ms.clamp(MIN_SCOPE_MS, MAX_SCOPE_MS);
+        let samples = (window_ms as f32 / 1000.0 * sample_rate) as usize;
+        let column_len = samples.div_ceil(SCOPE_POINTS).max(1);
+        if column_len != self.column_len {
+            self.column_len = column_len;
+            self.clear_scope();
+        }
+    }
+
+    fn clear_scope(&mut self) {
+        self.scope.iter_mut().for_each(|column| *column = [0.0; 2]);
+        self.column_fill = 0;
+        self.column = [f32::INFINITY, f32::NEG_INFINITY];
+    }
+
+    pub fn push(&mut self, samples: &[f32]) {
+        for &sample in samples {
+            self.ring.pop_front();
+            self.ring.push_back(sample);
+
+            self.column = [self.column[0].min(sample), self.column[1].max(sample)];
+            self.column_fill += 1;
+            if self.column_fill == self.column_len {
+                self.scope.pop_front();
+                self.scope.push_back(self.column);
+                self.column_fill = 0;
+                self.column = [f32::INFINITY, f32::NEG_INFINITY];
+            }
+        }
+    }
+
+    /// Analyses the latest frame into `output`. Skipped when the editor holds the lock, it will
+    /// simply see the next frame.
+    pub fn publish(
+        &mut self,
+        fft: &dyn Fft<f32>,
+        window: &[f32],
+        tap: AnalyzerTap,
+        sample_rate: f32,
+        output: &Mutex<AnalyzerData>,
+    ) {
+        let Ok(mut data) = output.try_lock() else {
+            return;
+        };
+
+        for ((bin, sample), w) in self.scratch.iter_mut().zip(&self.ring).zip(window) {
+            *bin = Complex::new(sample * w, 0.0);
+        }
+        fft.process(&mut self.scratch);
+
+        // A full scale sine peaks at half the window's sum, whichever window shape is selected
+        let norm = 2.0 / window.iter().sum::<f32>();
+        // Switching taps should not leave the old tap's peaks falling slowly
+        let fall = if data.tap == tap {
+            FALL_DB
+        } else {
+            f32::INFINITY
+        };
+        for ((level, db), bin) in data
+            .spectrum_db
+            .iter_mut()
+            .zip(self.levels_db.iter_mut())
+            .zip(&self.scratch)
+        {
+            *db = util::gain_to_db(bin.norm() * norm).max(FLOOR_DB);
+            *level = db.max(*level - fall);
+        }
+        let bin_hz = sample_rate / self.scratch.len() as f32;
+        find_partials(&self.levels_db, bin_hz, &mut data.partials);
+        for (out, column) in data.scope.iter_mut().zip(&self.scope) {
+            *out = *column;
+        }
+        data.scope_span = (SCOPE_POINTS * self.column_len) as f32 / sample_rate;
+        data.tap = tap;
+        data.sample_rate = sample_rate;
+    }
+}
+
+/// Fills `partials` with the loudest local maxima of `levels_db`, loudest first. Each peak's
+/// frequency and level come from a parabola through it and its neighbours.
+fn find_partials(levels_db: &[f32], bin_hz: f32, partials: &mut Vec<Partial>) {
+    partials.clear();
+    for (bin, window) in levels_db.windows(3).enumerate() {
+        let [below, peak, above] = [window[0], window[1], window[2]];
+        if peak < PARTIAL_FLOOR_DB || peak <= below || peak < above {
+            continue;
+        }
+        let curve = below - 2.0 * peak + above;
+        let offset = if curve < 0.0 {
+            0.5 * (below - above) / curve
+        } else {
+            0.0
+        };
+        let partial = Partial {
+            freq: (bin as f32 + 1.0 + offset) * bin_hz,
+            level_db: peak - 0.25 * (below - above) * offset,
+        };
+        let idx = partials.partition_point(|louder| louder.level_db >= partial.level_db);
+        if idx < MAX_PARTIALS {
+            partials.insert(idx, partial);
+            partials.truncate(MAX_PARTIALS);
+        }
+    }
+}
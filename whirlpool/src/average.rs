@@ -0,0 +1,75 @@
+use rustfft::num_complex::Complex;
+use std::f32::consts::TAU;
+
+/// Time constant of the average at full `Average`, in seconds.
+pub const MAX_TIME: f32 = 2.0;
+/// Magnitude below which a bin is treated as silent and keeps turning at its own rate.
+const SILENCE: f32 = 1e-9;
+
+/// Blends every frame's magnitudes with an exponential average of the frames before it, which
+/// washes the sound out into a smooth sustain. Phases are those of the current frame, so
+/// partials stay where they are instead of smearing like with blur.
+pub struct SpectralAverage {
+    magnitudes: Vec<f32>,
+    /// Phase of every bin in the last frame, carried on through silent bins so an average that
+    /// outlasts its source keeps ringing.
+    phases: Vec<f32>,
+    /// Whether `magnitudes` holds a previous frame. Cleared while the average is off, so turning
+    /// it back on starts from the current frame.
+    primed: bool,
+}
+
+impl SpectralAverage {
+    pub fn new(bins: usize) -> Self {
+        Self {
+            magnitudes: vec![0.0; bins],
+            phases: vec![0.0; bins],
+            primed: false,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.primed = false;
+    }
+
+    /// Share of the average every frame keeps for an `amount` in `0..=1`, with frames
+    /// `hop_seconds` apart. Zero turns the average off.
+    pub fn keep(amount: f32, hop_seconds: f32) -> f32 {
+        let time = MAX_TIME * amount * amount;
+        if time > 0.0 {
+            (-hop_seconds / time).exp()
+        } else {
+            0.0
+        }
+    }
+
+    /// Averages the positive-frequency half `spectrum` in place. `hop_ratio` is the hop size over
+    /// the FFT size.
+    pub fn process(&mut self, spectrum: &mut [Complex<f32>], keep: f32, hop_ratio: f32) {
+        if keep <= 0.0 {
+            self.primed = false;
+            return;
+        }
+
+        let len = spectrum.len().min(self.magnitudes.len());
+        let bins = spectrum[..len]
+            .iter_mut()
+            .zip(&mut self.magnitudes[..len])
+            .zip(&mut self.phases[..len]);
+        for (i, ((bin, average), phase)) in bins.enumerate() {
+            let (magnitude, current) = bin.to_polar();
+            *average = if self.primed {
+                magnitude + (*average - magnitude) * keep
+            } else {
+                magnitude
+            };
+            *phase = if magnitude > SILENCE {
+                current
+            } else {
+                (*phase + TAU * i as f32 * hop_ratio).rem_euclid(TAU)
+            };
+            *bin = Complex::from_polar(*average, *phase);
+        }
+        self.primed = true;
+    }
+}
@@ -0,0 +1,64 @@
+use std::ops::Range;
+
+use crate::grain_filter::{GrainFilter, GrainFilterType};
+
+/// Sums the low end of a stereo signal to mono. The side signal is high passed by a fourth order
+/// Linkwitz-Riley filter, so everything below the crossover is left in the middle.
+pub struct BassMono {
+    /// Two cascaded Butterworth high passes on the side signal.
+    side_filters: [GrainFilter; 2],
+    crossover: f32,
+    sample_rate: f32,
+}
+
+impl BassMono {
+    pub fn new(crossover: f32, sample_rate: f32) -> Self {
+        let filter = GrainFilter::with_cutoff(GrainFilterType::HighPass, crossover, sample_rate);
+        Self {
+            side_filters: [filter; 2],
+            crossover,
+            sample_rate,
+        }
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.update_filters();
+        self.reset();
+    }
+
+    pub fn set_crossover(&mut self, crossover: f32) {
+        if crossover != self.crossover {
+            self.crossover = crossover;
+            self.update_filters();
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.side_filters.iter_mut().for_each(GrainFilter::reset);
+    }
+
+    fn update_filters(&mut self) {
+        for filter in self.side_filters.iter_mut() {
+            filter.set_cutoff(self.crossover, self.sample_rate);
+        }
+    }
+
+    /// Makes `range` of a stereo `channels` mono below the crossover, in place. `bypass` holds the
+    /// bypass fade for every sample in the range. Anything but two channels is left alone.
+    pub fn process(&mut self, channels: &mut [&mut [f32]], range: Range<usize>, bypass: &[f32]) {
+        let [left, right] = channels else {
+            return;
+        };
+        for (i, bypass) in range.zip(bypass) {
+            let mid = (left[i] + right[i]) * 0.5;
+            let side = (left[i] - right[i]) * 0.5;
+            let high_side = self
+                .side_filters
+                .iter_mut()
+                .fold(side, |side, filter| filter.process(side));
+            left[i] = (mid + high_side) * (1.0 - bypass) + left[i] * bypass;
+            right[i] = (mid - high_side) * (1.0 - bypass) + right[i] * bypass;
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::transient::TransientDetector;
+
+/// Longest bloom attack, in seconds.
+pub const MAX_ATTACK: f32 = 2.0;
+/// Longest bloom release, in seconds.
+pub const MAX_RELEASE: f32 = 5.0;
+/// Input level below which the input counts as stopped and the wet releases, about -60 dBFS.
+const PLAYING_LEVEL: f32 = 0.001;
+/// Release of the input level the bloom watches, bridging the cycles of low notes.
+const LEVEL_RELEASE: f32 = 0.02;
+/// Onset sensitivity of the detector restarting the attack.
+const SENSITIVITY: f32 = 0.5;
+
+/// Shapes the wet signal's level after the input: every onset fades the wet out and lets it
+/// swell back in over the attack, and once the input stops the wet fades away over the
+/// release. Leaves the wet untouched with neither set.
+pub struct Bloom {
+    detector: TransientDetector,
+    level: f32,
+    level_coeff: f32,
+    gain: f32,
+}
+
+impl Bloom {
+    pub fn new(sample_rate: f32) -> Self {
+        let mut bloom = Self {
+            detector: TransientDetector::new(sample_rate),
+            level: 0.0,
+            level_coeff: 0.0,
+            gain: 1.0,
+        };
+        bloom.set_sample_rate(sample_rate);
+        bloom
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.detector.set_sample_rate(sample_rate);
+        self.level_coeff = (-1.0 / (LEVEL_RELEASE * sample_rate)).exp();
+    }
+
+    pub fn reset(&mut self) {
+        self.detector.reset();
+        self.level = 0.0;
+        self.gain = 1.0;
+    }
+
+    /// Steps of the gain per sample for an `attack` and `release` in seconds: the rise per
+    /// sample and the share of the gain kept per sample. Zero turns either end off.
+    pub fn steps(attack: f32, release: f32, sample_rate: f32) -> (f32, f32) {
+        let rise = if attack > 0.0 {
+            (attack * sample_rate).recip()
+        } else {
+            1.0
+        };
+        let keep = if release > 0.0 {
+            (-1.0 / (release * sample_rate)).exp()
+        } else {
+            1.0
+        };
+        (rise, keep)
+    }
+
+    /// Feeds one sample of the input, lined up with the wet signal, and returns the wet's gain.
+    pub fn process(&mut self, input: f32, (rise, keep): (f32, f32)) -> f32 {
+        let onset = self.detector.process(input, SENSITIVITY);
+        self.level = input.abs().max(self.level * self.level_coeff);
+        if rise >= 1.0 && keep >= 1.0 {
+            self.gain = 1.0;
+            return self.gain;
+        }
+
+        if onset && rise < 1.0 {
+            self.gain = 0.0;
+        }
+        if self.level > PLAYING_LEVEL {
+            self.gain = (self.gain + rise).min(1.0);
+        } else {
+            self.gain *= keep;
+        }
+        self.gain
+    }
+}
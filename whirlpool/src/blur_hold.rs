@@ -0,0 +1,44 @@
+use crate::tempo::SyncGrid;
+
+/// Decides which frames draw new random blur phases. In between, every frame reuses the same
+/// phase offsets, which turns the shimmer of per-frame randomization into a steady wash.
+pub struct BlurHold {
+    /// Frames until the next refresh when free running.
+    frames_left: f32,
+    /// Transport grid of synced holds, ticked once per frame.
+    grid: SyncGrid,
+}
+
+impl BlurHold {
+    pub fn new() -> Self {
+        Self {
+            frames_left: 0.0,
+            grid: SyncGrid::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Advances by one frame when refreshing every `frames` frames, which may be fractional.
+    /// Returns whether this frame refreshes.
+    pub fn next_frame(&mut self, frames: f32) -> bool {
+        let frames = frames.max(1.0);
+        self.grid.reset();
+        // Shortening the hold takes effect right away instead of after the old one runs out
+        self.frames_left = self.frames_left.min(frames) - 1.0;
+        if self.frames_left > 0.0 {
+            return false;
+        }
+        self.frames_left += frames;
+        true
+    }
+
+    /// Advances by one frame at transport position `beats` when refreshing every `length` quarter
+    /// notes. `humanize` is in frames, see [`SyncGrid::tick()`]. Returns whether this frame
+    /// refreshes.
+    pub fn next_frame_at(&mut self, beats: f64, length: f64, swing: f64, humanize: f32) -> bool {
+        self.grid.tick(beats, length, swing, humanize)
+    }
+}
@@ -0,0 +1,75 @@
+use nih_plug::prelude::*;
+
+/// Bins between the random points the smooth noise interpolates.
+const SMOOTH_SPACING: usize = 8;
+
+/// Character of the random phase offsets the blur applies across the spectrum.
+#[derive(Enum, Debug, Clone, Copy, PartialEq)]
+pub enum BlurNoise {
+    /// Independent offsets per bin, a diffuse hiss-like wash.
+    White,
+    /// Offsets drifting slowly across the spectrum, so neighbouring bins stay related and the
+    /// blur smears less.
+    Smooth,
+}
+
+fn fast_rand(x: usize, seed: u32) -> f32 {
+    let mut n = (x as u32).wrapping_mul(374761393).wrapping_add(seed);
+    n = (n ^ (n >> 13)).wrapping_mul(1274126177);
+    (n as f32) / (u32::MAX as f32)
+}
+
+/// Random blur offsets per bin in `0..1`, one table for the source bins and one for the bins the
+/// harmony voice lands on. They are only regenerated when the seed or the noise changes, so a
+/// held blur costs nothing per frame.
+pub struct BlurTable {
+    pub bins: Vec<f32>,
+    pub harmony: Vec<f32>,
+    /// Seed and noise the tables were generated with, `None` before the first time.
+    generated: Option<(u32, BlurNoise)>,
+}
+
+impl BlurTable {
+    pub fn new(bins: usize) -> Self {
+        Self {
+            bins: vec![0.0; bins],
+            harmony: vec![0.0; bins],
+            generated: None,
+        }
+    }
+
+    /// Makes the tables match `seed` and `noise`.
+    pub fn update(&mut self, seed: u32, noise: BlurNoise) {
+        if self.generated == Some((seed, noise)) {
+            return;
+        }
+        self.generated = Some((seed, noise));
+
+        fill(&mut self.bins, noise, |x| {
+            fast_rand(x + seed as usize, seed)
+        });
+        let harmony_seed = seed.wrapping_mul(2);
+        fill(&mut self.harmony, noise, |x| {
+            fast_rand(x + seed as usize, harmony_seed)
+        });
+    }
+}
+
+fn fill(table: &mut [f32], noise: BlurNoise, rand: impl Fn(usize) -> f32) {
+    match noise {
+        BlurNoise::White => {
+            for (i, value) in table.iter_mut().enumerate() {
+                *value = rand(i);
+            }
+        }
+        BlurNoise::Smooth => {
+            for (i, value) in table.iter_mut().enumerate() {
+                let point = i / SMOOTH_SPACING;
+                let t = (i % SMOOTH_SPACING) as f32 / SMOOTH_SPACING as f32;
+                let t = t * t * (3.0 - 2.0 * t);
+                let (from, to) = (rand(point), rand(point + 1));
+                *value = from + (to - from) * t;
+            }
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use nih_plug::prelude::*;
+
+/// Most notes the harmony voice follows at once in MIDI shift mode.
+pub const MAX_CHORD_NOTES: usize = 6;
+
+/// Level, pan and shift of one harmony voice. Voice one is the single voice of the ratio and
+/// frequency shift modes, in MIDI shift mode the voices follow the held notes oldest first.
+#[derive(Params)]
+pub struct HarmonyVoiceParams {
+    #[id = "level"]
+    pub level: FloatParam,
+    #[id = "pan"]
+    pub pan: FloatParam,
+    /// Semitones on top of the voice's own shift.
+    #[id = "shift"]
+    pub shift: FloatParam,
+}
+
+impl HarmonyVoiceParams {
+    /// The settings of voice number `idx`, counted from zero.
+    pub fn new(idx: usize) -> Self {
+        let number = idx + 1;
+        Self {
+            level: FloatParam::new(
+                format!("Voice {number} Level"),
+                1.0,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            pan: FloatParam::new(
+                format!("Voice {number} Pan"),
+                0.0,
+                FloatRange::Linear {
+                    min: -1.0,
+                    max: 1.0,
+                },
+            )
+            .with_value_to_string(formatters::v2s_f32_panning())
+            .with_string_to_value(formatters::s2v_f32_panning()),
+            shift: FloatParam::new(
+                format!("Voice {number} Shift"),
+                0.0,
+                FloatRange::Linear {
+                    min: -12.0,
+                    max: 12.0,
+                },
+            )
+            .with_step_size(0.01)
+            .with_unit(" st"),
+        }
+    }
+
+    pub fn voicing(&self) -> Voicing {
+        Voicing {
+            level: self.level.value(),
+            pan: self.pan.value(),
+            shift: self.shift.value(),
+        }
+    }
+}
+
+/// The values of a voice's [`HarmonyVoiceParams`] for one block.
+#[derive(Clone, Copy)]
+pub struct Voicing {
+    level: f32,
+    pan: f32,
+    shift: f32,
+}
+
+/// Notes held on the MIDI input, oldest first. Past `MAX_CHORD_NOTES` a new note replaces the
+/// oldest one. While the sustain pedal is down released notes keep playing until it comes up.
+pub struct HeldNotes {
+    notes: [u8; MAX_CHORD_NOTES],
+    /// Whether the key of every note is up, the note only held by the pedal.
+    released: [bool; MAX_CHORD_NOTES],
+    count: usize,
+    pedal: bool,
+}
+
+impl HeldNotes {
+    pub fn new() -> Self {
+        Self {
+            notes: [0; MAX_CHORD_NOTES],
+            released: [false; MAX_CHORD_NOTES],
+            count: 0,
+            pedal: false,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.count = 0;
+        self.pedal = false;
+    }
+
+    pub fn press(&mut self, note: u8) {
+        self.remove(note);
+        if self.count == MAX_CHORD_NOTES {
+            self.notes.copy_within(1.., 0);
+            self.released.copy_within(1.., 0);
+            self.count -= 1;
+        }
+        self.notes[self.count] = note;
+        self.released[self.count] = false;
+        self.count += 1;
+    }
+
+    pub fn release(&mut self, note: u8) {
+        if !self.pedal {
+            self.remove(note);
+        } else if let Some(idx) = self.position(note) {
+            self.released[idx] = true;
+        }
+    }
+
+    /// Whether the sustain pedal is down.
+    pub fn sustained(&self) -> bool {
+        self.pedal
+    }
+
+    /// Puts the sustain pedal down or lets it up, which drops the notes it was holding.
+    pub fn set_pedal(&mut self, down: bool) {
+        self.pedal = down;
+        if down {
+            return;
+        }
+        let mut kept = 0;
+        for idx in 0..self.count {
+            if !self.released[idx] {
+                self.notes[kept] = self.notes[idx];
+                self.released[kept] = false;
+                kept += 1;
+            }
+        }
+        self.count = kept;
+    }
+
+    fn position(&self, note: u8) -> Option<usize> {
+        self.notes[..self.count]
+            .iter()
+            .position(|&held| held == note)
+    }
+
+    fn remove(&mut self, note: u8) {
+        if let Some(idx) = self.position(note) {
+            self.notes.copy_within(idx + 1..self.count, idx);
+            self.released.copy_within(idx + 1..self.count, idx);
+            self.count -= 1;
+        }
+    }
+
+    /// The chord the held notes play over a source at `root`.
+    pub fn chord(&self, root: u8) -> Chord {
+        let mut chord = Chord {
+            count: self.count,
+            moved: true,
+            gain: (self.count.max(1) as f32).sqrt().recip(),
+            ..Chord::SINGLE
+        };
+        for (ratio, &note) in chord.ratios.iter_mut().zip(&self.notes[..self.count]) {
+            *ratio = ((note as f32 - root as f32) / 12.0).exp2();
+        }
+        chord
+    }
+}
+
+/// Shift ratios of the harmony voices rendered in one frame, on top of the bin mapping.
+#[derive(Clone, Copy)]
+pub struct Chord {
+    count: usize,
+    ratios: [f32; MAX_CHORD_NOTES],
+    /// Level of every voice on the channel rendering it, from its [`Voicing`].
+    levels: [f32; MAX_CHORD_NOTES],
+    /// Whether the ratios come from held notes, whose voices need their phases turned along.
+    moved: bool,
+    /// Level of every voice, keeping the loudness of a chord near that of a single voice.
+    gain: f32,
+}
+
+impl Chord {
+    /// The one harmony voice of the ratio and frequency shift modes.
+    pub const SINGLE: Self = Self {
+        count: 1,
+        ratios: [1.0; MAX_CHORD_NOTES],
+        levels: [1.0; MAX_CHORD_NOTES],
+        moved: false,
+        gain: 1.0,
+    };
+
+    /// This chord with every voice shifted, leveled and panned by its `voicings` entry, as
+    /// rendered by `channel`, `None` for mono.
+    pub fn voiced(&self, voicings: &[Voicing], channel: Option<usize>) -> Self {
+        let mut chord = *self;
+        for (idx, voicing) in voicings.iter().enumerate().take(self.count) {
+            chord.ratios[idx] *= (voicing.shift / 12.0).exp2();
+            chord.levels[idx] =
+                voicing.level * channel.map_or(1.0, |channel| crate::balance(voicing.pan)[channel]);
+            chord.moved |= voicing.shift != 0.0;
+        }
+        chord
+    }
+
+    /// The frequency ratio and level of every voice.
+    pub fn voices(&self) -> impl Iterator<Item = (f32, f32)> + '_ {
+        self.ratios
+            .iter()
+            .copied()
+            .zip(self.levels.iter().copied())
+            .take(self.count)
+    }
+
+    pub fn moved(&self) -> bool {
+        self.moved
+    }
+
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+}
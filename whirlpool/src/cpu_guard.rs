@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+use crate::grain_delay::MIN_GRAINS;
+
+/// Processing time, as a fraction of the block's real-time duration, above which grain voices are
+/// shed.
+const SHED_LOAD: f32 = 0.75;
+/// Load below which shed voices are given back.
+const RESTORE_LOAD: f32 = 0.5;
+/// Seconds to wait after a change before judging its effect.
+const HOLD_TIME: f32 = 0.25;
+/// Weight of the previous estimate in the smoothed load, per block.
+const LOAD_SMOOTHING: f32 = 0.9;
+
+/// Watches how long blocks take to process and caps the number of grain voices when the plugin
+/// gets close to missing its deadline. Voices are shed and restored one at a time, which thins
+/// the grain cloud gradually instead of letting the host drop out.
+pub struct CpuGuard {
+    /// Smoothed processing load, 1.0 being exactly real time.
+    load: f32,
+    /// Most grain voices currently allowed.
+    cap: usize,
+    /// Samples left before the cap may change again.
+    hold: usize,
+}
+
+impl CpuGuard {
+    pub fn new(max_voices: usize) -> Self {
+        Self {
+            load: 0.0,
+            cap: max_voices,
+            hold: 0,
+        }
+    }
+
+    /// Records that processing `num_samples` took `elapsed`.
+    pub fn update(
+        &mut self,
+        elapsed: Duration,
+        num_samples: usize,
+        sample_rate: f32,
+        max_voices: usize,
+    ) {
+        if num_samples == 0 {
+            return;
+        }
+        let budget = num_samples as f32 / sample_rate;
+        let load = elapsed.as_secs_f32() / budget;
+        self.load = self.load * LOAD_SMOOTHING + load * (1.0 - LOAD_SMOOTHING);
+
+        self.hold = self.hold.saturating_sub(num_samples);
+        if self.hold > 0 {
+            return;
+        }
+        if self.load > SHED_LOAD && self.cap > MIN_GRAINS {
+            self.cap -= 1;
+        } else if self.load < RESTORE_LOAD && self.cap < max_voices {
+            self.cap += 1;
+        } else {
+            return;
+        }
+        self.hold = (HOLD_TIME * sample_rate) as usize;
+    }
+
+    /// The number of voices to run when `requested` are asked for.
+    pub fn voices(&self, requested: usize) -> usize {
+        requested.min(self.cap)
+    }
+
+    pub fn load(&self) -> f32 {
+        self.load
+    }
+}
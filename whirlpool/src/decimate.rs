@@ -0,0 +1,61 @@
+use nih_plug::prelude::*;
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+
+/// Which bins survive decimation.
+#[derive(Enum, Debug, Clone, Copy, PartialEq)]
+pub enum DecimateMode {
+    /// Every Nth bin counting from DC, a fixed comb that sounds metallic.
+    #[name = "Every Nth"]
+    Stride,
+    /// The loudest `1/N` of the bins in each frame, a resonator that follows the input.
+    Strongest,
+}
+
+/// Thins out a spectrum before resynthesis for lo-fi, resonator-like sounds.
+pub struct Decimator {
+    /// Squared magnitudes, reordered when picking the strongest bins.
+    magnitudes: Vec<f32>,
+}
+
+impl Decimator {
+    pub fn new(bins: usize) -> Self {
+        Self {
+            magnitudes: vec![0.0; bins],
+        }
+    }
+
+    /// Keeps one in `factor` bins of the positive-frequency half `spectrum` and clears the rest.
+    /// A factor of one leaves the spectrum alone.
+    pub fn process(&mut self, spectrum: &mut [Complex<f32>], mode: DecimateMode, factor: usize) {
+        if factor <= 1 {
+            return;
+        }
+
+        match mode {
+            DecimateMode::Stride => {
+                for (i, bin) in spectrum.iter_mut().enumerate() {
+                    if i % factor != 0 {
+                        *bin = Complex::zero();
+                    }
+                }
+            }
+            DecimateMode::Strongest => {
+                let bins = spectrum.len().min(self.magnitudes.len());
+                let keep = (bins / factor).max(1);
+                let magnitudes = &mut self.magnitudes[..bins];
+                for (mag, bin) in magnitudes.iter_mut().zip(spectrum.iter()) {
+                    *mag = bin.norm_sqr();
+                }
+                // Ties with the weakest kept bin survive as well, which only matters for silence
+                let (_, &mut threshold, _) =
+                    magnitudes.select_nth_unstable_by(keep - 1, |a, b| b.total_cmp(a));
+                for bin in spectrum.iter_mut() {
+                    if bin.norm_sqr() < threshold {
+                        *bin = Complex::zero();
+                    }
+                }
+            }
+        }
+    }
+}
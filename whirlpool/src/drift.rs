@@ -0,0 +1,124 @@
+use nih_plug::prelude::*;
+use rustfft::num_complex::Complex;
+
+/// Control points of the magnitude drift, spread over the spectrum in octaves.
+const POINTS: usize = 12;
+/// Largest magnitude drift at full amount, in dB either way.
+const MAX_GAIN_DB: f32 = 4.0;
+/// Largest shift ratio drift at full amount, in cents either way.
+const MAX_CENTS: f32 = 12.0;
+/// Rate at which the slowest control point picks new levels, in Hz. The others run up to half
+/// again as fast, so they never line up.
+const RATE: f32 = 0.25;
+
+/// What the drift does to one frame, decided once per hop for all channels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DriftFrame {
+    /// Gain at every control point, all one without drift.
+    gains: [f32; POINTS],
+}
+
+impl Default for DriftFrame {
+    fn default() -> Self {
+        Self {
+            gains: [1.0; POINTS],
+        }
+    }
+}
+
+impl DriftFrame {
+    /// Scales the magnitudes of the positive-frequency half `spectrum` by the gains, glided
+    /// between the control points.
+    pub fn apply(&self, spectrum: &mut [Complex<f32>]) {
+        if self.gains == [1.0; POINTS] {
+            return;
+        }
+
+        let octaves = (spectrum.len() as f32).log2();
+        for (i, bin) in spectrum.iter_mut().enumerate().skip(1) {
+            let pos = (i as f32).log2() / octaves * (POINTS - 1) as f32;
+            let idx = (pos as usize).min(POINTS - 2);
+            let t = pos - idx as f32;
+            *bin *= self.gains[idx] + (self.gains[idx + 1] - self.gains[idx]) * t;
+        }
+    }
+}
+
+/// Slow correlated noise that wanders the magnitudes and the shift ratio like the components
+/// of an analog circuit warming up. The last point drives the ratio, the others the spectrum.
+pub struct Drift {
+    /// Position of every point in its current cycle, in `0..1`.
+    phases: [f32; POINTS + 1],
+    /// Levels in `-1..=1` every point glides between during its current cycle.
+    from: [f32; POINTS + 1],
+    to: [f32; POINTS + 1],
+    rng_state: u32,
+}
+
+impl Drift {
+    pub fn new() -> Self {
+        let mut drift = Self {
+            phases: [0.0; POINTS + 1],
+            from: [0.0; POINTS + 1],
+            to: [0.0; POINTS + 1],
+            rng_state: 1,
+        };
+        // Start the points at different places in their cycles, all still at zero
+        for (k, phase) in drift.phases.iter_mut().enumerate() {
+            *phase = k as f32 / (POINTS + 1) as f32;
+        }
+        drift
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Moves `seconds` on.
+    pub fn advance(&mut self, seconds: f32) {
+        for k in 0..=POINTS {
+            let rate = RATE * (1.0 + 0.5 * k as f32 / POINTS as f32);
+            self.phases[k] += rate * seconds;
+            if self.phases[k] >= 1.0 {
+                self.phases[k] = self.phases[k].fract();
+                self.from[k] = self.to[k];
+                self.to[k] = self.next_random() * 2.0 - 1.0;
+            }
+        }
+    }
+
+    /// Current level of point `k` in `-1..=1`.
+    fn level(&self, k: usize) -> f32 {
+        let phase = self.phases[k];
+        let t = phase * phase * (3.0 - 2.0 * phase);
+        self.from[k] + (self.to[k] - self.from[k]) * t
+    }
+
+    /// Magnitude drift with `amount` in `0..=1`.
+    pub fn frame(&self, amount: f32) -> DriftFrame {
+        if amount <= 0.0 {
+            return DriftFrame::default();
+        }
+        let mut frame = DriftFrame::default();
+        for (k, gain) in frame.gains.iter_mut().enumerate() {
+            *gain = util::db_to_gain(self.level(k) * amount * MAX_GAIN_DB);
+        }
+        frame
+    }
+
+    /// Factor on the shift ratio with `amount` in `0..=1`.
+    pub fn ratio(&self, amount: f32) -> f32 {
+        if amount <= 0.0 {
+            return 1.0;
+        }
+        (self.level(POINTS) * amount * MAX_CENTS / 1200.0).exp2()
+    }
+
+    /// Uniform in `0..1`.
+    fn next_random(&mut self) -> f32 {
+        self.rng_state ^= self.rng_state << 13;
+        self.rng_state ^= self.rng_state >> 17;
+        self.rng_state ^= self.rng_state << 5;
+        self.rng_state as f32 / u32::MAX as f32
+    }
+}
@@ -0,0 +1,75 @@
+use nih_plug::prelude::*;
+use std::f32::consts::TAU;
+
+use crate::grain_filter::{GrainFilter, GrainFilterType};
+
+/// Lowest dry low cut, where it is off and the dry signal keeps its full low end.
+pub const MIN_LOW_CUT: f32 = 20.0;
+/// Largest tilt either way, in dB at the ends of the spectrum relative to the pivot.
+pub const MAX_TILT: f32 = 6.0;
+/// Frequency the tilt turns around, left at its level either way.
+const TILT_PIVOT: f32 = 1000.0;
+
+/// Thins the dry signal before it is mixed with the wet one: a Butterworth low cut and a first
+/// order tilt around `TILT_PIVOT`. Left untouched at the lowest low cut and no tilt.
+pub struct DryTone {
+    low_cut: Option<GrainFilter>,
+    /// Gains of the parts below and above the pivot, `None` without tilt.
+    tilt: Option<[f32; 2]>,
+    /// One-pole low pass splitting off the part below the pivot.
+    tilt_coefficient: f32,
+    tilt_low: f32,
+}
+
+impl DryTone {
+    pub fn new() -> Self {
+        Self {
+            low_cut: None,
+            tilt: None,
+            tilt_coefficient: 0.0,
+            tilt_low: 0.0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        if let Some(filter) = self.low_cut.as_mut() {
+            filter.reset();
+        }
+        self.tilt_low = 0.0;
+    }
+
+    /// Picks up the settings for the next block, keeping the filters' state.
+    pub fn set(&mut self, low_cut: f32, tilt_db: f32, sample_rate: f32) {
+        if low_cut > MIN_LOW_CUT {
+            match self.low_cut.as_mut() {
+                Some(filter) => filter.set_cutoff(low_cut, sample_rate),
+                None => {
+                    self.low_cut = Some(GrainFilter::with_cutoff(
+                        GrainFilterType::HighPass,
+                        low_cut,
+                        sample_rate,
+                    ))
+                }
+            }
+        } else {
+            self.low_cut = None;
+        }
+
+        self.tilt =
+            (tilt_db != 0.0).then(|| [util::db_to_gain(-tilt_db), util::db_to_gain(tilt_db)]);
+        self.tilt_coefficient = 1.0 - (-TAU * TILT_PIVOT / sample_rate).exp();
+    }
+
+    pub fn process(&mut self, input: f32) -> f32 {
+        let mut output = input;
+        if let Some(filter) = self.low_cut.as_mut() {
+            output = filter.process(output);
+        }
+        // Keeps splitting without tilt, so turning it on does not start from silence
+        self.tilt_low += (output - self.tilt_low) * self.tilt_coefficient;
+        if let Some([low_gain, high_gain]) = self.tilt {
+            output = self.tilt_low * low_gain + (output - self.tilt_low) * high_gain;
+        }
+        output
+    }
+}
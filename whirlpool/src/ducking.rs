@@ -0,0 +1,68 @@
+use nih_plug::util;
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::Fft;
+use std::collections::VecDeque;
+
+/// Bins are never pulled down further than this.
+const MAX_REDUCTION_DB: f32 = -30.0;
+/// Sidechain bin level, relative to a full scale sine, that halves a main bin at full amount.
+const HALF_GAIN_LEVEL: f32 = 0.03;
+
+/// Per-bin gains that pull the main signal down wherever the sidechain has energy, so a vocal on
+/// the sidechain carves its own space out of the processed signal.
+pub struct SpectralDucker {
+    ring: VecDeque<f32>,
+    scratch: Vec<Complex<f32>>,
+    /// Sidechain magnitude per bin with instant attack and a one-pole release.
+    envelope: Vec<f32>,
+    gains: Vec<f32>,
+}
+
+impl SpectralDucker {
+    pub fn new(fft_size: usize) -> Self {
+        Self {
+            ring: VecDeque::from(vec![0.0; fft_size]),
+            scratch: vec![Complex::zero(); fft_size],
+            envelope: vec![0.0; fft_size / 2],
+            gains: vec![1.0; fft_size / 2],
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.ring.iter_mut().for_each(|x| *x = 0.0);
+        self.envelope.fill(0.0);
+        self.gains.fill(1.0);
+    }
+
+    pub fn push(&mut self, sample: f32) {
+        self.ring.pop_front();
+        self.ring.push_back(sample);
+    }
+
+    /// Analyses the latest sidechain frame and recomputes the gains. `release` is the envelope's
+    /// per-call decay coefficient, `amount` in `0..=1` scales the reduction.
+    pub fn update(&mut self, fft: &dyn Fft<f32>, window: &[f32], release: f32, amount: f32) {
+        for ((bin, sample), w) in self.scratch.iter_mut().zip(&self.ring).zip(window) {
+            *bin = Complex::new(sample * w, 0.0);
+        }
+        fft.process(&mut self.scratch);
+
+        // A Hann-windowed full scale sine peaks at a quarter of the FFT size
+        let norm = 4.0 / self.scratch.len() as f32;
+        let floor = util::db_to_gain(MAX_REDUCTION_DB);
+        for ((env, gain), bin) in self
+            .envelope
+            .iter_mut()
+            .zip(self.gains.iter_mut())
+            .zip(&self.scratch)
+        {
+            *env = (bin.norm() * norm).max(*env * release);
+            *gain = (1.0 + amount * *env / HALF_GAIN_LEVEL).recip().max(floor);
+        }
+    }
+
+    pub fn gains(&self) -> &[f32] {
+        &self.gains
+    }
+}
@@ -0,0 +1,1955 @@
+use nih_plug::prelude::*;
+use nih_plug_egui::egui::{self, Align2, Color32, FontId, Pos2, Rect, Sense, Shape, Stroke};
+use nih_plug_egui::{create_egui_editor, widgets, EguiState};
+use std::collections::BTreeMap;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use crate::analyzer::{
+    AnalyzerData, AnalyzerTap, Partial, FLOOR_DB, MAX_SCOPE_MS, MIN_SCOPE_MS, SCOPE_POINTS,
+};
+use crate::euclid::EuclidPattern;
+use crate::fft_size;
+use crate::freeze_bank::NUM_SLOTS;
+use crate::grain_delay::{MAX_BUFFER, MIN_BUFFER};
+use crate::morph::MorphPresets;
+use crate::motion::{MotionSequence, MotionStep, MAX_STEPS};
+use crate::scale::{self, Scale};
+use crate::smoothing::{Smoothed, SmoothingTimes, MAX_SMOOTHING_MS};
+use crate::spectral_eq::{self, EqPoint, MAX_GAIN_DB, MIN_GAIN_DB};
+use crate::tempo::TapTempo;
+use crate::xy_axes::XyAxes;
+use crate::{Meters, WhirlpoolParams, FFT_SIZE, HOP_SIZE};
+
+mod access;
+mod knob;
+mod locale;
+mod palette;
+mod prefs;
+mod share;
+mod toast;
+mod touch;
+mod xy_pad;
+
+use knob::Knob;
+use locale::Language;
+use palette::{Action, Palette};
+use prefs::{DragMode, KnobPrefs, ResponseCurve, MAX_SENSITIVITY, MIN_SENSITIVITY};
+use toast::{ToastKind, Toasts};
+use xy_pad::XyPad;
+
+const WIDTH: u32 = 640;
+const HEIGHT: u32 = 760;
+const COMPACT_WIDTH: u32 = 800;
+const COMPACT_HEIGHT: u32 = 200;
+
+const BACKGROUND: Color32 = Color32::from_rgb(18, 24, 32);
+const GRID: Color32 = Color32::from_rgb(44, 56, 70);
+const CURVE: Color32 = Color32::from_rgb(80, 200, 230);
+const SPECTRUM: Color32 = Color32::from_rgb(120, 220, 140);
+const CLIP: Color32 = Color32::from_rgb(230, 60, 50);
+const LABEL: Color32 = Color32::from_rgb(110, 126, 144);
+const POINT_RADIUS: f32 = 5.0;
+/// Level at the center line of the waveform display, which is drawn in dB towards its edges.
+const SCOPE_FLOOR_DB: f32 = -60.0;
+/// Labelled levels of the waveform display.
+const SCOPE_LEVELS_DB: [f32; 5] = [0.0, -6.0, -12.0, -24.0, -48.0];
+/// Spacing of the spectrum's level lines in dB.
+const SPECTRUM_STEP_DB: usize = 12;
+/// Frequencies that may be labelled, labels closer than `MIN_LABEL_SPACING` pixels are skipped.
+const FREQ_LABELS: [f32; 10] = [
+    20.0, 50.0, 100.0, 200.0, 500.0, 1000.0, 2000.0, 5000.0, 10000.0, 20000.0,
+];
+const MIN_LABEL_SPACING: f32 = 36.0;
+/// Overlap-add ripple, relative to the mean level, above which the settings page warns.
+const COLA_TOLERANCE: f32 = 0.01;
+
+pub(crate) fn default_state() -> Arc<EguiState> {
+    EguiState::from_size(WIDTH, HEIGHT)
+}
+
+/// UI-only state that does not need to be persisted with the project.
+#[derive(Default)]
+struct EditorState {
+    tab: Tab,
+    /// Index of the EQ breakpoint currently being dragged.
+    dragged_point: Option<usize>,
+    tap_tempo: TapTempo,
+    toasts: Toasts,
+    /// Meter states seen in the last frame, so their changes can be toasted.
+    was_clipping: bool,
+    was_learning_gate: bool,
+    /// The XY pad axis that was waiting for a MIDI CC.
+    was_learning_xy: Option<usize>,
+    /// Whether a system font for the Japanese labels has been looked for.
+    cjk_font_loaded: bool,
+    /// The analyzer frame shown while the analyzer is paused.
+    analyzer_paused: Option<AnalyzerData>,
+    /// Hides the analyzer's grid lines, leaving only the axis labels.
+    hide_grid: bool,
+    spectrum_zoom: Zoom,
+    scope_zoom: Zoom,
+    /// Loaded from the user's configuration when the editor opens.
+    knob_prefs: KnobPrefs,
+    /// Preset text pasted into the settings, applied with the Paste Preset button.
+    preset_text: String,
+    /// Shares the frozen spectrum along with copied presets.
+    share_freeze: bool,
+    palette: Palette,
+}
+
+/// The part of a display's full range it shows, both ends in `0..=1`. Dragging across the
+/// display zooms into the dragged part, double-clicking zooms back out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Zoom {
+    start: f32,
+    end: f32,
+    /// Where the current drag started and where it is now, in the full range.
+    drag: Option<(f32, f32)>,
+}
+
+impl Default for Zoom {
+    fn default() -> Self {
+        Self {
+            start: 0.0,
+            end: 1.0,
+            drag: None,
+        }
+    }
+}
+
+impl Zoom {
+    /// Position in the full range shown at `x` in `rect`.
+    fn unit_at(&self, rect: Rect, x: f32) -> f32 {
+        let shown = ((x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+        self.start + shown * (self.end - self.start)
+    }
+
+    /// Where `unit` of the full range is shown in `rect`, outside it when zoomed past.
+    fn x(&self, rect: Rect, unit: f32) -> f32 {
+        rect.left() + (unit - self.start) / (self.end - self.start) * rect.width()
+    }
+
+    /// Follows a drag or double-click on the display, shading the part being dragged over.
+    fn interact(&mut self, response: &egui::Response, painter: &egui::Painter) {
+        let rect = response.rect;
+        if response.double_clicked() {
+            *self = Self::default();
+            return;
+        }
+        if let Some(pos) = response.interact_pointer_pos() {
+            let unit = self.unit_at(rect, pos.x);
+            if response.drag_started() {
+                self.drag = Some((unit, unit));
+            } else if let Some((_, to)) = &mut self.drag {
+                *to = unit;
+            }
+        }
+
+        let Some((from, to)) = self.drag else {
+            return;
+        };
+        let (left, right) = (self.x(rect, from.min(to)), self.x(rect, from.max(to)));
+        if response.drag_stopped() {
+            self.drag = None;
+            // A click or a twitch of the mouse is not a selection
+            if right - left > 4.0 {
+                (self.start, self.end) = (from.min(to), from.max(to));
+            }
+        } else {
+            painter.rect_filled(
+                Rect::from_x_y_ranges(left..=right, rect.y_range()),
+                0.0,
+                GRID.gamma_multiply(0.6),
+            );
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum Tab {
+    #[default]
+    Main,
+    XyPad,
+    Motion,
+    Freeze,
+    Delay,
+    Analyzer,
+    Settings,
+}
+
+pub(crate) fn create(
+    params: Arc<WhirlpoolParams>,
+    meters: Arc<Meters>,
+    editor_state: Arc<EguiState>,
+) -> Option<Box<dyn Editor>> {
+    create_egui_editor(
+        editor_state,
+        EditorState {
+            knob_prefs: KnobPrefs::load(),
+            ..EditorState::default()
+        },
+        |_, _| {},
+        move |egui_ctx, setter, state| {
+            watch_meters(state, &params, &meters);
+            if language(&params) == Language::Japanese && !state.cjk_font_loaded {
+                if !locale::install_cjk_font(egui_ctx) {
+                    state.toasts.push(
+                        ToastKind::Warning,
+                        "No Japanese system font found, labels may not show",
+                    );
+                }
+                state.cjk_font_loaded = true;
+            }
+            egui::CentralPanel::default().show(egui_ctx, |ui| {
+                if state.knob_prefs.touch_mode {
+                    touch_spacing(ui);
+                }
+                if params.compact_editor.load(Ordering::Relaxed) {
+                    compact_view(ui, &params, setter, &meters, &state.knob_prefs);
+                    return;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.heading("WHIRLPOOL");
+                    ui.add_space(16.0);
+                    ui.label(pitch_readout(meters.pitch.load(Ordering::Relaxed)));
+                    ui.add_space(16.0);
+                    ui.label(tempo_readout(&meters));
+                    ui.add_space(16.0);
+                    clip_indicator(ui, &meters);
+                    ui.add_space(16.0);
+                    ui.selectable_value(&mut state.tab, Tab::Main, tr(&params, "Main"));
+                    ui.selectable_value(&mut state.tab, Tab::XyPad, tr(&params, "XY Pad"));
+                    ui.selectable_value(&mut state.tab, Tab::Motion, tr(&params, "Motion"));
+                    ui.selectable_value(&mut state.tab, Tab::Freeze, tr(&params, "Freeze Bank"));
+                    ui.selectable_value(&mut state.tab, Tab::Delay, tr(&params, "Delay"));
+                    ui.selectable_value(&mut state.tab, Tab::Analyzer, tr(&params, "Analyzer"));
+                    ui.selectable_value(&mut state.tab, Tab::Settings, tr(&params, "Settings"));
+                    if ui.button(tr(&params, "Compact")).clicked() {
+                        set_compact(&params, true);
+                    }
+                });
+                ui.add_space(6.0);
+
+                match state.tab {
+                    Tab::Main => main_tab(ui, &params, setter, &meters, state),
+                    Tab::XyPad => {
+                        xy_pad_tab(ui, &params, setter, &meters, state.knob_prefs.touch_mode)
+                    }
+                    Tab::Motion => motion_tab(ui, &params, setter, &meters),
+                    Tab::Freeze => freeze_bank_tab(ui, &params, setter, &meters),
+                    Tab::Delay => delay_tab(ui, &params, setter, &meters),
+                    Tab::Analyzer => analyzer_tab(ui, &params, &meters, state),
+                    Tab::Settings => settings_tab(ui, &params, setter, &meters, state),
+                }
+            });
+            if let Some(action) = state.palette.show(egui_ctx, &params, setter.raw_context) {
+                run_action(action, egui_ctx, &params, setter, state);
+            }
+            state.toasts.show(egui_ctx);
+        },
+    )
+}
+
+/// Carries out an action picked in the command palette.
+fn run_action(
+    action: Action,
+    ctx: &egui::Context,
+    params: &WhirlpoolParams,
+    setter: &ParamSetter,
+    state: &mut EditorState,
+) {
+    match action {
+        Action::ToggleFreeze => {
+            setter.begin_set_parameter(&params.freeze);
+            setter.set_parameter(&params.freeze, !params.freeze.value());
+            setter.end_set_parameter(&params.freeze);
+        }
+        Action::Randomize => {
+            randomize(ctx, params, setter);
+            state
+                .toasts
+                .push(ToastKind::Info, "Randomized the main controls");
+        }
+        Action::CopyPreset => copy_preset(ctx, setter, state),
+        Action::StoreMorphA | Action::StoreMorphB => {
+            let Ok(mut presets) = params.morph_presets.write() else {
+                return;
+            };
+            if action == Action::StoreMorphA {
+                presets.a = morph_snapshot(params);
+                state.toasts.push(ToastKind::Info, "Stored morph preset A");
+            } else {
+                presets.b = morph_snapshot(params);
+                state.toasts.push(ToastKind::Info, "Stored morph preset B");
+            }
+            params.morph_changed.store(true, Ordering::Release);
+        }
+        Action::ToggleCompact => {
+            set_compact(params, !params.compact_editor.load(Ordering::Relaxed));
+        }
+    }
+}
+
+/// Sets the smoothed parameters to random values. The output gain is left alone so a roll of
+/// the dice never blasts the monitors.
+fn randomize(ctx: &egui::Context, params: &WhirlpoolParams, setter: &ParamSetter) {
+    // Seeded from the editor's clock, any two presses are far enough apart to differ
+    let mut rng_state = (ctx.input(|input| input.time) * 1000.0) as u32 | 1;
+    for param in Smoothed::ALL {
+        if param == Smoothed::OutGain {
+            continue;
+        }
+        rng_state ^= rng_state << 13;
+        rng_state ^= rng_state >> 17;
+        rng_state ^= rng_state << 5;
+        let param = params.smoothed(param);
+        setter.begin_set_parameter(param);
+        setter.set_parameter_normalized(param, rng_state as f32 / u32::MAX as f32);
+        setter.end_set_parameter(param);
+    }
+}
+
+/// Taller sliders and buttons with more room between them, for touch mode.
+fn touch_spacing(ui: &mut egui::Ui) {
+    let spacing = ui.spacing_mut();
+    spacing.interact_size.y = 32.0;
+    spacing.slider_width *= 1.5;
+    spacing.item_spacing.y = 8.0;
+    spacing.button_padding.y = 8.0;
+}
+
+/// Toasts what the audio thread finished or ran into since the last frame.
+fn watch_meters(state: &mut EditorState, params: &WhirlpoolParams, meters: &Meters) {
+    let clipping = meters.clip.load(Ordering::Relaxed);
+    if clipping && !state.was_clipping {
+        state.toasts.push(
+            ToastKind::Warning,
+            "Clipping detected, try a lower input pad",
+        );
+    }
+    state.was_clipping = clipping;
+
+    let learning_gate = meters.gate_learning.load(Ordering::Relaxed);
+    if state.was_learning_gate && !learning_gate {
+        state.toasts.push(ToastKind::Info, "Noise profile learned");
+    }
+    state.was_learning_gate = learning_gate;
+
+    let learning_xy = usize::try_from(meters.xy_learning.load(Ordering::Relaxed)).ok();
+    if let (Some(axis), None) = (state.was_learning_xy, learning_xy) {
+        let cc = params
+            .xy_axes
+            .try_read()
+            .ok()
+            .and_then(|axes| axes.axes[axis].cc);
+        if let Some(cc) = cc {
+            let name = ["X", "Y"][axis];
+            state
+                .toasts
+                .push(ToastKind::Info, format!("{name} axis mapped to CC {cc}"));
+        }
+    }
+    state.was_learning_xy = learning_xy;
+}
+
+fn main_tab(
+    ui: &mut egui::Ui,
+    params: &WhirlpoolParams,
+    setter: &ParamSetter,
+    meters: &Meters,
+    state: &mut EditorState,
+) {
+    ui.horizontal(|ui| main_knobs(ui, params, setter, &state.knob_prefs));
+    ui.add_space(6.0);
+
+    egui::Grid::new("params").num_columns(2).show(ui, |ui| {
+        ui.label(tr(params, "Input Pad"));
+        param_slider(ui, &params.input_pad, setter);
+        ui.end_row();
+        ui.label(tr(params, "Input Mute"));
+        param_slider(ui, &params.input_mute, setter);
+        ui.end_row();
+        ui.label(tr(params, "Harmony Pan"));
+        param_slider(ui, &params.harmony_pan, setter);
+        ui.end_row();
+        for (idx, voice) in params.harmony_voices.iter().enumerate() {
+            ui.label(format!("{} {}", tr(params, "Voice"), idx + 1));
+            ui.horizontal(|ui| {
+                param_slider(ui, &voice.level, setter);
+                param_slider(ui, &voice.pan, setter);
+                param_slider(ui, &voice.shift, setter);
+            });
+            ui.end_row();
+        }
+        ui.label(tr(params, "Unison"));
+        ui.horizontal(|ui| {
+            param_slider(ui, &params.unison, setter);
+            param_slider(ui, &params.unison_width, setter);
+        });
+        ui.end_row();
+        ui.label(tr(params, "Shift Mode"));
+        ui.horizontal(|ui| {
+            param_slider(ui, &params.shift_mode, setter);
+            param_slider(ui, &params.midi_root, setter);
+        });
+        ui.end_row();
+        ui.label(tr(params, "Bend Range"));
+        param_slider(ui, &params.bend_range, setter);
+        ui.end_row();
+        ui.label(tr(params, "Scale"));
+        param_slider(ui, &params.scale, setter);
+        ui.end_row();
+        ui.label(tr(params, "Key"));
+        param_slider(ui, &params.key, setter);
+        ui.end_row();
+        ui.label("");
+        scale_keys(ui, params);
+        ui.end_row();
+        ui.label(tr(params, "Preserve Body"));
+        param_slider(ui, &params.preserve_envelope, setter);
+        ui.end_row();
+        ui.label(tr(params, "Pre-Delay"));
+        param_slider(ui, &params.pre_delay, setter);
+        ui.end_row();
+        ui.label(tr(params, "Decimate"));
+        ui.horizontal(|ui| {
+            param_slider(ui, &params.decimate, setter);
+            param_slider(ui, &params.decimate_mode, setter);
+        });
+        ui.end_row();
+        ui.label(tr(params, "Rotate"));
+        param_slider(ui, &params.rotate, setter);
+        ui.end_row();
+        ui.label(tr(params, "Rotate Mod"));
+        ui.horizontal(|ui| {
+            param_slider(ui, &params.rotate_mod_depth, setter);
+            param_slider(ui, &params.rotate_mod_rate, setter);
+        });
+        ui.end_row();
+        ui.label(tr(params, "Invert"));
+        ui.horizontal(|ui| {
+            param_slider(ui, &params.invert, setter);
+            param_slider(ui, &params.invert_center, setter);
+        });
+        ui.end_row();
+        ui.label(tr(params, "Invert Wet"));
+        param_slider(ui, &params.invert_wet, setter);
+        ui.end_row();
+        ui.label(tr(params, "Tame"));
+        param_slider(ui, &params.tame, setter);
+        ui.end_row();
+        ui.label(tr(params, "Blur Hold"));
+        ui.horizontal(|ui| {
+            param_slider(ui, &params.blur_hold, setter);
+            param_slider(ui, &params.blur_sync, setter);
+        });
+        ui.end_row();
+        ui.label(tr(params, "Blur Noise"));
+        param_slider(ui, &params.blur_noise, setter);
+        ui.end_row();
+        ui.label(tr(params, "Average"));
+        param_slider(ui, &params.average, setter);
+        ui.end_row();
+        ui.label(tr(params, "Harmony Source"));
+        param_slider(ui, &params.harmony_source, setter);
+        ui.end_row();
+        ui.label(tr(params, "Process"));
+        param_slider(ui, &params.tonal_split, setter);
+        ui.end_row();
+        ui.label(tr(params, "Tonality"));
+        param_slider(ui, &params.tonality, setter);
+        ui.end_row();
+        ui.label(tr(params, "Harmony Bins"));
+        ui.horizontal(|ui| {
+            param_slider(ui, &params.harmony_bins, setter);
+            param_slider(ui, &params.peak_count, setter);
+        });
+        ui.end_row();
+        ui.label(tr(params, "Dry Tone"));
+        ui.horizontal(|ui| {
+            param_slider(ui, &params.dry_low_cut, setter);
+            param_slider(ui, &params.dry_tilt, setter);
+        });
+        ui.end_row();
+        ui.label(tr(params, "Bloom"));
+        ui.horizontal(|ui| {
+            param_slider(ui, &params.bloom_attack, setter);
+            param_slider(ui, &params.bloom_release, setter);
+        });
+        ui.end_row();
+        ui.label(tr(params, "Low Cut"));
+        param_slider(ui, &params.low_cut, setter);
+        ui.end_row();
+        ui.label(tr(params, "High Cut"));
+        param_slider(ui, &params.high_cut, setter);
+        ui.end_row();
+        ui.label(tr(params, "Freeze"));
+        ui.horizontal(|ui| {
+            param_slider(ui, &params.freeze, setter);
+            param_slider(ui, &params.freeze_amount, setter);
+        });
+        ui.end_row();
+        ui.label(tr(params, "Shimmer"));
+        ui.horizontal(|ui| {
+            param_slider(ui, &params.shimmer, setter);
+            param_slider(ui, &params.shimmer_level, setter);
+        });
+        ui.end_row();
+        ui.label(tr(params, "Drift"));
+        param_slider(ui, &params.drift, setter);
+        ui.end_row();
+        ui.label(tr(params, "Save Freeze"));
+        param_slider(ui, &params.save_freeze, setter);
+        ui.end_row();
+        ui.label(tr(params, "Freeze Trigger"));
+        param_slider(ui, &params.freeze_trigger, setter);
+        ui.end_row();
+        ui.label(tr(params, "Sensitivity"));
+        param_slider(ui, &params.trigger_sensitivity, setter);
+        ui.end_row();
+        ui.label(tr(params, "Sidechain Trigger"));
+        ui.horizontal(|ui| {
+            param_slider(ui, &params.sidechain_grains, setter);
+            param_slider(ui, &params.sidechain_blur, setter);
+            param_slider(ui, &params.trigger_hold, setter);
+        });
+        ui.end_row();
+        ui.label(tr(params, "MIDI Out"));
+        param_slider(ui, &params.midi_out, setter);
+        ui.end_row();
+        ui.label(tr(params, "True Peak Limit"));
+        ui.horizontal(|ui| {
+            param_slider(ui, &params.tp_limit, setter);
+            true_peak_readout(ui, params, meters);
+        });
+        ui.end_row();
+        ui.label(tr(params, "TP Ceiling"));
+        param_slider(ui, &params.tp_ceiling, setter);
+        ui.end_row();
+        ui.label(tr(params, "Bass Mono"));
+        ui.horizontal(|ui| {
+            param_slider(ui, &params.bass_mono, setter);
+            param_slider(ui, &params.bass_mono_freq, setter);
+        });
+        ui.end_row();
+        ui.label(tr(params, "Spectral Gate"));
+        ui.horizontal(|ui| {
+            param_slider(ui, &params.gate, setter);
+            gate_learn_controls(ui, params, meters);
+        });
+        ui.end_row();
+        ui.label(tr(params, "Gate Threshold"));
+        param_slider(ui, &params.gate_threshold, setter);
+        ui.end_row();
+        ui.label(tr(params, "Gate Reduction"));
+        param_slider(ui, &params.gate_reduction, setter);
+        ui.end_row();
+        ui.label(tr(params, "Spectral Duck"));
+        param_slider(ui, &params.sidechain_duck, setter);
+        ui.end_row();
+        ui.label(tr(params, "Duck Amount"));
+        param_slider(ui, &params.duck_amount, setter);
+        ui.end_row();
+        ui.label(tr(params, "Duck Release"));
+        param_slider(ui, &params.duck_release, setter);
+        ui.end_row();
+        ui.label(tr(params, "Sidechain Listen"));
+        param_slider(ui, &params.sidechain_listen, setter);
+        ui.end_row();
+    });
+
+    ui.add_space(8.0);
+    ui.horizontal(|ui| {
+        ui.label(tr(params, "Spectral EQ"));
+        if ui.button(tr(params, "Reset")).clicked() {
+            if let Ok(mut curve) = params.eq_curve.write() {
+                curve.points.clear();
+            }
+            params.eq_curve_changed.store(true, Ordering::Release);
+            state.dragged_point = None;
+        }
+    });
+    eq_curve_editor(ui, params, state);
+}
+
+/// A parameter's slider, described to screen readers.
+fn param_slider<P: Param>(ui: &mut egui::Ui, param: &P, setter: &ParamSetter) -> egui::Response {
+    let response = ui.add(widgets::ParamSlider::for_param(param, setter));
+    access::describe_param(&response, param, param.name());
+    response
+}
+
+/// The most played controls, on top of the main tab and in the compact view.
+fn knob_params(params: &WhirlpoolParams) -> [&FloatParam; 7] {
+    [
+        &params.harmonics,
+        &params.shift,
+        &params.shift_hz,
+        &params.blur,
+        &params.mix,
+        &params.out_gain,
+        &params.morph,
+    ]
+}
+
+fn main_knobs(
+    ui: &mut egui::Ui,
+    params: &WhirlpoolParams,
+    setter: &ParamSetter,
+    prefs: &KnobPrefs,
+) {
+    for param in knob_params(params) {
+        ui.add(
+            Knob::for_param(param, setter)
+                .with_name(tr(params, param.name()))
+                .with_prefs(prefs),
+        );
+    }
+}
+
+/// A single row of the main knobs and small meters, for keeping the editor open next to many
+/// others while mixing.
+fn compact_view(
+    ui: &mut egui::Ui,
+    params: &WhirlpoolParams,
+    setter: &ParamSetter,
+    meters: &Meters,
+    prefs: &KnobPrefs,
+) {
+    ui.horizontal(|ui| {
+        ui.vertical(|ui| {
+            ui.heading("WHIRLPOOL");
+            ui.label(pitch_readout(meters.pitch.load(Ordering::Relaxed)));
+            ui.horizontal(|ui| {
+                clip_indicator(ui, meters);
+                true_peak_readout(ui, params, meters);
+            });
+            if ui.button(tr(params, "Full")).clicked() {
+                set_compact(params, false);
+            }
+        });
+        ui.add_space(16.0);
+        main_knobs(ui, params, setter, prefs);
+    });
+}
+
+/// Switches between the compact view and the full editor, resizing the window to fit.
+fn set_compact(params: &WhirlpoolParams, compact: bool) {
+    params.compact_editor.store(compact, Ordering::Relaxed);
+    params.editor_state.set_requested_size(if compact {
+        (COMPACT_WIDTH, COMPACT_HEIGHT)
+    } else {
+        (WIDTH, HEIGHT)
+    });
+}
+
+/// Two smoothed parameters played together on a pad, each assignable and MIDI learnable.
+fn xy_pad_tab(
+    ui: &mut egui::Ui,
+    params: &WhirlpoolParams,
+    setter: &ParamSetter,
+    meters: &Meters,
+    touch_mode: bool,
+) {
+    let Ok(mut axes) = params.xy_axes.write() else {
+        return;
+    };
+    let before = axes.clone();
+    let learning = meters.xy_learning.load(Ordering::Relaxed);
+
+    egui::Grid::new("xy_axes").num_columns(4).show(ui, |ui| {
+        for (axis, name) in ["X", "Y"].into_iter().enumerate() {
+            let mut param = axes.param(axis);
+            ui.label(name);
+            egui::ComboBox::from_id_salt(("xy_axis", axis))
+                .selected_text(params.smoothed(param).name())
+                .show_ui(ui, |ui| {
+                    for option in Smoothed::ALL {
+                        ui.selectable_value(&mut param, option, params.smoothed(option).name());
+                    }
+                });
+            axes.axes[axis].param = param.id().to_owned();
+
+            if learning == axis as i8 {
+                ui.label(egui::RichText::new("Move a controller...").color(SPECTRUM));
+            } else {
+                match axes.axes[axis].cc {
+                    Some(cc) => ui.label(format!("CC {cc}")),
+                    None => ui.label(egui::RichText::new("No CC").color(GRID)),
+                };
+            }
+            ui.horizontal(|ui| {
+                if ui
+                    .button(tr(params, "Learn"))
+                    .on_hover_text("Assign the next MIDI CC to this axis")
+                    .clicked()
+                {
+                    params.xy_learn.store(axis as i8, Ordering::Release);
+                }
+                if ui.button(tr(params, "Clear")).clicked() {
+                    axes.axes[axis].cc = None;
+                }
+            });
+            ui.end_row();
+        }
+    });
+    if ui.button(tr(params, "Reset")).clicked() {
+        *axes = XyAxes::default();
+    }
+    ui.add_space(6.0);
+
+    let external = std::array::from_fn(|axis| {
+        let position = meters.xy_cc[axis].load(Ordering::Relaxed);
+        (position >= 0.0).then_some(position)
+    });
+    ui.add(
+        XyPad::for_params(
+            params.smoothed(axes.param(0)),
+            params.smoothed(axes.param(1)),
+            setter,
+        )
+        .with_external(external)
+        .with_touch_mode(touch_mode),
+    );
+
+    if *axes != before {
+        params.xy_axes_changed.store(true, Ordering::Release);
+    }
+}
+
+/// The motion sequencer's settings and steps.
+fn motion_tab(ui: &mut egui::Ui, params: &WhirlpoolParams, setter: &ParamSetter, meters: &Meters) {
+    let Ok(mut sequence) = params.motion_sequence.write() else {
+        return;
+    };
+    let before = sequence.clone();
+    sequence.steps.resize(MAX_STEPS, MotionStep::default());
+
+    egui::Grid::new("motion").num_columns(2).show(ui, |ui| {
+        ui.label(tr(params, "Motion"));
+        param_slider(ui, &params.motion, setter);
+        ui.end_row();
+        ui.label(tr(params, "Record"));
+        param_slider(ui, &params.motion_record, setter)
+            .on_hover_text("Write the parameter's own value into every step the sequence passes");
+        ui.end_row();
+        ui.label(tr(params, "Parameter"));
+        let mut param = sequence.param();
+        egui::ComboBox::from_id_salt("motion_param")
+            .selected_text(params.smoothed(param).name())
+            .show_ui(ui, |ui| {
+                for option in Smoothed::ALL {
+                    ui.selectable_value(&mut param, option, params.smoothed(option).name());
+                }
+            });
+        sequence.param = param.id().to_owned();
+        ui.end_row();
+        ui.label(tr(params, "Steps"));
+        ui.horizontal(|ui| {
+            param_slider(ui, &params.motion_steps, setter);
+            param_slider(ui, &params.motion_division, setter);
+        });
+        ui.end_row();
+    });
+    ui.add_space(6.0);
+
+    let length = (params.motion_steps.value() as usize).min(MAX_STEPS);
+    let playing = usize::try_from(meters.motion_step.load(Ordering::Relaxed)).ok();
+    step_editor(ui, &mut sequence.steps[..length], playing);
+    if ui.button(tr(params, "Reset")).clicked() {
+        *sequence = MotionSequence::default();
+    }
+
+    if *sequence != before {
+        params.motion_changed.store(true, Ordering::Release);
+    }
+    drop(sequence);
+
+    ui.separator();
+    egui::Grid::new("euclid").num_columns(2).show(ui, |ui| {
+        ui.label(tr(params, "Euclid"));
+        ui.horizontal(|ui| {
+            param_slider(ui, &params.euclid, setter);
+            param_slider(ui, &params.euclid_division, setter);
+        });
+        ui.end_row();
+        ui.label(tr(params, "Steps"));
+        ui.horizontal(|ui| {
+            param_slider(ui, &params.euclid_steps, setter);
+            param_slider(ui, &params.euclid_fills, setter);
+            param_slider(ui, &params.euclid_rotation, setter);
+        });
+        ui.end_row();
+    });
+    let pattern = EuclidPattern {
+        steps: params.euclid_steps.value() as usize,
+        fills: params.euclid_fills.value() as usize,
+        rotation: params.euclid_rotation.value() as usize,
+    };
+    let playing = usize::try_from(meters.euclid_step.load(Ordering::Relaxed)).ok();
+    euclid_display(ui, pattern, playing);
+}
+
+/// A dot for every step of the Euclidean pattern, filled for hits, with the playing one
+/// highlighted.
+fn euclid_display(ui: &mut egui::Ui, pattern: EuclidPattern, playing: Option<usize>) {
+    let size = egui::vec2(ui.available_width(), 24.0);
+    let (rect, response) = ui.allocate_exact_size(size, Sense::hover());
+    access::describe_display(&response, "Euclid", || {
+        let hits: Vec<String> = (0..pattern.steps)
+            .filter(|&step| pattern.hit(step))
+            .map(|step| (step + 1).to_string())
+            .collect();
+        format!("hits on steps {} of {}", hits.join(", "), pattern.steps)
+    });
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 4.0, BACKGROUND);
+
+    let width = rect.width() / pattern.steps.max(1) as f32;
+    let radius = (width * 0.5 - 2.0).clamp(1.0, 6.0);
+    for step in 0..pattern.steps {
+        let center = Pos2::new(rect.left() + (step as f32 + 0.5) * width, rect.center().y);
+        let color = if playing == Some(step) {
+            SPECTRUM
+        } else {
+            CURVE
+        };
+        if pattern.hit(step) {
+            painter.circle_filled(center, radius, color);
+        } else {
+            painter.circle_stroke(center, radius, Stroke::new(1.0, color.gamma_multiply(0.5)));
+        }
+    }
+}
+
+/// Bars for the motion steps with the playing one highlighted. Drag over them to draw their
+/// values, right-click a step to make it glide into the next.
+fn step_editor(ui: &mut egui::Ui, steps: &mut [MotionStep], playing: Option<usize>) {
+    let size = egui::vec2(ui.available_width(), 160.0);
+    let (response, painter) = ui.allocate_painter(size, Sense::click_and_drag());
+    let rect = response.rect;
+    painter.rect_filled(rect, 4.0, BACKGROUND);
+    if steps.is_empty() {
+        return;
+    }
+
+    let width = rect.width() / steps.len() as f32;
+    let step_at =
+        |pos: Pos2| (((pos.x - rect.left()) / width).max(0.0) as usize).min(steps.len() - 1);
+    let value_y = |value: f32| rect.bottom() - value * rect.height();
+    if let Some(pos) = response.interact_pointer_pos() {
+        let idx = step_at(pos);
+        if response.secondary_clicked() {
+            steps[idx].glide = !steps[idx].glide;
+        } else if response.clicked() || response.dragged() {
+            steps[idx].value = ((rect.bottom() - pos.y) / rect.height()).clamp(0.0, 1.0);
+        }
+    }
+    access::describe_display(&response, "Steps", || {
+        let values: Vec<String> = steps
+            .iter()
+            .map(|step| format!("{:.0} %", step.value * 100.0))
+            .collect();
+        values.join(", ")
+    });
+
+    for (idx, step) in steps.iter().enumerate() {
+        let left = rect.left() + idx as f32 * width;
+        let color = if playing == Some(idx) {
+            SPECTRUM
+        } else {
+            CURVE
+        };
+        let bar = Rect::from_min_max(
+            Pos2::new(left + 1.0, value_y(step.value)),
+            Pos2::new(left + width - 1.0, rect.bottom()),
+        );
+        painter.rect_filled(bar, 0.0, color.gamma_multiply(0.5));
+        if step.glide {
+            let next = steps[(idx + 1) % steps.len()];
+            painter.line_segment(
+                [
+                    Pos2::new(left + width * 0.5, value_y(step.value)),
+                    Pos2::new(left + width * 1.5, value_y(next.value)),
+                ],
+                Stroke::new(2.0, color),
+            );
+        }
+    }
+}
+
+/// The granular delay in the feedback path and its modulation.
+fn delay_tab(ui: &mut egui::Ui, params: &WhirlpoolParams, setter: &ParamSetter, meters: &Meters) {
+    egui::Grid::new("delay_params")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label(tr(params, "Grain Feedback"));
+            param_slider(ui, &params.grain_feedback, setter);
+            ui.end_row();
+            ui.label(tr(params, "Delay Time"));
+            param_slider(ui, &params.delay_time, setter);
+            ui.end_row();
+            ui.label(tr(params, "Delay Feedback"));
+            param_slider(ui, &params.delay_feedback, setter);
+            ui.end_row();
+            ui.label(tr(params, "Hold"));
+            param_slider(ui, &params.delay_hold, setter);
+            ui.end_row();
+            ui.label(tr(params, "Stretch"));
+            param_slider(ui, &params.stretch, setter);
+            ui.end_row();
+            ui.label(tr(params, "Reverse"));
+            param_slider(ui, &params.grain_reverse, setter);
+            ui.end_row();
+            ui.label(tr(params, "Tape Stop"));
+            ui.horizontal(|ui| {
+                param_slider(ui, &params.tape_stop, setter);
+                param_slider(ui, &params.tape_stop_time, setter);
+            });
+            ui.end_row();
+            ui.label(tr(params, "Macro Notes"));
+            ui.horizontal(|ui| {
+                param_slider(ui, &params.macro_notes, setter);
+                param_slider(ui, &params.macro_base_note, setter);
+            });
+            ui.end_row();
+            ui.label(tr(params, "Wow / Flutter"));
+            ui.horizontal(|ui| {
+                param_slider(ui, &params.delay_wow, setter);
+                param_slider(ui, &params.delay_flutter, setter);
+            });
+            ui.end_row();
+            ui.label(tr(params, "Saturation"));
+            param_slider(ui, &params.delay_saturation, setter);
+            ui.end_row();
+            ui.label(tr(params, "Cross Feedback"));
+            param_slider(ui, &params.delay_cross, setter);
+            ui.end_row();
+            ui.label(tr(params, "Grain Source"));
+            param_slider(ui, &params.grain_source, setter);
+            ui.end_row();
+            ui.label(tr(params, "Grain Voices"));
+            ui.horizontal(|ui| {
+                param_slider(ui, &params.grain_voices, setter);
+                param_slider(ui, &params.grain_normalize, setter);
+                cpu_guard_indicator(ui, params, meters);
+            });
+            ui.end_row();
+            ui.label(tr(params, "Grain Shape"));
+            param_slider(ui, &params.grain_shape, setter);
+            ui.end_row();
+            ui.label(tr(params, "Jitter"));
+            ui.horizontal(|ui| {
+                param_slider(ui, &params.jitter_distribution, setter);
+                param_slider(ui, &params.jitter_bias, setter);
+            });
+            ui.end_row();
+            ui.label(tr(params, "Grain Chord"));
+            ui.horizontal(|ui| {
+                param_slider(ui, &params.grain_chord, setter);
+                param_slider(ui, &params.chord_weight, setter);
+            });
+            ui.end_row();
+            ui.label(tr(params, "Surround Spread"));
+            param_slider(ui, &params.surround_spread, setter);
+            ui.end_row();
+            ui.label(tr(params, "Grain Filter"));
+            ui.horizontal(|ui| {
+                param_slider(ui, &params.grain_filter, setter);
+                param_slider(ui, &params.grain_filter_type, setter);
+            });
+            ui.end_row();
+            ui.label(tr(params, "Grain Cutoff"));
+            param_slider(ui, &params.grain_cutoff, setter);
+            ui.end_row();
+            ui.label(tr(params, "Cutoff Spread"));
+            param_slider(ui, &params.grain_cutoff_spread, setter);
+            ui.end_row();
+            ui.label(tr(params, "Grain Sync"));
+            ui.horizontal(|ui| {
+                param_slider(ui, &params.grain_sync, setter);
+                param_slider(ui, &params.grain_division, setter);
+            });
+            ui.end_row();
+            ui.label(tr(params, "MIDI Grains"));
+            ui.horizontal(|ui| {
+                param_slider(ui, &params.midi_grains, setter);
+                param_slider(ui, &params.grain_velocity, setter);
+                param_slider(ui, &params.grain_key_track, setter);
+            });
+            ui.end_row();
+            ui.label(tr(params, "Invert Feedback"));
+            param_slider(ui, &params.delay_invert, setter);
+            ui.end_row();
+            ui.label(tr(params, "Mod Rate"));
+            param_slider(ui, &params.delay_mod_rate, setter);
+            ui.end_row();
+            ui.label(tr(params, "Mod Depth"));
+            param_slider(ui, &params.delay_mod_depth, setter);
+            ui.end_row();
+            ui.label(tr(params, "Mod Shape"));
+            param_slider(ui, &params.delay_mod_shape, setter);
+            ui.end_row();
+            ui.label(tr(params, "CPU Guard"));
+            param_slider(ui, &params.cpu_guard, setter);
+            ui.end_row();
+        });
+}
+
+/// Launcher pads for the freeze bank. An empty pad captures the current spectrum, a filled one
+/// plays it and the playing one returns to the live signal.
+fn freeze_bank_tab(
+    ui: &mut egui::Ui,
+    params: &WhirlpoolParams,
+    setter: &ParamSetter,
+    meters: &Meters,
+) {
+    let filled = meters.slots_filled.load(Ordering::Relaxed);
+    let active = meters.active_slot.load(Ordering::Relaxed);
+
+    egui::Grid::new("slot_pads").num_columns(4).show(ui, |ui| {
+        for slot in 0..NUM_SLOTS {
+            let fill = if active == slot as i8 {
+                SPECTRUM
+            } else if filled & (1 << slot) != 0 {
+                CURVE
+            } else {
+                GRID
+            };
+            let pad = egui::Button::new(
+                egui::RichText::new(format!("{}", slot + 1))
+                    .strong()
+                    .color(BACKGROUND),
+            )
+            .fill(fill)
+            .min_size(egui::vec2(72.0, 56.0));
+            let response = ui
+                .add(pad)
+                .on_hover_text("Click to capture or launch, right-click to erase");
+            if response.clicked() {
+                params.slot_launch.fetch_or(1 << slot, Ordering::AcqRel);
+            } else if response.secondary_clicked() {
+                params.slot_erase.fetch_or(1 << slot, Ordering::AcqRel);
+            }
+            if slot % 4 == 3 {
+                ui.end_row();
+            }
+        }
+    });
+    ui.add_space(8.0);
+
+    egui::Grid::new("slot_params")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label(tr(params, "Slot Fade"));
+            param_slider(ui, &params.slot_fade, setter);
+            ui.end_row();
+            ui.label(tr(params, "Slot Notes"));
+            param_slider(ui, &params.slot_notes, setter);
+            ui.end_row();
+            ui.label(tr(params, "Base Note"));
+            param_slider(ui, &params.slot_base_note, setter);
+            ui.end_row();
+        });
+}
+
+/// Spectrum and waveform of the input, the wet signal or the output. Both can be paused and
+/// zoomed into, the waveform spans an adjustable time.
+fn analyzer_tab(
+    ui: &mut egui::Ui,
+    params: &WhirlpoolParams,
+    meters: &Meters,
+    state: &mut EditorState,
+) {
+    let mut tap = AnalyzerTap::from_index(params.analyzer_tap.load(Ordering::Relaxed));
+    let mut window_ms = params.scope_window.load(Ordering::Relaxed);
+    ui.horizontal(|ui| {
+        ui.label(tr(params, "Listen to"));
+        for option in AnalyzerTap::ALL {
+            ui.selectable_value(&mut tap, option, option.label());
+        }
+        ui.separator();
+        let label = if state.analyzer_paused.is_some() {
+            "Resume"
+        } else {
+            "Pause"
+        };
+        if ui.button(tr(params, label)).clicked() {
+            state.analyzer_paused = match state.analyzer_paused {
+                Some(_) => None,
+                None => meters.analyzer.lock().ok().map(|data| data.clone()),
+            };
+        }
+        ui.separator();
+        let mut grid = !state.hide_grid;
+        ui.checkbox(&mut grid, tr(params, "Grid"));
+        state.hide_grid = !grid;
+        ui.separator();
+        ui.label(tr(params, "Time"));
+        ui.add(
+            egui::Slider::new(&mut window_ms, MIN_SCOPE_MS..=MAX_SCOPE_MS)
+                .logarithmic(true)
+                .suffix(" ms"),
+        );
+    });
+    params.analyzer_tap.store(tap.index(), Ordering::Relaxed);
+    params.scope_window.store(window_ms, Ordering::Relaxed);
+    ui.add_space(6.0);
+
+    let live = meters.analyzer.lock();
+    let data = match (&state.analyzer_paused, &live) {
+        (Some(paused), _) => paused,
+        (None, Ok(live)) => &**live,
+        (None, Err(_)) => return,
+    };
+    // Until the audio thread publishes the new tap, the display shows the old one
+    let stale = state.analyzer_paused.is_none() && data.tap != tap;
+    tuner(
+        ui,
+        params,
+        meters.pitch.load(Ordering::Relaxed),
+        if stale { &[] } else { &data.partials },
+    );
+    ui.add_space(6.0);
+
+    let height = (ui.available_height() - 6.0) * 0.65;
+    let (response, painter) = ui.allocate_painter(
+        egui::vec2(ui.available_width(), height.max(120.0)),
+        Sense::click_and_drag(),
+    );
+    let rect = response.rect;
+    let zoom = &mut state.spectrum_zoom;
+    painter.rect_filled(rect, 4.0, BACKGROUND);
+    let grid = !state.hide_grid;
+    draw_freq_grid(&painter, rect, *zoom, grid);
+    for level_db in (FLOOR_DB as i32..0).step_by(SPECTRUM_STEP_DB).skip(1) {
+        let y = level_to_y(rect, level_db as f32);
+        draw_level_line(&painter, rect, y, &format!("{level_db} dB"), grid);
+    }
+    if !stale {
+        let bin_hz = data.sample_rate / (data.spectrum_db.len() * 2) as f32;
+        let line: Vec<Pos2> = data
+            .spectrum_db
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(bin, level_db)| {
+                let unit = spectral_eq::freq_to_unit(bin as f32 * bin_hz);
+                Pos2::new(zoom.x(rect, unit), level_to_y(rect, *level_db))
+            })
+            .collect();
+        painter.add(Shape::line(line, Stroke::new(1.5, SPECTRUM)));
+    }
+    zoom.interact(&response, &painter);
+
+    ui.add_space(6.0);
+    let (response, painter) = ui.allocate_painter(
+        egui::vec2(ui.available_width(), ui.available_height().max(60.0)),
+        Sense::click_and_drag(),
+    );
+    let rect = response.rect;
+    let zoom = &mut state.scope_zoom;
+    painter.rect_filled(rect, 4.0, BACKGROUND);
+    painter.line_segment(
+        [
+            Pos2::new(rect.left(), rect.center().y),
+            Pos2::new(rect.right(), rect.center().y),
+        ],
+        Stroke::new(1.0, GRID),
+    );
+    for level_db in SCOPE_LEVELS_DB {
+        let gain = util::db_to_gain(level_db);
+        let label = format!("{level_db} dB");
+        draw_level_line(&painter, rect, sample_to_y(rect, gain), &label, grid);
+        draw_level_line(&painter, rect, sample_to_y(rect, -gain), "", grid);
+    }
+    if !stale && data.scope_span > 0.0 {
+        // The history spans at least the window, its newest columns fill the display
+        let window = window_ms as f32 / 1000.0;
+        let shown = ((window / data.scope_span * SCOPE_POINTS as f32).ceil() as usize)
+            .clamp(1, data.scope.len());
+        let columns = &data.scope[data.scope.len() - shown..];
+        let first = (zoom.start * shown as f32).floor() as usize;
+        let last = ((zoom.end * shown as f32).ceil() as usize).clamp(first + 1, shown);
+        let width = rect.width() / (last - first) as f32;
+        for (idx, [low, high]) in columns[first..last].iter().enumerate() {
+            let x = rect.left() + (idx as f32 + 0.5) * width;
+            painter.line_segment(
+                [
+                    Pos2::new(x, sample_to_y(rect, *high) - 0.5),
+                    Pos2::new(x, sample_to_y(rect, *low) + 0.5),
+                ],
+                Stroke::new(width.max(1.0), SPECTRUM),
+            );
+        }
+    }
+    zoom.interact(&response, &painter);
+}
+
+fn settings_tab(
+    ui: &mut egui::Ui,
+    params: &WhirlpoolParams,
+    setter: &ParamSetter,
+    meters: &Meters,
+    state: &mut EditorState,
+) {
+    language_settings(ui, params);
+    ui.add_space(12.0);
+    preset_sharing(ui, params, setter, state);
+    ui.add_space(12.0);
+    knob_settings(ui, params, state);
+    ui.add_space(12.0);
+    smoothing_settings(ui, params);
+    ui.add_space(12.0);
+    morph_settings(ui, params, &mut state.toasts);
+    ui.add_space(12.0);
+    clock_settings(ui, params, setter, state);
+    ui.add_space(12.0);
+    analysis_settings(ui, params, setter, meters);
+    ui.add_space(12.0);
+    buffer_settings(ui, params, meters);
+    ui.add_space(12.0);
+    diagnostics(ui, meters, &mut state.toasts);
+}
+
+fn language_settings(ui: &mut egui::Ui, params: &WhirlpoolParams) {
+    ui.horizontal(|ui| {
+        ui.label(tr(params, "Language"));
+        let mut language = language(params);
+        for option in Language::ALL {
+            ui.selectable_value(&mut language, option, option.name());
+        }
+        params.language.store(language.index(), Ordering::Relaxed);
+    });
+}
+
+/// Copies the whole state as text for sharing, and applies text shared by others.
+fn preset_sharing(
+    ui: &mut egui::Ui,
+    params: &WhirlpoolParams,
+    setter: &ParamSetter,
+    state: &mut EditorState,
+) {
+    let context = setter.raw_context;
+    ui.horizontal(|ui| {
+        ui.label(tr(params, "Share Preset"));
+        if ui.button(tr(params, "Copy Preset")).clicked() {
+            copy_preset(ui.ctx(), setter, state);
+        }
+        ui.checkbox(&mut state.share_freeze, tr(params, "With Freeze"));
+    });
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut state.preset_text)
+                .hint_text(tr(params, "Paste a preset here")),
+        );
+        if ui.button(tr(params, "Paste Preset")).clicked() {
+            match share::decode(&state.preset_text, &context.get_state()) {
+                Ok(preset) => {
+                    context.set_state(preset);
+                    state.preset_text.clear();
+                    state.toasts.push(ToastKind::Info, "Preset applied");
+                }
+                Err(err) => state.toasts.push(ToastKind::Warning, err),
+            }
+        }
+    });
+}
+
+/// Puts the state on the clipboard as preset text.
+fn copy_preset(ctx: &egui::Context, setter: &ParamSetter, state: &mut EditorState) {
+    let text = share::encode(setter.raw_context.get_state(), state.share_freeze);
+    ctx.copy_text(text);
+    state
+        .toasts
+        .push(ToastKind::Info, "Preset copied to the clipboard");
+}
+
+/// How the knobs drag and map onto their parameters, saved for the user on every change.
+fn knob_settings(ui: &mut egui::Ui, params: &WhirlpoolParams, state: &mut EditorState) {
+    let prefs = &mut state.knob_prefs;
+    let before = prefs.clone();
+
+    ui.horizontal(|ui| {
+        ui.label(tr(params, "Knobs"));
+        if ui.button(tr(params, "Reset")).clicked() {
+            *prefs = KnobPrefs::default();
+        }
+    });
+    egui::Grid::new("knobs").num_columns(2).show(ui, |ui| {
+        ui.label(tr(params, "Drag Sensitivity"));
+        ui.add(
+            egui::Slider::new(&mut prefs.sensitivity, MIN_SENSITIVITY..=MAX_SENSITIVITY)
+                .logarithmic(true)
+                .suffix(" ×"),
+        );
+        ui.end_row();
+        ui.label(tr(params, "Touch Mode"));
+        ui.checkbox(&mut prefs.touch_mode, "");
+        ui.end_row();
+        ui.label(tr(params, "Drag Mode"));
+        ui.horizontal(|ui| {
+            for mode in DragMode::ALL {
+                ui.selectable_value(&mut prefs.drag_mode, mode, tr(params, mode.name()));
+            }
+        });
+        ui.end_row();
+
+        for param in knob_params(params) {
+            let mut curve = prefs.curve(param.name());
+            ui.label(tr(params, param.name()));
+            ui.horizontal(|ui| {
+                for option in ResponseCurve::ALL {
+                    ui.selectable_value(&mut curve, option, tr(params, option.name()));
+                }
+            });
+            prefs.set_curve(param.name(), curve);
+            ui.end_row();
+        }
+    });
+
+    if *prefs != before {
+        if let Err(err) = prefs.save() {
+            state.toasts.push(
+                ToastKind::Warning,
+                format!("Could not save knob settings: {err}"),
+            );
+        }
+    }
+}
+
+/// Automation smoothing times. Every parameter follows the global time unless it has its own.
+fn smoothing_settings(ui: &mut egui::Ui, params: &WhirlpoolParams) {
+    let Ok(mut times) = params.smoothing.write() else {
+        return;
+    };
+    let before = times.clone();
+
+    ui.horizontal(|ui| {
+        ui.label(tr(params, "Automation Smoothing"));
+        if ui.button(tr(params, "Reset")).clicked() {
+            *times = SmoothingTimes::default();
+        }
+    });
+    egui::Grid::new("smoothing").num_columns(3).show(ui, |ui| {
+        ui.label(tr(params, "Global"));
+        ui.label("");
+        ui.add(smoothing_slider(&mut times.global_ms));
+        ui.end_row();
+
+        for param in Smoothed::ALL {
+            let mut own_time = times.overrides.contains_key(param.id());
+            let mut time_ms = times.time_ms(param);
+            ui.label(params.smoothed(param).name());
+            ui.checkbox(&mut own_time, "Own time");
+            ui.add_enabled(own_time, smoothing_slider(&mut time_ms));
+            if own_time {
+                times.overrides.insert(param.id().to_owned(), time_ms);
+            } else {
+                times.overrides.remove(param.id());
+            }
+            ui.end_row();
+        }
+    });
+
+    if *times != before {
+        params.smoothing_changed.store(true, Ordering::Release);
+    }
+}
+
+/// Stores the current values as morph preset A or B and picks the parameters the morph drives.
+fn morph_settings(ui: &mut egui::Ui, params: &WhirlpoolParams, toasts: &mut Toasts) {
+    let Ok(mut presets) = params.morph_presets.write() else {
+        return;
+    };
+    let before = presets.clone();
+
+    ui.horizontal(|ui| {
+        ui.label(tr(params, "Morph Presets"));
+        if ui.button(tr(params, "Store A")).clicked() {
+            presets.a = morph_snapshot(params);
+            toasts.push(ToastKind::Info, "Stored morph preset A");
+        }
+        if ui.button(tr(params, "Store B")).clicked() {
+            presets.b = morph_snapshot(params);
+            toasts.push(ToastKind::Info, "Stored morph preset B");
+        }
+        if ui.button(tr(params, "Clear")).clicked() {
+            *presets = MorphPresets::default();
+            toasts.push(ToastKind::Info, "Cleared the morph presets");
+        }
+    });
+    ui.horizontal_wrapped(|ui| {
+        for param in Smoothed::ALL {
+            let mut included = !presets.excluded.contains(param.id());
+            if ui
+                .checkbox(&mut included, params.smoothed(param).name())
+                .changed()
+            {
+                if included {
+                    presets.excluded.remove(param.id());
+                } else {
+                    presets.excluded.insert(param.id().to_owned());
+                }
+            }
+        }
+    });
+
+    if *presets != before {
+        params.morph_changed.store(true, Ordering::Release);
+    }
+}
+
+fn morph_snapshot(params: &WhirlpoolParams) -> BTreeMap<String, f32> {
+    Smoothed::ALL
+        .into_iter()
+        .map(|param| {
+            let value = params.smoothed(param).unmodulated_normalized_value();
+            (param.id().to_owned(), value)
+        })
+        .collect()
+}
+
+/// The internal clock tempo-synced features fall back to when the host has no tempo, and the
+/// groove applied to their events.
+fn clock_settings(
+    ui: &mut egui::Ui,
+    params: &WhirlpoolParams,
+    setter: &ParamSetter,
+    state: &mut EditorState,
+) {
+    ui.label(tr(params, "Internal Clock"));
+    egui::Grid::new("clock").num_columns(2).show(ui, |ui| {
+        ui.label(tr(params, "Tempo"));
+        ui.horizontal(|ui| {
+            param_slider(ui, &params.internal_bpm, setter);
+            if ui.button(tr(params, "Tap")).clicked() {
+                let now = ui.input(|i| i.time);
+                if let Some(bpm) = state.tap_tempo.tap(now) {
+                    setter.begin_set_parameter(&params.internal_bpm);
+                    setter.set_parameter(&params.internal_bpm, bpm as f32);
+                    setter.end_set_parameter(&params.internal_bpm);
+                }
+            }
+        });
+        ui.end_row();
+        ui.label(tr(params, "Run"));
+        param_slider(ui, &params.internal_run, setter);
+        ui.end_row();
+        ui.label(tr(params, "Swing"));
+        param_slider(ui, &params.swing, setter);
+        ui.end_row();
+        ui.label(tr(params, "Humanize"));
+        param_slider(ui, &params.humanize, setter);
+        ui.end_row();
+    });
+}
+
+/// STFT window and frame size, their time and frequency resolution and whether the windows
+/// overlap-add to a constant.
+fn analysis_settings(
+    ui: &mut egui::Ui,
+    params: &WhirlpoolParams,
+    setter: &ParamSetter,
+    meters: &Meters,
+) {
+    let sample_rate = meters.sample_rate.load(Ordering::Relaxed);
+    let ripple = meters.cola_ripple.load(Ordering::Relaxed);
+    let mut size = fft_size::from_setting(params.fft_size.load(Ordering::Relaxed));
+    let hop = size * HOP_SIZE / FFT_SIZE;
+
+    ui.label(tr(params, "Analysis"));
+    egui::Grid::new("analysis").num_columns(2).show(ui, |ui| {
+        ui.label(tr(params, "Window"));
+        param_slider(ui, &params.window, setter);
+        ui.end_row();
+        ui.label(tr(params, "Pre-Emphasis"));
+        param_slider(ui, &params.pre_emphasis, setter);
+        ui.end_row();
+        ui.label(tr(params, "Frame"));
+        egui::ComboBox::from_id_salt("fft_size")
+            .selected_text(format!(
+                "{size} samples ({:.1} ms)",
+                size as f32 / sample_rate * 1000.0
+            ))
+            .show_ui(ui, |ui| {
+                for option in fft_size::sizes() {
+                    ui.selectable_value(&mut size, option, format!("{option} samples"));
+                }
+            })
+            .response
+            .on_hover_text("Shorter frames smear transients less, longer ones resolve pitch finer");
+        ui.end_row();
+        ui.label(tr(params, "Hop"));
+        ui.label(format!(
+            "{hop} samples ({:.1} ms), {}x overlap",
+            hop as f32 / sample_rate * 1000.0,
+            size / hop
+        ));
+        ui.end_row();
+        ui.label(tr(params, "Resolution"));
+        ui.label(format!("{:.1} Hz per bin", sample_rate / size as f32));
+        ui.end_row();
+        ui.label(tr(params, "Overlap-Add"));
+        if ripple <= COLA_TOLERANCE {
+            ui.label(format!("Constant ({:.2} % ripple)", ripple * 100.0));
+        } else {
+            ui.label(egui::RichText::new(format!("{:.1} % ripple", ripple * 100.0)).color(CLIP))
+                .on_hover_text(
+                    "The windows do not overlap-add to a constant, expect level modulation",
+                );
+        }
+        ui.end_row();
+    });
+    params.fft_size.store(size as u16, Ordering::Relaxed);
+}
+
+/// Seconds of audio the granular delay holds, and the memory its buffers take.
+fn buffer_settings(ui: &mut egui::Ui, params: &WhirlpoolParams, meters: &Meters) {
+    let mut seconds = params.buffer_length.load(Ordering::Relaxed);
+    // Both channels' buffers of 32-bit samples
+    let bytes = meters.grain_buffer.load(Ordering::Relaxed) as f32 * 2.0 * 4.0;
+
+    ui.label(tr(params, "Grain Buffer"));
+    egui::Grid::new("grain_buffer")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label(tr(params, "Length"));
+            ui.add(egui::Slider::new(&mut seconds, MIN_BUFFER..=MAX_BUFFER).suffix(" s"))
+                .on_hover_text("Longer buffers let a slow Stretch reach further back");
+            ui.end_row();
+            ui.label(tr(params, "Memory"));
+            ui.label(format!("{:.1} MB", bytes / 1_000_000.0));
+            ui.end_row();
+        });
+    params.buffer_length.store(seconds, Ordering::Relaxed);
+}
+
+/// What a support request needs to know about the host setup, with a button to copy it as text.
+fn diagnostics(ui: &mut egui::Ui, meters: &Meters, toasts: &mut Toasts) {
+    let rows = diagnostics_rows(meters);
+    ui.horizontal(|ui| {
+        ui.label("Diagnostics");
+        if ui
+            .button("Copy")
+            .on_hover_text("Copy these details for a support request")
+            .clicked()
+        {
+            let report: String = rows
+                .iter()
+                .map(|(name, value)| format!("{name}: {value}\n"))
+                .collect();
+            ui.ctx().copy_text(report);
+            toasts.push(ToastKind::Info, "Copied the diagnostics");
+        }
+    });
+    egui::Grid::new("diagnostics")
+        .num_columns(2)
+        .show(ui, |ui| {
+            for (name, value) in &rows {
+                ui.label(*name);
+                ui.label(value);
+                ui.end_row();
+            }
+        });
+}
+
+fn diagnostics_rows(meters: &Meters) -> [(&'static str, String); 6] {
+    let sample_rate = meters.sample_rate.load(Ordering::Relaxed);
+    let block_size = meters.block_size.load(Ordering::Relaxed);
+    let max_block_size = meters.max_block_size.load(Ordering::Relaxed);
+    let block_time = meters.block_time.load(Ordering::Relaxed);
+    let latency = meters.latency.load(Ordering::Relaxed);
+    let fft_size = meters.fft_size.load(Ordering::Relaxed) as usize;
+    let load = if block_size > 0 {
+        block_time * sample_rate / block_size as f32
+    } else {
+        0.0
+    };
+    [
+        ("Version", env!("CARGO_PKG_VERSION").to_owned()),
+        ("Sample Rate", format!("{sample_rate} Hz")),
+        (
+            "Buffer Size",
+            format!("{block_size} samples, up to {max_block_size}"),
+        ),
+        (
+            "Latency",
+            format!(
+                "{latency} samples ({:.1} ms)",
+                latency as f32 / sample_rate * 1000.0
+            ),
+        ),
+        (
+            "FFT",
+            format!(
+                "{fft_size} samples, {} sample hop",
+                fft_size * HOP_SIZE / FFT_SIZE
+            ),
+        ),
+        (
+            "CPU",
+            format!(
+                "{:.2} ms per block, {:.0} % of real time",
+                block_time * 1000.0,
+                load * 100.0
+            ),
+        ),
+    ]
+}
+
+fn language(params: &WhirlpoolParams) -> Language {
+    Language::from_index(params.language.load(Ordering::Relaxed))
+}
+
+/// `text` in the editor's language.
+fn tr<'a>(params: &WhirlpoolParams, text: &'a str) -> &'a str {
+    language(params).translate(text)
+}
+
+fn smoothing_slider(time_ms: &mut f32) -> egui::Slider<'_> {
+    egui::Slider::new(time_ms, 0.0..=MAX_SMOOTHING_MS)
+        .logarithmic(true)
+        .suffix(" ms")
+}
+
+/// Formats the detected fundamental as note name, cents offset and frequency.
+fn pitch_readout(pitch: f32) -> String {
+    if pitch <= 0.0 {
+        return String::from("Pitch: --");
+    }
+
+    let (name, cents) = nearest_note(pitch);
+    format!("Pitch: {name} {cents:+} ct ({pitch:.1} Hz)")
+}
+
+/// Name and octave of the note nearest to `freq`, and how many cents `freq` is off it.
+fn nearest_note(freq: f32) -> (String, i32) {
+    let note = scale::freq_to_note(freq);
+    let nearest = note.round();
+    let cents = ((note - nearest) * 100.0).round() as i32;
+    let name = scale::NOTE_NAMES[(nearest as i32).rem_euclid(12) as usize];
+    let octave = (nearest as i32).div_euclid(12) - 1;
+    (format!("{name}{octave}"), cents)
+}
+
+/// The input's fundamental as a note with a cents needle, and the analyzed tap's loudest
+/// partials, to line Shift and the key up with what is playing.
+fn tuner(ui: &mut egui::Ui, params: &WhirlpoolParams, pitch: f32, partials: &[Partial]) {
+    ui.horizontal(|ui| {
+        ui.label(tr(params, "Tuner"));
+        let (name, cents) = if pitch > 0.0 {
+            nearest_note(pitch)
+        } else {
+            (String::from("--"), 0)
+        };
+        ui.label(egui::RichText::new(name).size(22.0).strong());
+
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(160.0, 18.0), Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 4.0, BACKGROUND);
+        painter.line_segment(
+            [rect.center_top(), rect.center_bottom()],
+            Stroke::new(1.0, GRID),
+        );
+        if pitch > 0.0 {
+            let x = rect.center().x + cents as f32 / 50.0 * rect.width() * 0.5;
+            let color = if cents.abs() <= 5 { SPECTRUM } else { CURVE };
+            painter.line_segment(
+                [Pos2::new(x, rect.top()), Pos2::new(x, rect.bottom())],
+                Stroke::new(3.0, color),
+            );
+            ui.label(format!("{cents:+} ct  {pitch:.1} Hz"));
+        }
+    });
+
+    ui.label(tr(params, "Partials"));
+    egui::Grid::new("partials")
+        .num_columns(4)
+        .striped(true)
+        .show(ui, |ui| {
+            for partial in partials {
+                let (name, cents) = nearest_note(partial.freq);
+                ui.label(format!("{:.1} Hz", partial.freq));
+                ui.label(name);
+                ui.label(format!("{cents:+} ct"));
+                ui.label(format!("{:.1} dB", partial.level_db));
+                ui.end_row();
+            }
+        });
+}
+
+fn tempo_readout(meters: &Meters) -> String {
+    let tempo = meters.tempo.load(Ordering::Relaxed);
+    let source = if meters.host_tempo.load(Ordering::Relaxed) {
+        "host"
+    } else {
+        "internal"
+    };
+    format!("{tempo:.1} BPM ({source})")
+}
+
+/// Shows the processing load while the CPU guard is on, highlighted when it is shedding grain
+/// voices.
+fn cpu_guard_indicator(ui: &mut egui::Ui, params: &WhirlpoolParams, meters: &Meters) {
+    if !params.cpu_guard.value() {
+        return;
+    }
+    let load = meters.cpu_load.load(Ordering::Relaxed) * 100.0;
+    let running = meters.grain_voices.load(Ordering::Relaxed) as i32;
+    let requested = params.grain_voices.value();
+    if running < requested {
+        ui.label(egui::RichText::new(format!("CPU {load:.0}%, {running} voices")).color(CLIP))
+            .on_hover_text(
+                "Processing is close to real time, grain voices are reduced until it recovers",
+            );
+    } else {
+        ui.label(egui::RichText::new(format!("CPU {load:.0}%")).color(GRID));
+    }
+}
+
+/// Learn button for the spectral gate's noise floor, and whether one has been learned.
+fn gate_learn_controls(ui: &mut egui::Ui, params: &WhirlpoolParams, meters: &Meters) {
+    if meters.gate_learning.load(Ordering::Relaxed) {
+        ui.label(egui::RichText::new("Learning...").color(SPECTRUM));
+        return;
+    }
+    if ui
+        .button(tr(params, "Learn"))
+        .on_hover_text("Play a couple of seconds of noise only to learn its spectrum")
+        .clicked()
+    {
+        params.gate_learn.store(true, Ordering::Release);
+    }
+    let learned = params
+        .gate_profile
+        .try_read()
+        .is_ok_and(|profile| profile.bins > 0);
+    if !learned {
+        ui.label(egui::RichText::new("No noise profile").color(GRID));
+    }
+}
+
+/// Highest inter-sample peak of the output since the last click, highlighted above the ceiling
+/// when limiting and above 0 dBTP otherwise.
+fn true_peak_readout(ui: &mut egui::Ui, params: &WhirlpoolParams, meters: &Meters) {
+    let peak_db = util::gain_to_db(meters.true_peak.load(Ordering::Relaxed));
+    let limit_db = if params.tp_limit.value() {
+        params.tp_ceiling.value()
+    } else {
+        0.0
+    };
+    let text = if peak_db <= util::MINUS_INFINITY_DB {
+        egui::RichText::new("TP -inf")
+    } else {
+        egui::RichText::new(format!("TP {peak_db:+.1} dBTP"))
+    };
+    // Leave a little room for rounding in the peak interpolation
+    let text = if peak_db > limit_db + 0.05 {
+        text.color(CLIP)
+    } else {
+        text.color(GRID)
+    };
+    let response = ui
+        .add(egui::Label::new(text).sense(Sense::click()))
+        .on_hover_text("Highest output true peak, click to reset");
+    if response.clicked() {
+        meters.true_peak.store(0.0, Ordering::Relaxed);
+    }
+}
+
+/// Latching overload light for the `tanh` stage. Click it to clear.
+fn clip_indicator(ui: &mut egui::Ui, meters: &Meters) {
+    let clipping = meters.clip.load(Ordering::Relaxed);
+    let text = egui::RichText::new("CLIP").strong();
+    let text = if clipping {
+        text.color(CLIP)
+    } else {
+        text.color(GRID)
+    };
+    let response = ui
+        .add(egui::Label::new(text).sense(Sense::click()))
+        .on_hover_text("Input is overloading the saturation stage, try a lower input pad");
+    if response.clicked() {
+        meters.clip.store(false, Ordering::Relaxed);
+    }
+}
+
+/// A row of 12 toggles showing the active scale. The toggles edit the custom mask and are only
+/// enabled while the `Custom` scale is selected.
+fn scale_keys(ui: &mut egui::Ui, params: &WhirlpoolParams) {
+    let scale = params.scale.value();
+    let custom_mask = params.custom_scale.load(Ordering::Relaxed);
+    let mask = scale.mask(params.key.value(), custom_mask);
+
+    ui.horizontal(|ui| {
+        for (pitch_class, name) in scale::NOTE_NAMES.iter().enumerate() {
+            let bit = 1 << pitch_class;
+            let toggle = egui::SelectableLabel::new(mask & bit != 0, *name);
+            if ui.add_enabled(scale == Scale::Custom, toggle).clicked() {
+                params.custom_scale.fetch_xor(bit, Ordering::Relaxed);
+            }
+        }
+    });
+}
+
+/// Breakpoint editor over a log-frequency axis. Click to add a point, drag to move it and
+/// right-click to remove it.
+fn eq_curve_editor(ui: &mut egui::Ui, params: &WhirlpoolParams, state: &mut EditorState) {
+    let size = egui::vec2(ui.available_width(), ui.available_height().max(120.0));
+    let (response, painter) = ui.allocate_painter(size, Sense::click_and_drag());
+    let rect = response.rect;
+
+    painter.rect_filled(rect, 4.0, BACKGROUND);
+    draw_freq_grid(&painter, rect, Zoom::default(), true);
+    let zero_y = gain_to_y(rect, 0.0);
+    painter.line_segment(
+        [
+            Pos2::new(rect.left(), zero_y),
+            Pos2::new(rect.right(), zero_y),
+        ],
+        Stroke::new(1.0, GRID),
+    );
+
+    let mut changed = false;
+    if let Ok(mut curve) = params.eq_curve.write() {
+        let hit_point = |pos: Pos2, points: &[EqPoint]| {
+            points
+                .iter()
+                .position(|p| point_pos(rect, p).distance(pos) <= POINT_RADIUS * 2.0)
+        };
+
+        if let Some(pos) = response.interact_pointer_pos() {
+            if response.drag_started() {
+                state.dragged_point = hit_point(pos, &curve.points);
+            } else if response.dragged() {
+                if let Some(idx) = state.dragged_point {
+                    curve.move_point(idx, pos_to_point(rect, pos));
+                    changed = true;
+                }
+            } else if response.secondary_clicked() {
+                if let Some(idx) = hit_point(pos, &curve.points) {
+                    curve.remove(idx);
+                    changed = true;
+                }
+            } else if response.clicked() && hit_point(pos, &curve.points).is_none() {
+                curve.insert(pos_to_point(rect, pos));
+                changed = true;
+            }
+        }
+        if response.drag_stopped() {
+            state.dragged_point = None;
+        }
+        access::describe_display(&response, "Spectral EQ", || {
+            let points: Vec<String> = curve
+                .points
+                .iter()
+                .map(|point| format!("{:.0} Hz {:+.1} dB", point.freq, point.gain_db))
+                .collect();
+            if points.is_empty() {
+                "flat".to_owned()
+            } else {
+                points.join(", ")
+            }
+        });
+
+        let steps = rect.width().max(2.0) as usize;
+        let line: Vec<Pos2> = (0..=steps)
+            .map(|i| {
+                let unit = i as f32 / steps as f32;
+                let gain_db = curve.gain_db_at(spectral_eq::unit_to_freq(unit));
+                Pos2::new(rect.left() + unit * rect.width(), gain_to_y(rect, gain_db))
+            })
+            .collect();
+        painter.add(Shape::line(line, Stroke::new(2.0, CURVE)));
+        for point in &curve.points {
+            painter.circle_filled(point_pos(rect, point), POINT_RADIUS, CURVE);
+        }
+    }
+
+    if changed {
+        params.eq_curve_changed.store(true, Ordering::Release);
+    }
+}
+
+/// Decade lines on the same logarithmic axis as the EQ curve.
+/// Labels the frequencies in view, with a vertical line for each if `lines` is set.
+fn draw_freq_grid(painter: &egui::Painter, rect: Rect, zoom: Zoom, lines: bool) {
+    let mut last_label = f32::NEG_INFINITY;
+    for freq in FREQ_LABELS {
+        let x = zoom.x(rect, spectral_eq::freq_to_unit(freq));
+        if x < rect.left() || x > rect.right() || x - last_label < MIN_LABEL_SPACING {
+            continue;
+        }
+        last_label = x;
+        if lines {
+            painter.line_segment(
+                [Pos2::new(x, rect.top()), Pos2::new(x, rect.bottom())],
+                Stroke::new(1.0, GRID),
+            );
+        }
+        painter.text(
+            Pos2::new(x + 3.0, rect.bottom() - 3.0),
+            Align2::LEFT_BOTTOM,
+            if freq >= 1000.0 {
+                format!("{}k", freq / 1000.0)
+            } else {
+                format!("{freq}")
+            },
+            FontId::proportional(10.0),
+            LABEL,
+        );
+    }
+}
+
+/// Labels the level at `y`, with a horizontal line across `rect` if `line` is set.
+fn draw_level_line(painter: &egui::Painter, rect: Rect, y: f32, label: &str, line: bool) {
+    if line {
+        painter.line_segment(
+            [Pos2::new(rect.left(), y), Pos2::new(rect.right(), y)],
+            Stroke::new(1.0, GRID),
+        );
+    }
+    painter.text(
+        Pos2::new(rect.left() + 3.0, y + 1.0),
+        Align2::LEFT_TOP,
+        label,
+        FontId::proportional(10.0),
+        LABEL,
+    );
+}
+
+/// Height of `sample` on the waveform display, in dB from the center line to the edges.
+fn sample_to_y(rect: Rect, sample: f32) -> f32 {
+    let level_db = util::gain_to_db(sample.abs()).max(SCOPE_FLOOR_DB);
+    let unit = (1.0 - level_db / SCOPE_FLOOR_DB).min(1.0);
+    rect.center().y - sample.signum() * unit * rect.height() / 2.0
+}
+
+fn level_to_y(rect: Rect, level_db: f32) -> f32 {
+    let unit = (level_db / -FLOOR_DB + 1.0).clamp(0.0, 1.0);
+    rect.bottom() - unit * rect.height()
+}
+
+fn gain_to_y(rect: Rect, gain_db: f32) -> f32 {
+    let unit = (gain_db - MIN_GAIN_DB) / (MAX_GAIN_DB - MIN_GAIN_DB);
+    rect.bottom() - unit * rect.height()
+}
+
+fn point_pos(rect: Rect, point: &EqPoint) -> Pos2 {
+    Pos2::new(
+        rect.left() + spectral_eq::freq_to_unit(point.freq) * rect.width(),
+        gain_to_y(rect, point.gain_db),
+    )
+}
+
+fn pos_to_point(rect: Rect, pos: Pos2) -> EqPoint {
+    let x = (pos.x - rect.left()) / rect.width();
+    let y = (rect.bottom() - pos.y) / rect.height();
+    EqPoint {
+        freq: spectral_eq::unit_to_freq(x),
+        gain_db: MIN_GAIN_DB + y * (MAX_GAIN_DB - MIN_GAIN_DB),
+    }
+}
@@ -0,0 +1,57 @@
+use nih_plug::prelude::*;
+use nih_plug_egui::egui::{EventFilter, Key, Response, Ui, WidgetInfo, WidgetType};
+
+/// Normalized change per arrow key press on a focused control, Shift divides it by ten.
+const KEY_STEP: f32 = 0.01;
+/// Normalized change per Page Up or Page Down press.
+const PAGE_STEP: f32 = 0.1;
+
+/// Tells screen readers about a control playing `param`: its `name`, its normalized value in
+/// `0..=1` and the value as the editor shows it. egui announces the new value whenever the
+/// response reports a change.
+pub fn describe_param<P: Param>(response: &Response, param: &P, name: &str) {
+    let value = param.unmodulated_normalized_value();
+    response.widget_info(|| WidgetInfo {
+        current_text_value: Some(param.normalized_value_to_string(value, true)),
+        ..WidgetInfo::slider(response.enabled(), value as f64, name)
+    });
+}
+
+/// Tells screen readers what a display that is not a control shows.
+pub fn describe_display(response: &Response, name: &str, text: impl Fn() -> String) {
+    response.widget_info(|| {
+        WidgetInfo::labeled(WidgetType::Label, true, format!("{name}: {}", text()))
+    });
+}
+
+/// Normalized change asked for by the keys pressed this frame while `response` has focus,
+/// along `[horizontal, vertical]`. Keeps the arrow keys from moving the focus away.
+pub fn key_steps(ui: &Ui, response: &Response) -> [f32; 2] {
+    if !response.has_focus() {
+        return [0.0; 2];
+    }
+    ui.memory_mut(|memory| {
+        memory.set_focus_lock_filter(
+            response.id,
+            EventFilter {
+                horizontal_arrows: true,
+                vertical_arrows: true,
+                ..Default::default()
+            },
+        )
+    });
+
+    ui.input(|input| {
+        let step = if input.modifiers.shift {
+            KEY_STEP * 0.1
+        } else {
+            KEY_STEP
+        };
+        let count = |key| input.num_presses(key) as f32;
+        [
+            (count(Key::ArrowRight) - count(Key::ArrowLeft)) * step,
+            (count(Key::ArrowUp) - count(Key::ArrowDown)) * step
+                + (count(Key::PageUp) - count(Key::PageDown)) * PAGE_STEP,
+        ]
+    })
+}
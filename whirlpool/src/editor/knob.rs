@@ -0,0 +1,208 @@
+use nih_plug::prelude::*;
+use nih_plug_egui::egui::{
+    self, Align2, Color32, FontId, Pos2, Response, Sense, Shape, Stroke, StrokeKind, Ui, Vec2,
+};
+use std::f32::consts::PI;
+
+use super::prefs::{DragMode, KnobPrefs, ResponseCurve};
+use super::{access, touch};
+use super::{CURVE, GRID};
+
+/// Change in knob position per pixel of vertical drag at the default sensitivity, Shift
+/// divides it by ten.
+const DRAG_SENSITIVITY: f32 = 0.0025;
+const SIZE: f32 = 44.0;
+/// Dial size in touch mode, big enough to land a finger on.
+const TOUCH_SIZE: f32 = 66.0;
+/// The dial sweeps 270 degrees starting from the bottom left.
+const START_ANGLE: f32 = 0.75 * PI;
+const SWEEP: f32 = 1.5 * PI;
+const MODULATION: Color32 = Color32::from_rgb(240, 170, 60);
+
+/// A rotary control that draws the stored value and the host modulation on top of it
+/// separately. Dragging, double-clicking and the readout always act on the unmodulated value,
+/// so CLAP modulation never leaks into the saved state.
+pub struct Knob<'a, P: Param> {
+    param: &'a P,
+    setter: &'a ParamSetter<'a>,
+    name: &'a str,
+    /// Factor on the drag speed.
+    sensitivity: f32,
+    drag_mode: DragMode,
+    curve: ResponseCurve,
+    size: f32,
+}
+
+impl<'a, P: Param> Knob<'a, P> {
+    pub fn for_param(param: &'a P, setter: &'a ParamSetter<'a>) -> Self {
+        Self {
+            param,
+            setter,
+            name: param.name(),
+            sensitivity: 1.0,
+            drag_mode: DragMode::default(),
+            curve: ResponseCurve::default(),
+            size: SIZE,
+        }
+    }
+
+    /// Drags and maps the knob the way the user set up in `prefs`.
+    pub fn with_prefs(mut self, prefs: &KnobPrefs) -> Self {
+        self.sensitivity = prefs.sensitivity;
+        self.drag_mode = prefs.drag_mode;
+        self.curve = prefs.curve(self.param.name());
+        self.size = if prefs.touch_mode { TOUCH_SIZE } else { SIZE };
+        self
+    }
+
+    /// Shows `name` above the dial instead of the parameter's own name.
+    pub fn with_name(mut self, name: &'a str) -> Self {
+        self.name = name;
+        self
+    }
+
+    fn set_normalized(&self, normalized: f32) {
+        self.setter
+            .set_parameter_normalized(self.param, normalized.clamp(0.0, 1.0));
+    }
+
+    /// Turns the knob for a drag by `delta` to `pos` around `center`, and returns whether it
+    /// moved.
+    fn drag(&self, center: Pos2, pos: Option<Pos2>, delta: Vec2, fine: bool) -> bool {
+        let speed = if fine {
+            self.sensitivity * 0.1
+        } else {
+            self.sensitivity
+        };
+        let travel = match (self.drag_mode, pos) {
+            (DragMode::Circular, Some(pos)) => {
+                let angle = |pos: Pos2| (pos - center).angle();
+                let turn = (angle(pos) - angle(pos - delta) + PI).rem_euclid(2.0 * PI) - PI;
+                turn / SWEEP * speed
+            }
+            _ => -delta.y * DRAG_SENSITIVITY * speed,
+        };
+        self.turn(travel)
+    }
+
+    /// Moves the knob `travel` of its full turn, and returns whether it moved.
+    fn turn(&self, travel: f32) -> bool {
+        if travel == 0.0 {
+            return false;
+        }
+        let value = self.param.unmodulated_normalized_value();
+        let position = (self.curve.position(value) + travel).clamp(0.0, 1.0);
+        self.set_normalized(self.curve.value(position));
+        true
+    }
+}
+
+impl<P: Param> egui::Widget for Knob<'_, P> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let size = self.size;
+        let desired = egui::vec2(size + 24.0, size + 30.0);
+        let (rect, mut response) = ui.allocate_exact_size(desired, Sense::click_and_drag());
+        let center = Pos2::new(rect.center().x, rect.top() + 14.0 + size / 2.0);
+
+        if response.double_clicked() {
+            self.setter.begin_set_parameter(self.param);
+            self.set_normalized(self.param.default_normalized_value());
+            self.setter.end_set_parameter(self.param);
+            response.mark_changed();
+        } else if response.drag_started() {
+            self.setter.begin_set_parameter(self.param);
+        } else if response.dragged() {
+            let fine = ui.input(|i| i.modifiers.shift);
+            let pos = response.interact_pointer_pos();
+            if self.drag(center, pos, response.drag_delta(), fine) {
+                response.mark_changed();
+            }
+        }
+        if response.drag_stopped() {
+            self.setter.end_set_parameter(self.param);
+        }
+        // Further fingers, the first one may already arrive as the pointer above
+        if !response.dragged() {
+            if let Some(touch) = touch::drag(ui, response.id, rect) {
+                if touch.started {
+                    self.setter.begin_set_parameter(self.param);
+                }
+                if self.drag(center, Some(touch.pos), touch.delta, false) {
+                    response.mark_changed();
+                }
+                if touch.ended {
+                    self.setter.end_set_parameter(self.param);
+                }
+            }
+        }
+        let [right, up] = access::key_steps(ui, &response);
+        if right + up != 0.0 {
+            self.setter.begin_set_parameter(self.param);
+            self.turn(right + up);
+            self.setter.end_set_parameter(self.param);
+            response.mark_changed();
+        }
+        access::describe_param(&response, self.param, self.name);
+
+        let painter = ui.painter_at(rect);
+        if response.has_focus() {
+            painter.rect_stroke(rect, 4.0, Stroke::new(1.0, CURVE), StrokeKind::Inside);
+        }
+        let radius = size / 2.0 - 3.0;
+        let base = self.param.unmodulated_normalized_value();
+        let modulated = self.param.modulated_normalized_value();
+        let (base_pos, modulated_pos) = (self.curve.position(base), self.curve.position(modulated));
+
+        painter.text(
+            Pos2::new(rect.center().x, rect.top()),
+            Align2::CENTER_TOP,
+            self.name,
+            FontId::proportional(11.0),
+            ui.visuals().text_color(),
+        );
+        painter.add(arc(center, radius, 0.0, 1.0, Stroke::new(3.0, GRID)));
+        painter.add(arc(center, radius, 0.0, base_pos, Stroke::new(3.0, CURVE)));
+        if (modulated - base).abs() > f32::EPSILON {
+            painter.add(arc(
+                center,
+                radius - 5.0,
+                base_pos.min(modulated_pos),
+                base_pos.max(modulated_pos),
+                Stroke::new(2.0, MODULATION),
+            ));
+        }
+        let pointer = angle_pos(center, radius - 2.0, base_pos);
+        painter.line_segment([center, pointer], Stroke::new(2.0, CURVE));
+
+        // Show the stored value, and where the host has pushed it when modulated
+        let value = self.param.normalized_value_to_string(base, true);
+        let (text, color) = if (modulated - base).abs() > f32::EPSILON {
+            let modulated = self.param.normalized_value_to_string(modulated, true);
+            (format!("{value} > {modulated}"), MODULATION)
+        } else {
+            (value, ui.visuals().text_color())
+        };
+        painter.text(
+            Pos2::new(rect.center().x, rect.bottom()),
+            Align2::CENTER_BOTTOM,
+            text,
+            FontId::proportional(10.0),
+            color,
+        );
+
+        response
+    }
+}
+
+fn angle_pos(center: Pos2, radius: f32, normalized: f32) -> Pos2 {
+    let angle = START_ANGLE + normalized * SWEEP;
+    center + radius * egui::vec2(angle.cos(), angle.sin())
+}
+
+fn arc(center: Pos2, radius: f32, from: f32, to: f32, stroke: Stroke) -> Shape {
+    let steps = ((to - from) * 48.0).ceil().max(1.0) as usize;
+    let points = (0..=steps)
+        .map(|i| angle_pos(center, radius, from + (to - from) * i as f32 / steps as f32))
+        .collect();
+    Shape::line(points, stroke)
+}
@@ -0,0 +1,254 @@
+use nih_plug_egui::egui::{Context, FontData, FontDefinitions, FontFamily};
+use std::sync::Arc;
+
+/// System fonts with Japanese glyphs, which egui's own fonts lack, tried in order.
+const CJK_FONTS: &[&str] = &[
+    "/System/Library/Fonts/ヒラギノ角ゴシック W3.ttc",
+    "/System/Library/Fonts/Hiragino Sans GB.ttc",
+    "C:\\Windows\\Fonts\\YuGothM.ttc",
+    "C:\\Windows\\Fonts\\msgothic.ttc",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+];
+
+/// Language of the editor's labels. Parameter IDs and the names hosts show stay the same in
+/// every language, so sessions and automation carry over.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+    Japanese,
+}
+
+impl Language {
+    pub const ALL: [Self; 3] = [Self::English, Self::German, Self::Japanese];
+
+    pub fn index(self) -> u8 {
+        self as u8
+    }
+
+    /// Falls back to English for indices of unknown languages.
+    pub fn from_index(index: u8) -> Self {
+        Self::ALL.get(index as usize).copied().unwrap_or_default()
+    }
+
+    /// The language's own name for itself.
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+            Language::Japanese => "日本語",
+        }
+    }
+
+    /// `text` in this language, or as is when it has no translation.
+    pub fn translate(self, text: &str) -> &str {
+        LABELS
+            .iter()
+            .find(|row| row[0] == text)
+            .map_or(text, |row| row[self as usize])
+    }
+}
+
+/// Adds the first system font found in `CJK_FONTS` as a fallback for the glyphs egui's own
+/// fonts do not have. Returns whether one was found.
+pub fn install_cjk_font(ctx: &Context) -> bool {
+    let Some(data) = CJK_FONTS.iter().find_map(|path| std::fs::read(path).ok()) else {
+        return false;
+    };
+    let mut fonts = FontDefinitions::default();
+    fonts
+        .font_data
+        .insert("cjk".to_owned(), Arc::new(FontData::from_owned(data)));
+    for family in [FontFamily::Proportional, FontFamily::Monospace] {
+        fonts
+            .families
+            .entry(family)
+            .or_default()
+            .push("cjk".to_owned());
+    }
+    ctx.set_fonts(fonts);
+    true
+}
+
+/// Editor labels and parameter names as English, German and Japanese.
+const LABELS: &[[&str; 3]] = &[
+    ["Main", "Haupt", "メイン"],
+    ["XY Pad", "XY-Pad", "XYパッド"],
+    ["Motion", "Motion", "モーション"],
+    ["Freeze Bank", "Freeze-Bank", "フリーズバンク"],
+    ["Delay", "Delay", "ディレイ"],
+    ["Analyzer", "Analyzer", "アナライザー"],
+    ["Settings", "Einstellungen", "設定"],
+    ["Compact", "Kompakt", "コンパクト"],
+    ["Full", "Voll", "フル"],
+    ["Language", "Sprache", "言語"],
+    ["Reset", "Zurücksetzen", "リセット"],
+    ["Learn", "Lernen", "学習"],
+    ["Record", "Aufnehmen", "録音"],
+    ["Parameter", "Parameter", "パラメーター"],
+    ["Steps", "Schritte", "ステップ"],
+    ["Euclid", "Euklid", "ユークリッド"],
+    ["Clear", "Löschen", "クリア"],
+    ["Store A", "A speichern", "Aに保存"],
+    ["Store B", "B speichern", "Bに保存"],
+    ["Tap", "Tap", "タップ"],
+    ["Harmonics", "Harmonische", "ハーモニクス"],
+    ["Shift", "Verschiebung", "シフト"],
+    ["Shift Hz", "Verschiebung Hz", "シフト Hz"],
+    ["Blur", "Unschärfe", "ブラー"],
+    ["Dry/Wet", "Dry/Wet", "ドライ/ウェット"],
+    ["Volume", "Lautstärke", "音量"],
+    ["Morph", "Morph", "モーフ"],
+    ["Input Pad", "Eingangsdämpfung", "入力パッド"],
+    ["Input Mute", "Eingang stumm", "入力ミュート"],
+    ["Harmony Pan", "Harmonie-Panorama", "ハーモニーパン"],
+    ["Voice", "Stimme", "ボイス"],
+    ["Unison", "Unisono", "ユニゾン"],
+    ["Shift Mode", "Verschiebungsmodus", "シフトモード"],
+    ["Bend Range", "Pitch-Bend-Bereich", "ベンド幅"],
+    ["Scale", "Tonleiter", "スケール"],
+    ["Key", "Tonart", "キー"],
+    ["Preserve Body", "Körper erhalten", "ボディ保持"],
+    ["Pre-Delay", "Pre-Delay", "プリディレイ"],
+    ["Decimate", "Dezimieren", "デシメート"],
+    ["Rotate", "Rotieren", "ローテート"],
+    ["Rotate Mod", "Rotations-Mod", "ローテートモッド"],
+    ["Invert", "Invertieren", "反転"],
+    ["Invert Wet", "Wet invertieren", "ウェット反転"],
+    ["Tame", "Zähmen", "テイム"],
+    ["Blur Hold", "Unschärfe halten", "ブラーホールド"],
+    ["Blur Noise", "Unschärferauschen", "ブラーノイズ"],
+    ["Average", "Mittelung", "平均化"],
+    ["Harmony Source", "Harmoniequelle", "ハーモニーソース"],
+    ["Process", "Verarbeitung", "処理"],
+    ["Tonality", "Tonalität", "トーナリティ"],
+    ["Harmony Bins", "Harmonie-Bins", "ハーモニービン"],
+    ["Dry Tone", "Dry-Klang", "ドライトーン"],
+    ["Bloom", "Bloom", "ブルーム"],
+    ["Low Cut", "Tiefensperre", "ローカット"],
+    ["High Cut", "Höhensperre", "ハイカット"],
+    ["Freeze", "Einfrieren", "フリーズ"],
+    ["Shimmer", "Shimmer", "シマー"],
+    ["Drift", "Drift", "ドリフト"],
+    ["Save Freeze", "Freeze speichern", "フリーズを保存"],
+    ["Freeze Trigger", "Freeze-Auslöser", "フリーズトリガー"],
+    ["Sensitivity", "Empfindlichkeit", "感度"],
+    [
+        "Sidechain Trigger",
+        "Sidechain-Auslöser",
+        "サイドチェーントリガー",
+    ],
+    ["MIDI Out", "MIDI-Ausgang", "MIDI出力"],
+    ["True Peak Limit", "True-Peak-Limit", "トゥルーピーク制限"],
+    ["TP Ceiling", "TP-Obergrenze", "TP上限"],
+    ["Bass Mono", "Bass mono", "ベースモノ"],
+    ["Spectral Gate", "Spektrales Gate", "スペクトラルゲート"],
+    ["Gate Threshold", "Gate-Schwelle", "ゲートしきい値"],
+    ["Gate Reduction", "Gate-Absenkung", "ゲートリダクション"],
+    ["Spectral Duck", "Spektrales Ducking", "スペクトラルダック"],
+    ["Duck Amount", "Ducking-Stärke", "ダック量"],
+    ["Duck Release", "Ducking-Release", "ダックリリース"],
+    [
+        "Sidechain Listen",
+        "Sidechain abhören",
+        "サイドチェーン試聴",
+    ],
+    ["Spectral EQ", "Spektral-EQ", "スペクトラルEQ"],
+    ["Grain Feedback", "Grain-Feedback", "グレインフィードバック"],
+    ["Delay Time", "Delay-Zeit", "ディレイタイム"],
+    ["Delay Feedback", "Delay-Feedback", "ディレイフィードバック"],
+    ["Hold", "Halten", "ホールド"],
+    ["Stretch", "Dehnung", "ストレッチ"],
+    ["Reverse", "Rückwärts", "リバース"],
+    ["Tape Stop", "Bandstopp", "テープストップ"],
+    ["Macro Notes", "Makro-Noten", "マクロノート"],
+    ["Grain Buffer", "Grain-Puffer", "グレインバッファ"],
+    ["Length", "Länge", "長さ"],
+    ["Memory", "Speicher", "メモリ"],
+    ["Wow / Flutter", "Wow / Flutter", "ワウ / フラッター"],
+    ["Saturation", "Sättigung", "サチュレーション"],
+    ["Cross Feedback", "Kreuz-Feedback", "クロスフィードバック"],
+    ["Grain Source", "Grain-Quelle", "グレインソース"],
+    ["Grain Voices", "Grain-Stimmen", "グレインボイス"],
+    ["Grain Shape", "Grain-Form", "グレイン形状"],
+    ["Jitter", "Jitter", "ジッター"],
+    ["Grain Chord", "Grain-Akkord", "グレインコード"],
+    ["Surround Spread", "Surround-Streuung", "サラウンド拡散"],
+    ["Grain Filter", "Grain-Filter", "グレインフィルター"],
+    ["Grain Cutoff", "Grain-Cutoff", "グレインカットオフ"],
+    ["Cutoff Spread", "Cutoff-Streuung", "カットオフ拡散"],
+    ["Grain Sync", "Grain-Sync", "グレイン同期"],
+    ["MIDI Grains", "MIDI-Grains", "MIDIグレイン"],
+    [
+        "Invert Feedback",
+        "Feedback invertieren",
+        "フィードバック反転",
+    ],
+    ["Mod Rate", "Mod-Rate", "モッドレート"],
+    ["Mod Depth", "Mod-Tiefe", "モッド深さ"],
+    ["Mod Shape", "Mod-Form", "モッド波形"],
+    ["CPU Guard", "CPU-Schutz", "CPUガード"],
+    ["Slot Fade", "Slot-Überblendung", "スロットフェード"],
+    ["Slot Notes", "Slot-Noten", "スロットノート"],
+    ["Base Note", "Grundnote", "ベースノート"],
+    ["Listen to", "Abhören", "試聴"],
+    ["Pause", "Pause", "一時停止"],
+    ["Resume", "Fortsetzen", "再開"],
+    ["Time", "Zeit", "時間"],
+    ["Grid", "Raster", "グリッド"],
+    ["Tuner", "Stimmgerät", "チューナー"],
+    ["Partials", "Teiltöne", "倍音"],
+    ["Share Preset", "Preset teilen", "プリセット共有"],
+    ["Copy Preset", "Preset kopieren", "プリセットをコピー"],
+    ["Paste Preset", "Preset einfügen", "プリセットを貼り付け"],
+    ["With Freeze", "Mit Freeze", "フリーズ込み"],
+    [
+        "Paste a preset here",
+        "Preset hier einfügen",
+        "ここにプリセットを貼り付け",
+    ],
+    ["Toggle Freeze", "Freeze umschalten", "フリーズ切替"],
+    ["Randomize", "Zufällig", "ランダム化"],
+    ["Store Morph A", "Morph A speichern", "モーフAに保存"],
+    ["Store Morph B", "Morph B speichern", "モーフBに保存"],
+    ["Compact View", "Kompaktansicht", "コンパクト表示"],
+    [
+        "Search parameters and actions",
+        "Parameter und Aktionen suchen",
+        "パラメーターとアクションを検索",
+    ],
+    [
+        "Up/Down pick, Left/Right adjust, = value, Enter apply",
+        "Auf/Ab wählen, Links/Rechts ändern, = Wert, Enter anwenden",
+        "上下で選択、左右で調整、= 値、Enterで適用",
+    ],
+    ["Knobs", "Regler", "ノブ"],
+    ["Drag Sensitivity", "Zieh-Empfindlichkeit", "ドラッグ感度"],
+    ["Touch Mode", "Touch-Modus", "タッチモード"],
+    ["Drag Mode", "Ziehmodus", "ドラッグモード"],
+    ["Linear", "Linear", "リニア"],
+    ["Circular", "Kreisförmig", "円形"],
+    ["Log", "Logarithmisch", "対数"],
+    [
+        "Automation Smoothing",
+        "Automations-Glättung",
+        "オートメーションスムージング",
+    ],
+    ["Global", "Global", "グローバル"],
+    ["Morph Presets", "Morph-Presets", "モーフプリセット"],
+    ["Internal Clock", "Interne Clock", "内部クロック"],
+    ["Tempo", "Tempo", "テンポ"],
+    ["Run", "Läuft", "実行"],
+    ["Swing", "Swing", "スウィング"],
+    ["Humanize", "Humanisieren", "ヒューマナイズ"],
+    ["Analysis", "Analyse", "解析"],
+    ["Window", "Fenster", "窓関数"],
+    ["Pre-Emphasis", "Vorverzerrung", "プリエンファシス"],
+    ["Frame", "Frame", "フレーム"],
+    ["Hop", "Hop", "ホップ"],
+    ["Resolution", "Auflösung", "分解能"],
+    ["Overlap-Add", "Overlap-Add", "オーバーラップ加算"],
+];
@@ -0,0 +1,277 @@
+use nih_plug::prelude::*;
+use nih_plug_egui::egui::{
+    self, Align2, Context, Id, Key, Modifiers, Order, RichText, ScrollArea, TextEdit,
+};
+
+use super::{tr, CURVE, LABEL};
+use crate::WhirlpoolParams;
+
+/// Matches listed for a typed query, the best first.
+const MAX_ROWS: usize = 12;
+/// Normalized change per Left or Right press on a continuous parameter, Shift divides it by ten.
+const NUDGE_STEP: f32 = 0.01;
+
+/// What the palette can do besides editing a parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    ToggleFreeze,
+    Randomize,
+    CopyPreset,
+    StoreMorphA,
+    StoreMorphB,
+    ToggleCompact,
+}
+
+impl Action {
+    const ALL: [Self; 6] = [
+        Self::ToggleFreeze,
+        Self::Randomize,
+        Self::CopyPreset,
+        Self::StoreMorphA,
+        Self::StoreMorphB,
+        Self::ToggleCompact,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Action::ToggleFreeze => "Toggle Freeze",
+            Action::Randomize => "Randomize",
+            Action::CopyPreset => "Copy Preset",
+            Action::StoreMorphA => "Store Morph A",
+            Action::StoreMorphB => "Store Morph B",
+            Action::ToggleCompact => "Compact View",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Entry {
+    Action(Action),
+    Param(ParamPtr),
+}
+
+/// The Ctrl+K (Cmd+K on macOS) palette: every parameter and action, found by typing a few
+/// letters of its name. Up and Down pick a row, Left and Right nudge the picked parameter and
+/// Enter runs the picked action. Typing `= value` after the name sets the parameter to the
+/// value on Enter, parsed the way the host's text entry would.
+#[derive(Default)]
+pub struct Palette {
+    open: bool,
+    query: String,
+    /// Row picked with Up and Down, into the matches of the current query.
+    selected: usize,
+}
+
+impl Palette {
+    /// Opens or closes the palette on Ctrl+K and draws it when open. Returns the action that
+    /// was run, for the editor to carry out, parameter edits are made right away.
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        params: &WhirlpoolParams,
+        context: &dyn GuiContext,
+    ) -> Option<Action> {
+        if ctx.input_mut(|input| input.consume_key(Modifiers::COMMAND, Key::K)) {
+            self.open = !self.open;
+            self.query.clear();
+            self.selected = 0;
+        }
+        if !self.open {
+            return None;
+        }
+        if ctx.input_mut(|input| input.consume_key(Modifiers::NONE, Key::Escape)) {
+            self.open = false;
+            return None;
+        }
+
+        let (name_query, value) = match self.query.split_once('=') {
+            Some((name, value)) => (name.trim(), Some(value.trim())),
+            None => (self.query.trim(), None),
+        };
+        let matches = matching_entries(params, name_query);
+
+        // Taken before the text field sees them, which keeps the cursor at the end of the query
+        let (up, down, left, right, enter, fine) = ctx.input_mut(|input| {
+            (
+                input.count_and_consume_key(Modifiers::NONE, Key::ArrowUp),
+                input.count_and_consume_key(Modifiers::NONE, Key::ArrowDown),
+                input.count_and_consume_key(Modifiers::NONE, Key::ArrowLeft),
+                input.count_and_consume_key(Modifiers::NONE, Key::ArrowRight),
+                input.consume_key(Modifiers::NONE, Key::Enter),
+                input.modifiers.shift,
+            )
+        });
+        self.selected = (self.selected + down)
+            .saturating_sub(up)
+            .min(matches.len().saturating_sub(1));
+        let picked = matches.get(self.selected).map(|(entry, _)| *entry);
+
+        let mut action = None;
+        match picked {
+            Some(Entry::Param(param)) => {
+                let steps = right as isize - left as isize;
+                if steps != 0 {
+                    nudge(context, param, steps, fine);
+                }
+                if let (true, Some(value)) = (enter, value) {
+                    // SAFETY: the pointer comes from `params`, which outlives this call
+                    if let Some(normalized) = unsafe { param.string_to_normalized_value(value) } {
+                        set(context, param, normalized);
+                    }
+                    self.query
+                        .truncate(self.query.find('=').unwrap_or(self.query.len()));
+                }
+            }
+            Some(Entry::Action(picked)) if enter => {
+                action = Some(picked);
+                self.open = false;
+            }
+            _ => (),
+        }
+
+        egui::Area::new(Id::new("palette"))
+            .anchor(Align2::CENTER_TOP, egui::vec2(0.0, 48.0))
+            .order(Order::Foreground)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_width(360.0);
+                    let field = ui.add(
+                        TextEdit::singleline(&mut self.query)
+                            .hint_text(tr(params, "Search parameters and actions"))
+                            .desired_width(f32::INFINITY),
+                    );
+                    field.request_focus();
+                    if field.changed() {
+                        self.selected = 0;
+                    }
+
+                    ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                        for (row, (entry, name)) in matches.iter().enumerate() {
+                            let text = match entry {
+                                Entry::Action(_) => RichText::new(name).color(CURVE),
+                                Entry::Param(param) => {
+                                    // SAFETY: as above
+                                    let shown = unsafe {
+                                        param.normalized_value_to_string(
+                                            param.unmodulated_normalized_value(),
+                                            true,
+                                        )
+                                    };
+                                    RichText::new(format!("{name}    {shown}"))
+                                }
+                            };
+                            let response = ui.selectable_label(row == self.selected, text);
+                            if row == self.selected {
+                                response.scroll_to_me(None);
+                            }
+                            if response.clicked() {
+                                self.selected = row;
+                                if let Entry::Action(picked) = entry {
+                                    action = Some(*picked);
+                                    self.open = false;
+                                }
+                            }
+                        }
+                    });
+                    ui.label(
+                        RichText::new(tr(
+                            params,
+                            "Up/Down pick, Left/Right adjust, = value, Enter apply",
+                        ))
+                        .small()
+                        .color(LABEL),
+                    );
+                });
+            });
+        action
+    }
+}
+
+/// The actions and visible parameters matching `query`, best first, with the names shown for
+/// them. An empty query lists everything in its usual order.
+fn matching_entries(params: &WhirlpoolParams, query: &str) -> Vec<(Entry, String)> {
+    let actions = Action::ALL
+        .into_iter()
+        .map(|action| (Entry::Action(action), tr(params, action.name()).to_owned()));
+    let parameters = params.param_map().into_iter().filter_map(|(_, param, _)| {
+        // SAFETY: the pointers come from `params` and live as long as it does
+        let (flags, name) = unsafe { (param.flags(), param.name()) };
+        (!flags.contains(ParamFlags::HIDDEN))
+            .then(|| (Entry::Param(param), tr(params, name).to_owned()))
+    });
+
+    let mut scored: Vec<_> = actions
+        .chain(parameters)
+        .filter_map(|(entry, name)| Some((fuzzy_score(query, &name)?, entry, name)))
+        .collect();
+    // Stable, so equal scores keep the order the parameters are declared in
+    scored.sort_by_key(|(score, _, _)| std::cmp::Reverse(*score));
+    scored
+        .into_iter()
+        .take(if query.is_empty() {
+            usize::MAX
+        } else {
+            MAX_ROWS
+        })
+        .map(|(_, entry, name)| (entry, name))
+        .collect()
+}
+
+/// How well `text` matches `query` when the query's letters appear in it in order, ignoring
+/// case and spaces. Letters that follow each other or start a word score higher, so "fa"
+/// ranks Freeze Amount above Feedback Damping.
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut from = 0;
+    let mut last = None;
+    for wanted in query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+    {
+        let found = from + text[from..].iter().position(|&c| c == wanted)?;
+        score += 1;
+        if last.is_some_and(|last| last + 1 == found) {
+            score += 4;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 6;
+        }
+        last = Some(found);
+        from = found + 1;
+    }
+    // Shorter names win ties, they match more of what was typed
+    Some(score * 64 - text.len() as i32)
+}
+
+/// Moves `param` by `steps` of its own steps, or of `NUDGE_STEP` when it is continuous.
+fn nudge(context: &dyn GuiContext, param: ParamPtr, steps: isize, fine: bool) {
+    // SAFETY: the pointer comes from the editor's parameters, which outlive this call
+    let mut value = unsafe { param.unmodulated_normalized_value() };
+    if unsafe { param.step_count() }.is_some() {
+        for _ in 0..steps.unsigned_abs() {
+            value = unsafe {
+                if steps > 0 {
+                    param.next_normalized_step(value, fine)
+                } else {
+                    param.previous_normalized_step(value, fine)
+                }
+            };
+        }
+    } else {
+        let step = if fine { NUDGE_STEP * 0.1 } else { NUDGE_STEP };
+        value = (value + steps as f32 * step).clamp(0.0, 1.0);
+    }
+    set(context, param, value);
+}
+
+/// Sets `param` as a single gesture, the way the host's own controls would.
+fn set(context: &dyn GuiContext, param: ParamPtr, normalized: f32) {
+    // SAFETY: as in `nudge()`
+    unsafe {
+        context.raw_begin_set_parameter(param);
+        context.raw_set_parameter_normalized(param, normalized);
+        context.raw_end_set_parameter(param);
+    }
+}
@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Slowest and fastest knob drag, as a factor on the default speed.
+pub const MIN_SENSITIVITY: f32 = 0.25;
+pub const MAX_SENSITIVITY: f32 = 4.0;
+/// Bend of the log response curve, the larger the more of a knob's travel goes to the low end.
+const LOG_BEND: f32 = 4.0;
+
+/// How dragging turns a knob.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DragMode {
+    /// Up turns it right, down turns it left.
+    #[default]
+    Linear,
+    /// The knob follows the pointer around its centre.
+    Circular,
+}
+
+impl DragMode {
+    pub const ALL: [Self; 2] = [Self::Linear, Self::Circular];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DragMode::Linear => "Linear",
+            DragMode::Circular => "Circular",
+        }
+    }
+}
+
+/// How a knob's travel maps onto its parameter's range.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ResponseCurve {
+    #[default]
+    Linear,
+    /// Spends more of the travel on the low end, for times and frequencies.
+    Log,
+}
+
+impl ResponseCurve {
+    pub const ALL: [Self; 2] = [Self::Linear, Self::Log];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ResponseCurve::Linear => "Linear",
+            ResponseCurve::Log => "Log",
+        }
+    }
+
+    /// Knob position in `0..=1` showing the normalized `value`.
+    pub fn position(self, value: f32) -> f32 {
+        match self {
+            ResponseCurve::Linear => value,
+            ResponseCurve::Log => (1.0 + value * LOG_BEND.exp_m1()).ln() / LOG_BEND,
+        }
+    }
+
+    /// Normalized value shown at knob `position`, the inverse of [`Self::position`].
+    pub fn value(self, position: f32) -> f32 {
+        match self {
+            ResponseCurve::Linear => position,
+            ResponseCurve::Log => (position * LOG_BEND).exp_m1() / LOG_BEND.exp_m1(),
+        }
+    }
+}
+
+/// How the controls feel, kept per user rather than per project so it follows them to every
+/// session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KnobPrefs {
+    /// Factor on the default drag speed.
+    pub sensitivity: f32,
+    pub drag_mode: DragMode,
+    /// Response curves by parameter name, linear when missing.
+    pub curves: BTreeMap<String, ResponseCurve>,
+    /// Bigger knobs, pads and sliders for playing with fingers.
+    pub touch_mode: bool,
+}
+
+impl Default for KnobPrefs {
+    fn default() -> Self {
+        Self {
+            sensitivity: 1.0,
+            drag_mode: DragMode::default(),
+            curves: BTreeMap::new(),
+            touch_mode: false,
+        }
+    }
+}
+
+impl KnobPrefs {
+    /// The user's saved preferences, or the defaults when there are none or they do not parse.
+    pub fn load() -> Self {
+        path()
+            .and_then(|path| std::fs::read(path).ok())
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = path().ok_or("No user configuration folder found")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+        }
+        let data = serde_json::to_vec_pretty(self).map_err(|err| err.to_string())?;
+        std::fs::write(path, data).map_err(|err| err.to_string())
+    }
+
+    pub fn curve(&self, name: &str) -> ResponseCurve {
+        self.curves.get(name).copied().unwrap_or_default()
+    }
+
+    pub fn set_curve(&mut self, name: &str, curve: ResponseCurve) {
+        if curve == ResponseCurve::Linear {
+            self.curves.remove(name);
+        } else {
+            self.curves.insert(name.to_owned(), curve);
+        }
+    }
+}
+
+/// Where the preferences live in the user's configuration folder.
+fn path() -> Option<PathBuf> {
+    let env = |name| std::env::var_os(name).map(PathBuf::from);
+    let dir = if cfg!(target_os = "windows") {
+        env("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env("HOME").map(|home| home.join("Library/Application Support"))
+    } else {
+        env("XDG_CONFIG_HOME").or_else(|| env("HOME").map(|home| home.join(".config")))
+    };
+    dir.map(|dir| dir.join("Whirlpool").join("editor.json"))
+}
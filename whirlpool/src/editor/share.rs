@@ -0,0 +1,45 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use nih_plug::wrapper::state::PluginState;
+
+/// Starts every shared preset, so pasting some other text fails with a clear message.
+const PREFIX: &str = "whirlpool:";
+/// Persisted fields that belong to the user's setup rather than the sound, never shared.
+const LOCAL_FIELDS: &[&str] = &["editor-state", "compact-editor", "language"];
+/// The captured freeze spectrum, which makes the text much longer and is only shared on request.
+const FREEZE_FIELD: &str = "freeze-snapshot";
+
+/// The full parameter state as a single line of text to paste into forums and chats.
+pub fn encode(mut state: PluginState, with_freeze: bool) -> String {
+    for field in LOCAL_FIELDS {
+        state.fields.remove(*field);
+    }
+    if !with_freeze {
+        state.fields.remove(FREEZE_FIELD);
+    }
+    let json = serde_json::to_vec(&state).unwrap_or_default();
+    format!("{PREFIX}{}", URL_SAFE_NO_PAD.encode(json))
+}
+
+/// The state shared as `text`, with the fields it leaves out taken from `current`.
+pub fn decode(text: &str, current: &PluginState) -> Result<PluginState, String> {
+    let data = text
+        .trim()
+        .strip_prefix(PREFIX)
+        .ok_or("This is not a Whirlpool preset")?;
+    let json = URL_SAFE_NO_PAD
+        .decode(data)
+        .map_err(|_| "The preset text is damaged or incomplete")?;
+    let mut state: PluginState =
+        serde_json::from_slice(&json).map_err(|_| "The preset text could not be read")?;
+
+    for field in LOCAL_FIELDS.iter().chain([&FREEZE_FIELD]) {
+        if let Some(value) = current.fields.get(*field) {
+            state
+                .fields
+                .entry((*field).to_owned())
+                .or_insert_with(|| value.clone());
+        }
+    }
+    Ok(state)
+}
@@ -0,0 +1,82 @@
+use nih_plug_egui::egui::{self, Align2, Color32, Context, Id, Order, RichText};
+use std::collections::VecDeque;
+
+use super::{CLIP, SPECTRUM};
+
+/// How long a toast stays up, in seconds.
+const DURATION: f64 = 4.0;
+/// Seconds at the end of `DURATION` a toast takes to fade out.
+const FADE: f64 = 0.5;
+/// Toasts shown at once, older ones make room for new ones.
+const MAX_TOASTS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToastKind {
+    Info,
+    Warning,
+}
+
+impl ToastKind {
+    fn color(self) -> Color32 {
+        match self {
+            ToastKind::Info => SPECTRUM,
+            ToastKind::Warning => CLIP,
+        }
+    }
+}
+
+struct Toast {
+    kind: ToastKind,
+    text: String,
+    /// Editor time the toast was first drawn at, `None` until then.
+    since: Option<f64>,
+}
+
+/// Short messages stacked in the bottom right corner, so results of work that happens out of
+/// sight, on the audio thread or after a click, are seen instead of passing silently.
+#[derive(Default)]
+pub struct Toasts {
+    toasts: VecDeque<Toast>,
+}
+
+impl Toasts {
+    pub fn push(&mut self, kind: ToastKind, text: impl Into<String>) {
+        if self.toasts.len() == MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+        self.toasts.push_back(Toast {
+            kind,
+            text: text.into(),
+            since: None,
+        });
+    }
+
+    /// Draws the current toasts on top of the editor and drops the expired ones.
+    pub fn show(&mut self, ctx: &Context) {
+        let now = ctx.input(|input| input.time);
+        self.toasts
+            .retain(|toast| toast.since.is_none_or(|since| now - since < DURATION));
+        if self.toasts.is_empty() {
+            return;
+        }
+
+        egui::Area::new(Id::new("toasts"))
+            .anchor(Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -12.0))
+            .order(Order::Foreground)
+            .interactable(false)
+            .show(ctx, |ui| {
+                for toast in self.toasts.iter_mut() {
+                    let age = now - *toast.since.get_or_insert(now);
+                    let opacity = ((DURATION - age) / FADE).clamp(0.0, 1.0) as f32;
+                    ui.scope(|ui| {
+                        ui.set_opacity(opacity);
+                        egui::Frame::popup(ui.style()).show(ui, |ui| {
+                            ui.label(RichText::new(&toast.text).color(toast.kind.color()));
+                        });
+                    });
+                }
+            });
+        // Keep animating the fade even when nothing else changes
+        ctx.request_repaint();
+    }
+}
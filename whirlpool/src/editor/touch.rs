@@ -0,0 +1,84 @@
+use nih_plug_egui::egui::{Event, Id, Pos2, Rect, TouchPhase, Ui, Vec2};
+use std::collections::BTreeMap;
+
+/// Which control every finger on the screen is playing, and where the finger was last.
+#[derive(Debug, Default, Clone)]
+struct Claims(BTreeMap<u64, (Id, Pos2)>);
+
+/// What a finger did to a control this frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TouchDrag {
+    /// The finger came down on the control this frame.
+    pub started: bool,
+    /// The finger lifted or the touch was cancelled this frame.
+    pub ended: bool,
+    pub pos: Pos2,
+    pub delta: Vec2,
+}
+
+/// The finger playing the control `id` at `rect`, which claims any finger that comes down on
+/// it. Every finger claims a control of its own, so two fingers play two controls at once
+/// where egui's single pointer only ever drives one.
+pub fn drag(ui: &Ui, id: Id, rect: Rect) -> Option<TouchDrag> {
+    let events = ui.input(|input| input.events.clone());
+    let mut drag: Option<TouchDrag> = None;
+    ui.data_mut(|data| {
+        let claims = data.get_temp_mut_or_default::<Claims>(Id::new("touch_claims"));
+        for event in &events {
+            let Event::Touch {
+                id: touch,
+                phase,
+                pos,
+                ..
+            } = *event
+            else {
+                continue;
+            };
+            let claim = claims.0.get(&touch.0).copied();
+            let last = claim
+                .filter(|(owner, _)| *owner == id)
+                .map(|(_, last)| last);
+            let update = match (phase, last) {
+                (TouchPhase::Start, _) if claim.is_none() && rect.contains(pos) => {
+                    claims.0.insert(touch.0, (id, pos));
+                    TouchDrag {
+                        started: true,
+                        ended: false,
+                        pos,
+                        delta: Vec2::ZERO,
+                    }
+                }
+                (TouchPhase::Move, Some(last)) => {
+                    claims.0.insert(touch.0, (id, pos));
+                    TouchDrag {
+                        started: false,
+                        ended: false,
+                        pos,
+                        delta: pos - last,
+                    }
+                }
+                (TouchPhase::End | TouchPhase::Cancel, Some(_)) => {
+                    claims.0.remove(&touch.0);
+                    TouchDrag {
+                        started: false,
+                        ended: true,
+                        pos,
+                        delta: Vec2::ZERO,
+                    }
+                }
+                _ => continue,
+            };
+            // Several events of one finger in a frame add up
+            drag = Some(match drag {
+                Some(drag) => TouchDrag {
+                    started: drag.started || update.started,
+                    ended: update.ended,
+                    pos: update.pos,
+                    delta: drag.delta + update.delta,
+                },
+                None => update,
+            });
+        }
+    });
+    drag
+}
@@ -0,0 +1,206 @@
+use nih_plug::prelude::*;
+use nih_plug_egui::egui::{
+    self, Align2, FontId, Pos2, Rect, Response, Sense, Stroke, Ui, WidgetInfo,
+};
+
+use super::{access, touch};
+use super::{CURVE, GRID, POINT_RADIUS, SPECTRUM};
+
+const SIZE: f32 = 240.0;
+/// Pad size in touch mode.
+const TOUCH_SIZE: f32 = 360.0;
+
+/// A square that sets one parameter horizontally and another vertically, so both can be
+/// played at once. Every drag is a single automation gesture on both parameters.
+pub struct XyPad<'a> {
+    x: &'a FloatParam,
+    y: &'a FloatParam,
+    setter: &'a ParamSetter<'a>,
+    /// Normalized positions a MIDI CC moved the axes to, drawn instead of the parameters' own.
+    external: [Option<f32>; 2],
+    size: f32,
+}
+
+impl<'a> XyPad<'a> {
+    pub fn for_params(x: &'a FloatParam, y: &'a FloatParam, setter: &'a ParamSetter<'a>) -> Self {
+        Self {
+            x,
+            y,
+            setter,
+            external: [None; 2],
+            size: SIZE,
+        }
+    }
+
+    /// Makes the pad bigger for playing with fingers.
+    pub fn with_touch_mode(mut self, touch_mode: bool) -> Self {
+        self.size = if touch_mode { TOUCH_SIZE } else { SIZE };
+        self
+    }
+
+    pub fn with_external(mut self, external: [Option<f32>; 2]) -> Self {
+        self.external = external;
+        self
+    }
+
+    fn begin(&self) {
+        self.setter.begin_set_parameter(self.x);
+        self.setter.begin_set_parameter(self.y);
+    }
+
+    fn end(&self) {
+        self.setter.end_set_parameter(self.x);
+        self.setter.end_set_parameter(self.y);
+    }
+
+    fn set_from_pos(&self, rect: Rect, pos: Pos2) {
+        let x = (pos.x - rect.left()) / rect.width();
+        let y = (rect.bottom() - pos.y) / rect.height();
+        self.setter
+            .set_parameter_normalized(self.x, x.clamp(0.0, 1.0));
+        self.setter
+            .set_parameter_normalized(self.y, y.clamp(0.0, 1.0));
+    }
+}
+
+impl egui::Widget for XyPad<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let (rect, mut response) =
+            ui.allocate_exact_size(egui::vec2(self.size, self.size), Sense::click_and_drag());
+
+        if response.double_clicked() {
+            self.begin();
+            self.setter
+                .set_parameter_normalized(self.x, self.x.default_normalized_value());
+            self.setter
+                .set_parameter_normalized(self.y, self.y.default_normalized_value());
+            self.end();
+            response.mark_changed();
+        } else if response.drag_started() {
+            self.begin();
+        } else if response.clicked() {
+            // A click without a drag jumps there as a gesture of its own
+            if let Some(pos) = response.interact_pointer_pos() {
+                self.begin();
+                self.set_from_pos(rect, pos);
+                self.end();
+                response.mark_changed();
+            }
+        }
+        if response.dragged() {
+            if let Some(pos) = response.interact_pointer_pos() {
+                self.set_from_pos(rect, pos);
+                response.mark_changed();
+            }
+        }
+        if response.drag_stopped() {
+            self.end();
+        }
+        // Further fingers, the first one may already arrive as the pointer above
+        if !response.dragged() {
+            if let Some(touch) = touch::drag(ui, response.id, rect) {
+                if touch.started {
+                    self.begin();
+                }
+                self.set_from_pos(rect, touch.pos);
+                response.mark_changed();
+                if touch.ended {
+                    self.end();
+                }
+            }
+        }
+        let [right, up] = access::key_steps(ui, &response);
+        if right != 0.0 || up != 0.0 {
+            self.begin();
+            for (param, step) in [(self.x, right), (self.y, up)] {
+                let value = param.unmodulated_normalized_value() + step;
+                self.setter
+                    .set_parameter_normalized(param, value.clamp(0.0, 1.0));
+            }
+            self.end();
+            response.mark_changed();
+        }
+        response.widget_info(|| {
+            let value = |param: &FloatParam| {
+                param.normalized_value_to_string(param.unmodulated_normalized_value(), true)
+            };
+            let name = format!("{} / {}", self.x.name(), self.y.name());
+            WidgetInfo {
+                current_text_value: Some(format!("{} / {}", value(self.x), value(self.y))),
+                ..WidgetInfo::slider(
+                    response.enabled(),
+                    self.x.unmodulated_normalized_value() as f64,
+                    name,
+                )
+            }
+        });
+
+        let painter = ui.painter_at(rect);
+        let border = if response.has_focus() { CURVE } else { GRID };
+        painter.rect_stroke(
+            rect,
+            0.0,
+            Stroke::new(1.0, border),
+            egui::StrokeKind::Inside,
+        );
+        for fraction in [0.25, 0.5, 0.75] {
+            let x = rect.left() + rect.width() * fraction;
+            let y = rect.top() + rect.height() * fraction;
+            painter.vline(x, rect.y_range(), Stroke::new(1.0, GRID));
+            painter.hline(rect.x_range(), y, Stroke::new(1.0, GRID));
+        }
+
+        let own = [
+            self.x.unmodulated_normalized_value(),
+            self.y.unmodulated_normalized_value(),
+        ];
+        let to_pos = |[x, y]: [f32; 2]| {
+            Pos2::new(
+                rect.left() + x * rect.width(),
+                rect.bottom() - y * rect.height(),
+            )
+        };
+        let puck = to_pos(own);
+        painter.line_segment(
+            [Pos2::new(puck.x, rect.bottom()), puck],
+            Stroke::new(1.0, CURVE),
+        );
+        painter.line_segment(
+            [Pos2::new(rect.left(), puck.y), puck],
+            Stroke::new(1.0, CURVE),
+        );
+        painter.circle_filled(puck, POINT_RADIUS, CURVE);
+        if self.external.iter().any(Option::is_some) {
+            let external = to_pos([
+                self.external[0].unwrap_or(own[0]),
+                self.external[1].unwrap_or(own[1]),
+            ]);
+            painter.circle_stroke(external, POINT_RADIUS + 2.0, Stroke::new(2.0, SPECTRUM));
+        }
+
+        let readout = |param: &FloatParam| {
+            format!(
+                "{} {}",
+                param.name(),
+                param.normalized_value_to_string(param.unmodulated_normalized_value(), true)
+            )
+        };
+        let text_color = ui.visuals().text_color();
+        painter.text(
+            rect.right_bottom() + egui::vec2(-4.0, -4.0),
+            Align2::RIGHT_BOTTOM,
+            readout(self.x),
+            FontId::proportional(11.0),
+            text_color,
+        );
+        painter.text(
+            rect.left_top() + egui::vec2(4.0, 4.0),
+            Align2::LEFT_TOP,
+            readout(self.y),
+            FontId::proportional(11.0),
+            text_color,
+        );
+
+        response
+    }
+}
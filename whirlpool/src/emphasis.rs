@@ -0,0 +1,62 @@
+use rustfft::num_complex::Complex;
+
+/// Frequency above which the pre-emphasis rises by 6 dB per octave.
+const CORNER: f32 = 300.0;
+
+/// Pre-emphasis of the spectrum the processing sees, flat below `CORNER` and rising by 6 dB per
+/// octave above it, with the matching de-emphasis after resynthesis. The upper partials of dark
+/// sources then stand out of their neighbourhood, so they are tracked and harmonized as readily
+/// as the fundamentals. Bins that pass through unprocessed come out as they went in, while the
+/// harmony voice keeps the weight of the bins it came from, so shifting up comes out darker and
+/// shifting down brighter. The gate, the freeze and the tamer work on the flat spectrum, so
+/// switching never changes what was learned or saved.
+pub struct Emphasis {
+    enabled: bool,
+    /// Weight per bin at `sample_rate`.
+    weights: Vec<f32>,
+    sample_rate: f32,
+}
+
+impl Emphasis {
+    pub fn new(bins: usize) -> Self {
+        Self {
+            enabled: false,
+            weights: vec![1.0; bins],
+            sample_rate: 0.0,
+        }
+    }
+
+    /// Picks up the settings for the next block.
+    pub fn set(&mut self, enabled: bool, sample_rate: f32) {
+        self.enabled = enabled;
+        if sample_rate == self.sample_rate {
+            return;
+        }
+        self.sample_rate = sample_rate;
+        // The weights cover the lower half of an FFT twice their length
+        let bin_hz = sample_rate / (2 * self.weights.len()) as f32;
+        for (i, weight) in self.weights.iter_mut().enumerate() {
+            *weight = (i as f32 * bin_hz / CORNER).hypot(1.0);
+        }
+    }
+
+    /// Tilts the analysed half spectrum `spectrum` up, nothing while the emphasis is off.
+    pub fn apply(&self, spectrum: &mut [Complex<f32>]) {
+        if !self.enabled {
+            return;
+        }
+        for (bin, weight) in spectrum.iter_mut().zip(&self.weights) {
+            *bin *= *weight;
+        }
+    }
+
+    /// Undoes [`apply()`](Self::apply) on the resynthesized half spectrum `spectrum`.
+    pub fn remove(&self, spectrum: &mut [Complex<f32>]) {
+        if !self.enabled {
+            return;
+        }
+        for (bin, weight) in spectrum.iter_mut().zip(&self.weights) {
+            *bin /= *weight;
+        }
+    }
+}
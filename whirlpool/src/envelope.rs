@@ -0,0 +1,47 @@
+use nih_plug::util;
+use rustfft::num_complex::Complex;
+
+/// Half-width of the smoothing, in bins. Wide enough to bridge the partials of a low voice so
+/// only the formants are left.
+const RADIUS: usize = 12;
+/// The correction never moves a bin further than this, so near-silent regions of the envelope
+/// cannot blow up the harmony voice.
+const MAX_CORRECTION_DB: f32 = 24.0;
+
+/// Smoothed log-magnitude spectrum of one frame, the shape harmony content is fitted to when the
+/// harmonizer preserves the source's body.
+pub struct SpectralEnvelope {
+    /// Running sum of the bin levels in dB, one longer than the spectrum.
+    prefix: Vec<f64>,
+    /// Level per bin in dB.
+    envelope: Vec<f32>,
+}
+
+impl SpectralEnvelope {
+    pub fn new(bins: usize) -> Self {
+        Self {
+            prefix: vec![0.0; bins + 1],
+            envelope: vec![util::MINUS_INFINITY_DB; bins],
+        }
+    }
+
+    /// Estimates the envelope of the positive-frequency half `spectrum`.
+    pub fn analyse(&mut self, spectrum: &[Complex<f32>]) {
+        for (i, bin) in spectrum.iter().enumerate() {
+            self.prefix[i + 1] = self.prefix[i] + util::gain_to_db(bin.norm()) as f64;
+        }
+
+        let bins = spectrum.len();
+        for (i, level) in self.envelope.iter_mut().enumerate() {
+            let start = i.saturating_sub(RADIUS);
+            let end = (i + RADIUS + 1).min(bins);
+            *level = ((self.prefix[end] - self.prefix[start]) / (end - start) as f64) as f32;
+        }
+    }
+
+    /// Gain that fits content moved from bin `from` to bin `to` onto the envelope at `to`.
+    pub fn correction(&self, from: usize, to: usize) -> f32 {
+        let db = self.envelope[to] - self.envelope[from];
+        util::db_to_gain(db.clamp(-MAX_CORRECTION_DB, MAX_CORRECTION_DB))
+    }
+}
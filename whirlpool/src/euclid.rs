@@ -0,0 +1,133 @@
+use nih_plug::prelude::*;
+
+use crate::tempo::SyncGrid;
+
+/// Most steps of a Euclidean pattern.
+pub const MAX_STEPS: usize = 32;
+/// Grains in the burst every hit spawns with the Grains target.
+const BURST_GRAINS: usize = 4;
+/// Time between the grains of a burst, in seconds.
+const BURST_SPACING: f32 = 0.02;
+
+/// What the hits of the Euclidean pattern do.
+#[derive(Enum, Debug, Clone, Copy, PartialEq)]
+pub enum EuclidTarget {
+    Off,
+    /// Every hit spawns a short burst of grains, which otherwise stay silent.
+    #[name = "Grain Bursts"]
+    Grains,
+    /// Every hit recaptures the spectrum held by Freeze.
+    #[name = "Freeze Captures"]
+    Freeze,
+    /// Every hit freezes the spectrum for half a step.
+    Stutter,
+}
+
+/// `fills` hits spread as evenly as possible over `steps` steps, turned `rotation` steps
+/// later.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EuclidPattern {
+    pub steps: usize,
+    pub fills: usize,
+    pub rotation: usize,
+}
+
+impl EuclidPattern {
+    /// Whether `step` of the looping pattern is a hit.
+    pub fn hit(self, step: usize) -> bool {
+        let steps = self.steps.clamp(1, MAX_STEPS);
+        let idx = (step + steps - self.rotation % steps) % steps;
+        (idx * self.fills.min(steps)) % steps < self.fills.min(steps)
+    }
+}
+
+/// Plays a [`EuclidPattern`] on the tempo grid, following the transport one sample at a time.
+pub struct EuclidSequencer {
+    grid: SyncGrid,
+    /// Pattern step the transport is in, `None` after a reset.
+    step: Option<usize>,
+    /// Grains of the current burst still to spawn, and samples until the next one.
+    burst: usize,
+    burst_wait: usize,
+    burst_spacing: usize,
+    /// Samples the current stutter still holds the spectrum.
+    stutter: usize,
+}
+
+impl EuclidSequencer {
+    pub fn new(sample_rate: f32) -> Self {
+        let mut sequencer = Self {
+            grid: SyncGrid::new(),
+            step: None,
+            burst: 0,
+            burst_wait: 0,
+            burst_spacing: 0,
+            stutter: 0,
+        };
+        sequencer.set_sample_rate(sample_rate);
+        sequencer
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.burst_spacing = (BURST_SPACING * sample_rate) as usize;
+    }
+
+    pub fn reset(&mut self) {
+        self.grid.reset();
+        self.step = None;
+        self.burst = 0;
+        self.stutter = 0;
+    }
+
+    /// Pattern step the transport is in, `None` after a reset.
+    pub fn step(&self) -> Option<usize> {
+        self.step
+    }
+
+    /// Moves to transport position `beats` and returns whether a hit fires at this sample.
+    /// Steps are `length` quarter notes long, swing and humanize work as on the grain grid.
+    pub fn tick(
+        &mut self,
+        beats: f64,
+        length: f64,
+        swing: f64,
+        humanize: f32,
+        pattern: EuclidPattern,
+    ) -> bool {
+        let steps = (beats / length).max(0.0) as usize;
+        let step = steps % pattern.steps.clamp(1, MAX_STEPS);
+        self.step = Some(step);
+        self.grid.tick(beats, length, swing, humanize) && pattern.hit(step)
+    }
+
+    /// Starts a burst of grains with this sample if `hit`, and returns whether one of the
+    /// burst's grains spawns at it.
+    pub fn burst(&mut self, hit: bool) -> bool {
+        if hit {
+            self.burst = BURST_GRAINS;
+            self.burst_wait = 0;
+        }
+        if self.burst == 0 {
+            return false;
+        }
+        if self.burst_wait > 0 {
+            self.burst_wait -= 1;
+            return false;
+        }
+        self.burst -= 1;
+        self.burst_wait = self.burst_spacing;
+        true
+    }
+
+    /// Holds the spectrum for the next `samples` samples.
+    pub fn start_stutter(&mut self, samples: usize) {
+        self.stutter = samples;
+    }
+
+    /// Whether a stutter holds the spectrum over the next `len` samples.
+    pub fn stutter(&mut self, len: usize) -> bool {
+        let holding = self.stutter > 0;
+        self.stutter = self.stutter.saturating_sub(len);
+        holding
+    }
+}
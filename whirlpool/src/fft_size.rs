@@ -0,0 +1,39 @@
+/// Smallest FFT size the STFT runs at. Every size is a power of two up to `FFT_SIZE`.
+pub const MIN_FFT_SIZE: usize = 256;
+/// Seconds the wet signal takes to fade over to the engine of a new FFT size.
+pub const FADE_TIME: f32 = 0.100;
+
+/// The FFT size the STFT runs at for a setting of `samples`.
+pub fn from_setting(samples: u16) -> usize {
+    (samples as usize)
+        .clamp(MIN_FFT_SIZE, crate::FFT_SIZE)
+        .next_power_of_two()
+}
+
+/// Every FFT size the STFT runs at, smallest first.
+pub fn sizes() -> impl Iterator<Item = usize> {
+    (MIN_FFT_SIZE.trailing_zeros()..=crate::FFT_SIZE.trailing_zeros()).map(|bits| 1 << bits)
+}
+
+/// Fills `to` with the per-bin values of a spectrum `len` bins long, as read by `from`, at the
+/// frequencies of `to`'s bins and times `scale`. Shrinking keeps the largest value each new bin
+/// covers, so a partial between the new bins is not lost.
+pub fn rebin(len: usize, from: impl Fn(usize) -> f32, to: &mut [f32], scale: f32) {
+    let step = len as f32 / to.len() as f32;
+    let reach = (step / 2.0) as usize;
+    for (i, value) in to.iter_mut().enumerate() {
+        let position = i as f32 * step;
+        *value = scale
+            * if step > 1.0 {
+                let centre = (position.round() as usize).min(len - 1);
+                (centre.saturating_sub(reach)..=(centre + reach).min(len - 1))
+                    .map(&from)
+                    .fold(0.0, f32::max)
+            } else {
+                let below = (position as usize).min(len - 1);
+                let above = (below + 1).min(len - 1);
+                let t = position - below as f32;
+                from(below) + (from(above) - from(below)) * t
+            };
+    }
+}
@@ -0,0 +1,180 @@
+use crate::fft_size::rebin;
+use rustfft::num_complex::Complex;
+use std::f32::consts::{FRAC_PI_2, PI};
+
+pub const NUM_SLOTS: usize = 8;
+
+/// What the bank does to one frame, decided once per hop for all channels.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct BankFrame {
+    /// Slot that stores this frame's spectrum before anything is played.
+    pub capture: Option<usize>,
+    /// Fading out, `None` for the live spectrum.
+    pub from: Option<usize>,
+    /// Fading in, `None` for the live spectrum.
+    pub to: Option<usize>,
+    /// Crossfade position from `from` to `to`, in `0..=1`.
+    pub fade: f32,
+}
+
+/// Clip-launcher state shared by all channels. Launching an empty slot captures into it and
+/// plays it, launching a filled one switches to it and launching the playing one returns to the
+/// live signal. Every switch is an equal-power crossfade.
+pub struct SlotControl {
+    filled: u8,
+    active: Option<usize>,
+    previous: Option<usize>,
+    fade: f32,
+    capture: Option<usize>,
+}
+
+impl SlotControl {
+    pub fn new() -> Self {
+        Self {
+            filled: 0,
+            active: None,
+            previous: None,
+            fade: 1.0,
+            capture: None,
+        }
+    }
+
+    /// Returns to the live signal. Captured slots are kept.
+    pub fn reset(&mut self) {
+        self.active = None;
+        self.previous = None;
+        self.fade = 1.0;
+        self.capture = None;
+    }
+
+    pub fn launch(&mut self, slot: usize) {
+        if slot >= NUM_SLOTS {
+            return;
+        }
+        if self.filled & (1 << slot) == 0 {
+            self.capture = Some(slot);
+            self.filled |= 1 << slot;
+            self.switch_to(Some(slot));
+        } else if self.active == Some(slot) {
+            self.switch_to(None);
+        } else {
+            self.switch_to(Some(slot));
+        }
+    }
+
+    pub fn erase(&mut self, slot: usize) {
+        if slot >= NUM_SLOTS {
+            return;
+        }
+        self.filled &= !(1 << slot);
+        if self.active == Some(slot) {
+            self.switch_to(None);
+        }
+    }
+
+    fn switch_to(&mut self, slot: Option<usize>) {
+        self.previous = self.active;
+        self.active = slot;
+        self.fade = 0.0;
+    }
+
+    /// Bit `n` is set when slot `n` holds a spectrum.
+    pub fn filled(&self) -> u8 {
+        self.filled
+    }
+
+    pub fn active(&self) -> Option<usize> {
+        self.active
+    }
+
+    /// Advances the crossfade by `fade_step` and returns what this hop's frame should do.
+    pub fn next_frame(&mut self, fade_step: f32) -> BankFrame {
+        self.fade = (self.fade + fade_step).min(1.0);
+        let frame = BankFrame {
+            capture: self.capture.take(),
+            from: self.previous,
+            to: self.active,
+            fade: self.fade,
+        };
+        if self.fade >= 1.0 {
+            self.previous = self.active;
+        }
+        frame
+    }
+}
+
+/// One channel's captured spectra.
+pub struct SlotSpectra {
+    mags: Vec<f32>,
+    phases: Vec<f32>,
+    bins: usize,
+    /// Phase advance per hop for a sinusoid centred on bin 1.
+    hop_phase_step: f32,
+}
+
+impl SlotSpectra {
+    pub fn new(fft_size: usize, hop_size: usize) -> Self {
+        let bins = fft_size / 2;
+        Self {
+            mags: vec![0.0; bins * NUM_SLOTS],
+            phases: vec![0.0; bins * NUM_SLOTS],
+            bins,
+            hop_phase_step: 2.0 * PI * hop_size as f32 / fft_size as f32,
+        }
+    }
+
+    /// Applies `frame` to the positive-frequency half of an analysed spectrum. Played slots keep
+    /// rotating at their bins' centre frequencies like the main freeze.
+    pub fn apply(&mut self, spectrum: &mut [Complex<f32>], frame: BankFrame) {
+        if let Some(slot) = frame.capture {
+            let range = self.range(slot);
+            for ((mag, phase), bin) in self.mags[range.clone()]
+                .iter_mut()
+                .zip(&mut self.phases[range])
+                .zip(spectrum.iter())
+            {
+                *mag = bin.norm();
+                *phase = bin.arg();
+            }
+        }
+        if frame.from.is_none() && frame.to.is_none() {
+            return;
+        }
+
+        let from_gain = (frame.fade * FRAC_PI_2).cos();
+        let to_gain = (frame.fade * FRAC_PI_2).sin();
+        for (i, bin) in spectrum.iter_mut().enumerate().take(self.bins) {
+            let from = self.advance(frame.from, i).unwrap_or(*bin);
+            let to = if frame.to == frame.from {
+                from
+            } else {
+                self.advance(frame.to, i).unwrap_or(*bin)
+            };
+            *bin = from * from_gain + to * to_gain;
+        }
+    }
+
+    /// Takes over the captured spectra of `other`, which runs at another FFT size, moved onto
+    /// these bins. Their phases start over.
+    pub fn take_over(&mut self, other: &SlotSpectra) {
+        // A partial's magnitude grows with the frame length
+        let scale = self.bins as f32 / other.bins as f32;
+        for slot in 0..NUM_SLOTS {
+            let (to, from) = (self.range(slot), other.range(slot));
+            let from = &other.mags[from];
+            rebin(other.bins, |i| from[i], &mut self.mags[to], scale);
+        }
+        self.phases.fill(0.0);
+    }
+
+    fn advance(&mut self, slot: Option<usize>, bin: usize) -> Option<Complex<f32>> {
+        let idx = self.range(slot?).start + bin;
+        let phase = (self.phases[idx] + self.hop_phase_step * bin as f32) % (2.0 * PI);
+        self.phases[idx] = phase;
+        Some(Complex::from_polar(self.mags[idx], phase))
+    }
+
+    fn range(&self, slot: usize) -> std::ops::Range<usize> {
+        slot * self.bins..(slot + 1) * self.bins
+    }
+}
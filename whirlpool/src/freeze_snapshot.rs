@@ -0,0 +1,120 @@
+use crate::fft_size::rebin;
+use serde::{Deserialize, Serialize};
+
+/// Level of the quietest stored magnitude in dB, anything below is saved as silence.
+const FLOOR_DB: f32 = -160.0;
+/// Steps per dB the magnitudes are quantized to. 1/256 dB is far below what can be heard, and
+/// leaves room for levels up to about +96 dB.
+const STEPS_PER_DB: f32 = 256.0;
+
+/// The frozen spectrum saved with the plugin state, so a project reopens with the drone it was
+/// left on instead of silence until the next capture. Magnitudes are stored as 16-bit dB steps,
+/// about a kilobyte per channel. Phases are not saved, frozen phases keep turning on their own
+/// anyway.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FreezeSnapshot {
+    /// Bins per channel, zero while nothing is saved.
+    pub bins: usize,
+    /// Quantized magnitude per bin, one channel after the other. Zero is silence.
+    pub levels: Vec<u16>,
+    /// Sample rate the spectrum was frozen at.
+    pub sample_rate: f32,
+}
+
+impl FreezeSnapshot {
+    /// Reserves room for `num_channels` spectra of `bins` bins, so storing them never allocates.
+    pub fn reserve(&mut self, num_channels: usize, bins: usize) {
+        self.levels
+            .reserve((num_channels * bins).saturating_sub(self.levels.len()));
+    }
+
+    pub fn clear(&mut self) {
+        self.bins = 0;
+        self.levels.clear();
+    }
+
+    /// Replaces the snapshot with one spectrum of magnitudes per channel, frozen at
+    /// `sample_rate`.
+    pub fn store<'a>(
+        &mut self,
+        bins: usize,
+        sample_rate: f32,
+        spectra: impl Iterator<Item = &'a [f32]>,
+    ) {
+        self.bins = bins;
+        self.sample_rate = sample_rate;
+        self.levels.clear();
+        for spectrum in spectra {
+            self.levels
+                .extend(spectrum.iter().map(|&magnitude| quantize(magnitude)));
+        }
+    }
+
+    /// Moves the spectra onto the bins of the same FFT at `sample_rate`, so the drone keeps its
+    /// pitch in a project at another rate. Bins past the old Nyquist frequency are silent, and
+    /// bins between two silent ones stay silent.
+    pub fn resample(&mut self, sample_rate: f32) {
+        if self.bins > 0 && self.sample_rate > 0.0 && self.sample_rate != sample_rate {
+            let (bins, scale) = (self.bins, sample_rate / self.sample_rate);
+            self.levels = self
+                .levels
+                .chunks_exact(bins)
+                .flat_map(|levels| {
+                    (0..bins).map(move |i| {
+                        let position = i as f32 * scale;
+                        let below = position as usize;
+                        if below + 1 >= bins {
+                            return 0;
+                        }
+                        // In linear magnitude, so a silent neighbour fades instead of
+                        // reading as the floor level
+                        let t = position - below as f32;
+                        let (low, high) =
+                            (dequantize(levels[below]), dequantize(levels[below + 1]));
+                        quantize(low + (high - low) * t)
+                    })
+                })
+                .collect();
+        }
+        self.sample_rate = sample_rate;
+    }
+
+    /// Writes channel `channel`'s magnitudes into `magnitudes`, channels past the saved ones
+    /// reuse the last spectrum. A spectrum frozen at another FFT size is moved onto the bins of
+    /// `magnitudes`. Returns false, leaving `magnitudes` alone, when nothing is saved.
+    pub fn restore(&self, channel: usize, magnitudes: &mut [f32]) -> bool {
+        if self.bins == 0 {
+            return false;
+        }
+        let mut spectra = self.levels.chunks_exact(self.bins);
+        match spectra.clone().nth(channel).or(spectra.next_back()) {
+            Some(levels) if self.bins == magnitudes.len() => {
+                for (magnitude, &level) in magnitudes.iter_mut().zip(levels) {
+                    *magnitude = dequantize(level);
+                }
+                true
+            }
+            // A partial's magnitude grows with the frame length
+            Some(levels) => {
+                let scale = magnitudes.len() as f32 / self.bins as f32;
+                rebin(self.bins, |i| dequantize(levels[i]), magnitudes, scale);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn quantize(magnitude: f32) -> u16 {
+    let db = 20.0 * magnitude.max(f32::MIN_POSITIVE).log10();
+    ((db - FLOOR_DB) * STEPS_PER_DB)
+        .round()
+        .clamp(0.0, u16::MAX as f32) as u16
+}
+
+fn dequantize(level: u16) -> f32 {
+    if level == 0 {
+        return 0.0;
+    }
+    10f32.powf((level as f32 / STEPS_PER_DB + FLOOR_DB) / 20.0)
+}
@@ -0,0 +1,604 @@
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+
+use crate::grain_filter::{GrainFilter, GrainFilterSettings};
+use crate::surround;
+use crate::tape;
+
+/// Longest delay time, in seconds. Buffers shorter than this cut longer delays short.
+pub const MAX_DELAY: f32 = 2.0;
+/// Range and default of the audio the buffer holds, in seconds.
+pub const MIN_BUFFER: u8 = 1;
+pub const MAX_BUFFER: u8 = 20;
+pub const DEFAULT_BUFFER: u8 = 2;
+/// Largest modulation added on top of the delay, in seconds.
+pub const MAX_MODULATION: f32 = 0.020;
+/// Fewest overlapping grains, below this the Hann envelopes no longer sum to a constant.
+pub const MIN_GRAINS: usize = 2;
+pub const MAX_GRAINS: usize = 8;
+/// Grain length in seconds. With `n` voices a new grain starts every `1/n` of a grain.
+const GRAIN_LENGTH: f32 = 0.060;
+/// Every grain starts up to this fraction of a grain length further back, which keeps repeats
+/// from sounding like a plain tape echo.
+const JITTER: f32 = 0.25;
+/// Time in seconds the hold takes to close the loop. The loop's splice lies inside this
+/// crossfade, so it repeats without a click.
+const HOLD_ATTACK: f32 = 0.050;
+/// Time in seconds the held loop takes to fade out after the hold is released.
+const HOLD_RELEASE: f32 = 2.0;
+/// Note a MIDI grain plays at its own pitch and at the delay time from.
+const KEY_TRACK_ROOT: f32 = 60.0;
+/// Playback rates a key tracked grain is kept within, two octaves either way. Also the range
+/// of the scan rate, see `set_stretch()`.
+pub const MIN_RATE: f32 = 0.25;
+pub const MAX_RATE: f32 = 4.0;
+/// Range and default of the time in seconds the tape stop takes to slow down to a halt.
+pub const MIN_TAPE_STOP: f32 = 0.050;
+pub const MAX_TAPE_STOP: f32 = 5.0;
+pub const DEFAULT_TAPE_STOP: f32 = 1.0;
+/// Rate of the exponential jitter distribution over the jitter range, higher packs the grains
+/// closer to the delay time.
+const EXPONENTIAL_RATE: f32 = 4.0;
+/// Grain lengths the overlap estimate of the normalized output averages the grain starts over.
+const OVERLAP_AVERAGING: f32 = 4.0;
+/// Width of the Gaussian envelope at the smooth end of the shape range, as a fraction of the
+/// grain length. Narrow enough that the ends of the grain are practically silent.
+const GAUSSIAN_WIDTH: f32 = 0.15;
+
+/// Average of `envelope()` squared over a grain, used to keep the power the same across shapes.
+fn envelope_mean_square(shape: f32) -> f32 {
+    const POINTS: usize = 64;
+    (0..POINTS)
+        .map(|i| envelope((i as f32 + 0.5) / POINTS as f32, shape).powi(2))
+        .sum::<f32>()
+        / POINTS as f32
+}
+
+/// Samples in a buffer holding `seconds` of audio at `sample_rate`, with room for the jitter and
+/// the modulation on top.
+pub fn buffer_len(sample_rate: f32, seconds: u8) -> usize {
+    let jitter = (JITTER * grain_samples(sample_rate) as f32).ceil() as usize;
+    let modulation =
+        ((MAX_MODULATION + tape::MAX_WOW + tape::MAX_FLUTTER) * sample_rate).ceil() as usize;
+    (seconds as f32 * sample_rate) as usize + jitter + modulation + 3
+}
+
+fn grain_samples(sample_rate: f32) -> usize {
+    ((GRAIN_LENGTH * sample_rate) as usize).max(2)
+}
+
+/// Envelope of a grain at `phase` in `0..1`. A `shape` of zero is rectangular, 0.5 is Hann and 1
+/// a Gaussian, values in between morph between the neighbouring envelopes.
+fn envelope(phase: f32, shape: f32) -> f32 {
+    if shape < 0.5 {
+        // Tukey window, tapering over `taper` of the grain
+        let taper = shape * 2.0;
+        let edge = phase.min(1.0 - phase);
+        if edge < taper / 2.0 {
+            0.5 - 0.5 * (2.0 * PI * edge / taper).cos()
+        } else {
+            1.0
+        }
+    } else {
+        let hann = 0.5 - 0.5 * (2.0 * PI * phase).cos();
+        let t = shape * 2.0 - 1.0;
+        if t > 0.0 {
+            let x = (phase - 0.5) / GAUSSIAN_WIDTH;
+            hann + ((-0.5 * x * x).exp() - hann) * t
+        } else {
+            hann
+        }
+    }
+}
+
+/// Average of `envelope()` over a grain, used to keep the level the same across shapes.
+fn envelope_mean(shape: f32) -> f32 {
+    if shape < 0.5 {
+        1.0 - shape
+    } else {
+        let gaussian = GAUSSIAN_WIDTH * (2.0 * PI).sqrt();
+        0.5 + (gaussian - 0.5) * (shape * 2.0 - 1.0)
+    }
+}
+
+/// How the jitter of new grains is spread over its range.
+#[derive(Enum, Debug, Clone, Copy, PartialEq)]
+pub enum JitterDistribution {
+    Uniform,
+    /// Bell shaped around the middle of the range.
+    Gaussian,
+    /// Most grains close to the delay time, fewer further back.
+    Exponential,
+}
+
+/// Pitches grains are played at besides their own, so the cloud forms a chord.
+#[derive(Enum, Debug, Clone, Copy, PartialEq)]
+pub enum GrainChord {
+    Off,
+    Fifth,
+    Octave,
+    Major,
+    Minor,
+}
+
+impl GrainChord {
+    /// The chord's tones above the root, in semitones.
+    fn upper_tones(self) -> &'static [f32] {
+        match self {
+            GrainChord::Off => &[],
+            GrainChord::Fifth => &[7.0],
+            GrainChord::Octave => &[12.0],
+            GrainChord::Major => &[4.0, 7.0],
+            GrainChord::Minor => &[3.0, 7.0],
+        }
+    }
+}
+
+/// What the note number of a MIDI grain changes.
+#[derive(Enum, Debug, Clone, Copy, PartialEq)]
+pub enum GrainKeyTrack {
+    Off,
+    /// Higher notes read closer to the write head, an octave halving the delay time.
+    Position,
+    /// Higher notes play the grain faster, in semitones from middle C.
+    Pitch,
+}
+
+/// How a grain started by a note differs from one started on its own.
+#[derive(Clone, Copy, PartialEq)]
+pub struct GrainNote {
+    /// Level of the grain, from the note's velocity.
+    pub gain: f32,
+    /// Factor on the delay time the grain starts reading at.
+    pub delay: f32,
+    /// Playback rate of the grain, one at its recorded pitch.
+    pub rate: f32,
+}
+
+impl GrainNote {
+    /// A grain as started by the steady rate or the sync grid.
+    pub const PLAIN: Self = Self {
+        gain: 1.0,
+        delay: 1.0,
+        rate: 1.0,
+    };
+
+    /// The grain played by `note` at `velocity` in `0..=1`. `velocity_amount` is how far the
+    /// velocity sets the grain's level, from not at all to fully.
+    pub fn from_midi(
+        note: u8,
+        velocity: f32,
+        velocity_amount: f32,
+        key_track: GrainKeyTrack,
+    ) -> Self {
+        let octaves = (note as f32 - KEY_TRACK_ROOT) / 12.0;
+        let mut grain = Self {
+            gain: 1.0 + (velocity - 1.0) * velocity_amount,
+            ..Self::PLAIN
+        };
+        match key_track {
+            GrainKeyTrack::Off => (),
+            GrainKeyTrack::Position => grain.delay = (-octaves).exp2(),
+            GrainKeyTrack::Pitch => grain.rate = octaves.exp2().clamp(MIN_RATE, MAX_RATE),
+        }
+        grain
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Grain {
+    /// Distance behind the write head this grain reads from.
+    delay: usize,
+    age: usize,
+    /// Playback rate, negative for a reversed grain. The read head drifts from `delay` by
+    /// `1 - rate` samples per sample, times the tape speed.
+    rate: f32,
+    /// Samples the read head has drifted from `delay` so far.
+    drift: f32,
+    /// Makes up for the number of voices overlapping and the envelope's area when the grain
+    /// started.
+    gain: f32,
+    shape: f32,
+    filter: Option<GrainFilter>,
+    /// Place of the grain from the front at zero to the back at one, for surround output.
+    depth: f32,
+}
+
+/// Delay line read by overlapping, windowed grains. The delay time, envelope shape and filter are
+/// picked up at the start of each grain, so changing them never causes a jump in the output.
+/// Changing the number of voices only affects grains started afterwards, running grains play out
+/// at their own gain. The modulation moves all read heads continuously, for chorus and flanger
+/// style pitch wobble, the tape stop slows all of them down to a halt.
+pub struct GrainDelay {
+    buffer: Vec<f32>,
+    write_pos: usize,
+    grains: [Grain; MAX_GRAINS],
+    grain_samples: usize,
+    /// Index of the grain started last.
+    current: usize,
+    voices: usize,
+    /// Whether grains are scaled to `overlap` instead of to the number of voices.
+    normalize: bool,
+    /// Grains running at once on average, the rate grains started at recently times the grain
+    /// length. Follows synced and triggered grains too, which `voices` does not describe.
+    overlap: f32,
+    shape: f32,
+    filter: Option<GrainFilterSettings>,
+    jitter_distribution: JitterDistribution,
+    /// Exponent the jitter is raised to, see `set_jitter()`.
+    jitter_curve: f32,
+    chord: GrainChord,
+    /// Share of the grains played at one of the chord's upper tones instead of the root.
+    chord_weight: f32,
+    /// How far towards the back new grains may be placed.
+    spread: f32,
+    /// The grains' output in the rear speakers for the last sample.
+    rear: f32,
+    /// Whether grains start on `trigger()` instead of at a steady rate.
+    synced: bool,
+    /// The grain the next sample starts while synced.
+    triggered: Option<GrainNote>,
+    hold: bool,
+    /// How far the buffer recirculates instead of taking new input, ramped by the hold.
+    hold_level: f32,
+    /// Length of the held loop, the delay time when the hold was engaged.
+    hold_delay: usize,
+    /// Speed the scan head moves through the buffer at, relative to the write head.
+    stretch: f32,
+    /// Distance the scan head has fallen behind the delay time, or wrapped around to after
+    /// catching up with it. New grains start this much further back.
+    scan: f32,
+    /// Whether new grains play backwards.
+    reverse: bool,
+    tape_stop: bool,
+    /// Seconds the tape stop takes to halt, and to spin back up after it is released.
+    tape_stop_time: f32,
+    /// Speed of every read head, ramped from one down to zero by the tape stop.
+    tape_speed: f32,
+    sample_rate: f32,
+    rng_state: u32,
+}
+
+impl GrainDelay {
+    pub fn new(sample_rate: f32) -> Self {
+        let mut delay = Self {
+            buffer: Vec::new(),
+            write_pos: 0,
+            grains: [Grain {
+                delay: 0,
+                age: 0,
+                rate: 1.0,
+                drift: 0.0,
+                gain: 1.0,
+                shape: 0.5,
+                filter: None,
+                depth: 0.0,
+            }; MAX_GRAINS],
+            grain_samples: 0,
+            current: 0,
+            voices: MIN_GRAINS,
+            normalize: false,
+            overlap: 0.0,
+            shape: 0.5,
+            filter: None,
+            jitter_distribution: JitterDistribution::Uniform,
+            jitter_curve: 1.0,
+            chord: GrainChord::Off,
+            chord_weight: 0.0,
+            spread: 0.0,
+            rear: 0.0,
+            synced: false,
+            triggered: None,
+            hold: false,
+            hold_level: 0.0,
+            hold_delay: 1,
+            stretch: 1.0,
+            scan: 0.0,
+            reverse: false,
+            tape_stop: false,
+            tape_stop_time: DEFAULT_TAPE_STOP,
+            tape_speed: 1.0,
+            sample_rate,
+            rng_state: 1,
+        };
+        delay.set_sample_rate(sample_rate, DEFAULT_BUFFER);
+        delay
+    }
+
+    /// Resizes the buffer to hold `seconds` of audio at `sample_rate`. This allocates and clears
+    /// the delay line.
+    pub fn set_sample_rate(&mut self, sample_rate: f32, seconds: u8) {
+        self.sample_rate = sample_rate;
+        self.grain_samples = grain_samples(sample_rate);
+        self.buffer = vec![0.0; buffer_len(sample_rate, seconds)];
+        self.reset();
+    }
+
+    /// Samples the buffer holds.
+    pub fn buffer_len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Swaps the buffer for `buffer`, allocated away from the audio thread with `buffer_len()`,
+    /// and clears the delay line. The old buffer ends up in `buffer` to be freed there too.
+    pub fn swap_buffer(&mut self, buffer: &mut Vec<f32>) {
+        std::mem::swap(&mut self.buffer, buffer);
+        self.reset();
+    }
+
+    pub fn reset(&mut self) {
+        self.buffer.fill(0.0);
+        self.write_pos = 0;
+        // All grains finished, so the first sample starts a new one
+        self.grains = [Grain {
+            delay: 1,
+            age: self.grain_samples,
+            rate: 1.0,
+            drift: 0.0,
+            gain: 1.0,
+            shape: 0.5,
+            filter: None,
+            depth: 0.0,
+        }; MAX_GRAINS];
+        self.current = 0;
+        self.overlap = 0.0;
+        self.triggered = None;
+        self.rear = 0.0;
+        self.hold_level = 0.0;
+        self.scan = 0.0;
+        self.tape_speed = 1.0;
+        self.rng_state = 1;
+    }
+
+    /// Sets how many grains overlap, clamped to `MIN_GRAINS..=MAX_GRAINS`.
+    pub fn set_voices(&mut self, voices: usize) {
+        self.voices = voices.clamp(MIN_GRAINS, MAX_GRAINS);
+    }
+
+    /// Scales new grains to the overlap of the grains started recently, so the cloud keeps about
+    /// the same loudness at any voice count, sync rate or trigger density. Grains read from
+    /// different places add up in power, so the gain falls with the square root of the overlap.
+    /// Without it grains are scaled to the number of voices, which leaves dense triggered grains
+    /// free to pile up.
+    pub fn set_normalize(&mut self, normalize: bool) {
+        self.normalize = normalize;
+    }
+
+    /// Sets the envelope of new grains, from rectangular at 0.0 through Hann at 0.5 to Gaussian
+    /// at 1.0.
+    pub fn set_shape(&mut self, shape: f32) {
+        self.shape = shape.clamp(0.0, 1.0);
+    }
+
+    /// Sets the filter new grains are played through, `None` to leave them unfiltered.
+    pub fn set_filter(&mut self, filter: Option<GrainFilterSettings>) {
+        self.filter = filter;
+    }
+
+    /// Sets how the jitter of new grains is spread. A `bias` in `-1..=1` pushes it towards the
+    /// delay time below zero and further back above.
+    pub fn set_jitter(&mut self, distribution: JitterDistribution, bias: f32) {
+        self.jitter_distribution = distribution;
+        self.jitter_curve = 4.0f32.powf(-bias.clamp(-1.0, 1.0));
+    }
+
+    /// Sets the chord new grains pick their pitch from. `weight` is the share of grains played
+    /// at one of its upper tones, the rest play at the root.
+    pub fn set_chord(&mut self, chord: GrainChord, weight: f32) {
+        self.chord = chord;
+        self.chord_weight = weight.clamp(0.0, 1.0);
+    }
+
+    /// Sets how far towards the back new grains are placed at random, from all at the front at
+    /// zero to anywhere between front and back at one. See `rear()`.
+    pub fn set_spread(&mut self, spread: f32) {
+        self.spread = spread.clamp(0.0, 1.0);
+    }
+
+    /// The grains' output for the rear speakers, returned by the last `process()` call.
+    pub fn rear(&self) -> f32 {
+        self.rear
+    }
+
+    /// Switches between grains started by `trigger()` and grains started every `1/voices` of a
+    /// grain length.
+    pub fn set_synced(&mut self, synced: bool) {
+        self.synced = synced;
+    }
+
+    /// Loops the repeats at the current delay time for as long as `hold` is set. New input is
+    /// ignored and grains play unfiltered, so the loop does not degrade. Releasing fades the
+    /// loop out over `HOLD_RELEASE`.
+    pub fn set_hold(&mut self, hold: bool) {
+        self.hold = hold;
+    }
+
+    /// Sets how fast new grains scan through the recorded audio, from a quarter to four times
+    /// real time, while the grains themselves keep their pitch. Below one the scan head crawls
+    /// further and further behind the input, stretching it, above one it catches up and wraps
+    /// back. While holding, the scan wraps around the loop instead, so a held loop can be
+    /// played back slowed down or sped up. At exactly one grains start at the delay time again.
+    pub fn set_stretch(&mut self, stretch: f32) {
+        self.stretch = stretch.clamp(MIN_RATE, MAX_RATE);
+        if self.stretch == 1.0 {
+            self.scan = 0.0;
+        }
+    }
+
+    /// Plays new grains backwards for as long as `reverse` is set. Running grains play out in
+    /// their own direction, so switching never clicks.
+    pub fn set_reverse(&mut self, reverse: bool) {
+        self.reverse = reverse;
+    }
+
+    /// Slows every read head down to a halt over `seconds` while `stop` is set, dropping the
+    /// pitch like a tape machine losing power. Releasing spins them back up just as fast.
+    pub fn set_tape_stop(&mut self, stop: bool, seconds: f32) {
+        self.tape_stop = stop;
+        self.tape_stop_time = seconds.clamp(MIN_TAPE_STOP, MAX_TAPE_STOP);
+    }
+
+    /// How much of the output is the held loop, from 0 when not holding to 1 when holding.
+    pub fn hold_level(&self) -> f32 {
+        self.hold_level
+    }
+
+    /// Starts `note`'s grain with the next sample while synced.
+    pub fn trigger(&mut self, note: GrainNote) {
+        self.triggered = Some(note);
+    }
+
+    /// Writes `input` and returns the grains read `delay` samples behind it, plus `modulation`
+    /// samples for every running grain.
+    pub fn process(&mut self, input: f32, delay: usize, modulation: f32) -> f32 {
+        let len = self.buffer.len();
+        if self.hold {
+            if self.hold_level == 0.0 {
+                self.hold_delay = delay.clamp(1, len - 1);
+            }
+            self.hold_level = (self.hold_level + 1.0 / (HOLD_ATTACK * self.sample_rate)).min(1.0);
+        } else if self.hold_level > 0.0 {
+            self.hold_level = (self.hold_level - 1.0 / (HOLD_RELEASE * self.sample_rate)).max(0.0);
+        }
+        self.buffer[self.write_pos] = if self.hold_level > 0.0 {
+            let looped = self.buffer[(self.write_pos + len - self.hold_delay) % len];
+            input + (looped - input) * self.hold_level
+        } else {
+            input
+        };
+
+        if self.stretch != 1.0 {
+            // The held loop repeats every `hold_delay` samples, so wrapping around it is seamless
+            let span = if self.hold_level > 0.0 {
+                self.hold_delay
+            } else {
+                len - 2 - delay.min(len - 3)
+            };
+            self.scan = (self.scan + 1.0 - self.stretch).rem_euclid(span as f32);
+        }
+        let ramp = 1.0 / (self.tape_stop_time * self.sample_rate);
+        self.tape_speed = if self.tape_stop {
+            (self.tape_speed - ramp).max(0.0)
+        } else {
+            (self.tape_speed + ramp).min(1.0)
+        };
+
+        let start = if self.synced {
+            self.triggered.take()
+        } else {
+            (self.grains[self.current].age >= self.grain_samples / self.voices)
+                .then_some(GrainNote::PLAIN)
+        };
+        // Every start adds a grain length of overlap, averaged over `OVERLAP_AVERAGING` of them
+        let added = if start.is_some() {
+            self.grain_samples as f32
+        } else {
+            0.0
+        };
+        self.overlap += (added - self.overlap) / (OVERLAP_AVERAGING * self.grain_samples as f32);
+        if let Some(mut note) = start {
+            // The oldest grain has faded out by now, or is the closest to it right after the
+            // number of voices dropped or while synced steps come faster than grains end
+            self.current = (0..MAX_GRAINS)
+                .max_by_key(|&idx| (self.grains[idx].age, MAX_GRAINS - idx))
+                .unwrap_or(0);
+            let jitter = (self.next_jitter() * JITTER * self.grain_samples as f32) as usize;
+            let filter = self
+                .filter
+                .filter(|_| !self.hold)
+                .map(|settings| GrainFilter::new(settings, self.next_random(), self.sample_rate));
+            let tones = self.chord.upper_tones();
+            if !tones.is_empty() && self.next_random() < self.chord_weight {
+                let tone = tones[(self.next_random() * tones.len() as f32) as usize % tones.len()];
+                note.rate = (note.rate * (tone / 12.0).exp2()).min(MAX_RATE);
+            }
+            let depth = if self.spread > 0.0 {
+                self.next_random() * self.spread
+            } else {
+                0.0
+            };
+            // A faster grain starts far enough back that its read head never passes the write
+            // head
+            let lead = (self.grain_samples as f32 * (note.rate - 1.0)).max(0.0) as usize + 1;
+            let delay = (delay as f32 * note.delay + self.scan) as usize;
+            let gain = if self.normalize {
+                // Grains further apart than a grain length play as loud as a single one running
+                // all the time, rather than being blown up
+                1.0 / (self.overlap.max(1.0) * envelope_mean_square(self.shape)).sqrt()
+            } else {
+                MIN_GRAINS as f32 / self.voices as f32 * 0.5 / envelope_mean(self.shape)
+            };
+            // A reversed grain reads back from the delay time, away from the write head, and
+            // starts close enough to it that its read head stays within the buffer
+            let rate = if self.reverse { -note.rate } else { note.rate };
+            let reach = (self.grain_samples as f32 * (1.0 - rate)).max(0.0) as usize;
+            self.grains[self.current] = Grain {
+                delay: (delay.max(lead) + jitter).clamp(1, (len - 2).saturating_sub(reach).max(1)),
+                age: 0,
+                rate,
+                drift: 0.0,
+                gain: gain * note.gain,
+                shape: self.shape,
+                filter,
+                depth,
+            };
+        }
+
+        let mut output = 0.0;
+        self.rear = 0.0;
+        for grain in self.grains.iter_mut() {
+            if grain.age >= self.grain_samples {
+                continue;
+            }
+            let phase = grain.age as f32 / self.grain_samples as f32;
+            let position = grain.delay as f32 + modulation + grain.drift;
+            let whole = (position as usize).min(len - 2);
+            let frac = (position - whole as f32).min(1.0);
+            let newer = self.buffer[(self.write_pos + len - whole) % len];
+            let older = self.buffer[(self.write_pos + len - whole - 1) % len];
+            let mut sample = newer + (older - newer) * frac;
+            if let Some(filter) = &mut grain.filter {
+                sample = filter.process(sample);
+            }
+            let sample = sample * envelope(phase, grain.shape) * grain.gain;
+            output += sample;
+            if grain.depth > 0.0 {
+                self.rear += sample * surround::rear_gain(grain.depth);
+            }
+            grain.age += 1;
+            grain.drift += 1.0 - grain.rate * self.tape_speed;
+        }
+
+        self.write_pos = (self.write_pos + 1) % len;
+        // A halted tape is silent rather than stuck on the sample under the read heads
+        self.rear *= self.tape_speed;
+        output * self.tape_speed
+    }
+
+    /// Position of the next grain within the jitter range, in `0..1`.
+    fn next_jitter(&mut self) -> f32 {
+        let jitter = match self.jitter_distribution {
+            JitterDistribution::Uniform => self.next_random(),
+            // The mean of four uniform draws, close enough to a normal distribution
+            JitterDistribution::Gaussian => (0..4).map(|_| self.next_random()).sum::<f32>() / 4.0,
+            // Inverse of the exponential distribution, truncated to the range
+            JitterDistribution::Exponential => {
+                let tail = (-EXPONENTIAL_RATE).exp();
+                -(1.0 - self.next_random() * (1.0 - tail)).ln() / EXPONENTIAL_RATE
+            }
+        };
+        if self.jitter_curve == 1.0 {
+            jitter
+        } else {
+            jitter.powf(self.jitter_curve)
+        }
+    }
+
+    /// Uniform in `0..1`.
+    fn next_random(&mut self) -> f32 {
+        self.rng_state ^= self.rng_state << 13;
+        self.rng_state ^= self.rng_state >> 17;
+        self.rng_state ^= self.rng_state << 5;
+        self.rng_state as f32 / u32::MAX as f32
+    }
+}
@@ -0,0 +1,87 @@
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+
+/// Highest cutoff as a fraction of the sample rate, where the filter is still stable.
+const MAX_CUTOFF: f32 = 0.45;
+/// Damping of a Butterworth response, so low and high pass do not ring.
+const DAMPING: f32 = std::f32::consts::SQRT_2;
+
+/// Response of the filter every grain is played through.
+#[derive(Enum, Debug, Clone, Copy, PartialEq)]
+pub enum GrainFilterType {
+    #[name = "Low Pass"]
+    LowPass,
+    #[name = "Band Pass"]
+    BandPass,
+    #[name = "High Pass"]
+    HighPass,
+}
+
+/// Filter settings picked up by new grains.
+#[derive(Clone, Copy)]
+pub struct GrainFilterSettings {
+    pub filter_type: GrainFilterType,
+    /// Cutoff in Hz at the centre of the spread.
+    pub cutoff: f32,
+    /// Every grain's cutoff lies up to this many octaves above or below `cutoff`.
+    pub spread: f32,
+}
+
+/// State variable filter of a single grain. Its cutoff is fixed for the grain's lifetime, so
+/// overlapping grains each get their own tone color. The bass mono crossover uses it as a plain
+/// Butterworth filter.
+#[derive(Clone, Copy)]
+pub struct GrainFilter {
+    filter_type: GrainFilterType,
+    g: f32,
+    a1: f32,
+    ic1eq: f32,
+    ic2eq: f32,
+}
+
+impl GrainFilter {
+    /// A filter for a new grain. `random` in `0..1` places its cutoff within the spread.
+    pub fn new(settings: GrainFilterSettings, random: f32, sample_rate: f32) -> Self {
+        let cutoff = settings.cutoff * 2.0f32.powf(settings.spread * (random * 2.0 - 1.0));
+        Self::with_cutoff(settings.filter_type, cutoff, sample_rate)
+    }
+
+    pub fn with_cutoff(filter_type: GrainFilterType, cutoff: f32, sample_rate: f32) -> Self {
+        let mut filter = Self {
+            filter_type,
+            g: 0.0,
+            a1: 0.0,
+            ic1eq: 0.0,
+            ic2eq: 0.0,
+        };
+        filter.set_cutoff(cutoff, sample_rate);
+        filter
+    }
+
+    /// Moves the cutoff while keeping the filter's state.
+    pub fn set_cutoff(&mut self, cutoff: f32, sample_rate: f32) {
+        let cutoff = cutoff.min(MAX_CUTOFF * sample_rate);
+        self.g = (PI * cutoff / sample_rate).tan();
+        self.a1 = 1.0 / (1.0 + self.g * (self.g + DAMPING));
+    }
+
+    pub fn reset(&mut self) {
+        self.ic1eq = 0.0;
+        self.ic2eq = 0.0;
+    }
+
+    pub fn process(&mut self, input: f32) -> f32 {
+        // Trapezoidal integrators with a prewarped cutoff, exact up to `MAX_CUTOFF`
+        let v1 = self.a1 * (self.ic1eq + self.g * (input - self.ic2eq));
+        let v2 = self.ic2eq + self.g * v1;
+        self.ic1eq = 2.0 * v1 - self.ic1eq;
+        self.ic2eq = 2.0 * v2 - self.ic2eq;
+
+        match self.filter_type {
+            GrainFilterType::LowPass => v2,
+            // Scaled to unity gain at the centre frequency
+            GrainFilterType::BandPass => DAMPING * v1,
+            GrainFilterType::HighPass => input - DAMPING * v1 - v2,
+        }
+    }
+}
@@ -0,0 +1,49 @@
+/// Selects the name and VST3 class the plugin is exported under. Every variant shares the same
+/// parameters and state, so sessions saved with one load in any other.
+pub trait ExportIdentity: 'static {
+    const NAME: &'static str;
+    const VST3_CLASS_ID: [u8; 16];
+}
+
+pub struct Current;
+
+/// The placeholder class ID used before the derived one. Still exported so existing VST3
+/// sessions find the plugin, it should not be used for new projects.
+pub struct Legacy;
+
+impl ExportIdentity for Current {
+    const NAME: &'static str = "Whirlpool Spectral";
+    const VST3_CLASS_ID: [u8; 16] = class_id("com.antigravity.whirlpool.vst3");
+}
+
+impl ExportIdentity for Legacy {
+    const NAME: &'static str = "Whirlpool Spectral (Legacy)";
+    const VST3_CLASS_ID: [u8; 16] = *b"WhirlpoolOlaV2__";
+}
+
+/// Derives a stable 16-byte class ID from a reverse-DNS string using two 64-bit FNV-1a passes
+/// with different offset bases. The input strings must never change once released.
+pub const fn class_id(name: &str) -> [u8; 16] {
+    let hi = fnv1a(name.as_bytes(), 0xcbf2_9ce4_8422_2325).to_be_bytes();
+    let lo = fnv1a(name.as_bytes(), 0x6c62_272e_07bb_0142).to_be_bytes();
+
+    let mut id = [0; 16];
+    let mut i = 0;
+    while i < 8 {
+        id[i] = hi[i];
+        id[i + 8] = lo[i];
+        i += 1;
+    }
+    id
+}
+
+const fn fnv1a(bytes: &[u8], offset_basis: u64) -> u64 {
+    let mut hash = offset_basis;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        i += 1;
+    }
+    hash
+}
@@ -0,0 +1,57 @@
+use rustfft::num_complex::Complex;
+use std::f32::consts::TAU;
+
+/// Settings of the spectral mirror for one frame.
+#[derive(Clone, Copy)]
+pub struct MirrorSettings {
+    /// Twice the mirror's center in bins, so bin `i` lands on bin `axis - i`.
+    pub axis: usize,
+    /// Blend from the untouched spectrum to the mirrored one.
+    pub wet: f32,
+}
+
+/// Reflects spectra around a center frequency, so partials above it end up below and the
+/// other way around. Whatever lands below DC or above Nyquist is dropped.
+pub struct SpectralMirror {
+    /// Unmirrored copy of the spectrum.
+    scratch: Vec<Complex<f32>>,
+    /// Phase offset added to every mirrored bin, advanced by every frame.
+    phase: f32,
+}
+
+impl SpectralMirror {
+    pub fn new(bins: usize) -> Self {
+        Self {
+            scratch: vec![Complex::default(); bins],
+            phase: 0.0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+
+    /// Mirrors the positive-frequency half `spectrum`. `hop_ratio` is the hop size over the FFT
+    /// size.
+    pub fn process(
+        &mut self,
+        spectrum: &mut [Complex<f32>],
+        settings: MirrorSettings,
+        hop_ratio: f32,
+    ) {
+        let len = spectrum.len().min(self.scratch.len());
+        // Conjugating turns every bin's phase backwards, which is how a partial moving down by
+        // as much as it was above the center has to turn. The offset adds the turn of the axis
+        // on top, so overlapping frames still add up
+        self.phase = (self.phase + TAU * settings.axis as f32 * hop_ratio).rem_euclid(TAU);
+        let rotation = Complex::from_polar(settings.wet, self.phase);
+        let scratch = &mut self.scratch[..len];
+        scratch.copy_from_slice(&spectrum[..len]);
+        for (i, bin) in spectrum[..len].iter_mut().enumerate() {
+            *bin *= 1.0 - settings.wet;
+            if let Some(source) = settings.axis.checked_sub(i).filter(|&source| source < len) {
+                *bin += scratch[source].conj() * rotation;
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+/// The parts of the signal path that delay the audio.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stage {
+    /// A sample reaches the wet signal once its full analysis frame has been collected.
+    Spectral,
+    /// The true peak limiter's lookahead, applied to the mixed output.
+    Limiter,
+}
+
+impl Stage {
+    const ALL: [Stage; 2] = [Stage::Spectral, Stage::Limiter];
+
+    /// Whether the stage only delays the wet signal, which the dry signal then has to match.
+    /// Stages after the mix delay both alike.
+    fn wet_only(self) -> bool {
+        match self {
+            Stage::Spectral => true,
+            Stage::Limiter => false,
+        }
+    }
+}
+
+/// Every stage's delay in one place, so the latency reported to the host and the delay that
+/// lines the dry signal up with the wet one always agree. A stage registers its delay when it
+/// is set up or switched, and registers zero when it drops out of the path.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LatencyLedger {
+    samples: [usize; Stage::ALL.len()],
+}
+
+impl LatencyLedger {
+    pub fn register(&mut self, stage: Stage, samples: usize) {
+        self.samples[stage as usize] = samples;
+    }
+
+    /// Latency of the whole plugin, for the host.
+    pub fn total(&self) -> u32 {
+        self.samples.iter().sum::<usize>() as u32
+    }
+
+    /// Delay the dry signal needs before it is mixed with the wet one.
+    pub fn dry_delay(&self) -> usize {
+        Stage::ALL
+            .into_iter()
+            .filter(|stage| stage.wet_only())
+            .map(|stage| self.samples[stage as usize])
+            .sum()
+    }
+}
@@ -0,0 +1,64 @@
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+
+/// Waveform of the delay time modulation.
+#[derive(Enum, Debug, Clone, Copy, PartialEq)]
+pub enum LfoShape {
+    Sine,
+    Triangle,
+    /// A new random level every cycle, glided to smoothly.
+    Random,
+}
+
+/// Unipolar low frequency oscillator. Every shape starts a cycle at zero, so a freshly reset
+/// oscillator does not move whatever it modulates.
+pub struct Lfo {
+    /// Position in the current cycle, in `0..1`.
+    phase: f32,
+    /// Levels the random shape glides between during the current cycle.
+    from: f32,
+    to: f32,
+    rng_state: u32,
+}
+
+impl Lfo {
+    pub fn new() -> Self {
+        Self {
+            phase: 0.0,
+            from: 0.0,
+            to: 0.0,
+            rng_state: 1,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Advances by `step` cycles and returns the new value in `0..=1`.
+    pub fn next(&mut self, shape: LfoShape, step: f32) -> f32 {
+        self.phase += step;
+        if self.phase >= 1.0 {
+            self.phase = self.phase.fract();
+            self.from = self.to;
+            self.to = self.next_random();
+        }
+
+        match shape {
+            LfoShape::Sine => 0.5 - 0.5 * (2.0 * PI * self.phase).cos(),
+            LfoShape::Triangle => 1.0 - (2.0 * self.phase - 1.0).abs(),
+            LfoShape::Random => {
+                let t = self.phase * self.phase * (3.0 - 2.0 * self.phase);
+                self.from + (self.to - self.from) * t
+            }
+        }
+    }
+
+    /// Uniform in `0..1`.
+    fn next_random(&mut self) -> f32 {
+        self.rng_state ^= self.rng_state << 13;
+        self.rng_state ^= self.rng_state >> 17;
+        self.rng_state ^= self.rng_state << 5;
+        self.rng_state as f32 / u32::MAX as f32
+    }
+}
@@ -1,270 +1,3976 @@
+use atomic_float::AtomicF32;
 use nih_plug::prelude::*;
+#[cfg(feature = "editor")]
+use nih_plug_egui::EguiState;
 use rustfft::{num_complex::Complex, Fft, FftPlanner};
 use rustfft::num_traits::Zero;
 use std::collections::VecDeque;
 use std::f32::consts::PI;
-use std::sync::Arc;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, AtomicI8, AtomicU16, AtomicU32, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+
+mod analyzer;
+mod average;
+mod bass_mono;
+mod bloom;
+mod blur_hold;
+mod blur_noise;
+mod chord;
+mod cpu_guard;
+mod decimate;
+mod drift;
+mod dry_tone;
+mod ducking;
+#[cfg(feature = "editor")]
+mod editor;
+mod emphasis;
+mod envelope;
+mod euclid;
+mod fft_size;
+mod freeze_bank;
+mod freeze_snapshot;
+mod grain_delay;
+mod grain_filter;
+mod ids;
+mod invert;
+mod latency;
+mod lfo;
+mod morph;
+mod motion;
+mod pitch;
+mod pre_delay;
+mod rotate;
+mod scale;
+mod shimmer;
+mod smoothing;
+mod spectral_eq;
+mod spectral_gate;
+mod surround;
+mod tame;
+mod tape;
+mod tempo;
+mod tonality;
+mod transient;
+mod true_peak;
+mod unison;
+mod window;
+mod xy_axes;
+
+use analyzer::{Analyzer, AnalyzerData, AnalyzerTap};
+use average::SpectralAverage;
+use bass_mono::BassMono;
+use bloom::Bloom;
+use blur_hold::BlurHold;
+pub use blur_noise::BlurNoise;
+use blur_noise::BlurTable;
+pub use chord::HarmonyVoiceParams;
+use chord::{Chord, HeldNotes, MAX_CHORD_NOTES};
+use cpu_guard::CpuGuard;
+use decimate::Decimator;
+use drift::{Drift, DriftFrame};
+use dry_tone::DryTone;
+use emphasis::Emphasis;
+pub use decimate::DecimateMode;
+use ducking::SpectralDucker;
+pub use euclid::EuclidTarget;
+use euclid::{EuclidPattern, EuclidSequencer};
+use envelope::SpectralEnvelope;
+use fft_size::{rebin, FADE_TIME};
+use freeze_bank::{BankFrame, SlotControl, SlotSpectra, NUM_SLOTS};
+use freeze_snapshot::FreezeSnapshot;
+pub use grain_delay::{GrainChord, GrainKeyTrack, JitterDistribution};
+use grain_delay::{GrainDelay, GrainNote};
+use grain_filter::GrainFilterSettings;
+pub use grain_filter::GrainFilterType;
+use ids::{Current, ExportIdentity, Legacy};
+use invert::{MirrorSettings, SpectralMirror};
+use latency::{LatencyLedger, Stage};
+use lfo::Lfo;
+pub use lfo::LfoShape;
+use morph::MorphPresets;
+use motion::{MotionSequence, MotionSequencer};
+use pitch::PitchDetector;
+use pre_delay::SpectralDelay;
+use rotate::Rotator;
+pub use scale::{Key, Scale};
+use shimmer::Shimmer;
+pub use shimmer::ShimmerInterval;
+use smoothing::{Smoothed, SmoothingTimes};
+use spectral_eq::SpectralEqCurve;
+use spectral_gate::{GateSettings, NoiseProfile, SpectralGate};
+use surround::{SurroundLayout, MAX_OUTPUT_CHANNELS};
+use tame::SpectralTamer;
+use tape::TapeWobble;
+use tempo::{HostTime, InternalClock, SyncGrid};
+pub use tempo::NoteDivision;
+pub use tonality::{HarmonyBins, TonalSplit};
+use transient::TransientDetector;
+use true_peak::{TruePeakLimiter, TruePeakMeter, LIMITER_LATENCY};
+use unison::Unison;
+pub use window::AnalysisWindow;
+use window::WindowGlide;
+use xy_axes::XyAxes;
 
 // --- DSP CONSTANTS for OVERLAP-ADD ---
+/// The largest FFT size, which sets the latency. Smaller sizes are delayed to match, so changing
+/// `params.fft_size` never changes the latency.
 const FFT_SIZE: usize = 1024;
+/// Hop of the largest FFT size. Every size keeps the same overlap, see `Engine::hop()`.
 const HOP_SIZE: usize = 256; // 4x Overlap (1024 / 256 = 4)
 const WINDOW_SIZE: usize = 1024;
+/// A sample enters the wet signal once its full analysis frame has been collected.
+const LATENCY: usize = FFT_SIZE - 1;
+/// Phase advance per hop for a sinusoid centred on bin 1, used to keep frozen spectra moving.
+const HOP_PHASE_STEP: f32 = 2.0 * PI * HOP_SIZE as f32 / FFT_SIZE as f32;
+/// Time the frozen spectrum takes to follow the input just below a full freeze amount, in
+/// seconds.
+const MAX_FREEZE_TRACKING: f32 = 20.0;
+/// Ratio changes larger than this between frames are crossfaded to avoid clicks.
+const RATIO_XFADE_THRESHOLD: f32 = 0.01;
+/// Same for frequency translation, in bins.
+const OFFSET_XFADE_THRESHOLD: f32 = 0.5;
+/// Wet level above which the output `tanh` stage is audibly saturating.
+const CLIP_LEVEL: f32 = 1.0;
+
+fn freq_to_midi_note(freq: f32) -> Option<u8> {
+    let note = scale::freq_to_note(freq).round();
+    (0.0..=127.0).contains(&note).then_some(note as u8)
+}
+
+/// Left and right gains for `pan` in `-1..=1`. The side the voice moves towards stays at full
+/// level, so a centered voice is left untouched.
+fn balance(pan: f32) -> [f32; 2] {
+    [1.0 - pan.max(0.0), 1.0 + pan.min(0.0)]
+}
+
+/// Share of the live spectrum the frozen one takes in every frame, with frames `hop_seconds`
+/// apart. A full `amount` holds still, lower amounts follow the input faster and faster.
+fn freeze_tracking(amount: f32, hop_seconds: f32) -> f32 {
+    if amount >= 1.0 {
+        return 0.0;
+    }
+    let time = MAX_FREEZE_TRACKING * amount.powi(3);
+    if time > 0.0 {
+        1.0 - (-hop_seconds / time).exp()
+    } else {
+        1.0
+    }
+}
+
+/// Largest deviation of the overlap-added analysis and synthesis windows from their mean, relative
+/// to the mean. Anything but a tiny ripple means the resynthesis modulates the signal's level.
+fn cola_ripple(window: &[f32], hop: usize) -> f32 {
+    let sums: Vec<f32> = (0..hop)
+        .map(|offset| window.iter().skip(offset).step_by(hop).map(|w| w * w).sum())
+        .collect();
+    let mean = sums.iter().sum::<f32>() / hop as f32;
+    sums.iter()
+        .map(|sum| (sum - mean).abs() / mean)
+        .fold(0.0, f32::max)
+}
+
+/// Note input and output for one block, provided by the host context in `process()`.
+trait NoteIo<P: Plugin> {
+    fn next_event(&mut self) -> Option<PluginNoteEvent<P>>;
+    fn send_event(&mut self, event: PluginNoteEvent<P>);
+}
+
+struct HostNotes<'a, C>(&'a mut C);
+
+impl<P: Plugin, C: ProcessContext<P>> NoteIo<P> for HostNotes<'_, C> {
+    fn next_event(&mut self) -> Option<PluginNoteEvent<P>> {
+        self.0.next_event()
+    }
+
+    fn send_event(&mut self, event: PluginNoteEvent<P>) {
+        self.0.send_event(event)
+    }
+}
 
-fn fast_rand(x: usize, seed: u32) -> f32 {
-    let mut n = (x as u32).wrapping_mul(374761393).wrapping_add(seed);
-    n = (n ^ (n >> 13)).wrapping_mul(1274126177);
-    (n as f32) / (u32::MAX as f32)
+/// Offline rendering plays a fixed list of incoming events and drops the outgoing ones.
+struct NoteList<'a>(std::slice::Iter<'a, NoteEvent<()>>);
+
+impl<P: Plugin<SysExMessage = ()>> NoteIo<P> for NoteList<'_> {
+    fn next_event(&mut self) -> Option<PluginNoteEvent<P>> {
+        self.0.next().copied()
+    }
+
+    fn send_event(&mut self, _event: PluginNoteEvent<P>) {}
 }
 
-struct Whirlpool {
+pub struct Whirlpool<I: ExportIdentity = Current> {
     params: Arc<WhirlpoolParams>,
 
+    /// FFT of the ducker and the analyzer, which stay at the largest size.
     forward_fft: Arc<dyn Fft<f32>>,
-    inverse_fft: Arc<dyn Fft<f32>>,
 
     channels: Vec<ChannelState>,
+    /// The STFT the wet signal comes from.
+    engine: Engine,
+    /// The engine of a new FFT size, running alongside `engine` until the wet signal faded over
+    /// to it.
+    incoming: Option<Engine>,
+    /// Share of `incoming` in the wet signal, negative while its first frames still fill up.
+    engine_fade: f32,
+    /// Window of the ducker and the analyzer.
     window: Vec<f32>,
+    window_glide: WindowGlide,
+
+    /// Mono sum of the input, analysed on the same hop grid as the channels.
+    analysis_ring: VecDeque<f32>,
+    analysis_counter: usize,
+    pitch_detector: PitchDetector,
+    fundamental: Option<f32>,
+    meters: Arc<Meters>,
+    /// Notes held on the MIDI input, played as chords in MIDI shift mode.
+    held_notes: HeldNotes,
+    /// Whether the macro notes holding Reverse and Tape Stop are down.
+    macro_keys: [bool; 2],
+    /// Position of the MIDI pitch wheel, from -1 at the bottom to 1 at the top.
+    pitch_bend: f32,
+    /// Notes currently held on the MIDI output: the fundamental and the harmony voice.
+    midi_notes: [Option<u8>; 2],
+    sidechain_detector: TransientDetector,
+    /// Whether a sidechain hit asked for new blur phases since the last frame.
+    blur_triggered: bool,
+    /// Shapes the wet signal's level after the input, see `params.bloom_attack`.
+    bloom: Bloom,
+    /// Fallback transport for when the host provides no tempo.
+    internal_clock: InternalClock,
+    slot_control: SlotControl,
+    blur_hold: BlurHold,
+    /// Steps synced grains start on.
+    grain_grid: SyncGrid,
+    /// Plays the Euclidean pattern while the transport runs.
+    euclid: EuclidSequencer,
+    /// Wanders the magnitudes and the shift ratio, shared by all channels.
+    drift: Drift,
+    /// Modulates the grain delay's read heads, shared by all channels.
+    delay_lfo: Lfo,
+    /// Sweeps the spectral rotation, advanced once per hop.
+    rotate_lfo: Lfo,
+    /// Wow and flutter on the grain delay's read heads, shared by all channels.
+    tape_wobble: TapeWobble,
+    cpu_guard: CpuGuard,
+    /// Whether the host renders offline, where blocks may take as long as they need and the CPU
+    /// guard stays out of the way.
+    offline: bool,
+    limiter: TruePeakLimiter,
+    /// Whether the limiter runs, and so whether its lookahead is part of the reported latency.
+    limiter_active: bool,
+    /// The delay of every stage, reported to the host and matched by the dry delays.
+    latency: LatencyLedger,
+    tp_meter: TruePeakMeter,
+    bass_mono: BassMono,
+    /// The output layout when it has more than two channels, set up in `initialize()`.
+    surround: Option<SurroundLayout>,
+    /// Every channel's grain delay return over the last two hops, so each channel can feed the
+    /// other's return back one hop late no matter where the segments split.
+    cross_returns: [Vec<f32>; 2],
+    /// Position in `cross_returns` of the segment being processed.
+    cross_pos: usize,
+    /// Grain delay buffers allocated by a [`BufferTask`] and waiting to be swapped in, or the
+    /// swapped out ones waiting to be freed.
+    spare_buffers: Arc<Mutex<Vec<Vec<f32>>>>,
+    /// Length of the buffers asked for from the background thread and not swapped in yet.
+    requested_buffer_len: Option<usize>,
+    /// An engine built by a [`BufferTask`] and waiting to be faded over to.
+    spare_engine: Arc<Mutex<Option<Engine>>>,
+    /// An engine faded out of the wet signal and waiting to be freed.
+    retired_engine: Arc<Mutex<Option<Engine>>>,
+    /// FFT size asked for from the background thread and not faded over to yet.
+    requested_fft_size: Option<usize>,
+    /// Set from the Learn button until the learned noise floor is saved to `params.gate_profile`.
+    gate_learning: bool,
+    /// Whether `params.freeze_snapshot` may hold a spectrum, so it is cleared only once after
+    /// saving is turned off.
+    freeze_saved: bool,
+    /// Parameters on the XY pad's axes, copied from `params.xy_axes`.
+    xy_params: [Smoothed; 2],
+    /// MIDI CCs learned for the XY pad's axes.
+    xy_ccs: [Option<u8>; 2],
+    /// The XY pad axis waiting for a CC to learn.
+    xy_learning: Option<usize>,
+    /// Normalized value a learned CC set per [`Smoothed`] parameter, together with the
+    /// parameter's own normalized value at the time. The parameter takes over once it moves.
+    cc_values: [Option<(f32, f32)>; Smoothed::ALL.len()],
+    /// Steps copied from `params.motion_sequence`, played from the transport.
+    motion: MotionSequencer,
+    /// Normalized value the motion sequencer sets its parameter to, `None` while it is not
+    /// playing.
+    motion_value: Option<f32>,
+    /// Whether steps were recorded that are not in `params.motion_sequence` yet.
+    motion_recorded: bool,
+
+    sample_rate: f32,
+    /// Crossfade between the processed output and the latency-compensated dry signal.
+    bypass_fade: Smoother<f32>,
+    /// Fade of the dry signal out of the mix while the input is muted.
+    input_mute_fade: Smoother<f32>,
+    /// Delay modulation depth in samples, ramped so depth changes do not make the read heads jump.
+    delay_mod_depth: Smoother<f32>,
+    /// Automation smoothers indexed by [`Smoothed`], timed by `params.smoothing`.
+    smoothers: [Smoother<f32>; Smoothed::ALL.len()],
+    /// Per-sample values of the segment being processed.
+    segment: SegmentValues,
+    /// Normalized A and B values per [`Smoothed`] parameter, copied from `params.morph_presets`.
+    morph_endpoints: [Option<(f32, f32)>; Smoothed::ALL.len()],
+    /// Transport position expected at the start of the next block, used to detect relocations.
+    next_transport_pos: Option<i64>,
+    /// Linear gain per bin rendered from the spectral EQ curve.
+    eq_gains: Vec<f32>,
+    ducker: SpectralDucker,
+    /// EQ and ducking gains combined, applied to every resynthesized frame.
+    bin_gains: Vec<f32>,
+    analyzer: Analyzer,
+
+    identity: PhantomData<fn() -> I>,
+}
+
+/// Spectral settings shared by every frame rendered during one block.
+#[derive(Clone, Copy)]
+struct FrameParams {
+    harmonics: f32,
+    shift_mode: ShiftMode,
+    shift: f32,
+    /// Frequency translation for `ShiftMode::Frequency`, in bins.
+    shift_bins: f32,
+    /// Pitch bend from the MIDI input as a frequency ratio, on top of every shift mode.
+    bend: f32,
+    blur: f32,
+    /// Bins below this one are never blurred.
+    blur_low_bin: usize,
+    /// Set at the hops whose frame draws new random blur phases.
+    blur_refresh: bool,
+    blur_noise: BlurNoise,
+    harmony_source: HarmonySource,
+    /// Bins in `low_bin..=high_bin` are processed, everything else passes through dry.
+    low_bin: usize,
+    high_bin: usize,
+    tonal_split: TonalSplit,
+    /// Linear magnitude ratio a peak needs over its neighbourhood to count as tonal.
+    tonal_threshold: f32,
+    /// Peaks per frame that feed the harmony voice, `None` for every bin.
+    harmony_peaks: Option<usize>,
+    /// Allowed pitch classes for the harmonic voice, zero when unconstrained.
+    scale_mask: u16,
+    /// Latest detected fundamental, updated at every analysis hop.
+    fundamental: Option<f32>,
+    freeze: bool,
+    /// Share of the live magnitudes blended into the frozen ones every frame, zero for a hard
+    /// freeze.
+    freeze_tracking: f32,
+    shimmer: ShimmerInterval,
+    shimmer_level: f32,
+    drift: DriftFrame,
+    bank: BankFrame,
+    /// Fit the harmony voice to the source's spectral envelope instead of moving it along.
+    preserve_envelope: bool,
+    /// Frames the harmony voice enters after the source.
+    harmony_delay: usize,
+    /// Keep one in this many bins, one keeps them all.
+    decimate: usize,
+    decimate_mode: DecimateMode,
+    /// Bins the spectrum is circularly shifted up by, including the LFO sweep.
+    rotate: isize,
+    /// Set while the spectrum is mirrored.
+    mirror: Option<MirrorSettings>,
+    /// Share of the running magnitude average every frame keeps, zero when off.
+    average: f32,
+    /// Ceiling of every bin over the frame's median magnitude, `None` while taming is off.
+    tame: Option<f32>,
+    /// Harmony voices played from the held notes, a single unshifted one outside MIDI mode.
+    chord: Chord,
+    /// Copies of the harmony voice as rendered by the current channel.
+    unison: Unison,
+    /// Set while the spectral gate is on.
+    gate: Option<GateSettings>,
+}
+
+/// Smoothed values for every sample of a segment, shared by all channels. A segment never spans
+/// more than one hop.
+struct SegmentValues {
+    mix: [f32; HOP_SIZE],
+    gain: [f32; HOP_SIZE],
+    bypass: [f32; HOP_SIZE],
+    input_mute: [f32; HOP_SIZE],
+    /// Share of the incoming engine in the wet signal while the FFT size changes.
+    engine_fade: [f32; HOP_SIZE],
+    /// Gain of the wet signal in the mix, from the bloom.
+    bloom: [f32; HOP_SIZE],
+    delay_feedback: [f32; HOP_SIZE],
+    delay_samples: [usize; HOP_SIZE],
+    /// Delay modulation in samples, added on top of `delay_samples`.
+    delay_mod: [f32; HOP_SIZE],
+    /// The grains synced or played grains start on every sample.
+    grain_spawn: [Option<GrainNote>; HOP_SIZE],
+    /// Whether a grain of a Euclidean burst or a sidechain hit starts on every sample.
+    trigger_spawn: [bool; HOP_SIZE],
+    /// Mono sidechain as seen by the ducker and the onset detector.
+    sidechain: [f32; HOP_SIZE],
+    scratch: [f32; HOP_SIZE],
+    /// Mono sum of the analyzer's tap.
+    tap: [f32; HOP_SIZE],
+    /// Each channel's grains in the rear speakers of a surround layout.
+    rear: [[f32; HOP_SIZE]; 2],
+}
+
+/// Analysis results published to the editor.
+struct Meters {
+    /// Detected fundamental in Hz, zero when the input is unvoiced.
+    pitch: AtomicF32,
+    /// Latched when the wet signal overloads the `tanh` stage, cleared by the editor.
+    clip: AtomicBool,
+    /// Tempo tempo-synced features currently follow.
+    tempo: AtomicF32,
+    /// Whether `tempo` comes from the host rather than the internal clock.
+    host_tempo: AtomicBool,
+    /// Spectrum and waveform of the tap selected by `params.analyzer_tap`.
+    analyzer: Mutex<AnalyzerData>,
+    /// Bit `n` is set when freeze bank slot `n` holds a spectrum.
+    slots_filled: AtomicU8,
+    /// The freeze bank slot being played, -1 for the live signal.
+    active_slot: AtomicI8,
+    /// Smoothed block processing time relative to real time.
+    cpu_load: AtomicF32,
+    /// Grain voices actually running, fewer than requested while the CPU guard is limiting.
+    grain_voices: AtomicU8,
+    /// Highest inter-sample peak of the output as linear gain, cleared by the editor.
+    true_peak: AtomicF32,
+    sample_rate: AtomicF32,
+    /// Largest block the host said it would send.
+    max_block_size: AtomicU32,
+    /// Size of the last block and how long it took to process, in seconds.
+    block_size: AtomicU32,
+    block_time: AtomicF32,
+    /// Latency last reported to the host, in samples.
+    latency: AtomicU32,
+    /// Samples in each channel's grain delay buffer.
+    grain_buffer: AtomicU32,
+    /// FFT size the wet signal comes from, in samples.
+    fft_size: AtomicU32,
+    /// See [`cola_ripple()`].
+    cola_ripple: AtomicF32,
+    gate_learning: AtomicBool,
+    /// The XY pad axis waiting for a CC to learn, -1 for none.
+    xy_learning: AtomicI8,
+    /// Normalized position a learned CC moved each XY pad axis to, -1 while the parameter's own
+    /// value applies.
+    xy_cc: [AtomicF32; 2],
+    /// The motion sequencer's current step, -1 while it is not playing.
+    motion_step: AtomicI8,
+    /// The Euclidean pattern's current step, -1 while it is not playing.
+    euclid_step: AtomicI8,
+}
+
+/// What the current frame's analysis decided about each bin of a channel.
+struct BinAnalysis {
+    /// Bins the current frame processes, everything else passes through dry.
+    process_mask: Vec<bool>,
+    /// Processed bins that feed the harmony voice, only filled while
+    /// `FrameParams::harmony_peaks` is set.
+    harmony_mask: Vec<bool>,
+    /// Candidate peaks as magnitude and bin, scratch space for `harmony_mask`.
+    peaks: Vec<(f32, usize)>,
+    /// Only analysed while `FrameParams::preserve_envelope` is set.
+    envelope: SpectralEnvelope,
 }
 
 struct ChannelState {
+    /// Delays the dry signal by the ledger's `dry_delay()` so it lines up with the wet signal.
+    dry_delay: VecDeque<f32>,
+    dry_tone: DryTone,
+
+    /// Granular delay in the feedback path around the spectral processor.
+    grain_delay: GrainDelay,
+    /// Last output of `grain_delay`, fed back into the next input sample.
+    grain_return: f32,
+}
+
+/// One channel's STFT at the FFT size of its [`Engine`].
+struct Stft {
+    /// Lifts the highs of the spectrum the processing sees and lowers them after it.
+    emphasis: Emphasis,
     input_ring: VecDeque<f32>,
     output_accum: VecDeque<f32>,
     scratch_in: Vec<Complex<f32>>,
     scratch_out: Vec<Complex<f32>>,
+    /// The current frame resynthesized with the previous frame's mapping, faded out across the
+    /// frame.
+    scratch_prev: Vec<Complex<f32>>,
+    /// The harmony voice of the current frame, rendered apart while it is pre-delayed.
+    scratch_harmony: Vec<Complex<f32>>,
+    pre_delay: SpectralDelay,
+    analysis: BinAnalysis,
+    /// Scratch space for the tonal peak detection.
+    tonal_bins: Vec<bool>,
+    decimator: Decimator,
+    rotator: Rotator,
+    mirror: SpectralMirror,
+    average: SpectralAverage,
+    tamer: SpectralTamer,
+    gate: SpectralGate,
+    /// Bin mapping used by the last frame, `None` before the first one.
+    last_map: Option<BinMap>,
     hop_counter: usize,
+    /// Frames rendered so far, which the unison copies' phase correction needs.
+    frames: usize,
     rng_state: u32,
+    /// Seed of the blur phases, renewed from `rng_state` whenever `FrameParams::blur_refresh` is
+    /// set.
+    blur_seed: u32,
+    blur_table: BlurTable,
+
+    /// Captured magnitudes and running phases of the frozen spectrum.
+    frozen_mags: Vec<f32>,
+    frozen_phases: Vec<f32>,
+    shimmer: Shimmer,
+    has_capture: bool,
+    /// Set by a freeze trigger, the next frame replaces the captured spectrum.
+    capture_pending: bool,
+    slots: SlotSpectra,
+}
+
+/// The STFT of every channel at one FFT size, with the FFTs and the window it runs on. Changing
+/// the size builds a new engine, which runs next to the current one while the wet signal fades
+/// over to it.
+struct Engine {
+    fft_size: usize,
+    forward_fft: Arc<dyn Fft<f32>>,
+    inverse_fft: Arc<dyn Fft<f32>>,
+    window: Vec<f32>,
+    window_glide: WindowGlide,
+    /// `Whirlpool::bin_gains` at this size's bins.
+    bin_gains: Vec<f32>,
+    channels: Vec<Stft>,
+    /// Sample rate the pre-delays have room for.
+    sample_rate: f32,
+}
+
+#[derive(Enum, Debug, Clone, Copy, PartialEq)]
+pub enum InputPad {
+    #[name = "-12 dB"]
+    Minus12,
+    #[name = "-6 dB"]
+    Minus6,
+    #[name = "0 dB"]
+    Unity,
+    #[name = "+6 dB"]
+    Plus6,
+}
+
+impl InputPad {
+    fn gain(self) -> f32 {
+        match self {
+            InputPad::Minus12 => util::db_to_gain(-12.0),
+            InputPad::Minus6 => util::db_to_gain(-6.0),
+            InputPad::Unity => 1.0,
+            InputPad::Plus6 => util::db_to_gain(6.0),
+        }
+    }
+}
+
+#[derive(Enum, Debug, Clone, Copy, PartialEq)]
+pub enum ShiftMode {
+    /// Multiplies every frequency, keeping harmonic relationships.
+    Ratio,
+    /// Adds a fixed offset to every frequency for inharmonic, ring-mod like results.
+    Frequency,
+    /// Plays a harmony voice for every held note, shifted by the note's distance from the MIDI
+    /// root.
+    #[name = "MIDI"]
+    Midi,
+}
+
+/// Which spectrum the harmony voice is built from.
+#[derive(Enum, Debug, Clone, Copy, PartialEq)]
+pub enum HarmonySource {
+    /// The clean analysis, so the harmony stays focused over a blurred source.
+    #[name = "Pre-Blur"]
+    PreBlur,
+    /// The blurred spectrum, so the harmony smears along with the source.
+    #[name = "Post-Blur"]
+    PostBlur,
+}
+
+/// What the granular delay records and the grains are drawn from.
+#[derive(Enum, Debug, Clone, Copy, PartialEq)]
+pub enum GrainSource {
+    /// The wet signal, so the repeats feed back into themselves.
+    Main,
+    /// The sidechain input, granulating another track into this one.
+    Sidechain,
+}
+
+/// Where the harmonic voice moves bin `i` to: `i * ratio + offset`.
+#[derive(Clone, Copy)]
+struct BinMap {
+    ratio: f32,
+    offset: f32,
+}
+
+impl BinMap {
+    fn target(self, bin: usize) -> f32 {
+        bin as f32 * self.ratio + self.offset
+    }
+
+    /// Whether moving from `other` to this mapping is large enough to click without a crossfade.
+    fn jumps_from(self, other: BinMap) -> bool {
+        (self.ratio - other.ratio).abs() > RATIO_XFADE_THRESHOLD
+            || (self.offset - other.offset).abs() > OFFSET_XFADE_THRESHOLD
+    }
+}
+
+impl FrameParams {
+    fn bin_map(&self) -> BinMap {
+        match self.shift_mode {
+            // Bent after quantizing, so the bend glides between the scale's steps
+            ShiftMode::Ratio => BinMap {
+                ratio: scale::quantize_ratio(self.fundamental, 1.0 + self.shift, self.scale_mask)
+                    * self.bend,
+                offset: 0.0,
+            },
+            // Translation is inharmonic by design, so the scale does not apply here
+            ShiftMode::Frequency => BinMap {
+                ratio: self.bend,
+                offset: self.shift_bins,
+            },
+            // The held notes set the ratios, see `chord`
+            ShiftMode::Midi => BinMap {
+                ratio: self.bend,
+                offset: 0.0,
+            },
+        }
+    }
+
+    /// These settings for an STFT of `fft_size`, whose bins are wider and whose frames come
+    /// more often than at `FFT_SIZE`.
+    fn at_size(&self, fft_size: usize) -> Self {
+        if fft_size == FFT_SIZE {
+            return *self;
+        }
+        let scale = fft_size as f32 / FFT_SIZE as f32;
+        let bins = |bin: usize| bin as f32 * scale;
+        Self {
+            shift_bins: self.shift_bins * scale,
+            blur_low_bin: bins(self.blur_low_bin).ceil() as usize,
+            low_bin: bins(self.low_bin).floor() as usize,
+            high_bin: bins(self.high_bin).ceil() as usize,
+            rotate: (self.rotate as f32 * scale).round() as isize,
+            mirror: self.mirror.map(|mirror| MirrorSettings {
+                axis: bins(mirror.axis).round() as usize,
+                ..mirror
+            }),
+            // Per-frame rates, moved to frames `scale` times as long
+            harmony_delay: (self.harmony_delay as f32 / scale).round() as usize,
+            freeze_tracking: 1.0 - (1.0 - self.freeze_tracking).powf(scale),
+            average: self.average.powf(scale),
+            gate: self.gate.map(|gate| GateSettings {
+                release: gate.release.powf(scale),
+                ..gate
+            }),
+            ..*self
+        }
+    }
+}
+
+#[derive(Enum, Debug, Clone, Copy, PartialEq)]
+pub enum FreezeTrigger {
+    Manual,
+    Sidechain,
+    #[name = "MIDI Note"]
+    MidiNote,
+}
+
+/// Work done on the background thread, as it allocates or frees memory.
+pub enum BufferTask {
+    /// Allocates `channels` zeroed grain delay buffers of `samples` samples into
+    /// `Whirlpool::spare_buffers`.
+    Allocate { channels: usize, samples: usize },
+    /// Frees the buffers in `Whirlpool::spare_buffers` after they were swapped out.
+    Release,
+    /// Builds an STFT engine of `fft_size` for `channels` channels into
+    /// `Whirlpool::spare_engine`.
+    Build {
+        fft_size: usize,
+        channels: usize,
+        window: AnalysisWindow,
+        sample_rate: f32,
+    },
+    /// Frees the engine in `Whirlpool::retired_engine` after the wet signal faded away from it.
+    Retire,
 }
 
 #[derive(Params)]
-struct WhirlpoolParams {
+pub struct WhirlpoolParams {
+    #[id = "bypass"]
+    pub bypass: BoolParam,
+    #[id = "input_pad"]
+    pub input_pad: EnumParam<InputPad>,
     #[id = "harmonics"]
     pub harmonics: FloatParam,
+    /// Places the harmony voice in the stereo field, the source stays where it is.
+    #[id = "harmony_pan"]
+    pub harmony_pan: FloatParam,
+    /// Level, pan and shift of every harmony voice.
+    #[nested(array, group = "Harmony Voice")]
+    pub harmony_voices: [HarmonyVoiceParams; MAX_CHORD_NOTES],
+    /// Stacks this many slightly detuned copies of the harmony voice for an ensemble.
+    #[id = "unison"]
+    pub unison: IntParam,
+    /// How far the unison copies spread across the stereo field.
+    #[id = "unison_width"]
+    pub unison_width: FloatParam,
+    #[id = "shift_mode"]
+    pub shift_mode: EnumParam<ShiftMode>,
+    /// Note the source is taken to be at in MIDI shift mode. Holding it plays the harmony voice
+    /// unshifted.
+    #[id = "midi_root"]
+    pub midi_root: IntParam,
+    /// How far the pitch wheel bends the harmony voice either way, in semitones.
+    #[id = "bend_range"]
+    pub bend_range: IntParam,
     #[id = "shift"]
     pub shift: FloatParam,
+    #[id = "shift_hz"]
+    pub shift_hz: FloatParam,
+    #[id = "scale"]
+    pub scale: EnumParam<Scale>,
+    #[id = "key"]
+    pub key: EnumParam<Key>,
     #[id = "blur"]
     pub blur: FloatParam,
+    /// Keeps the blur's random phases for this many frames, or sixteenth notes with
+    /// `blur_sync`, instead of drawing new ones every frame.
+    #[id = "blur_hold"]
+    pub blur_hold: IntParam,
+    #[id = "blur_sync"]
+    pub blur_sync: BoolParam,
+    #[id = "blur_noise"]
+    pub blur_noise: EnumParam<BlurNoise>,
+    #[id = "harmony_source"]
+    pub harmony_source: EnumParam<HarmonySource>,
+    /// Blends every frame with a running average of the ones before it, a smooth wash that
+    /// keeps the phases intact unlike blur.
+    #[id = "average"]
+    pub average: FloatParam,
+    /// Holds back single bins that ring out far above the rest of the frame, as feedback and
+    /// stacked harmonies tend to build up.
+    #[id = "tame"]
+    pub tame: FloatParam,
+    /// Keeps the harmony voice's formants where the source has them, so upward shifts do not
+    /// sound thin.
+    #[id = "env_preserve"]
+    pub preserve_envelope: BoolParam,
+    /// Lets the harmony voice enter after the source, so harmonies bloom after the note instead
+    /// of smearing its attack.
+    #[id = "pre_delay"]
+    pub pre_delay: FloatParam,
+    /// Attenuates the bins that do not stand out from the learned noise floor. At full wet this
+    /// turns the plugin into a broadband denoiser.
+    #[id = "gate"]
+    pub gate: BoolParam,
+    /// How far above the noise floor a bin has to be to pass.
+    #[id = "gate_threshold"]
+    pub gate_threshold: FloatParam,
+    #[id = "gate_reduction"]
+    pub gate_reduction: FloatParam,
+    /// STFT window. Changing it glides between the shapes instead of switching abruptly.
+    #[id = "window"]
+    pub window: EnumParam<AnalysisWindow>,
+    /// Lifts the highs by 6 dB per octave before the spectral processing and lowers them again
+    /// after it, so the upper partials of dark sources are tracked and harmonized as readily as
+    /// the fundamentals. Unprocessed bins null, the harmony voice keeps the tilt of its source.
+    #[id = "pre_emphasis"]
+    pub pre_emphasis: BoolParam,
+    /// Keeps only one in `decimate` bins before resynthesis.
+    #[id = "decimate"]
+    pub decimate: IntParam,
+    #[id = "decimate_mode"]
+    pub decimate_mode: EnumParam<DecimateMode>,
+    /// Circularly shifts the spectrum by this many bins, wrapping around at Nyquist.
+    #[id = "rotate"]
+    pub rotate: IntParam,
+    /// How far the LFO sweeps the rotation on top of `rotate`, in bins.
+    #[id = "rotate_mod_depth"]
+    pub rotate_mod_depth: IntParam,
+    #[id = "rotate_mod_rate"]
+    pub rotate_mod_rate: FloatParam,
+    /// Reflects the spectrum around `invert_center`, so highs become lows and lows become highs.
+    #[id = "invert"]
+    pub invert: BoolParam,
+    #[id = "invert_center"]
+    pub invert_center: FloatParam,
+    #[id = "invert_wet"]
+    pub invert_wet: FloatParam,
+    #[id = "tonal_split"]
+    pub tonal_split: EnumParam<TonalSplit>,
+    #[id = "tonality"]
+    pub tonality: FloatParam,
+    #[id = "harmony_bins"]
+    pub harmony_bins: EnumParam<HarmonyBins>,
+    /// Peaks per frame the harmony voice follows in `HarmonyBins::Peaks`.
+    #[id = "peak_count"]
+    pub peak_count: IntParam,
+    #[id = "low_cut"]
+    pub low_cut: FloatParam,
+    #[id = "high_cut"]
+    pub high_cut: FloatParam,
     #[id = "mix"]
     pub mix: FloatParam,
+    /// Takes the dry signal out of the mix while the input keeps feeding the processing, so
+    /// freezes and holds can be played live without the source leaking through.
+    #[id = "input_mute"]
+    pub input_mute: BoolParam,
+    /// Thins the dry signal only, for heavy wet textures that need room. At its lowest the low
+    /// cut is off.
+    #[id = "dry_low_cut"]
+    pub dry_low_cut: FloatParam,
+    /// Tilts the dry signal darker or brighter around 1 kHz.
+    #[id = "dry_tilt"]
+    pub dry_tilt: FloatParam,
+    /// Fades the wet signal in over this time after every onset of the input, so pads swell
+    /// behind the playing. Zero leaves the wet as it is.
+    #[id = "bloom_attack"]
+    pub bloom_attack: FloatParam,
+    /// Fades the wet signal out over this time once the input stops. Zero leaves the wet as it
+    /// is.
+    #[id = "bloom_release"]
+    pub bloom_release: FloatParam,
     #[id = "output_gain"]
     pub out_gain: FloatParam,
+    #[id = "midi_out"]
+    pub midi_out: BoolParam,
+    #[id = "freeze"]
+    pub freeze: BoolParam,
+    #[id = "freeze_trigger"]
+    pub freeze_trigger: EnumParam<FreezeTrigger>,
+    /// Below 100 % the frozen spectrum slowly follows the input instead of holding still, the
+    /// lower the faster.
+    #[id = "freeze_amount"]
+    pub freeze_amount: FloatParam,
+    /// Layers octave and fifth copies over the frozen spectrum.
+    #[id = "shimmer"]
+    pub shimmer: EnumParam<ShimmerInterval>,
+    #[id = "shimmer_level"]
+    pub shimmer_level: FloatParam,
+    /// Slowly wanders the magnitudes and the shift ratio, so frozen and washed out sounds keep
+    /// moving a little.
+    #[id = "drift"]
+    pub drift: FloatParam,
+    /// Saves the frozen spectrum with the project, so it reopens still playing.
+    #[id = "save_freeze"]
+    pub save_freeze: BoolParam,
+    #[id = "trigger_sens"]
+    pub trigger_sensitivity: FloatParam,
+    /// Time after a sidechain hit during which the detector ignores further hits.
+    #[id = "trigger_hold"]
+    pub trigger_hold: FloatParam,
+    /// Lets sidechain hits spawn the grains, which otherwise stay silent.
+    #[id = "sidechain_grains"]
+    pub sidechain_grains: BoolParam,
+    /// Lets sidechain hits draw new random blur phases.
+    #[id = "sidechain_blur"]
+    pub sidechain_blur: BoolParam,
+    /// Lets MIDI notes from `slot_base_note` upwards launch the freeze bank slots.
+    #[id = "slot_notes"]
+    pub slot_notes: BoolParam,
+    #[id = "slot_base_note"]
+    pub slot_base_note: IntParam,
+    /// Crossfade time when switching freeze bank slots.
+    #[id = "slot_fade"]
+    pub slot_fade: FloatParam,
+    /// Routes the wet signal through a granular delay back into the spectral processor.
+    #[id = "grain_feedback"]
+    pub grain_feedback: BoolParam,
+    #[id = "delay_time"]
+    pub delay_time: FloatParam,
+    #[id = "delay_feedback"]
+    pub delay_feedback: FloatParam,
+    /// Overlapping grains in the granular delay.
+    #[id = "grain_voices"]
+    pub grain_voices: IntParam,
+    /// Scales the grains to how many overlap rather than to the voice count, so the cloud stays
+    /// about as loud when the voices, the sync rate or the trigger density change.
+    #[id = "grain_normalize"]
+    pub grain_normalize: BoolParam,
+    /// Grain envelope, from rectangular through Hann to Gaussian.
+    #[id = "grain_shape"]
+    pub grain_shape: FloatParam,
+    /// How the random offsets of the grains behind the delay time are spread.
+    #[id = "jitter_distribution"]
+    pub jitter_distribution: EnumParam<JitterDistribution>,
+    /// Pushes the grains' random offsets towards the delay time below zero and away above.
+    #[id = "jitter_bias"]
+    pub jitter_bias: FloatParam,
+    /// Chord the grains pick their pitch from, so the cloud stays harmonic.
+    #[id = "grain_chord"]
+    pub grain_chord: EnumParam<GrainChord>,
+    /// Share of the grains playing the chord's upper tones rather than its root.
+    #[id = "chord_weight"]
+    pub chord_weight: FloatParam,
+    /// How far towards the back grains are placed at random in quad and 5.1 output.
+    #[id = "surround_spread"]
+    pub surround_spread: FloatParam,
+    /// Plays every grain through a filter with its own randomly spread cutoff.
+    #[id = "grain_filter"]
+    pub grain_filter: BoolParam,
+    #[id = "grain_filter_type"]
+    pub grain_filter_type: EnumParam<GrainFilterType>,
+    #[id = "grain_cutoff"]
+    pub grain_cutoff: FloatParam,
+    /// Octaves a grain's cutoff may lie above or below `grain_cutoff`.
+    #[id = "grain_cutoff_spread"]
+    pub grain_cutoff_spread: FloatParam,
+    /// Starts grains on the transport's `grain_division` grid while it is playing, instead of at
+    /// a steady rate.
+    #[id = "grain_sync"]
+    pub grain_sync: BoolParam,
+    #[id = "grain_division"]
+    pub grain_division: EnumParam<NoteDivision>,
+    /// Starts a grain on every incoming note instead, outside the freeze bank's notes.
+    #[id = "midi_grains"]
+    pub midi_grains: BoolParam,
+    /// How far a note's velocity sets the level of its grain.
+    #[id = "grain_velocity"]
+    pub grain_velocity: FloatParam,
+    #[id = "grain_key_track"]
+    pub grain_key_track: EnumParam<GrainKeyTrack>,
+    /// LFO on the delay time. Short loops with some depth give chorus and flanger sounds.
+    #[id = "delay_mod_rate"]
+    pub delay_mod_rate: FloatParam,
+    #[id = "delay_mod_depth"]
+    pub delay_mod_depth: FloatParam,
+    #[id = "delay_mod_shape"]
+    pub delay_mod_shape: EnumParam<LfoShape>,
+    /// Flips the polarity of the delay feedback.
+    #[id = "delay_invert"]
+    pub delay_invert: BoolParam,
+    /// Loops the current repeats indefinitely at full feedback, ignoring new input.
+    #[id = "delay_hold"]
+    pub delay_hold: BoolParam,
+    /// Plays new grains backwards while on.
+    #[id = "grain_reverse"]
+    pub grain_reverse: BoolParam,
+    /// Slows the grains down to a halt over `tape_stop_time` while on.
+    #[id = "tape_stop"]
+    pub tape_stop: BoolParam,
+    #[id = "tape_stop_time"]
+    pub tape_stop_time: FloatParam,
+    /// Lets `macro_base_note` hold Reverse and the note above it hold Tape Stop, outside the
+    /// notes the harmony and the grains play.
+    #[id = "macro_notes"]
+    pub macro_notes: BoolParam,
+    #[id = "macro_base_note"]
+    pub macro_base_note: IntParam,
+    /// Speed new grains scan through the recorded audio at, relative to real time. Below one
+    /// the input is stretched out, with the held loop it crawls through the loop.
+    #[id = "stretch"]
+    pub stretch: FloatParam,
+    /// Slow, drifting variation of the delay time, like a worn tape transport.
+    #[id = "delay_wow"]
+    pub delay_wow: FloatParam,
+    /// Fast, regular variation of the delay time.
+    #[id = "delay_flutter"]
+    pub delay_flutter: FloatParam,
+    /// Soft clipping of the delay's feedback, compounding with every repeat.
+    #[id = "delay_saturation"]
+    pub delay_saturation: FloatParam,
+    /// Feeds each channel's repeats back into the other channel instead, so they bounce across
+    /// the stereo field.
+    #[id = "delay_cross"]
+    pub delay_cross: FloatParam,
+    #[id = "grain_source"]
+    pub grain_source: EnumParam<GrainSource>,
+    /// Sheds grain voices while processing gets close to taking longer than real time.
+    #[id = "cpu_guard"]
+    pub cpu_guard: BoolParam,
+    /// Keeps the oversampled output peaks below `tp_ceiling`, at the cost of extra latency.
+    #[id = "tp_limit"]
+    pub tp_limit: BoolParam,
+    #[id = "tp_ceiling"]
+    pub tp_ceiling: FloatParam,
+    /// Keeps the output mono and unblurred below `bass_mono_freq`, so the low end holds up on
+    /// mono club systems.
+    #[id = "bass_mono"]
+    pub bass_mono: BoolParam,
+    #[id = "bass_mono_freq"]
+    pub bass_mono_freq: FloatParam,
+    /// Attenuates the bins of the main signal where the sidechain has energy.
+    #[id = "sc_duck"]
+    pub sidechain_duck: BoolParam,
+    #[id = "duck_amount"]
+    pub duck_amount: FloatParam,
+    #[id = "duck_release"]
+    pub duck_release: FloatParam,
+    /// Replaces the output with the sidechain signal the ducker and the freeze trigger listen to.
+    #[id = "sc_listen"]
+    pub sidechain_listen: BoolParam,
+    /// Blends the smoothed parameters from preset A to preset B.
+    #[id = "morph"]
+    pub morph: FloatParam,
+    /// Tempo of the internal clock, used when the host does not provide one.
+    #[id = "internal_bpm"]
+    pub internal_bpm: FloatParam,
+    #[id = "internal_run"]
+    pub internal_run: BoolParam,
+    /// Plays the motion sequence on its parameter while the transport runs.
+    #[id = "motion"]
+    pub motion: BoolParam,
+    #[id = "motion_steps"]
+    pub motion_steps: IntParam,
+    /// Length of a motion step.
+    #[id = "motion_division"]
+    pub motion_division: EnumParam<NoteDivision>,
+    /// Writes the sequenced parameter's own value into every motion step it passes.
+    #[id = "motion_record"]
+    pub motion_record: BoolParam,
+    /// What the hits of the Euclidean pattern trigger while the transport runs.
+    #[id = "euclid"]
+    pub euclid: EnumParam<EuclidTarget>,
+    #[id = "euclid_steps"]
+    pub euclid_steps: IntParam,
+    /// Hits spread over the steps, all of them with more hits than steps.
+    #[id = "euclid_fills"]
+    pub euclid_fills: IntParam,
+    /// Steps the pattern is turned later by.
+    #[id = "euclid_rotation"]
+    pub euclid_rotation: IntParam,
+    /// Length of a Euclidean step.
+    #[id = "euclid_division"]
+    pub euclid_division: EnumParam<NoteDivision>,
+    /// Delays every second step of the tempo-synced features, up to half a step at full swing.
+    #[id = "swing"]
+    pub swing: FloatParam,
+    /// Delays every tempo-synced event by a random time up to this many milliseconds.
+    #[id = "humanize"]
+    pub humanize: FloatParam,
+
+    #[cfg(feature = "editor")]
+    #[persist = "editor-state"]
+    pub editor_state: Arc<EguiState>,
+    /// Whether the editor shows a single row of knobs and meters instead of the full tabs.
+    #[cfg(feature = "editor")]
+    #[persist = "compact-editor"]
+    pub compact_editor: Arc<AtomicBool>,
+    /// Index of the language the editor shows its labels in.
+    #[cfg(feature = "editor")]
+    #[persist = "language"]
+    pub language: Arc<AtomicU8>,
+    #[persist = "eq-curve"]
+    pub eq_curve: Arc<RwLock<SpectralEqCurve>>,
+    /// Set by the editor whenever the EQ curve is edited so the audio thread re-renders its gains.
+    pub eq_curve_changed: Arc<AtomicBool>,
+    /// Pitch classes used by the `Custom` scale, C in bit 0.
+    #[persist = "custom-scale"]
+    pub custom_scale: Arc<AtomicU16>,
+    #[persist = "smoothing"]
+    pub smoothing: Arc<RwLock<SmoothingTimes>>,
+    /// Set by the editor whenever the smoothing times change.
+    pub smoothing_changed: Arc<AtomicBool>,
+    #[persist = "morph-presets"]
+    pub morph_presets: Arc<RwLock<MorphPresets>>,
+    /// Set by the editor whenever a morph preset is stored or a parameter is excluded.
+    pub morph_changed: Arc<AtomicBool>,
+    /// Index of the [`AnalyzerTap`] shown in the editor.
+    #[persist = "analyzer-tap"]
+    pub analyzer_tap: Arc<AtomicU8>,
+    /// Time the editor's waveform display spans, in milliseconds.
+    #[persist = "scope-window"]
+    pub scope_window: Arc<AtomicU16>,
+    #[persist = "gate-profile"]
+    pub gate_profile: Arc<RwLock<NoiseProfile>>,
+    /// Set whenever `gate_profile` is replaced from outside the audio thread.
+    pub gate_profile_changed: Arc<AtomicBool>,
+    /// Frozen spectrum kept while `save_freeze` is on.
+    #[persist = "freeze-snapshot"]
+    pub freeze_snapshot: Arc<RwLock<FreezeSnapshot>>,
+    /// Set whenever `freeze_snapshot` is replaced from outside the audio thread.
+    pub freeze_snapshot_changed: Arc<AtomicBool>,
+    /// Set by the editor's Learn button to start learning the noise floor.
+    pub gate_learn: Arc<AtomicBool>,
+    /// Freeze bank slots launched from the editor's pads, one bit per slot.
+    pub slot_launch: Arc<AtomicU8>,
+    /// Freeze bank slots erased from the editor's pads, one bit per slot.
+    pub slot_erase: Arc<AtomicU8>,
+    #[persist = "xy-axes"]
+    pub xy_axes: Arc<RwLock<XyAxes>>,
+    /// Set by the editor whenever an XY pad axis is reassigned or its CC is cleared.
+    pub xy_axes_changed: Arc<AtomicBool>,
+    /// Set by the editor's Learn buttons to the XY pad axis that learns the next CC, -1 for
+    /// none.
+    pub xy_learn: Arc<AtomicI8>,
+    #[persist = "motion-sequence"]
+    pub motion_sequence: Arc<RwLock<MotionSequence>>,
+    /// Set by the editor whenever a motion step or the sequenced parameter changes.
+    pub motion_changed: Arc<AtomicBool>,
+    /// Seconds of audio the granular delay holds, from `grain_delay::MIN_BUFFER` to
+    /// `grain_delay::MAX_BUFFER`. Longer buffers let a slow Stretch crawl further back.
+    #[persist = "buffer-length"]
+    pub buffer_length: Arc<AtomicU8>,
+    /// Samples in each STFT frame, a power of two from `fft_size::MIN_FFT_SIZE` to `FFT_SIZE`. Shorter
+    /// frames smear transients less, longer ones resolve pitches more finely. The latency stays
+    /// that of the longest.
+    #[persist = "fft-size"]
+    pub fft_size: Arc<AtomicU16>,
 }
 
-impl Default for Whirlpool {
+impl<I: ExportIdentity> Default for Whirlpool<I> {
     fn default() -> Self {
+        Self::with_params(WhirlpoolParams::default())
+    }
+}
+
+impl<I: ExportIdentity> Whirlpool<I> {
+    pub fn with_params(params: WhirlpoolParams) -> Self {
         let mut planner = FftPlanner::new();
         let forward_fft = planner.plan_fft_forward(FFT_SIZE);
-        let inverse_fft = planner.plan_fft_inverse(FFT_SIZE);
 
-        // Hanning Window for Smooth OLA
-        let window: Vec<f32> = (0..WINDOW_SIZE)
-            .map(|i| 0.5 * (1.0 - (2.0 * PI * i as f32 / (WINDOW_SIZE as f32 - 1.0)).cos()))
-            .collect();
+        let mut window = vec![0.0; WINDOW_SIZE];
+        let window_glide = WindowGlide::new(params.window.value(), &mut window, HOP_SIZE);
+        let ripple = cola_ripple(&window, HOP_SIZE);
+        let mut latency = LatencyLedger::default();
+        latency.register(Stage::Spectral, LATENCY);
+        let dry_delay = latency.dry_delay();
+        let engine = Engine::new(FFT_SIZE, 2, params.window.value(), 44100.0);
 
         Self {
-            params: Arc::new(WhirlpoolParams::default()),
+            params: Arc::new(params),
             forward_fft,
-            inverse_fft,
-            channels: vec![ChannelState::new(), ChannelState::new()],
+            channels: vec![ChannelState::new(dry_delay), ChannelState::new(dry_delay)],
+            engine,
+            incoming: None,
+            engine_fade: 1.0,
             window,
+            window_glide,
+            analysis_ring: VecDeque::from(vec![0.0; FFT_SIZE]),
+            analysis_counter: 0,
+            pitch_detector: PitchDetector::new(FFT_SIZE),
+            fundamental: None,
+            held_notes: HeldNotes::new(),
+            macro_keys: [false; 2],
+            pitch_bend: 0.0,
+            midi_notes: [None; 2],
+            sidechain_detector: TransientDetector::new(44100.0),
+            blur_triggered: false,
+            bloom: Bloom::new(44100.0),
+            internal_clock: InternalClock::new(),
+            slot_control: SlotControl::new(),
+            blur_hold: BlurHold::new(),
+            grain_grid: SyncGrid::new(),
+            euclid: EuclidSequencer::new(44100.0),
+            drift: Drift::new(),
+            delay_lfo: Lfo::new(),
+            rotate_lfo: Lfo::new(),
+            tape_wobble: TapeWobble::new(),
+            cpu_guard: CpuGuard::new(grain_delay::MAX_GRAINS),
+            offline: false,
+            limiter: TruePeakLimiter::new(MAX_OUTPUT_CHANNELS),
+            limiter_active: false,
+            latency,
+            tp_meter: TruePeakMeter::new(MAX_OUTPUT_CHANNELS),
+            bass_mono: BassMono::new(150.0, 44100.0),
+            surround: None,
+            cross_returns: std::array::from_fn(|_| vec![0.0; 2 * HOP_SIZE]),
+            cross_pos: 0,
+            spare_buffers: Arc::new(Mutex::new(Vec::new())),
+            requested_buffer_len: None,
+            spare_engine: Arc::new(Mutex::new(None)),
+            retired_engine: Arc::new(Mutex::new(None)),
+            requested_fft_size: None,
+            gate_learning: false,
+            freeze_saved: true,
+            xy_params: [Smoothed::Blur, Smoothed::Harmonics],
+            xy_ccs: [None; 2],
+            xy_learning: None,
+            cc_values: [None; Smoothed::ALL.len()],
+            motion: MotionSequencer::new(),
+            motion_value: None,
+            motion_recorded: false,
+            meters: Arc::new(Meters {
+                pitch: AtomicF32::new(0.0),
+                clip: AtomicBool::new(false),
+                tempo: AtomicF32::new(120.0),
+                host_tempo: AtomicBool::new(false),
+                analyzer: Mutex::new(AnalyzerData::new(FFT_SIZE)),
+                slots_filled: AtomicU8::new(0),
+                active_slot: AtomicI8::new(-1),
+                cpu_load: AtomicF32::new(0.0),
+                grain_voices: AtomicU8::new(grain_delay::MIN_GRAINS as u8),
+                true_peak: AtomicF32::new(0.0),
+                sample_rate: AtomicF32::new(44100.0),
+                max_block_size: AtomicU32::new(0),
+                block_size: AtomicU32::new(0),
+                block_time: AtomicF32::new(0.0),
+                latency: AtomicU32::new(0),
+                grain_buffer: AtomicU32::new(0),
+                fft_size: AtomicU32::new(FFT_SIZE as u32),
+                cola_ripple: AtomicF32::new(ripple),
+                gate_learning: AtomicBool::new(false),
+                xy_learning: AtomicI8::new(-1),
+                xy_cc: [AtomicF32::new(-1.0), AtomicF32::new(-1.0)],
+                motion_step: AtomicI8::new(-1),
+                euclid_step: AtomicI8::new(-1),
+            }),
+            sample_rate: 44100.0,
+            bypass_fade: Smoother::new(SmoothingStyle::Linear(10.0)),
+            input_mute_fade: Smoother::new(SmoothingStyle::Linear(10.0)),
+            delay_mod_depth: Smoother::new(SmoothingStyle::Linear(50.0)),
+            smoothers: std::array::from_fn(|_| Smoother::none()),
+            segment: SegmentValues::new(),
+            morph_endpoints: [None; Smoothed::ALL.len()],
+            next_transport_pos: None,
+            eq_gains: vec![1.0; FFT_SIZE / 2],
+            ducker: SpectralDucker::new(FFT_SIZE),
+            bin_gains: vec![1.0; FFT_SIZE / 2],
+            analyzer: Analyzer::new(FFT_SIZE),
+            identity: PhantomData,
         }
     }
 }
 
-impl ChannelState {
+impl SegmentValues {
     fn new() -> Self {
         Self {
-            input_ring: VecDeque::from(vec![0.0; FFT_SIZE]),
+            mix: [0.0; HOP_SIZE],
+            gain: [0.0; HOP_SIZE],
+            bypass: [0.0; HOP_SIZE],
+            input_mute: [0.0; HOP_SIZE],
+            engine_fade: [0.0; HOP_SIZE],
+            bloom: [1.0; HOP_SIZE],
+            delay_feedback: [0.0; HOP_SIZE],
+            delay_samples: [1; HOP_SIZE],
+            delay_mod: [0.0; HOP_SIZE],
+            grain_spawn: [None; HOP_SIZE],
+            trigger_spawn: [false; HOP_SIZE],
+            sidechain: [0.0; HOP_SIZE],
+            scratch: [0.0; HOP_SIZE],
+            tap: [0.0; HOP_SIZE],
+            rear: [[0.0; HOP_SIZE]; 2],
+        }
+    }
+}
+
+impl ChannelState {
+    fn new(dry_delay: usize) -> Self {
+        Self {
+            dry_delay: VecDeque::from(vec![0.0; dry_delay]),
+            dry_tone: DryTone::new(),
+            grain_delay: GrainDelay::new(44100.0),
+            grain_return: 0.0,
+        }
+    }
+
+    /// Clears all buffered audio without reallocating.
+    fn reset(&mut self) {
+        self.relocate();
+        self.dry_tone.reset();
+        self.grain_delay.reset();
+        self.grain_return = 0.0;
+    }
+
+    /// Clears the dry delay, which belongs to the position the transport left. The grain delay
+    /// is part of the performance and carries on.
+    fn relocate(&mut self) {
+        self.dry_delay.iter_mut().for_each(|x| *x = 0.0);
+    }
+}
+
+impl Stft {
+    fn new(fft_size: usize) -> Self {
+        let bins = fft_size / 2;
+        Self {
+            emphasis: Emphasis::new(bins),
+            input_ring: VecDeque::from(vec![0.0; fft_size]),
+            // Every size ends its frames where the largest one does, see `process_sample()`
             output_accum: VecDeque::from(vec![0.0; FFT_SIZE]),
-            scratch_in: vec![Complex::zero(); FFT_SIZE],
-            scratch_out: vec![Complex::zero(); FFT_SIZE],
+            scratch_in: vec![Complex::zero(); fft_size],
+            scratch_out: vec![Complex::zero(); fft_size],
+            scratch_prev: vec![Complex::zero(); fft_size],
+            scratch_harmony: vec![Complex::zero(); bins],
+            pre_delay: SpectralDelay::new(bins),
+            analysis: BinAnalysis {
+                process_mask: vec![true; bins],
+                harmony_mask: vec![true; bins],
+                peaks: Vec::with_capacity(bins),
+                envelope: SpectralEnvelope::new(bins),
+            },
+            tonal_bins: vec![false; bins],
+            decimator: Decimator::new(bins),
+            rotator: Rotator::new(bins),
+            mirror: SpectralMirror::new(bins),
+            average: SpectralAverage::new(bins),
+            tamer: SpectralTamer::new(bins),
+            gate: SpectralGate::new(bins),
+            last_map: None,
             hop_counter: 0,
+            frames: 0,
             rng_state: 0,
+            blur_seed: 0,
+            blur_table: BlurTable::new(bins),
+            frozen_mags: vec![0.0; bins],
+            frozen_phases: vec![0.0; bins],
+            shimmer: Shimmer::new(bins),
+            has_capture: false,
+            capture_pending: false,
+            slots: SlotSpectra::new(fft_size, fft_size * HOP_SIZE / FFT_SIZE),
         }
     }
+
+    /// Clears all buffered audio without reallocating.
+    fn reset(&mut self) {
+        self.relocate();
+        self.last_map = None;
+        self.hop_counter = 0;
+        self.frames = 0;
+        self.has_capture = false;
+        self.capture_pending = false;
+        self.gate.reset();
+        self.pre_delay.reset();
+        self.rotator.reset();
+        self.shimmer.reset();
+        self.mirror.reset();
+        self.average.reset();
+    }
+
+    /// Clears the audio on its way through the STFT, which belongs to the position the
+    /// transport left. The freeze capture and the effects' memory are part of the performance
+    /// and carry on.
+    fn relocate(&mut self) {
+        self.input_ring.iter_mut().for_each(|x| *x = 0.0);
+        self.output_accum.iter_mut().for_each(|x| *x = 0.0);
+    }
+
+    /// Carries the freeze capture, the slots and the noise floor over from `old`, which runs at
+    /// another FFT size.
+    fn take_over(&mut self, old: &Stft) {
+        // A partial's magnitude grows with the frame length
+        let scale = self.frozen_mags.len() as f32 / old.frozen_mags.len() as f32;
+        let frozen = &old.frozen_mags;
+        rebin(frozen.len(), |i| frozen[i], &mut self.frozen_mags, scale);
+        self.frozen_phases.fill(0.0);
+        self.has_capture = old.has_capture;
+        self.capture_pending = old.capture_pending;
+        self.slots.take_over(&old.slots);
+        self.gate.set_floor(Some(old.gate.floor()));
+    }
+}
+
+impl Engine {
+    /// Allocates everything, so while playing it is built on the background thread.
+    fn new(fft_size: usize, channels: usize, shape: AnalysisWindow, sample_rate: f32) -> Self {
+        let mut planner = FftPlanner::new();
+        let mut window = vec![0.0; fft_size];
+        // Glides advance once per analysis hop, whatever the size
+        let window_glide = WindowGlide::new(shape, &mut window, HOP_SIZE);
+        let mut engine = Self {
+            fft_size,
+            forward_fft: planner.plan_fft_forward(fft_size),
+            inverse_fft: planner.plan_fft_inverse(fft_size),
+            window,
+            window_glide,
+            bin_gains: vec![1.0; fft_size / 2],
+            channels: (0..channels).map(|_| Stft::new(fft_size)).collect(),
+            sample_rate: 0.0,
+        };
+        engine.set_sample_rate(sample_rate);
+        engine
+    }
+
+    /// Makes room for the longest harmony pre-delay at `sample_rate`. This allocates.
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        let frames = (pre_delay::MAX_PRE_DELAY * sample_rate / self.hop() as f32).ceil() as usize;
+        for stft in self.channels.iter_mut() {
+            stft.pre_delay.set_max_frames(frames);
+        }
+    }
+
+    /// Samples between frames, keeping the overlap of `HOP_SIZE` at every size.
+    fn hop(&self) -> usize {
+        self.fft_size * HOP_SIZE / FFT_SIZE
+    }
+
+    /// Copies the gains of `gains`, which has a gain for every bin of the largest size, that
+    /// land on this size's bins.
+    fn set_bin_gains(&mut self, gains: &[f32]) {
+        let step = FFT_SIZE / self.fft_size;
+        for (gain, &from) in self.bin_gains.iter_mut().zip(gains.iter().step_by(step)) {
+            *gain = from;
+        }
+    }
+}
+
+impl WhirlpoolParams {
+    pub(crate) fn smoothed(&self, param: Smoothed) -> &FloatParam {
+        match param {
+            Smoothed::Harmonics => &self.harmonics,
+            Smoothed::Shift => &self.shift,
+            Smoothed::ShiftHz => &self.shift_hz,
+            Smoothed::Blur => &self.blur,
+            Smoothed::Mix => &self.mix,
+            Smoothed::OutGain => &self.out_gain,
+            Smoothed::DelayTime => &self.delay_time,
+            Smoothed::DelayFeedback => &self.delay_feedback,
+        }
+    }
+
+    /// Whether anyone is looking at the meters. Headless builds have nothing to show them on.
+    fn editor_open(&self) -> bool {
+        #[cfg(feature = "editor")]
+        return self.editor_state.is_open();
+        #[cfg(not(feature = "editor"))]
+        false
+    }
 }
 
 impl Default for WhirlpoolParams {
     fn default() -> Self {
         Self {
+            bypass: BoolParam::new("Bypass", false).make_bypass(),
+            input_pad: EnumParam::new("Input Pad", InputPad::Unity),
             harmonics: FloatParam::new(
                 "Harmonics",
                 0.5,
                 FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            harmony_pan: FloatParam::new(
+                "Harmony Pan",
+                0.0,
+                FloatRange::Linear { min: -1.0, max: 1.0 },
+            )
+            .with_value_to_string(formatters::v2s_f32_panning())
+            .with_string_to_value(formatters::s2v_f32_panning()),
+            harmony_voices: std::array::from_fn(HarmonyVoiceParams::new),
+            unison: IntParam::new(
+                "Unison",
+                1,
+                IntRange::Linear {
+                    min: 1,
+                    max: unison::MAX_VOICES as i32,
+                },
             ),
+            unison_width: FloatParam::new(
+                "Unison Width",
+                0.5,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            shift_mode: EnumParam::new("Shift Mode", ShiftMode::Ratio),
+            midi_root: IntParam::new("MIDI Root", 60, IntRange::Linear { min: 0, max: 127 })
+                .with_value_to_string(formatters::v2s_i32_note_formatter())
+                .with_string_to_value(formatters::s2v_i32_note_formatter()),
+            bend_range: IntParam::new("Bend Range", 2, IntRange::Linear { min: 0, max: 24 })
+                .with_unit(" st"),
             shift: FloatParam::new(
                 "Shift",
                 1.0,
                 FloatRange::Linear { min: 0.5, max: 2.0 },
-            ),
+            )
+            .with_unit("x")
+            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+            shift_hz: FloatParam::new(
+                "Shift Hz",
+                0.0,
+                FloatRange::Linear { min: -2000.0, max: 2000.0 },
+            )
+            .with_unit(" Hz")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            scale: EnumParam::new("Scale", Scale::Off),
+            key: EnumParam::new("Key", Key::C),
             blur: FloatParam::new(
                 "Blur",
                 0.0,
                 FloatRange::Linear { min: 0.0, max: 1.0 },
-            ),
+            )
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            blur_hold: IntParam::new("Blur Hold", 1, IntRange::Linear { min: 1, max: 64 }),
+            blur_sync: BoolParam::new("Blur Sync", false),
+            blur_noise: EnumParam::new("Blur Noise", BlurNoise::White),
+            harmony_source: EnumParam::new("Harmony Source", HarmonySource::PostBlur),
+            average: FloatParam::new(
+                "Average",
+                0.0,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            tame: FloatParam::new("Tame", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 })
+                .with_unit(" %")
+                .with_value_to_string(formatters::v2s_f32_percentage(0))
+                .with_string_to_value(formatters::s2v_f32_percentage()),
+            preserve_envelope: BoolParam::new("Preserve Body", false),
+            pre_delay: FloatParam::new(
+                "Pre-Delay",
+                0.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: pre_delay::MAX_PRE_DELAY * 1000.0,
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+            gate: BoolParam::new("Spectral Gate", false),
+            gate_threshold: FloatParam::new(
+                "Gate Threshold",
+                6.0,
+                FloatRange::Linear { min: 0.0, max: 24.0 },
+            )
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            gate_reduction: FloatParam::new(
+                "Gate Reduction",
+                -30.0,
+                FloatRange::Linear { min: -60.0, max: 0.0 },
+            )
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            window: EnumParam::new("Window", AnalysisWindow::Hann),
+            pre_emphasis: BoolParam::new("Pre-Emphasis", false),
+            decimate: IntParam::new("Decimate", 1, IntRange::Linear { min: 1, max: 64 }),
+            decimate_mode: EnumParam::new("Decimate Mode", DecimateMode::Stride),
+            rotate: IntParam::new(
+                "Rotate",
+                0,
+                IntRange::Linear {
+                    min: -(FFT_SIZE as i32 / 2),
+                    max: FFT_SIZE as i32 / 2,
+                },
+            )
+            .with_unit(" bins"),
+            rotate_mod_depth: IntParam::new(
+                "Rotate Mod",
+                0,
+                IntRange::Linear {
+                    min: 0,
+                    max: FFT_SIZE as i32 / 2,
+                },
+            )
+            .with_unit(" bins"),
+            rotate_mod_rate: FloatParam::new(
+                "Rotate Rate",
+                0.25,
+                FloatRange::Skewed {
+                    min: 0.01,
+                    max: 10.0,
+                    factor: FloatRange::skew_factor(-1.5),
+                },
+            )
+            .with_unit(" Hz")
+            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+            invert: BoolParam::new("Invert", false),
+            invert_center: FloatParam::new(
+                "Invert Center",
+                1000.0,
+                FloatRange::Skewed {
+                    min: 20.0,
+                    max: 20000.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            )
+            .with_value_to_string(formatters::v2s_f32_hz_then_khz(0))
+            .with_string_to_value(formatters::s2v_f32_hz_then_khz()),
+            invert_wet: FloatParam::new(
+                "Invert Wet",
+                1.0,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            tonal_split: EnumParam::new("Process", TonalSplit::All),
+            tonality: FloatParam::new(
+                "Tonality",
+                9.0,
+                FloatRange::Linear { min: 0.0, max: 24.0 },
+            )
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            harmony_bins: EnumParam::new("Harmony Bins", HarmonyBins::All),
+            peak_count: IntParam::new("Peak Count", 8, IntRange::Linear { min: 1, max: 32 }),
+            low_cut: FloatParam::new(
+                "Low Cut",
+                20.0,
+                FloatRange::Skewed {
+                    min: 20.0,
+                    max: 20000.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            )
+            .with_unit(" Hz")
+            .with_value_to_string(formatters::v2s_f32_hz_then_khz(0))
+            .with_string_to_value(formatters::s2v_f32_hz_then_khz()),
+            high_cut: FloatParam::new(
+                "High Cut",
+                20000.0,
+                FloatRange::Skewed {
+                    min: 20.0,
+                    max: 20000.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            )
+            .with_unit(" Hz")
+            .with_value_to_string(formatters::v2s_f32_hz_then_khz(0))
+            .with_string_to_value(formatters::s2v_f32_hz_then_khz()),
             mix: FloatParam::new(
                 "Dry/Wet",
                 0.8,
                 FloatRange::Linear { min: 0.0, max: 1.0 },
-            ),
+            )
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            input_mute: BoolParam::new("Input Mute", false),
+            dry_low_cut: FloatParam::new(
+                "Dry Low Cut",
+                dry_tone::MIN_LOW_CUT,
+                FloatRange::Skewed {
+                    min: dry_tone::MIN_LOW_CUT,
+                    max: 2000.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            )
+            .with_unit(" Hz")
+            .with_value_to_string(formatters::v2s_f32_hz_then_khz(0))
+            .with_string_to_value(formatters::s2v_f32_hz_then_khz()),
+            dry_tilt: FloatParam::new(
+                "Dry Tilt",
+                0.0,
+                FloatRange::Linear {
+                    min: -dry_tone::MAX_TILT,
+                    max: dry_tone::MAX_TILT,
+                },
+            )
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            bloom_attack: FloatParam::new(
+                "Bloom Attack",
+                0.0,
+                FloatRange::Skewed {
+                    min: 0.0,
+                    max: bloom::MAX_ATTACK * 1000.0,
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+            bloom_release: FloatParam::new(
+                "Bloom Release",
+                0.0,
+                FloatRange::Skewed {
+                    min: 0.0,
+                    max: bloom::MAX_RELEASE * 1000.0,
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
             out_gain: FloatParam::new(
                 "Volume",
                 1.0,
                 FloatRange::Linear { min: 0.0, max: 2.0 },
+            )
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_gain_to_db(1))
+            .with_string_to_value(formatters::s2v_f32_gain_to_db()),
+            midi_out: BoolParam::new("MIDI Out", false),
+            freeze: BoolParam::new("Freeze", false),
+            freeze_trigger: EnumParam::new("Freeze Trigger", FreezeTrigger::Manual),
+            freeze_amount: FloatParam::new(
+                "Freeze Amount",
+                1.0,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            shimmer: EnumParam::new("Shimmer", ShimmerInterval::Off),
+            shimmer_level: FloatParam::new(
+                "Shimmer Level",
+                0.5,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            drift: FloatParam::new("Drift", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 })
+                .with_unit(" %")
+                .with_value_to_string(formatters::v2s_f32_percentage(0))
+                .with_string_to_value(formatters::s2v_f32_percentage()),
+            save_freeze: BoolParam::new("Save Freeze", false),
+            trigger_sensitivity: FloatParam::new(
+                "Trigger Sensitivity",
+                0.5,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            trigger_hold: FloatParam::new(
+                "Trigger Hold",
+                50.0,
+                FloatRange::Skewed {
+                    min: 10.0,
+                    max: 1000.0,
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+            sidechain_grains: BoolParam::new("Sidechain Grains", false),
+            sidechain_blur: BoolParam::new("Sidechain Blur", false),
+            slot_notes: BoolParam::new("Slot Notes", false),
+            slot_base_note: IntParam::new(
+                "Slot Base Note",
+                36,
+                IntRange::Linear {
+                    min: 0,
+                    max: 127 - NUM_SLOTS as i32 + 1,
+                },
+            )
+            .with_value_to_string(formatters::v2s_i32_note_formatter())
+            .with_string_to_value(formatters::s2v_i32_note_formatter()),
+            slot_fade: FloatParam::new(
+                "Slot Fade",
+                500.0,
+                FloatRange::Skewed {
+                    min: 10.0,
+                    max: 5000.0,
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+            grain_feedback: BoolParam::new("Grain Feedback", false),
+            delay_time: FloatParam::new(
+                "Delay Time",
+                250.0,
+                FloatRange::Skewed {
+                    min: 50.0,
+                    max: grain_delay::MAX_DELAY * 1000.0,
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+            delay_feedback: FloatParam::new(
+                "Delay Feedback",
+                0.5,
+                FloatRange::Linear { min: 0.0, max: 0.95 },
+            )
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            grain_voices: IntParam::new(
+                "Grain Voices",
+                grain_delay::MIN_GRAINS as i32,
+                IntRange::Linear {
+                    min: grain_delay::MIN_GRAINS as i32,
+                    max: grain_delay::MAX_GRAINS as i32,
+                },
+            ),
+            grain_normalize: BoolParam::new("Grain Normalize", false),
+            grain_shape: FloatParam::new(
+                "Grain Shape",
+                0.5,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            jitter_distribution: EnumParam::new("Distribution", JitterDistribution::Uniform),
+            jitter_bias: FloatParam::new(
+                "Jitter Bias",
+                0.0,
+                FloatRange::Linear {
+                    min: -1.0,
+                    max: 1.0,
+                },
+            )
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            grain_chord: EnumParam::new("Grain Chord", GrainChord::Off),
+            chord_weight: FloatParam::new(
+                "Chord Weight",
+                0.5,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            surround_spread: FloatParam::new(
+                "Surround Spread",
+                0.5,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            grain_filter: BoolParam::new("Grain Filter", false),
+            grain_filter_type: EnumParam::new("Filter Type", GrainFilterType::LowPass),
+            grain_cutoff: FloatParam::new(
+                "Grain Cutoff",
+                2000.0,
+                FloatRange::Skewed {
+                    min: 20.0,
+                    max: 20000.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            )
+            .with_unit(" Hz")
+            .with_value_to_string(formatters::v2s_f32_hz_then_khz(0))
+            .with_string_to_value(formatters::s2v_f32_hz_then_khz()),
+            grain_cutoff_spread: FloatParam::new(
+                "Cutoff Spread",
+                1.0,
+                FloatRange::Linear { min: 0.0, max: 4.0 },
+            )
+            .with_unit(" oct")
+            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+            grain_sync: BoolParam::new("Grain Sync", false),
+            midi_grains: BoolParam::new("MIDI Grains", false),
+            grain_velocity: FloatParam::new(
+                "Grain Velocity",
+                1.0,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            grain_key_track: EnumParam::new("Key Track", GrainKeyTrack::Off),
+            grain_division: EnumParam::new("Grain Rate", NoteDivision::Sixteenth),
+            delay_mod_rate: FloatParam::new(
+                "Mod Rate",
+                0.5,
+                FloatRange::Skewed {
+                    min: 0.05,
+                    max: 10.0,
+                    factor: FloatRange::skew_factor(-1.5),
+                },
+            )
+            .with_unit(" Hz")
+            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+            delay_mod_depth: FloatParam::new(
+                "Mod Depth",
+                0.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: grain_delay::MAX_MODULATION * 1000.0,
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            delay_mod_shape: EnumParam::new("Mod Shape", LfoShape::Sine),
+            delay_invert: BoolParam::new("Invert Feedback", false),
+            delay_hold: BoolParam::new("Hold", false),
+            grain_reverse: BoolParam::new("Reverse", false),
+            tape_stop: BoolParam::new("Tape Stop", false),
+            tape_stop_time: FloatParam::new(
+                "Tape Stop Time",
+                grain_delay::DEFAULT_TAPE_STOP * 1000.0,
+                FloatRange::Skewed {
+                    min: grain_delay::MIN_TAPE_STOP * 1000.0,
+                    max: grain_delay::MAX_TAPE_STOP * 1000.0,
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+            macro_notes: BoolParam::new("Macro Notes", false),
+            macro_base_note: IntParam::new(
+                "Macro Base Note",
+                24,
+                IntRange::Linear { min: 0, max: 126 },
+            )
+            .with_value_to_string(formatters::v2s_i32_note_formatter())
+            .with_string_to_value(formatters::s2v_i32_note_formatter()),
+            stretch: FloatParam::new(
+                "Stretch",
+                1.0,
+                FloatRange::Skewed {
+                    min: grain_delay::MIN_RATE,
+                    max: grain_delay::MAX_RATE,
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            )
+            .with_unit("x")
+            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+            delay_wow: FloatParam::new("Wow", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 })
+                .with_unit(" %")
+                .with_value_to_string(formatters::v2s_f32_percentage(0))
+                .with_string_to_value(formatters::s2v_f32_percentage()),
+            delay_flutter: FloatParam::new(
+                "Flutter",
+                0.0,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            delay_saturation: FloatParam::new(
+                "Saturation",
+                0.0,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            delay_cross: FloatParam::new(
+                "Cross Feedback",
+                0.0,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            grain_source: EnumParam::new("Grain Source", GrainSource::Main),
+            cpu_guard: BoolParam::new("CPU Guard", true),
+            tp_limit: BoolParam::new("True Peak Limit", false),
+            tp_ceiling: FloatParam::new(
+                "TP Ceiling",
+                -1.0,
+                FloatRange::Linear { min: -12.0, max: 0.0 },
+            )
+            .with_unit(" dBTP")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            bass_mono: BoolParam::new("Bass Mono", false),
+            bass_mono_freq: FloatParam::new(
+                "Mono Below",
+                150.0,
+                FloatRange::Skewed {
+                    min: 20.0,
+                    max: 500.0,
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            )
+            .with_unit(" Hz")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+            sidechain_duck: BoolParam::new("Spectral Duck", false),
+            duck_amount: FloatParam::new(
+                "Duck Amount",
+                0.5,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            duck_release: FloatParam::new(
+                "Duck Release",
+                150.0,
+                FloatRange::Skewed {
+                    min: 10.0,
+                    max: 1000.0,
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+            sidechain_listen: BoolParam::new("Sidechain Listen", false),
+            morph: FloatParam::new(
+                "Morph",
+                0.0,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            internal_bpm: FloatParam::new(
+                "Internal BPM",
+                120.0,
+                FloatRange::Linear { min: 20.0, max: 300.0 },
+            )
+            .with_step_size(0.1)
+            .with_unit(" BPM")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            internal_run: BoolParam::new("Internal Run", true),
+            motion: BoolParam::new("Motion", false),
+            motion_steps: IntParam::new(
+                "Motion Steps",
+                motion::MIN_STEPS as i32,
+                IntRange::Linear {
+                    min: motion::MIN_STEPS as i32,
+                    max: motion::MAX_STEPS as i32,
+                },
+            ),
+            motion_division: EnumParam::new("Motion Rate", NoteDivision::Sixteenth),
+            motion_record: BoolParam::new("Motion Record", false),
+            euclid: EnumParam::new("Euclid", EuclidTarget::Off),
+            euclid_steps: IntParam::new(
+                "Euclid Steps",
+                16,
+                IntRange::Linear {
+                    min: 1,
+                    max: euclid::MAX_STEPS as i32,
+                },
             ),
+            euclid_fills: IntParam::new(
+                "Euclid Fills",
+                5,
+                IntRange::Linear {
+                    min: 0,
+                    max: euclid::MAX_STEPS as i32,
+                },
+            ),
+            euclid_rotation: IntParam::new(
+                "Euclid Rotation",
+                0,
+                IntRange::Linear {
+                    min: 0,
+                    max: euclid::MAX_STEPS as i32 - 1,
+                },
+            ),
+            euclid_division: EnumParam::new("Euclid Rate", NoteDivision::Sixteenth),
+            swing: FloatParam::new("Swing", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 })
+                .with_unit(" %")
+                .with_value_to_string(formatters::v2s_f32_percentage(0))
+                .with_string_to_value(formatters::s2v_f32_percentage()),
+            humanize: FloatParam::new(
+                "Humanize",
+                0.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: tempo::MAX_HUMANIZE * 1000.0,
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+
+            #[cfg(feature = "editor")]
+            editor_state: editor::default_state(),
+            #[cfg(feature = "editor")]
+            compact_editor: Arc::new(AtomicBool::new(false)),
+            #[cfg(feature = "editor")]
+            language: Arc::new(AtomicU8::new(0)),
+            eq_curve: Arc::new(RwLock::new(SpectralEqCurve::default())),
+            eq_curve_changed: Arc::new(AtomicBool::new(true)),
+            custom_scale: Arc::new(AtomicU16::new(scale::CHROMATIC_MASK)),
+            smoothing: Arc::new(RwLock::new(SmoothingTimes::default())),
+            smoothing_changed: Arc::new(AtomicBool::new(true)),
+            morph_presets: Arc::new(RwLock::new(MorphPresets::default())),
+            morph_changed: Arc::new(AtomicBool::new(true)),
+            analyzer_tap: Arc::new(AtomicU8::new(AnalyzerTap::default().index())),
+            scope_window: Arc::new(AtomicU16::new(100)),
+            gate_profile: Arc::new(RwLock::new(NoiseProfile::default())),
+            gate_profile_changed: Arc::new(AtomicBool::new(true)),
+            freeze_snapshot: Arc::new(RwLock::new(FreezeSnapshot::default())),
+            freeze_snapshot_changed: Arc::new(AtomicBool::new(true)),
+            gate_learn: Arc::new(AtomicBool::new(false)),
+            slot_launch: Arc::new(AtomicU8::new(0)),
+            slot_erase: Arc::new(AtomicU8::new(0)),
+            xy_axes: Arc::new(RwLock::new(XyAxes::default())),
+            xy_axes_changed: Arc::new(AtomicBool::new(true)),
+            xy_learn: Arc::new(AtomicI8::new(-1)),
+            motion_sequence: Arc::new(RwLock::new(MotionSequence::default())),
+            motion_changed: Arc::new(AtomicBool::new(true)),
+            buffer_length: Arc::new(AtomicU8::new(grain_delay::DEFAULT_BUFFER)),
+            fft_size: Arc::new(AtomicU16::new(FFT_SIZE as u16)),
         }
     }
 }
 
-impl Plugin for Whirlpool {
-    const NAME: &'static str = "Whirlpool Spectral";
+impl<I: ExportIdentity> Plugin for Whirlpool<I> {
+    const NAME: &'static str = I::NAME;
     const VENDOR: &'static str = "Antigravity";
     const URL: &'static str = "https://example.com";
     const EMAIL: &'static str = "info@example.com";
-    const VERSION: &'static str = "2.5.0";
+    const VERSION: &'static str = env!("CARGO_PKG_VERSION");
 
     const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[
         AudioIOLayout {
             main_input_channels: NonZeroU32::new(2),
             main_output_channels: NonZeroU32::new(2),
+            aux_input_ports: &[new_nonzero_u32(2)],
+            names: PortNames {
+                aux_inputs: &["Sidechain"],
+                ..PortNames::const_default()
+            },
+            ..AudioIOLayout::const_default()
+        },
+        AudioIOLayout {
+            main_input_channels: NonZeroU32::new(2),
+            main_output_channels: NonZeroU32::new(4),
+            aux_input_ports: &[new_nonzero_u32(2)],
+            names: PortNames {
+                layout: Some("Quad"),
+                aux_inputs: &["Sidechain"],
+                ..PortNames::const_default()
+            },
+            ..AudioIOLayout::const_default()
+        },
+        AudioIOLayout {
+            main_input_channels: NonZeroU32::new(2),
+            main_output_channels: NonZeroU32::new(6),
+            aux_input_ports: &[new_nonzero_u32(2)],
+            names: PortNames {
+                layout: Some("5.1"),
+                aux_inputs: &["Sidechain"],
+                ..PortNames::const_default()
+            },
             ..AudioIOLayout::const_default()
         },
     ];
-    const MIDI_INPUT: MidiConfig = MidiConfig::None;
+    const MIDI_INPUT: MidiConfig = MidiConfig::MidiCCs;
+    const MIDI_OUTPUT: MidiConfig = MidiConfig::Basic;
+    // The wrapper splits the buffer at every parameter change, `process_block()` reads all
+    // parameters and retargets the smoothers at the start of each split
     const SAMPLE_ACCURATE_AUTOMATION: bool = true;
     type SysExMessage = ();
-    type BackgroundTask = ();
+    type BackgroundTask = BufferTask;
 
     fn params(&self) -> Arc<dyn Params> {
         self.params.clone()
     }
 
+    fn task_executor(&mut self) -> TaskExecutor<Self> {
+        let spare_buffers = self.spare_buffers.clone();
+        let spare_engine = self.spare_engine.clone();
+        let retired_engine = self.retired_engine.clone();
+        Box::new(move |task| match task {
+            BufferTask::Allocate { channels, samples } => {
+                if let Ok(mut spare) = spare_buffers.lock() {
+                    *spare = vec![vec![0.0; samples]; channels];
+                }
+            }
+            BufferTask::Release => {
+                if let Ok(mut spare) = spare_buffers.lock() {
+                    spare.clear();
+                }
+            }
+            BufferTask::Build {
+                fft_size,
+                channels,
+                window,
+                sample_rate,
+            } => {
+                // Built before taking the lock, so the audio thread never waits for it
+                let engine = Engine::new(fft_size, channels, window, sample_rate);
+                if let Ok(mut spare) = spare_engine.lock() {
+                    *spare = Some(engine);
+                }
+            }
+            BufferTask::Retire => {
+                let retired = retired_engine
+                    .lock()
+                    .ok()
+                    .and_then(|mut retired| retired.take());
+                // Freed after the lock is released
+                drop(retired);
+            }
+        })
+    }
+
+    #[cfg(feature = "editor")]
     fn editor(&mut self, _async_executor: AsyncExecutor<Self>) -> Option<Box<dyn Editor>> {
-        None
+        editor::create(
+            self.params.clone(),
+            self.meters.clone(),
+            self.params.editor_state.clone(),
+        )
+    }
+
+    fn initialize(
+        &mut self,
+        audio_io_layout: &AudioIOLayout,
+        buffer_config: &BufferConfig,
+        context: &mut impl InitContext<Self>,
+    ) -> bool {
+        self.sample_rate = buffer_config.sample_rate;
+        self.offline = buffer_config.process_mode == ProcessMode::Offline;
+        self.surround = SurroundLayout::from_outputs(
+            audio_io_layout
+                .main_output_channels
+                .map_or(0, |channels| channels.get() as usize),
+        );
+        self.meters
+            .sample_rate
+            .store(self.sample_rate, Ordering::Relaxed);
+        self.meters
+            .max_block_size
+            .store(buffer_config.max_buffer_size, Ordering::Relaxed);
+        self.limiter.set_sample_rate(self.sample_rate);
+        self.bass_mono.set_sample_rate(self.sample_rate);
+        self.limiter_active = self.params.tp_limit.value();
+        self.register_limiter();
+        // Stages may have registered new delays, this is the place to allocate for them
+        let dry_delay = self.latency.dry_delay();
+        for state in self.channels.iter_mut() {
+            state.dry_delay.resize(dry_delay, 0.0);
+        }
+        context.set_latency_samples(self.latency());
+        self.meters.latency.store(self.latency(), Ordering::Relaxed);
+        self.sidechain_detector.set_sample_rate(self.sample_rate);
+        self.bloom.set_sample_rate(self.sample_rate);
+        self.pitch_detector.set_sample_rate(self.sample_rate);
+        self.euclid.set_sample_rate(self.sample_rate);
+        let buffer_length = self.buffer_length();
+        self.requested_buffer_len = None;
+        for state in self.channels.iter_mut() {
+            state.grain_delay.set_sample_rate(self.sample_rate, buffer_length);
+        }
+        let fft_size = self.fft_size();
+        self.engine = Engine::new(
+            fft_size,
+            self.channels.len(),
+            self.params.window.value(),
+            self.sample_rate,
+        );
+        self.incoming = None;
+        self.engine_fade = 1.0;
+        self.requested_fft_size = None;
+        if let Ok(mut spare) = self.spare_engine.lock() {
+            *spare = None;
+        }
+        self.meters
+            .fft_size
+            .store(fft_size as u32, Ordering::Relaxed);
+        self.meters.grain_buffer.store(
+            grain_delay::buffer_len(self.sample_rate, buffer_length) as u32,
+            Ordering::Relaxed,
+        );
+        // The curve may have been restored from state, so always re-render it here
+        self.params.eq_curve_changed.store(true, Ordering::Release);
+        self.params.smoothing_changed.store(true, Ordering::Release);
+        self.params.morph_changed.store(true, Ordering::Release);
+        self.params.motion_changed.store(true, Ordering::Release);
+        self.params.gate_profile_changed.store(true, Ordering::Release);
+        self.params.freeze_snapshot_changed.store(true, Ordering::Release);
+        if let Ok(mut snapshot) = self.params.freeze_snapshot.write() {
+            snapshot.resample(self.sample_rate);
+            snapshot.reserve(self.channels.len(), FFT_SIZE / 2);
+        }
+        if let Ok(mut profile) = self.params.gate_profile.write() {
+            // A profile restored from a session at another rate would gate the wrong frequencies
+            profile.resample(self.sample_rate);
+            profile.reserve(self.channels.len(), FFT_SIZE / 2);
+        }
+        true
+    }
+
+    fn reset(&mut self) {
+        for state in self.channels.iter_mut() {
+            state.reset();
+        }
+        for engine in std::iter::once(&mut self.engine).chain(&mut self.incoming) {
+            for stft in engine.channels.iter_mut() {
+                stft.reset();
+            }
+            engine.window_glide.finish(&mut engine.window);
+        }
+        // Nothing is left to fade from, the next block moves to the incoming engine
+        if self.incoming.is_some() {
+            self.engine_fade = 1.0;
+        }
+        self.analysis_ring.iter_mut().for_each(|x| *x = 0.0);
+        self.pitch_detector.reset();
+        self.analysis_counter = 0;
+        self.fundamental = None;
+        self.held_notes.reset();
+        self.macro_keys = [false; 2];
+        self.pitch_bend = 0.0;
+        self.sidechain_detector.reset();
+        self.blur_triggered = false;
+        self.bloom.reset();
+        self.internal_clock.reset();
+        self.motion.reset();
+        self.slot_control.reset();
+        self.blur_hold.reset();
+        self.grain_grid.reset();
+        self.euclid.reset();
+        self.drift.reset();
+        self.ducker.reset();
+        self.analyzer.reset();
+        self.limiter.reset();
+        self.tp_meter.reset();
+        self.bass_mono.reset();
+        self.next_transport_pos = None;
+        self.bypass_fade.reset(self.bypass_target());
+        self.input_mute_fade.reset(self.input_mute_target());
+        self.delay_lfo.reset();
+        self.rotate_lfo.reset();
+        self.tape_wobble.reset();
+        for returns in self.cross_returns.iter_mut() {
+            returns.fill(0.0);
+        }
+        self.window_glide.finish(&mut self.window);
+        // Resetting dropped the captures, a saved freeze comes back before the next frame instead
+        // of being replaced by whatever plays after a loop or seek
+        if self.params.save_freeze.value() {
+            self.params
+                .freeze_snapshot_changed
+                .store(true, Ordering::Release);
+        }
+        self.meters
+            .cola_ripple
+            .store(cola_ripple(&self.window, HOP_SIZE), Ordering::Relaxed);
+        self.delay_mod_depth.reset(self.delay_mod_target());
+
+        self.sync_morph_endpoints();
+        for param in Smoothed::ALL {
+            self.smoothers[param as usize].reset(self.smoothed_target(param));
+        }
     }
 
     fn process(
         &mut self,
         buffer: &mut Buffer,
-        _aux: &mut AuxiliaryBuffers,
-        _context: &mut impl ProcessContext<Self>,
+        aux: &mut AuxiliaryBuffers,
+        context: &mut impl ProcessContext<Self>,
     ) -> ProcessStatus {
-        let harmonics = self.params.harmonics.value();
-        let shift = self.params.shift.value();
-        let blur = self.params.blur.value();
-        let mix = self.params.mix.value();
-        let gain = self.params.out_gain.value();
-
-        for mut channel_samples in buffer.iter_samples() {
-            for (ch, sample) in channel_samples.iter_mut().enumerate() {
-                if ch >= self.channels.len() {
-                    continue;
-                }
-                let state = &mut self.channels[ch];
-                let input = *sample;
-
-                let wet = Self::process_sample(
-                    state,
-                    input,
-                    harmonics,
-                    shift,
-                    blur,
-                    self.forward_fft.as_ref(),
-                    self.inverse_fft.as_ref(),
-                    &self.window,
-                );
-                let final_wet = wet.tanh();
-                let output = input * (1.0 - mix) + final_wet * mix;
+        let transport = context.transport();
+        let pos = transport.pos_samples().filter(|_| transport.playing);
+        if pos.is_some() && self.next_transport_pos.is_some() && pos != self.next_transport_pos {
+            self.relocate();
+        }
+        self.next_transport_pos = pos.map(|pos| pos + buffer.samples() as i64);
 
-                *sample = output * gain;
-            }
+        let host_time = HostTime {
+            tempo: transport.tempo,
+            pos_beats: transport.pos_beats(),
+            playing: transport.playing,
+        };
+        let sidechain = aux.inputs.first().map(|input| input.as_slice_immutable());
+        self.follow_buffer_length(context);
+        self.follow_fft_size(context);
+        let started = Instant::now();
+        let latency = self.latency();
+        self.process_block(buffer, sidechain, host_time, &mut HostNotes(context));
+        let elapsed = started.elapsed();
+        if self.latency() != latency {
+            context.set_latency_samples(self.latency());
+            self.meters.latency.store(self.latency(), Ordering::Relaxed);
+        }
+        self.meters
+            .block_size
+            .store(buffer.samples() as u32, Ordering::Relaxed);
+        self.meters
+            .block_time
+            .store(elapsed.as_secs_f32(), Ordering::Relaxed);
+
+        // Only real-time processing is guarded, offline renders may take as long as they need
+        if self.cpu_guarded() {
+            self.cpu_guard.update(
+                elapsed,
+                buffer.samples(),
+                self.sample_rate,
+                self.params.grain_voices.value() as usize,
+            );
         }
+        self.meters
+            .cpu_load
+            .store(self.cpu_guard.load(), Ordering::Relaxed);
 
         ProcessStatus::Normal
     }
 }
 
-impl Whirlpool {
-    fn process_sample(
-        state: &mut ChannelState,
-        input: f32,
-        harmonics: f32,
-        shift: f32,
-        blur: f32,
-        forward_fft: &dyn Fft<f32>,
-        inverse_fft: &dyn Fft<f32>,
-        window: &[f32],
-    ) -> f32 {
-        state.input_ring.push_back(input);
-        if state.input_ring.len() > FFT_SIZE {
-            state.input_ring.pop_front();
-        }
+impl<I: ExportIdentity> Whirlpool<I> {
+    /// Renders `channels` in place without a host, for offline processing and tests. There is no
+    /// transport or MIDI here, so MIDI output and note-triggered freezes are unavailable and tempo
+    /// comes from the internal clock, see [`render_with_notes()`](Self::render_with_notes) for
+    /// MIDI input. This allocates and must not be called from the audio thread.
+    pub fn render(&mut self, channels: &mut [&mut [f32]], sidechain: Option<&[&mut [f32]]>) {
+        self.render_with_notes(channels, sidechain, &[]);
+    }
 
-        state.hop_counter += 1;
-        if state.hop_counter >= HOP_SIZE && state.input_ring.len() == FFT_SIZE {
-            state.hop_counter = 0;
-            let frame_seed = state.rng_state;
+    /// Like [`render()`](Self::render), with `events` as the incoming MIDI. Their timings are
+    /// sample offsets into `channels` and must be in order. MIDI output is still dropped.
+    pub fn render_with_notes(
+        &mut self,
+        channels: &mut [&mut [f32]],
+        sidechain: Option<&[&mut [f32]]>,
+        events: &[NoteEvent<()>],
+    ) {
+        // Nothing runs in the background here, so engines are built and freed in place
+        if self.engine_fade >= 1.0 {
+            drop(self.finish_fade());
+        }
+        if let Some(fft_size) = self.wanted_fft_size() {
+            let engine = Engine::new(
+                fft_size,
+                self.channels.len(),
+                self.params.window.value(),
+                self.sample_rate,
+            );
+            self.start_engine(engine);
+        }
 
-            for i in 0..FFT_SIZE {
-                state.scratch_in[i] = Complex::new(state.input_ring[i] * window[i], 0.0);
-            }
+        let num_samples = channels.first().map_or(0, |channel| channel.len());
+        let mut buffer = Buffer::default();
+        // SAFETY: every slice outlives `buffer` and they all have `num_samples` samples
+        unsafe {
+            buffer.set_slices(num_samples, |slices| {
+                slices.extend(channels.iter_mut().map(|channel| &mut **channel));
+            });
+        }
 
-            forward_fft.process(&mut state.scratch_in);
+        self.process_block(
+            &mut buffer,
+            sidechain,
+            HostTime::default(),
+            &mut NoteList(events.iter()),
+        );
+    }
 
-            for x in state.scratch_out.iter_mut() {
-                *x = Complex::zero();
+    /// Drops the audio still on its way from before a jump in the source, which looping or
+    /// seeking would otherwise smear into the new position. Unlike a reset, a live freeze, the
+    /// held grain loop and the effects' memory carry on, so looping a section in the DAW keeps
+    /// the performance going. Hosts report jumps through the transport, offline renders that
+    /// seek call this themselves.
+    pub fn relocate(&mut self) {
+        for state in self.channels.iter_mut() {
+            state.relocate();
+        }
+        for engine in std::iter::once(&mut self.engine).chain(&mut self.incoming) {
+            for stft in engine.channels.iter_mut() {
+                stft.relocate();
             }
-            let half = FFT_SIZE / 2;
-
-            for i in 0..half {
-                let bin = state.scratch_in[i];
-                if bin.norm_sqr() < 1e-6 {
-                    continue;
-                }
-
-                let mag = bin.norm();
-                let phase = bin.arg();
-
-                if blur > 0.0 {
-                    let r = fast_rand(i + frame_seed as usize, frame_seed);
-                    let new_phase = phase + (r * 2.0 * PI * blur);
-                    state.scratch_out[i] += Complex::from_polar(mag, new_phase);
-                } else {
-                    state.scratch_out[i] += bin;
-                }
+        }
+        self.analysis_ring.iter_mut().for_each(|x| *x = 0.0);
+        self.pitch_detector.reset();
+    }
 
-                if harmonics > 0.01 {
-                    let target_idx = (i as f32 * (1.0 + shift)).round() as usize;
-                    if target_idx < half {
-                        let mag_h = mag * harmonics;
-                        let r = fast_rand(target_idx + frame_seed as usize, frame_seed.wrapping_mul(2));
-                        let phase_h = if blur > 0.0 {
-                            phase + (r * 2.0 * PI * blur)
-                        } else {
-                            phase
-                        };
-                        state.scratch_out[target_idx] += Complex::from_polar(mag_h, phase_h);
-                    }
+    /// Copies the morph presets after the editor changed them.
+    fn sync_morph_endpoints(&mut self) {
+        if !self.params.morph_changed.swap(false, Ordering::AcqRel) {
+            return;
+        }
+        match self.params.morph_presets.try_read() {
+            Ok(presets) => {
+                for param in Smoothed::ALL {
+                    self.morph_endpoints[param as usize] = presets.endpoints(param.id());
                 }
             }
+            // The editor is holding the lock, try again next block
+            Err(_) => self.params.morph_changed.store(true, Ordering::Release),
+        }
+    }
 
-            for i in 1..half {
-                state.scratch_out[FFT_SIZE - i] = state.scratch_out[i].conj();
+    /// Copies the XY pad's axes after the editor changed them, and drops CC values of
+    /// parameters that moved since.
+    fn sync_xy_axes(&mut self) {
+        for param in Smoothed::ALL {
+            let own = self.params.smoothed(param).unmodulated_normalized_value();
+            let value = &mut self.cc_values[param as usize];
+            if value.is_some_and(|(_, seen)| seen != own) {
+                *value = None;
             }
+        }
 
-            inverse_fft.process(&mut state.scratch_out);
+        if let Ok(axis) = usize::try_from(self.params.xy_learn.swap(-1, Ordering::AcqRel)) {
+            self.xy_learning = Some(axis);
+        }
+        if !self.params.xy_axes_changed.swap(false, Ordering::AcqRel) {
+            return;
+        }
+        match self.params.xy_axes.try_read() {
+            Ok(axes) => {
+                for axis in 0..2 {
+                    self.xy_params[axis] = axes.param(axis);
+                    self.xy_ccs[axis] = axes.axes[axis].cc;
+                }
+            }
+            Err(_) => self.params.xy_axes_changed.store(true, Ordering::Release),
+        }
+    }
 
-            let norm = 1.0 / FFT_SIZE as f32;
-            for i in 0..FFT_SIZE {
-                let val = state.scratch_out[i].re * norm * window[i];
-                if i < state.output_accum.len() {
-                    state.output_accum[i] += val;
-                } else {
-                    state.output_accum.push_back(val);
+    /// Learns `cc` for the armed XY pad axis, or moves the axes it is learned for.
+    fn handle_cc(&mut self, cc: u8, value: f32) {
+        if let Some(axis) = self.xy_learning {
+            // Saved with the plugin state, if the editor holds the lock the next CC is learned
+            if let Ok(mut axes) = self.params.xy_axes.try_write() {
+                axes.learn(axis, cc);
+                for axis in 0..2 {
+                    self.xy_ccs[axis] = axes.axes[axis].cc;
                 }
+                self.xy_learning = None;
             }
+            return;
         }
 
-        let wet_sig = state.output_accum.pop_front().unwrap_or(0.0);
+        for axis in 0..2 {
+            if self.xy_ccs[axis] != Some(cc) {
+                continue;
+            }
+            let param = self.xy_params[axis];
+            let own = self.params.smoothed(param).unmodulated_normalized_value();
+            self.cc_values[param as usize] = Some((value, own));
+            let target = self.smoothed_target(param);
+            self.smoothers[param as usize].set_target(self.sample_rate, target);
+        }
+    }
+
+    /// The value `param` glides towards: the last value a learned CC sent, otherwise the motion
+    /// sequencer's step, its own value or the morph between the two presets when both have it
+    /// stored.
+    fn smoothed_target(&self, param: Smoothed) -> f32 {
+        let float_param = self.params.smoothed(param);
+        if let Some((value, _)) = self.cc_values[param as usize] {
+            return float_param.preview_plain(value);
+        }
+        if let Some(value) = self.motion_value.filter(|_| self.motion.param() == param) {
+            return float_param.preview_plain(value);
+        }
+        match self.morph_endpoints[param as usize] {
+            Some((a, b)) => {
+                let morph = self.params.morph.value();
+                float_param.preview_plain(a + (b - a) * morph)
+            }
+            None => float_param.value(),
+        }
+    }
+
+    /// Copies the motion sequence after the editor changed it, saving recorded steps first.
+    fn sync_motion(&mut self) {
+        if self.motion_recorded {
+            // Saved with the plugin state, if the editor holds the lock try again next block
+            if let Ok(mut sequence) = self.params.motion_sequence.try_write() {
+                self.motion.store(&mut sequence);
+                self.motion_recorded = false;
+            }
+        }
+        if !self.params.motion_changed.swap(false, Ordering::AcqRel) {
+            return;
+        }
+        match self.params.motion_sequence.try_read() {
+            Ok(sequence) => self.motion.load(&sequence),
+            Err(_) => self.params.motion_changed.store(true, Ordering::Release),
+        }
+    }
+
+    /// Moves the motion sequencer to transport position `beats`. Recording writes the sequenced
+    /// parameter's own value into every step it enters, playing retargets the parameter.
+    fn advance_motion(&mut self, beats: f64, step_beats: f64, length: usize, record: bool) {
+        let param = self.motion.param();
+        let (step, phase) = MotionSequencer::position(beats, step_beats, length);
+        let entered = self.motion.enter(step);
+        self.meters.motion_step.store(step as i8, Ordering::Relaxed);
+        if record {
+            if entered {
+                let own = self.params.smoothed(param).unmodulated_normalized_value();
+                self.motion.record(step, own);
+                self.motion_recorded = true;
+            }
+            return;
+        }
+        self.motion_value = Some(self.motion.value(step, phase, length));
+        let target = self.smoothed_target(param);
+        self.smoothers[param as usize].set_target(self.sample_rate, target);
+    }
+
+    /// Advances `param` by `len` samples and returns the last value.
+    fn advance_smoothed(&mut self, param: Smoothed, len: usize) -> f32 {
+        let scratch = &mut self.segment.scratch;
+        self.smoothers[param as usize].next_block(scratch, len);
+        scratch[len - 1]
+    }
+
+    fn latency(&self) -> u32 {
+        self.latency.total()
+    }
+
+    fn buffer_length(&self) -> u8 {
+        self.params
+            .buffer_length
+            .load(Ordering::Relaxed)
+            .clamp(grain_delay::MIN_BUFFER, grain_delay::MAX_BUFFER)
+    }
+
+    /// Resizes the grain delay buffers after the buffer length changed. The new buffers are
+    /// allocated on the background thread and swapped in once they are ready, the old ones go
+    /// back there to be freed, so the audio thread never allocates.
+    fn follow_buffer_length(&mut self, context: &mut impl ProcessContext<Self>) {
+        let wanted = grain_delay::buffer_len(self.sample_rate, self.buffer_length());
+        let current = self.channels[0].grain_delay.buffer_len();
+        if wanted == current {
+            self.requested_buffer_len = None;
+            return;
+        }
+        if self.requested_buffer_len != Some(wanted) {
+            self.requested_buffer_len = Some(wanted);
+            context.execute_background(BufferTask::Allocate {
+                channels: self.channels.len(),
+                samples: wanted,
+            });
+            return;
+        }
+
+        let Ok(mut spare) = self.spare_buffers.try_lock() else {
+            return;
+        };
+        // Still allocating, or left over from an earlier length
+        if spare.len() != self.channels.len() || spare.iter().any(|buffer| buffer.len() != wanted)
+        {
+            return;
+        }
+        for (state, buffer) in self.channels.iter_mut().zip(spare.iter_mut()) {
+            state.grain_delay.swap_buffer(buffer);
+            state.grain_return = 0.0;
+        }
+        drop(spare);
+        self.requested_buffer_len = None;
+        self.meters
+            .grain_buffer
+            .store(wanted as u32, Ordering::Relaxed);
+        context.execute_background(BufferTask::Release);
+    }
+
+    fn fft_size(&self) -> usize {
+        fft_size::from_setting(self.params.fft_size.load(Ordering::Relaxed))
+    }
+
+    /// The FFT size to move the STFT to, `None` while it runs at it already, is still fading
+    /// over to another one or is learning a noise floor.
+    fn wanted_fft_size(&self) -> Option<usize> {
+        let wanted = self.fft_size();
+        (wanted != self.engine.fft_size && self.incoming.is_none() && !self.gate_learning)
+            .then_some(wanted)
+    }
+
+    /// Moves the STFT to a new FFT size after it changed. The new engine is built on the
+    /// background thread and runs next to the current one while the wet signal fades over to
+    /// it, then the old one goes back there to be freed, so the audio thread never allocates.
+    fn follow_fft_size(&mut self, context: &mut impl ProcessContext<Self>) {
+        if self.engine_fade >= 1.0 && self.incoming.is_some() {
+            let retired_engine = self.retired_engine.clone();
+            // While the last retired engine is still being freed, the fade ends a block later
+            if let Ok(mut retired) = retired_engine.try_lock() {
+                if retired.is_none() {
+                    *retired = self.finish_fade();
+                    drop(retired);
+                    context.execute_background(BufferTask::Retire);
+                }
+            }
+        }
+        let Some(wanted) = self.wanted_fft_size() else {
+            self.requested_fft_size = None;
+            return;
+        };
+        if self.requested_fft_size != Some(wanted) {
+            self.requested_fft_size = Some(wanted);
+            context.execute_background(BufferTask::Build {
+                fft_size: wanted,
+                channels: self.channels.len(),
+                window: self.params.window.value(),
+                sample_rate: self.sample_rate,
+            });
+            return;
+        }
+
+        let Ok(mut spare) = self.spare_engine.try_lock() else {
+            return;
+        };
+        // Still building, or left over from an earlier size or sample rate
+        let ready = spare.as_ref().is_some_and(|engine| {
+            engine.fft_size == wanted && engine.sample_rate == self.sample_rate
+        });
+        let engine = if ready { spare.take() } else { None };
+        drop(spare);
+        if let Some(engine) = engine {
+            self.start_engine(engine);
+        }
+    }
+
+    /// Starts fading the wet signal over to `engine`, which takes over the freeze captures, the
+    /// slots and the noise floors of the current one.
+    fn start_engine(&mut self, mut engine: Engine) {
+        engine
+            .window_glide
+            .set_shape(self.params.window.value(), &engine.window);
+        engine.window_glide.finish(&mut engine.window);
+        engine.set_bin_gains(&self.bin_gains);
+        let hop = engine.hop();
+        for (stft, old) in engine.channels.iter_mut().zip(&self.engine.channels) {
+            stft.take_over(old);
+            // Frames then still render on the analysis hops, plus the ones in between
+            stft.hop_counter = self.analysis_counter % hop;
+        }
+        // Its first frames are still filling up from silence
+        self.engine_fade = -(LATENCY as f32) / (FADE_TIME * self.sample_rate);
+        self.incoming = Some(engine);
+        self.requested_fft_size = None;
+    }
+
+    /// Ends a finished fade, returning the engine the incoming one replaced.
+    fn finish_fade(&mut self) -> Option<Engine> {
+        let incoming = self.incoming.take()?;
+        self.meters
+            .fft_size
+            .store(incoming.fft_size as u32, Ordering::Relaxed);
+        Some(std::mem::replace(&mut self.engine, incoming))
+    }
+
+    /// Whether the CPU guard may cap the grain voices, which a bounce must never depend on.
+    fn cpu_guarded(&self) -> bool {
+        self.params.cpu_guard.value() && !self.offline
+    }
+
+    fn register_limiter(&mut self) {
+        let lookahead = if self.limiter_active {
+            LIMITER_LATENCY
+        } else {
+            0
+        };
+        self.latency.register(Stage::Limiter, lookahead);
+    }
+
+    fn bypass_target(&self) -> f32 {
+        if self.params.bypass.value() {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    fn input_mute_target(&self) -> f32 {
+        if self.params.input_mute.value() {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    fn delay_mod_target(&self) -> f32 {
+        self.params.delay_mod_depth.value() / 1000.0 * self.sample_rate
+    }
+
+    fn process_block(
+        &mut self,
+        buffer: &mut Buffer,
+        sidechain: Option<&[&mut [f32]]>,
+        host_time: HostTime,
+        midi: &mut impl NoteIo<Self>,
+    ) {
+        let clock = self.internal_clock.advance(
+            host_time,
+            self.params.internal_bpm.value() as f64,
+            self.params.internal_run.value(),
+            buffer.samples(),
+            self.sample_rate,
+        );
+        self.meters
+            .tempo
+            .store(clock.tempo as f32, Ordering::Relaxed);
+        self.meters
+            .host_tempo
+            .store(clock.from_host, Ordering::Relaxed);
+
+        let bin_hz = self.sample_rate / FFT_SIZE as f32;
+        let freeze = self.params.freeze.value();
+        let mut frame = FrameParams {
+            harmonics: self.params.harmonics.value(),
+            shift_mode: self.params.shift_mode.value(),
+            shift: self.params.shift.value(),
+            shift_bins: self.params.shift_hz.value() / bin_hz,
+            blur: self.params.blur.value(),
+            blur_low_bin: if self.params.bass_mono.value() {
+                (self.params.bass_mono_freq.value() / bin_hz).ceil() as usize
+            } else {
+                0
+            },
+            blur_refresh: false,
+            blur_noise: self.params.blur_noise.value(),
+            harmony_source: self.params.harmony_source.value(),
+            low_bin: (self.params.low_cut.value() / bin_hz).floor() as usize,
+            high_bin: (self.params.high_cut.value() / bin_hz).ceil() as usize,
+            tonal_split: self.params.tonal_split.value(),
+            tonal_threshold: util::db_to_gain(self.params.tonality.value()),
+            harmony_peaks: (self.params.harmony_bins.value() == HarmonyBins::Peaks)
+                .then(|| self.params.peak_count.value() as usize),
+            scale_mask: self.params.scale.value().mask(
+                self.params.key.value(),
+                self.params.custom_scale.load(Ordering::Relaxed),
+            ),
+            fundamental: self.fundamental,
+            freeze,
+            freeze_tracking: freeze_tracking(
+                self.params.freeze_amount.value(),
+                HOP_SIZE as f32 / self.sample_rate,
+            ),
+            shimmer: self.params.shimmer.value(),
+            shimmer_level: self.params.shimmer_level.value(),
+            drift: DriftFrame::default(),
+            bank: BankFrame::default(),
+            preserve_envelope: self.params.preserve_envelope.value(),
+            harmony_delay: (self.params.pre_delay.value() / 1000.0 * self.sample_rate
+                / HOP_SIZE as f32)
+                .round() as usize,
+            decimate: self.params.decimate.value() as usize,
+            decimate_mode: self.params.decimate_mode.value(),
+            rotate: self.params.rotate.value() as isize,
+            mirror: self.params.invert.value().then(|| MirrorSettings {
+                axis: (2.0 * self.params.invert_center.value() / bin_hz).round() as usize,
+                wet: self.params.invert_wet.value(),
+            }),
+            average: SpectralAverage::keep(
+                self.params.average.value(),
+                HOP_SIZE as f32 / self.sample_rate,
+            ),
+            tame: SpectralTamer::ceiling(self.params.tame.value()),
+            bend: 1.0,
+            chord: Chord::SINGLE,
+            unison: Unison::OFF,
+            gate: self.params.gate.value().then(|| GateSettings {
+                threshold: util::db_to_gain(self.params.gate_threshold.value()),
+                reduction: util::db_to_gain(self.params.gate_reduction.value()),
+                release: (-(HOP_SIZE as f32) / (spectral_gate::RELEASE_TIME * self.sample_rate))
+                    .exp(),
+            }),
+        };
+        let rotate_depth = self.params.rotate_mod_depth.value() as f32;
+        let rotate_step = self.params.rotate_mod_rate.value() * HOP_SIZE as f32 / self.sample_rate;
+        let swing = self.params.swing.value() as f64;
+        let humanize = self.params.humanize.value() / 1000.0 * self.sample_rate;
+        let blur_hold = self.params.blur_hold.value();
+        let blur_sync = self.params.blur_sync.value();
+        let beats_per_sample = clock.tempo / 60.0 / self.sample_rate as f64;
+        // Stopped, a synced hold keeps its length in frames at the current tempo
+        let blur_hold_frames = if blur_sync {
+            (blur_hold as f64 / 4.0 / beats_per_sample / HOP_SIZE as f64) as f32
+        } else {
+            blur_hold as f32
+        };
+        let freeze_trigger = self.params.freeze_trigger.value();
+        let trigger_sensitivity = self.params.trigger_sensitivity.value();
+        let trigger_hold = self.params.trigger_hold.value() / 1000.0 * self.sample_rate;
+        self.sidechain_detector.set_holdoff(trigger_hold as usize);
+        let sidechain_grains = self.params.sidechain_grains.value();
+        let sidechain_blur = self.params.sidechain_blur.value();
+        let bloom_steps = Bloom::steps(
+            self.params.bloom_attack.value() / 1000.0,
+            self.params.bloom_release.value() / 1000.0,
+            self.sample_rate,
+        );
+        let slot_notes = self.params.slot_notes.value();
+        let slot_base_note = self.params.slot_base_note.value();
+        let macro_notes = self.params.macro_notes.value();
+        let macro_base_note = self.params.macro_base_note.value();
+        let slot_fade_step =
+            HOP_SIZE as f32 / (self.params.slot_fade.value() / 1000.0 * self.sample_rate);
+        let launched = self.params.slot_launch.swap(0, Ordering::AcqRel);
+        let erased = self.params.slot_erase.swap(0, Ordering::AcqRel);
+        for slot in 0..NUM_SLOTS {
+            if erased & (1 << slot) != 0 {
+                self.slot_control.erase(slot);
+            }
+            if launched & (1 << slot) != 0 {
+                self.slot_control.launch(slot);
+            }
+        }
+        let mut next_event = midi.next_event();
+        let pad = self.params.input_pad.value().gain();
+        self.bypass_fade
+            .set_target(self.sample_rate, self.bypass_target());
+        self.input_mute_fade
+            .set_target(self.sample_rate, self.input_mute_target());
+        let grain_feedback = self.params.grain_feedback.value();
+        let feedback_polarity = if self.params.delay_invert.value() {
+            -1.0
+        } else {
+            1.0
+        };
+        self.delay_mod_depth
+            .set_target(self.sample_rate, self.delay_mod_target());
+        let delay_mod_shape = self.params.delay_mod_shape.value();
+        let delay_mod_step = self.params.delay_mod_rate.value() / self.sample_rate;
+        let mut grain_voices = self.params.grain_voices.value() as usize;
+        if self.cpu_guarded() {
+            grain_voices = self.cpu_guard.voices(grain_voices);
+        }
+        let grain_shape = self.params.grain_shape.value();
+        let grain_normalize = self.params.grain_normalize.value();
+        let jitter_distribution = self.params.jitter_distribution.value();
+        let jitter_bias = self.params.jitter_bias.value();
+        let grain_chord = self.params.grain_chord.value();
+        let chord_weight = self.params.chord_weight.value();
+        // Stereo has no back to place grains at
+        let surround_spread = if self.surround.is_some() {
+            self.params.surround_spread.value()
+        } else {
+            0.0
+        };
+        let delay_hold = self.params.delay_hold.value();
+        let grain_reverse = self.params.grain_reverse.value();
+        let tape_stop = self.params.tape_stop.value();
+        let tape_stop_time = self.params.tape_stop_time.value() / 1000.0;
+        let stretch = self.params.stretch.value();
+        let delay_wow = self.params.delay_wow.value();
+        let delay_flutter = self.params.delay_flutter.value();
+        let delay_saturation = self.params.delay_saturation.value();
+        let delay_cross = self.params.delay_cross.value();
+        let grain_source = self.params.grain_source.value();
+        let harmony_pan = self.params.harmony_pan.value();
+        let voicings = self.params.harmony_voices.each_ref().map(HarmonyVoiceParams::voicing);
+        let dry_low_cut = self.params.dry_low_cut.value();
+        let dry_tilt = self.params.dry_tilt.value();
+        let pre_emphasis = self.params.pre_emphasis.value();
+        for state in self.channels.iter_mut() {
+            state.dry_tone.set(dry_low_cut, dry_tilt, self.sample_rate);
+        }
+        let window = self.params.window.value();
+        for engine in std::iter::once(&mut self.engine).chain(&mut self.incoming) {
+            engine.window_glide.set_shape(window, &engine.window);
+            for stft in engine.channels.iter_mut() {
+                stft.emphasis.set(pre_emphasis, self.sample_rate);
+            }
+        }
+        let unison = self.params.unison.value() as usize;
+        let unison_width = self.params.unison_width.value();
+        // Stopped, grains fall back to their steady rate
+        let grain_sync = self.params.grain_sync.value() && clock.playing;
+        if !grain_sync {
+            self.grain_grid.reset();
+        }
+        let grain_step = self.params.grain_division.value().beats();
+        // Stopped, the pattern rewinds and nothing fires
+        let euclid = if clock.playing {
+            self.params.euclid.value()
+        } else {
+            EuclidTarget::Off
+        };
+        if euclid == EuclidTarget::Off {
+            self.euclid.reset();
+            self.meters.euclid_step.store(-1, Ordering::Relaxed);
+        }
+        let euclid_pattern = EuclidPattern {
+            steps: self.params.euclid_steps.value() as usize,
+            fills: self.params.euclid_fills.value() as usize,
+            rotation: self.params.euclid_rotation.value() as usize,
+        };
+        let euclid_step = self.params.euclid_division.value().beats();
+        let stutter_samples = (euclid_step * 0.5 / beats_per_sample) as usize;
+        let midi_grains = self.params.midi_grains.value();
+        let midi_root = self.params.midi_root.value() as u8;
+        let bend_range = self.params.bend_range.value() as f32;
+        let drift = self.params.drift.value();
+        let grain_velocity = self.params.grain_velocity.value();
+        let grain_key_track = self.params.grain_key_track.value();
+        let grain_filter = self
+            .params
+            .grain_filter
+            .value()
+            .then(|| GrainFilterSettings {
+                filter_type: self.params.grain_filter_type.value(),
+                cutoff: self.params.grain_cutoff.value(),
+                spread: self.params.grain_cutoff_spread.value(),
+            });
+        for state in self.channels.iter_mut() {
+            state.grain_delay.set_voices(grain_voices);
+            state.grain_delay.set_normalize(grain_normalize);
+            state.grain_delay.set_shape(grain_shape);
+            state
+                .grain_delay
+                .set_jitter(jitter_distribution, jitter_bias);
+            state.grain_delay.set_chord(grain_chord, chord_weight);
+            state.grain_delay.set_spread(surround_spread);
+            state.grain_delay.set_filter(grain_filter);
+            state.grain_delay.set_synced(
+                grain_sync || midi_grains || euclid == EuclidTarget::Grains || sidechain_grains,
+            );
+            state.grain_delay.set_hold(delay_hold);
+            state.grain_delay.set_stretch(stretch);
+        }
+        self.meters
+            .grain_voices
+            .store(grain_voices as u8, Ordering::Relaxed);
+        self.window_glide.set_shape(window, &self.window);
+        let ducking = self.params.sidechain_duck.value();
+        let sidechain_listen = self.params.sidechain_listen.value();
+        let duck_amount = self.params.duck_amount.value();
+        let duck_release = (-(HOP_SIZE as f32)
+            / (self.params.duck_release.value() / 1000.0 * self.sample_rate))
+            .exp();
+        // Nobody looks at the analyzer while the editor is closed
+        let analyzer_tap = self
+            .params
+            .editor_open()
+            .then(|| AnalyzerTap::from_index(self.params.analyzer_tap.load(Ordering::Relaxed)));
+        if analyzer_tap.is_some() {
+            let window_ms = self.params.scope_window.load(Ordering::Relaxed);
+            self.analyzer.set_scope_window(window_ms, self.sample_rate);
+        }
+        let tp_limit = self.params.tp_limit.value();
+        if tp_limit != self.limiter_active {
+            // Starts from silence, the host realigns to the new latency anyway
+            self.limiter_active = tp_limit;
+            self.register_limiter();
+            self.limiter.reset();
+        }
+        let tp_ceiling = self.params.tp_ceiling.value();
+        let bass_mono = self.params.bass_mono.value();
+        if bass_mono {
+            self.bass_mono
+                .set_crossover(self.params.bass_mono_freq.value());
+        } else {
+            // Starts from silence when switched back on
+            self.bass_mono.reset();
+        }
+        let metering = self.params.editor_open();
+        let midi_out = self.params.midi_out.value();
+        if !midi_out {
+            self.send_midi_notes([None; 2], 0.0, 0, midi);
+        }
+
+        if self.params.smoothing_changed.swap(false, Ordering::AcqRel) {
+            match self.params.smoothing.try_read() {
+                Ok(times) => {
+                    for param in Smoothed::ALL {
+                        self.smoothers[param as usize].style = times.style(param);
+                    }
+                }
+                Err(_) => self.params.smoothing_changed.store(true, Ordering::Release),
+            }
+        }
+        // With sample-accurate automation this runs at the exact sample a parameter changed, so
+        // ramps start there instead of at the next host buffer
+        self.sync_morph_endpoints();
+        self.sync_xy_axes();
+        self.sync_motion();
+        let motion_length = self.params.motion_steps.value() as usize;
+        let motion_step = self.params.motion_division.value().beats();
+        let motion_record = self.params.motion_record.value();
+        // Stopped or recording, the parameter goes back to its own value
+        let motion = self.params.motion.value() && clock.playing;
+        if !motion || motion_record {
+            self.motion_value = None;
+        }
+        if !motion {
+            self.motion.reset();
+            self.meters.motion_step.store(-1, Ordering::Relaxed);
+        }
+        for param in Smoothed::ALL {
+            let target = self.smoothed_target(param);
+            self.smoothers[param as usize].set_target(self.sample_rate, target);
+        }
+
+        if self.params.eq_curve_changed.swap(false, Ordering::AcqRel) {
+            match self.params.eq_curve.try_read() {
+                Ok(curve) => curve.fill_bin_gains(&mut self.eq_gains, FFT_SIZE, self.sample_rate),
+                // The editor is holding the lock, try again next block
+                Err(_) => self.params.eq_curve_changed.store(true, Ordering::Release),
+            }
+        }
+
+        if self.params.gate_profile_changed.swap(false, Ordering::AcqRel) {
+            match self.params.gate_profile.try_read() {
+                Ok(profile) => {
+                    for engine in std::iter::once(&mut self.engine).chain(&mut self.incoming) {
+                        for (idx, stft) in engine.channels.iter_mut().enumerate() {
+                            stft.gate.set_floor(profile.floor(idx));
+                        }
+                    }
+                }
+                Err(_) => self.params.gate_profile_changed.store(true, Ordering::Release),
+            }
+        }
+        if self.params.freeze_snapshot_changed.swap(false, Ordering::AcqRel) {
+            match self.params.freeze_snapshot.try_read() {
+                Ok(snapshot) if self.params.save_freeze.value() => {
+                    for engine in std::iter::once(&mut self.engine).chain(&mut self.incoming) {
+                        for (idx, stft) in engine.channels.iter_mut().enumerate() {
+                            if snapshot.restore(idx, &mut stft.frozen_mags) {
+                                stft.has_capture = true;
+                                stft.capture_pending = false;
+                            }
+                        }
+                    }
+                }
+                Ok(_) => {}
+                Err(_) => self.params.freeze_snapshot_changed.store(true, Ordering::Release),
+            }
+        }
+        if self.params.gate_learn.swap(false, Ordering::AcqRel) {
+            for engine in std::iter::once(&mut self.engine).chain(&mut self.incoming) {
+                let frames =
+                    (spectral_gate::LEARN_TIME * self.sample_rate / engine.hop() as f32) as usize;
+                for stft in engine.channels.iter_mut() {
+                    stft.gate.start_learning(frames);
+                }
+            }
+            self.gate_learning = true;
+        }
+
+        let num_samples = buffer.samples();
+        let channels = buffer.as_slice();
+        // The analysis listens to the input channels only, the rest of a surround layout is
+        // output only
+        let num_inputs = self.channels.len().min(channels.len());
+        let num_channels = num_inputs as f32;
+        let mut segment_start = 0;
+        while segment_start < num_samples {
+            // Segments end on analysis hops, which is also where every channel renders its next
+            // frame, so the per-sample work below runs in tight per-channel loops
+            let mut len = (HOP_SIZE - self.analysis_counter).min(num_samples - segment_start);
+
+            self.segment.grain_spawn[..len].fill(None);
+            // A capture trigger only matters to the next frame, which at the earliest renders on
+            // the segment's last sample
+            let mut triggered = false;
+            while let Some(event) = next_event {
+                // Segments also end right before events, so a learned CC retargets its parameter
+                // at the exact sample it arrives on, like host automation does
+                if event.timing() as usize > segment_start {
+                    len = len.min(event.timing() as usize - segment_start);
+                    break;
+                }
+                match event {
+                    NoteEvent::NoteOn {
+                        timing,
+                        note,
+                        velocity,
+                        ..
+                    } => {
+                        let slot = note as i32 - slot_base_note;
+                        let key = note as i32 - macro_base_note;
+                        if macro_notes && (0..2).contains(&key) {
+                            self.macro_keys[key as usize] = true;
+                        } else if slot_notes && (0..NUM_SLOTS as i32).contains(&slot) {
+                            self.slot_control.launch(slot as usize);
+                        } else {
+                            triggered |= freeze_trigger == FreezeTrigger::MidiNote;
+                            self.held_notes.press(note);
+                            if midi_grains {
+                                let idx = (timing as usize).saturating_sub(segment_start);
+                                self.segment.grain_spawn[idx] = Some(GrainNote::from_midi(
+                                    note,
+                                    velocity,
+                                    grain_velocity,
+                                    grain_key_track,
+                                ));
+                            }
+                        }
+                    }
+                    NoteEvent::NoteOff { note, .. } => {
+                        let key = note as i32 - macro_base_note;
+                        if macro_notes && (0..2).contains(&key) {
+                            self.macro_keys[key as usize] = false;
+                        } else {
+                            self.held_notes.release(note);
+                        }
+                    }
+                    NoteEvent::MidiPitchBend { value, .. } => self.pitch_bend = value * 2.0 - 1.0,
+                    // The sustain pedal
+                    NoteEvent::MidiCC { cc: 64, value, .. } => {
+                        self.held_notes.set_pedal(value >= 0.5)
+                    }
+                    NoteEvent::MidiCC { cc, value, .. } => self.handle_cc(cc, value),
+                    _ => (),
+                }
+                next_event = midi.next_event();
+            }
+            let segment = segment_start..segment_start + len;
+            let last_idx = segment.end - 1;
+
+            for sample_idx in segment.clone() {
+                let sidechain_level = sidechain.map_or(0.0, |sidechain| {
+                    sidechain.iter().map(|ch| ch[sample_idx]).sum::<f32>()
+                        / sidechain.len().max(1) as f32
+                });
+                self.ducker.push(sidechain_level);
+                self.segment.sidechain[sample_idx - segment_start] = sidechain_level;
+                let onset = sidechain.is_some()
+                    && self
+                        .sidechain_detector
+                        .process(sidechain_level, trigger_sensitivity);
+                triggered |= onset && freeze_trigger == FreezeTrigger::Sidechain;
+                self.blur_triggered |= onset && sidechain_blur;
+                self.segment.trigger_spawn[sample_idx - segment_start] = onset && sidechain_grains;
+
+                let mono = channels[..num_inputs]
+                    .iter()
+                    .map(|ch| ch[sample_idx] * pad)
+                    .sum::<f32>()
+                    / num_channels;
+                // The ring is as long as the latency, so its oldest sample lines up with the wet
+                let aligned = self.analysis_ring.pop_front().unwrap_or(0.0);
+                self.analysis_ring.push_back(mono);
+                self.pitch_detector.push(mono);
+                self.segment.bloom[sample_idx - segment_start] =
+                    self.bloom.process(aligned, bloom_steps);
+                if analyzer_tap == Some(AnalyzerTap::Input) {
+                    self.segment.tap[sample_idx - segment_start] = mono;
+                }
+            }
+            if euclid != EuclidTarget::Off {
+                for i in 0..len {
+                    let beats = clock.pos_beats + (segment_start + i) as f64 * beats_per_sample;
+                    let hit = self
+                        .euclid
+                        .tick(beats, euclid_step, swing, humanize, euclid_pattern);
+                    self.segment.trigger_spawn[i] |=
+                        euclid == EuclidTarget::Grains && self.euclid.burst(hit);
+                    triggered |= hit && euclid == EuclidTarget::Freeze;
+                    if hit && euclid == EuclidTarget::Stutter {
+                        self.euclid.start_stutter(stutter_samples);
+                    }
+                }
+                self.meters.euclid_step.store(
+                    self.euclid.step().map_or(-1, |step| step as i8),
+                    Ordering::Relaxed,
+                );
+            }
+            if triggered {
+                for engine in std::iter::once(&mut self.engine).chain(&mut self.incoming) {
+                    for stft in engine.channels.iter_mut() {
+                        stft.capture_pending = true;
+                    }
+                }
+            }
+
+            if motion {
+                let beats = clock.pos_beats + segment_start as f64 * beats_per_sample;
+                self.advance_motion(beats, motion_step, motion_length, motion_record);
+            }
+
+            // Spectral settings track their smoothers so a frame always sees its end-of-hop
+            // values, the crossfade in `process_sample` covers the start of the frame
+            frame.harmonics = self.advance_smoothed(Smoothed::Harmonics, len);
+            if frame.shift_mode == ShiftMode::Midi {
+                frame.chord = self.held_notes.chord(midi_root);
+            }
+            self.drift.advance(len as f32 / self.sample_rate);
+            frame.bend = (self.pitch_bend * bend_range / 12.0).exp2() * self.drift.ratio(drift);
+            frame.drift = self.drift.frame(drift);
+            // With notes capturing the freeze, the sustain pedal holds it like the Freeze switch
+            frame.freeze = freeze
+                || freeze_trigger == FreezeTrigger::MidiNote && self.held_notes.sustained()
+                || self.euclid.stutter(len);
+            frame.shift = self.advance_smoothed(Smoothed::Shift, len);
+            frame.shift_bins = self.advance_smoothed(Smoothed::ShiftHz, len) / bin_hz;
+            frame.blur = self.advance_smoothed(Smoothed::Blur, len);
+            let values = &mut self.segment;
+            self.smoothers[Smoothed::Mix as usize].next_block(&mut values.mix, len);
+            self.smoothers[Smoothed::OutGain as usize].next_block(&mut values.gain, len);
+            self.smoothers[Smoothed::DelayFeedback as usize]
+                .next_block(&mut values.delay_feedback, len);
+            self.smoothers[Smoothed::DelayTime as usize].next_block(&mut values.scratch, len);
+            self.bypass_fade.next_block(&mut values.bypass, len);
+            self.input_mute_fade.next_block(&mut values.input_mute, len);
+            if self.incoming.is_some() {
+                let step = 1.0 / (FADE_TIME * self.sample_rate);
+                for fade in values.engine_fade[..len].iter_mut() {
+                    self.engine_fade = (self.engine_fade + step).min(1.0);
+                    *fade = self.engine_fade.max(0.0);
+                }
+            }
+            let delay_ms = &values.scratch[..len];
+            for (delay_samples, delay_ms) in values.delay_samples.iter_mut().zip(delay_ms) {
+                // The loop runs through the spectral processor, so its latency is part of the
+                // echo time
+                *delay_samples = ((delay_ms / 1000.0 * self.sample_rate) as usize)
+                    .saturating_sub(LATENCY)
+                    .max(1);
+            }
+            self.delay_mod_depth.next_block(&mut values.scratch, len);
+            for (delay_mod, depth) in values.delay_mod.iter_mut().zip(&values.scratch[..len]) {
+                *delay_mod = depth * self.delay_lfo.next(delay_mod_shape, delay_mod_step)
+                    + self
+                        .tape_wobble
+                        .next(delay_wow, delay_flutter, self.sample_rate);
+            }
+            // Notes take over from the grid, their grains are already in place
+            if grain_sync && !midi_grains {
+                for (i, spawn) in values.grain_spawn[..len].iter_mut().enumerate() {
+                    let beats = clock.pos_beats + (segment_start + i) as f64 * beats_per_sample;
+                    *spawn = self
+                        .grain_grid
+                        .tick(beats, grain_step, swing, humanize)
+                        .then_some(GrainNote::PLAIN);
+                }
+            }
+            if euclid == EuclidTarget::Grains || sidechain_grains {
+                for (spawn, hit) in values.grain_spawn[..len]
+                    .iter_mut()
+                    .zip(&values.trigger_spawn)
+                {
+                    if *hit {
+                        *spawn = Some(GrainNote::PLAIN);
+                    }
+                }
+            }
+
+            self.analysis_counter += len;
+            if self.analysis_counter >= HOP_SIZE {
+                self.analysis_counter = 0;
+                // Every channel renders its next frame at the end of this segment
+                if self.window_glide.advance(&mut self.window, self.sample_rate) {
+                    self.meters
+                        .cola_ripple
+                        .store(cola_ripple(&self.window, HOP_SIZE), Ordering::Relaxed);
+                }
+                for engine in std::iter::once(&mut self.engine).chain(&mut self.incoming) {
+                    engine
+                        .window_glide
+                        .advance(&mut engine.window, self.sample_rate);
+                }
+                self.fundamental = self.pitch_detector.detect();
+                frame.fundamental = self.fundamental;
+                frame.bank = self.slot_control.next_frame(slot_fade_step);
+                frame.blur_refresh = if blur_sync && clock.playing {
+                    let beats = clock.pos_beats + last_idx as f64 * beats_per_sample;
+                    let length = blur_hold as f64 / 4.0;
+                    let humanize = humanize / HOP_SIZE as f32;
+                    self.blur_hold.next_frame_at(beats, length, swing, humanize)
+                } else {
+                    self.blur_hold.next_frame(blur_hold_frames)
+                };
+                frame.blur_refresh |= std::mem::take(&mut self.blur_triggered);
+                let sweep = rotate_depth * self.rotate_lfo.next(LfoShape::Sine, rotate_step);
+                frame.rotate = self.params.rotate.value() as isize + sweep.round() as isize;
+                self.meters
+                    .slots_filled
+                    .store(self.slot_control.filled(), Ordering::Relaxed);
+                self.meters.active_slot.store(
+                    self.slot_control.active().map_or(-1, |slot| slot as i8),
+                    Ordering::Relaxed,
+                );
+
+                // Runs on the same hop grid as the channels, so every frame sees fresh gains
+                if ducking {
+                    self.ducker.update(
+                        self.forward_fft.as_ref(),
+                        &self.window,
+                        duck_release,
+                        duck_amount,
+                    );
+                    for ((gain, eq), duck) in self
+                        .bin_gains
+                        .iter_mut()
+                        .zip(&self.eq_gains)
+                        .zip(self.ducker.gains())
+                    {
+                        *gain = eq * duck;
+                    }
+                } else {
+                    self.bin_gains.copy_from_slice(&self.eq_gains);
+                }
+                for engine in std::iter::once(&mut self.engine).chain(&mut self.incoming) {
+                    engine.set_bin_gains(&self.bin_gains);
+                }
+                self.meters
+                    .pitch
+                    .store(self.fundamental.unwrap_or(0.0), Ordering::Relaxed);
+
+                if midi_out {
+                    let notes = self.fundamental.map_or([None; 2], |f0| {
+                        let map = frame.bin_map();
+                        let harmony_freq = f0 * map.ratio + map.offset * bin_hz;
+                        let harmony =
+                            (frame.harmonics > 0.01).then(|| freq_to_midi_note(harmony_freq));
+                        [freq_to_midi_note(f0), harmony.flatten()]
+                    });
+                    // Map -60..0 dBFS frame energy onto the velocity range
+                    let level_db = 10.0 * self.pitch_detector.energy().max(1e-12).log10();
+                    let velocity = ((level_db + 60.0) / 60.0).clamp(0.05, 1.0);
+                    self.send_midi_notes(notes, velocity, last_idx as u32, midi);
+                }
+            }
+
+            let values = &mut self.segment;
+            if matches!(analyzer_tap, Some(AnalyzerTap::Wet | AnalyzerTap::Output)) {
+                values.tap[..len].fill(0.0);
+            }
+            let mut clipped = false;
+            let surround = self.surround;
+            let stereo = channels.len() == 2 || surround.is_some();
+            // Mono has nothing to cross over to
+            let cross = if stereo { delay_cross } else { 0.0 };
+            let cross_len = self.cross_returns[0].len();
+            let harmony_gains = if stereo {
+                balance(harmony_pan)
+            } else {
+                [1.0; 2]
+            };
+            // The macro notes hold the switches as long as they are down
+            let reverse = grain_reverse || self.macro_keys[0];
+            let stopped = tape_stop || self.macro_keys[1];
+            for (idx, (state, channel)) in
+                self.channels.iter_mut().zip(channels.iter_mut()).enumerate()
+            {
+                let frame = FrameParams {
+                    harmonics: frame.harmonics * harmony_gains[idx],
+                    chord: frame.chord.voiced(&voicings, stereo.then_some(idx)),
+                    unison: Unison::new(unison, unison_width, stereo.then_some(idx)),
+                    ..frame
+                };
+                // A mono sidechain feeds both channels' grains, a missing one records silence
+                let grain_sidechain = match grain_source {
+                    GrainSource::Main => None,
+                    GrainSource::Sidechain => Some(
+                        sidechain.and_then(|sidechain| sidechain.get(idx).or(sidechain.last())),
+                    ),
+                };
+                state.grain_delay.set_reverse(reverse);
+                state.grain_delay.set_tape_stop(stopped, tape_stop_time);
+                for (i, sample) in channel[segment.clone()].iter_mut().enumerate() {
+                    let mut input = *sample * pad;
+                    // The held loop returns at full feedback, even with the loop otherwise off
+                    let hold = state.grain_delay.hold_level();
+                    if grain_feedback || hold > 0.0 {
+                        let feedback = if grain_feedback {
+                            values.delay_feedback[i]
+                        } else {
+                            0.0
+                        };
+                        // The other channel's return from a hop ago, which it wrote before
+                        // this segment no matter which channel runs first
+                        let crossed_pos = (self.cross_pos + i + HOP_SIZE) % cross_len;
+                        let crossed = self.cross_returns[1 - idx][crossed_pos];
+                        let returned = state.grain_return * (1.0 - cross) + crossed * cross;
+                        input +=
+                            returned * (feedback + (1.0 - feedback) * hold) * feedback_polarity;
+                    }
+
+                    let mut wet = Self::process_sample(&mut self.engine, idx, input, &frame);
+                    // Both sizes share the latency, so a plain crossfade lines them up
+                    if let Some(incoming) = self.incoming.as_mut() {
+                        let new = Self::process_sample(incoming, idx, input, &frame);
+                        let t = values.engine_fade[i];
+                        wet = wet * (1.0 - t) + new * t;
+                    }
+                    clipped |= wet.abs() > CLIP_LEVEL;
+                    let final_wet = wet.tanh();
+                    if analyzer_tap == Some(AnalyzerTap::Wet) {
+                        values.tap[i] += final_wet / num_channels;
+                    }
+                    if let Some(note) = values.grain_spawn[i] {
+                        state.grain_delay.trigger(note);
+                    }
+                    let recorded = match grain_sidechain {
+                        None => final_wet,
+                        Some(input) => input.map_or(0.0, |input| input[segment.start + i]),
+                    };
+                    // Keep the delay line running while the loop is off so enabling it does not
+                    // replay stale audio
+                    let delayed = state.grain_delay.process(
+                        recorded,
+                        values.delay_samples[i],
+                        values.delay_mod[i],
+                    );
+                    state.grain_return = tape::saturate(delayed, delay_saturation);
+                    self.cross_returns[idx][(self.cross_pos + i) % cross_len] = state.grain_return;
+                    // The delay holds the unpadded input so bypass passes the signal through as is
+                    state.dry_delay.push_back(*sample);
+                    let dry = state.dry_delay.pop_front().unwrap_or(0.0);
+                    let toned = state.dry_tone.process(dry) * (1.0 - values.input_mute[i]);
+                    let (mix, gain, bypass) = (values.mix[i], values.gain[i], values.bypass[i]);
+                    if surround.is_some() {
+                        // The cloud is as loud at the back as it is fed back at the front
+                        let feedback = if grain_feedback {
+                            values.delay_feedback[i]
+                        } else {
+                            0.0
+                        };
+                        let send = if sidechain_listen {
+                            0.0
+                        } else {
+                            (feedback + (1.0 - feedback) * hold) * mix * gain
+                        };
+                        values.rear[idx][i] =
+                            state.grain_delay.rear() * send * values.bloom[i] * (1.0 - bypass);
+                    }
+                    // Listening monitors the detector input as is, without waiting for the
+                    // processing latency
+                    let output = if sidechain_listen {
+                        values.sidechain[i]
+                    } else {
+                        (toned * pad * (1.0 - mix) + final_wet * values.bloom[i] * mix) * gain
+                    };
+
+                    // Written so that either end of the fade is exact: a fully bypassed plugin
+                    // nulls against the delayed input
+                    *sample = output * (1.0 - bypass) + dry * bypass;
+                }
+            }
+            if clipped {
+                self.meters.clip.store(true, Ordering::Relaxed);
+            }
+            self.cross_pos = (self.cross_pos + len) % cross_len;
+            if let Some(surround) = surround {
+                for (rear, idx) in self.segment.rear.iter().zip(surround.rear()) {
+                    if let Some(channel) = channels.get_mut(idx) {
+                        channel[segment.clone()].copy_from_slice(&rear[..len]);
+                    }
+                }
+                for idx in surround.silent() {
+                    if let Some(channel) = channels.get_mut(idx) {
+                        channel[segment.clone()].fill(0.0);
+                    }
+                }
+            }
+
+            if bass_mono {
+                // Only the front pair of a surround layout is stereo
+                let front = if surround.is_some() {
+                    channels.len().min(2)
+                } else {
+                    channels.len()
+                };
+                self.bass_mono.process(
+                    &mut channels[..front],
+                    segment.clone(),
+                    &self.segment.bypass[..len],
+                );
+            }
+            if self.limiter_active {
+                self.limiter.process(
+                    channels,
+                    segment.clone(),
+                    tp_ceiling,
+                    &self.segment.bypass[..len],
+                );
+            }
+            if metering {
+                let peak = self.tp_meter.measure(channels, segment.clone());
+                if peak > self.meters.true_peak.load(Ordering::Relaxed) {
+                    self.meters.true_peak.store(peak, Ordering::Relaxed);
+                }
+            }
+            if analyzer_tap == Some(AnalyzerTap::Output) {
+                for channel in channels[..num_inputs].iter() {
+                    let samples = &channel[segment.clone()];
+                    for (tap, sample) in self.segment.tap.iter_mut().zip(samples) {
+                        *tap += sample / num_channels;
+                    }
+                }
+            }
+
+            if let Some(tap) = analyzer_tap {
+                self.analyzer.push(&self.segment.tap[..len]);
+                if self.analysis_counter == 0 {
+                    self.analyzer.publish(
+                        self.forward_fft.as_ref(),
+                        &self.window,
+                        tap,
+                        self.sample_rate,
+                        &self.meters.analyzer,
+                    );
+                }
+            }
+
+            segment_start = segment.end;
+        }
+
+        let stfts = &self.engine.channels;
+        if self.gate_learning && !stfts.iter().any(|stft| stft.gate.is_learning()) {
+            // Saved with the plugin state, if the editor holds the lock try again next block
+            if let Ok(mut profile) = self.params.gate_profile.try_write() {
+                profile.store(
+                    self.engine.fft_size / 2,
+                    self.sample_rate,
+                    stfts.iter().map(|stft| stft.gate.floor()),
+                );
+                self.gate_learning = false;
+            }
+        }
+        let save_freeze = self.params.save_freeze.value();
+        if save_freeze && self.params.freeze.value() && stfts.iter().all(|stft| stft.has_capture) {
+            // Follows a tracking freeze as it moves, if the editor holds the lock try again next
+            // block
+            if let Ok(mut snapshot) = self.params.freeze_snapshot.try_write() {
+                snapshot.store(
+                    self.engine.fft_size / 2,
+                    self.sample_rate,
+                    stfts.iter().map(|stft| &stft.frozen_mags[..]),
+                );
+                self.freeze_saved = true;
+            }
+        } else if !save_freeze && self.freeze_saved {
+            if let Ok(mut snapshot) = self.params.freeze_snapshot.try_write() {
+                snapshot.clear();
+                self.freeze_saved = false;
+            }
+        }
+        self.meters
+            .gate_learning
+            .store(self.gate_learning, Ordering::Relaxed);
+        self.meters.xy_learning.store(
+            self.xy_learning.map_or(-1, |axis| axis as i8),
+            Ordering::Relaxed,
+        );
+        for (axis, position) in self.meters.xy_cc.iter().enumerate() {
+            let value = self.cc_values[self.xy_params[axis] as usize];
+            position.store(value.map_or(-1.0, |(value, _)| value), Ordering::Relaxed);
+        }
+    }
+
+    /// Swaps the analysed spectrum for the frozen one, capturing it first if needed. Every bin
+    /// keeps rotating at its centre frequency so the drone does not sound static.
+    fn play_frozen(state: &mut Stft, tracking: f32) {
+        let half = state.frozen_mags.len();
+        if !state.has_capture || state.capture_pending {
+            for i in 0..half {
+                state.frozen_mags[i] = state.scratch_in[i].norm();
+                state.frozen_phases[i] = state.scratch_in[i].arg();
+            }
+            state.has_capture = true;
+            state.capture_pending = false;
+        } else if tracking > 0.0 {
+            for (frozen, live) in state.frozen_mags.iter_mut().zip(&state.scratch_in[..half]) {
+                *frozen += (live.norm() - *frozen) * tracking;
+            }
+        }
+
+        for i in 0..half {
+            let phase = (state.frozen_phases[i] + HOP_PHASE_STEP * i as f32) % (2.0 * PI);
+            state.frozen_phases[i] = phase;
+            state.scratch_in[i] = Complex::from_polar(state.frozen_mags[i], phase);
+        }
+    }
+
+    /// Moves the MIDI output to `notes`, releasing any held note that changed.
+    fn send_midi_notes(
+        &mut self,
+        notes: [Option<u8>; 2],
+        velocity: f32,
+        timing: u32,
+        midi: &mut impl NoteIo<Self>,
+    ) {
+        for (held, new) in self.midi_notes.iter_mut().zip(notes) {
+            if *held == new {
+                continue;
+            }
+            if let Some(note) = held.take() {
+                midi.send_event(NoteEvent::NoteOff {
+                    timing,
+                    voice_id: None,
+                    channel: 0,
+                    note,
+                    velocity: 0.0,
+                });
+            }
+            if let Some(note) = new {
+                midi.send_event(NoteEvent::NoteOn {
+                    timing,
+                    voice_id: None,
+                    channel: 0,
+                    note,
+                    velocity,
+                });
+                *held = Some(note);
+            }
+        }
+    }
+
+    /// Fills `state.analysis.process_mask` from the Low/High Cut band and the tonal/noisy split,
+    /// and `harmony_mask` from its strongest peaks.
+    fn select_bins(state: &mut Stft, frame: &FrameParams) {
+        let half = state.scratch_in.len() / 2;
+        let spectrum = &state.scratch_in[..half];
+        let split = frame.tonal_split;
+        if split != TonalSplit::All {
+            tonality::find_tonal_bins(
+                spectrum,
+                frame.tonal_threshold,
+                &mut state.tonal_bins,
+            );
+        }
+
+        for (i, processed) in state.analysis.process_mask.iter_mut().enumerate() {
+            let in_band = i >= frame.low_bin && i <= frame.high_bin;
+            *processed = in_band
+                && match split {
+                    TonalSplit::All => true,
+                    TonalSplit::Tonal => state.tonal_bins[i],
+                    TonalSplit::Noisy => !state.tonal_bins[i],
+                };
+        }
+
+        if let Some(count) = frame.harmony_peaks {
+            let analysis = &mut state.analysis;
+            tonality::find_strongest_peaks(
+                spectrum,
+                &analysis.process_mask,
+                count,
+                &mut analysis.peaks,
+                &mut analysis.harmony_mask,
+            );
+        }
+    }
+
+    /// Resynthesizes the analysed half spectrum `input` into the first half of `output`. The
+    /// harmony voice goes to `harmony` instead when given, so it can be delayed on its own.
+    fn render_spectrum(
+        input: &[Complex<f32>],
+        output: &mut [Complex<f32>],
+        mut harmony: Option<&mut [Complex<f32>]>,
+        map: BinMap,
+        frame: &FrameParams,
+        blur_table: &BlurTable,
+        analysis: &BinAnalysis,
+    ) {
+        let FrameParams {
+            harmonics,
+            blur,
+            blur_low_bin,
+            ..
+        } = *frame;
+        for x in output.iter_mut() {
+            *x = Complex::zero();
+        }
+        if let Some(harmony) = harmony.as_deref_mut() {
+            harmony.fill(Complex::zero());
+        }
+        let half = input.len() / 2;
+
+        for i in 0..half {
+            let bin = input[i];
+            if bin.norm_sqr() < 1e-6 {
+                continue;
+            }
+
+            if !analysis.process_mask[i] {
+                output[i] += bin;
+                continue;
+            }
+
+            let mag = bin.norm();
+            let phase = bin.arg();
+
+            if blur > 0.0 && i >= blur_low_bin {
+                let new_phase = phase + (blur_table.bins[i] * 2.0 * PI * blur);
+                output[i] += Complex::from_polar(mag, new_phase);
+            } else {
+                output[i] += bin;
+            }
+
+            let harmony_bin = frame.harmony_peaks.is_none() || analysis.harmony_mask[i];
+            if harmonics > 0.01 && harmony_bin {
+                let voices = frame.chord.voices().flat_map(|(ratio, level)| {
+                    frame
+                        .unison
+                        .voices()
+                        .map(move |(detune, gain)| (ratio * detune, level * gain))
+                });
+                for (ratio, gain) in voices {
+                    let target = (map.target(i) * ratio).round();
+                    if target < 0.0 || (target as usize) >= half {
+                        continue;
+                    }
+                    let target_idx = target as usize;
+                    let mut mag_h = mag * harmonics * gain * frame.chord.gain();
+                    if frame.preserve_envelope {
+                        mag_h *= analysis.envelope.correction(i, target_idx);
+                    }
+                    let phase = phase
+                        + if frame.chord.moved() {
+                            frame.unison.moved_phase(i, target_idx)
+                        } else {
+                            frame.unison.phase(i, target_idx)
+                        };
+                    let blurred = frame.harmony_source == HarmonySource::PostBlur;
+                    let phase_h = if blurred && blur > 0.0 && target_idx >= blur_low_bin {
+                        phase + (blur_table.harmony[target_idx] * 2.0 * PI * blur)
+                    } else {
+                        phase
+                    };
+                    let voice = harmony.as_deref_mut().unwrap_or(&mut *output);
+                    voice[target_idx] += Complex::from_polar(mag_h, phase_h);
+                }
+            }
+        }
+    }
+
+    /// Adds the delayed `harmony` to the rendered `output`, takes the pre-emphasis back out and
+    /// completes it into a full spectrum, ready for the inverse FFT.
+    fn finish_spectrum(
+        output: &mut [Complex<f32>],
+        harmony: Option<&[Complex<f32>]>,
+        emphasis: &Emphasis,
+        tamer: &mut SpectralTamer,
+        tame: Option<f32>,
+        bin_gains: &[f32],
+    ) {
+        let len = output.len();
+        let half = len / 2;
+        if let Some(harmony) = harmony {
+            for (bin, voice) in output[..half].iter_mut().zip(harmony) {
+                *bin += voice;
+            }
+        }
+        emphasis.remove(&mut output[..half]);
+
+        if let Some(ceiling) = tame {
+            tamer.process(&mut output[..half], ceiling);
+        }
+
+        // Spectral EQ and ducking on the resynthesized magnitudes
+        for (bin, gain) in output[..half].iter_mut().zip(bin_gains) {
+            *bin *= *gain;
+        }
+
+        for i in 1..half {
+            output[len - i] = output[i].conj();
+        }
+    }
+
+    /// Feeds `input` to channel `channel` of `engine` and returns its wet signal.
+    fn process_sample(
+        engine: &mut Engine,
+        channel: usize,
+        input: f32,
+        frame: &FrameParams,
+    ) -> f32 {
+        let (fft_size, hop) = (engine.fft_size, engine.hop());
+        let half = fft_size / 2;
+        let forward_fft = engine.forward_fft.as_ref();
+        let inverse_fft = engine.inverse_fft.as_ref();
+        let (window, bin_gains) = (&engine.window, &engine.bin_gains);
+        let state = &mut engine.channels[channel];
+        state.input_ring.push_back(input);
+        if state.input_ring.len() > fft_size {
+            state.input_ring.pop_front();
+        }
+
+        state.hop_counter += 1;
+        if state.hop_counter >= hop && state.input_ring.len() == fft_size {
+            state.hop_counter = 0;
+            state.frames += 1;
+            let frame = &FrameParams {
+                unison: frame.unison.at_frame(state.frames),
+                ..frame.at_size(fft_size)
+            };
+            if frame.blur_refresh {
+                state.blur_seed = state.rng_state;
+            }
+            if frame.blur > 0.0 {
+                state.blur_table.update(state.blur_seed, frame.blur_noise);
+            }
+            let freeze = frame.freeze;
+
+            for i in 0..fft_size {
+                state.scratch_in[i] = Complex::new(state.input_ring[i] * window[i], 0.0);
+            }
+
+            forward_fft.process(&mut state.scratch_in);
+
+            if state.gate.is_learning() {
+                state.gate.learn(&state.scratch_in[..half]);
+            } else if let Some(gate) = frame.gate {
+                state.gate.apply(&mut state.scratch_in[..half], gate);
+            }
+
+            if freeze {
+                Self::play_frozen(state, frame.freeze_tracking);
+                state.shimmer.process(
+                    &mut state.scratch_in[..half],
+                    &state.frozen_mags,
+                    frame.shimmer,
+                    frame.shimmer_level,
+                    HOP_SIZE as f32 / FFT_SIZE as f32,
+                );
+            } else {
+                state.has_capture = false;
+            }
+            state.slots.apply(&mut state.scratch_in[..half], frame.bank);
+            frame.drift.apply(&mut state.scratch_in[..half]);
+            state.decimator.process(
+                &mut state.scratch_in[..half],
+                frame.decimate_mode,
+                frame.decimate,
+            );
+            state.rotator.process(
+                &mut state.scratch_in[..half],
+                frame.rotate,
+                HOP_SIZE as f32 / FFT_SIZE as f32,
+            );
+            if let Some(mirror) = frame.mirror {
+                state.mirror.process(
+                    &mut state.scratch_in[..half],
+                    mirror,
+                    HOP_SIZE as f32 / FFT_SIZE as f32,
+                );
+            }
+            state.average.process(
+                &mut state.scratch_in[..half],
+                frame.average,
+                HOP_SIZE as f32 / FFT_SIZE as f32,
+            );
+            // Everything up to here learns from or saves the flat spectrum
+            state.emphasis.apply(&mut state.scratch_in[..half]);
+
+            Self::select_bins(state, frame);
+            if frame.preserve_envelope {
+                state.analysis.envelope.analyse(&state.scratch_in[..half]);
+            }
+            let map = frame.bin_map();
+            let delay_harmony = frame.harmony_delay > 0;
+            Self::render_spectrum(
+                &state.scratch_in,
+                &mut state.scratch_out,
+                delay_harmony.then_some(&mut state.scratch_harmony[..]),
+                map,
+                frame,
+                &state.blur_table,
+                &state.analysis,
+            );
+            let delayed_harmony = delay_harmony
+                .then(|| state.pre_delay.push(&state.scratch_harmony, frame.harmony_delay));
+            Self::finish_spectrum(
+                &mut state.scratch_out,
+                delayed_harmony,
+                &state.emphasis,
+                &mut state.tamer,
+                frame.tame,
+                bin_gains,
+            );
+            inverse_fft.process(&mut state.scratch_out);
+
+            // A large shift jump would splice two unrelated frames together, so fade from the
+            // previous mapping to the new one across this frame
+            let last_map = state.last_map.replace(map).unwrap_or(map);
+            let crossfade = map.jumps_from(last_map);
+            if crossfade {
+                // The delayed harmony was rendered with the mapping of its own frame
+                Self::render_spectrum(
+                    &state.scratch_in,
+                    &mut state.scratch_prev,
+                    delay_harmony.then_some(&mut state.scratch_harmony[..]),
+                    last_map,
+                    frame,
+                    &state.blur_table,
+                    &state.analysis,
+                );
+                Self::finish_spectrum(
+                    &mut state.scratch_prev,
+                    delayed_harmony,
+                    &state.emphasis,
+                    &mut state.tamer,
+                    frame.tame,
+                    bin_gains,
+                );
+                inverse_fft.process(&mut state.scratch_prev);
+            }
+
+            // Smaller frames end where the largest one would, so every size has its latency
+            let pad = FFT_SIZE - fft_size;
+            let norm = 1.0 / fft_size as f32;
+            for i in 0..fft_size {
+                let mut re = state.scratch_out[i].re;
+                if crossfade {
+                    let t = i as f32 / fft_size as f32;
+                    re = state.scratch_prev[i].re * (1.0 - t) + re * t;
+                }
+                let val = re * norm * window[i];
+                if pad + i < state.output_accum.len() {
+                    state.output_accum[pad + i] += val;
+                } else {
+                    state.output_accum.push_back(val);
+                }
+            }
+        }
+
+        let wet_sig = state.output_accum.pop_front().unwrap_or(0.0);
         state.output_accum.push_back(0.0);
         while state.output_accum.len() < FFT_SIZE {
             state.output_accum.push_back(0.0);
@@ -275,19 +3981,215 @@ impl Whirlpool {
     }
 }
 
-impl ClapPlugin for Whirlpool {
+impl<I: ExportIdentity> ClapPlugin for Whirlpool<I> {
     const CLAP_ID: &'static str = "com.antigravity.whirlpool";
     const CLAP_DESCRIPTION: Option<&'static str> = Some("Whirlpool Spectral Harmonizer");
     const CLAP_MANUAL_URL: Option<&'static str> = Some(Self::URL);
     const CLAP_SUPPORT_URL: Option<&'static str> = None;
-    const CLAP_FEATURES: &'static [ClapFeature] = &[ClapFeature::AudioEffect, ClapFeature::Stereo];
+    const CLAP_FEATURES: &'static [ClapFeature] = &[
+        ClapFeature::AudioEffect,
+        ClapFeature::Stereo,
+        ClapFeature::Surround,
+        ClapFeature::PitchShifter,
+        ClapFeature::PhaseVocoder,
+        ClapFeature::Granular,
+        ClapFeature::NoteDetector,
+    ];
+
+    // Full pages of eight for control surfaces, grouped by section, most played controls first
+    fn remote_controls(&self, context: &mut impl RemoteControlsContext) {
+        let params = &self.params;
+        context.add_section("Spectral", |section| {
+            section.add_page("Main", |page| {
+                page.add_param(&params.harmonics);
+                page.add_param(&params.shift);
+                page.add_param(&params.blur);
+                page.add_param(&params.mix);
+                page.add_param(&params.shift_hz);
+                page.add_param(&params.tonality);
+                page.add_param(&params.low_cut);
+                page.add_param(&params.high_cut);
+            });
+            section.add_page("Blur", |page| {
+                page.add_param(&params.blur);
+                page.add_param(&params.blur_hold);
+                page.add_param(&params.blur_sync);
+                page.add_param(&params.blur_noise);
+                page.add_param(&params.average);
+                page.add_param(&params.harmony_source);
+                page.add_param(&params.bloom_attack);
+                page.add_param(&params.bloom_release);
+            });
+            section.add_page("Spectrum", |page| {
+                page.add_param(&params.rotate);
+                page.add_param(&params.rotate_mod_depth);
+                page.add_param(&params.rotate_mod_rate);
+                page.add_param(&params.invert);
+                page.add_param(&params.invert_center);
+                page.add_param(&params.invert_wet);
+                page.add_param(&params.tame);
+                page.add_param(&params.drift);
+            });
+            section.add_page("Gate", |page| {
+                page.add_param(&params.gate);
+                page.add_param(&params.gate_threshold);
+                page.add_param(&params.gate_reduction);
+                page.add_param(&params.sidechain_duck);
+                page.add_param(&params.duck_amount);
+                page.add_param(&params.duck_release);
+                page.add_param(&params.sidechain_listen);
+                page.add_param(&params.sidechain_blur);
+            });
+        });
+        context.add_section("Harmony", |section| {
+            section.add_page("Harmony", |page| {
+                page.add_param(&params.shift_mode);
+                page.add_param(&params.scale);
+                page.add_param(&params.key);
+                page.add_param(&params.tonal_split);
+                page.add_param(&params.preserve_envelope);
+                page.add_param(&params.pre_delay);
+                page.add_param(&params.decimate);
+                page.add_param(&params.decimate_mode);
+            });
+            section.add_page("Voicing", |page| {
+                page.add_param(&params.harmony_bins);
+                page.add_param(&params.peak_count);
+                page.add_param(&params.shimmer);
+                page.add_param(&params.shimmer_level);
+                page.add_param(&params.tonality);
+                page.add_param(&params.harmony_source);
+                page.add_param(&params.window);
+                page.add_param(&params.pre_emphasis);
+            });
+            section.add_page("Voice Levels", |page| {
+                page.add_param(&params.harmonics);
+                page.add_param(&params.unison);
+                for voice in &params.harmony_voices {
+                    page.add_param(&voice.level);
+                }
+            });
+            section.add_page("Voice Pans", |page| {
+                page.add_param(&params.harmony_pan);
+                page.add_param(&params.unison_width);
+                for voice in &params.harmony_voices {
+                    page.add_param(&voice.pan);
+                }
+            });
+            section.add_page("Voice Shifts", |page| {
+                page.add_param(&params.midi_root);
+                page.add_param(&params.bend_range);
+                for voice in &params.harmony_voices {
+                    page.add_param(&voice.shift);
+                }
+            });
+        });
+        context.add_section("Grain Delay", |section| {
+            section.add_page("Delay", |page| {
+                page.add_param(&params.grain_feedback);
+                page.add_param(&params.delay_time);
+                page.add_param(&params.delay_feedback);
+                page.add_param(&params.delay_invert);
+                page.add_param(&params.delay_mod_rate);
+                page.add_param(&params.delay_mod_depth);
+                page.add_param(&params.delay_mod_shape);
+                page.add_param(&params.grain_shape);
+            });
+            section.add_page("Repeats", |page| {
+                page.add_param(&params.delay_hold);
+                page.add_param(&params.stretch);
+                page.add_param(&params.delay_wow);
+                page.add_param(&params.delay_flutter);
+                page.add_param(&params.delay_saturation);
+                page.add_param(&params.delay_cross);
+                page.add_param(&params.jitter_distribution);
+                page.add_param(&params.jitter_bias);
+            });
+            section.add_page("Grains", |page| {
+                page.add_param(&params.grain_filter);
+                page.add_param(&params.grain_filter_type);
+                page.add_param(&params.grain_cutoff);
+                page.add_param(&params.grain_cutoff_spread);
+                page.add_param(&params.grain_sync);
+                page.add_param(&params.grain_division);
+                page.add_param(&params.swing);
+                page.add_param(&params.humanize);
+            });
+            section.add_page("Playback", |page| {
+                page.add_param(&params.grain_voices);
+                page.add_param(&params.grain_normalize);
+                page.add_param(&params.grain_chord);
+                page.add_param(&params.chord_weight);
+                page.add_param(&params.grain_reverse);
+                page.add_param(&params.tape_stop);
+                page.add_param(&params.tape_stop_time);
+                page.add_param(&params.surround_spread);
+            });
+            section.add_page("Triggers", |page| {
+                page.add_param(&params.grain_source);
+                page.add_param(&params.sidechain_grains);
+                page.add_param(&params.trigger_hold);
+                page.add_param(&params.midi_grains);
+                page.add_param(&params.grain_velocity);
+                page.add_param(&params.grain_key_track);
+                page.add_param(&params.macro_notes);
+                page.add_param(&params.macro_base_note);
+            });
+        });
+        context.add_section("Freeze/Output", |section| {
+            section.add_page("Output", |page| {
+                page.add_param(&params.out_gain);
+                page.add_param(&params.input_pad);
+                page.add_param(&params.tp_limit);
+                page.add_param(&params.tp_ceiling);
+                page.add_param(&params.morph);
+                page.add_param(&params.midi_out);
+                page.add_param(&params.bass_mono);
+                page.add_param(&params.bypass);
+            });
+            section.add_page("Freeze", |page| {
+                page.add_param(&params.freeze);
+                page.add_param(&params.freeze_trigger);
+                page.add_param(&params.freeze_amount);
+                page.add_param(&params.input_mute);
+                page.add_param(&params.trigger_sensitivity);
+                page.add_param(&params.slot_fade);
+                page.add_param(&params.slot_notes);
+                page.add_param(&params.slot_base_note);
+            });
+            section.add_page("Dry/Motion", |page| {
+                page.add_param(&params.dry_low_cut);
+                page.add_param(&params.dry_tilt);
+                page.add_param(&params.bass_mono_freq);
+                page.add_param(&params.save_freeze);
+                page.add_param(&params.motion);
+                page.add_param(&params.motion_steps);
+                page.add_param(&params.motion_division);
+                page.add_param(&params.motion_record);
+            });
+            section.add_page("Euclid/Clock", |page| {
+                page.add_param(&params.euclid);
+                page.add_param(&params.euclid_steps);
+                page.add_param(&params.euclid_fills);
+                page.add_param(&params.euclid_rotation);
+                page.add_param(&params.euclid_division);
+                page.add_param(&params.internal_bpm);
+                page.add_param(&params.internal_run);
+                page.add_param(&params.cpu_guard);
+            });
+        });
+    }
 }
 
-impl Vst3Plugin for Whirlpool {
-    const VST3_CLASS_ID: [u8; 16] = *b"WhirlpoolOlaV2__";
-    const VST3_SUBCATEGORIES: &'static [Vst3SubCategory] =
-        &[Vst3SubCategory::Fx, Vst3SubCategory::Modulation];
+impl<I: ExportIdentity> Vst3Plugin for Whirlpool<I> {
+    const VST3_CLASS_ID: [u8; 16] = I::VST3_CLASS_ID;
+    const VST3_SUBCATEGORIES: &'static [Vst3SubCategory] = &[
+        Vst3SubCategory::Fx,
+        Vst3SubCategory::PitchShift,
+        Vst3SubCategory::Stereo,
+    ];
 }
 
+// The CLAP ID never changed, only VST3 needs the legacy class for old sessions
 nih_export_clap!(Whirlpool);
-nih_export_vst3!(Whirlpool);
+nih_export_vst3!(Whirlpool, Whirlpool<Legacy>);
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The two snapshots the Morph parameter blends between. Values are normalized and keyed by
+/// parameter ID, so presets survive parameters being added or reordered.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MorphPresets {
+    pub a: BTreeMap<String, f32>,
+    pub b: BTreeMap<String, f32>,
+    /// Parameter IDs that keep their own value while morphing.
+    pub excluded: BTreeSet<String>,
+}
+
+impl MorphPresets {
+    /// The normalized values `id` morphs between, `None` if it does not take part.
+    pub fn endpoints(&self, id: &str) -> Option<(f32, f32)> {
+        if self.excluded.contains(id) {
+            return None;
+        }
+        Some((*self.a.get(id)?, *self.b.get(id)?))
+    }
+}
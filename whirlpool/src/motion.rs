@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+
+use crate::smoothing::Smoothed;
+
+pub const MIN_STEPS: usize = 16;
+pub const MAX_STEPS: usize = 64;
+const DEFAULT_PARAM: Smoothed = Smoothed::Blur;
+
+/// One step of the motion sequencer.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MotionStep {
+    /// Normalized value of the sequenced parameter.
+    pub value: f32,
+    /// Glides into the next step's value over the step instead of holding.
+    pub glide: bool,
+}
+
+impl Default for MotionStep {
+    fn default() -> Self {
+        Self {
+            value: 0.5,
+            glide: false,
+        }
+    }
+}
+
+/// The motion sequencer's steps and the parameter they move. Like the XY pad axes the parameter
+/// is stored by ID, so the assignment survives parameters being added or reordered. Only as many
+/// steps as the Motion Steps parameter asks for play, the rest keep their values.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MotionSequence {
+    /// ID of the [`Smoothed`] parameter the steps move.
+    pub param: String,
+    pub steps: Vec<MotionStep>,
+}
+
+impl Default for MotionSequence {
+    fn default() -> Self {
+        Self {
+            param: DEFAULT_PARAM.id().to_owned(),
+            steps: vec![MotionStep::default(); MAX_STEPS],
+        }
+    }
+}
+
+impl MotionSequence {
+    /// The sequenced parameter. Unknown IDs fall back to the default.
+    pub fn param(&self) -> Smoothed {
+        Smoothed::from_id(&self.param).unwrap_or(DEFAULT_PARAM)
+    }
+}
+
+/// The audio thread's copy of a [`MotionSequence`], played from the transport position.
+pub struct MotionSequencer {
+    param: Smoothed,
+    steps: [MotionStep; MAX_STEPS],
+    /// Step the transport was in at the last call to `enter()`, `None` after a reset.
+    step: Option<usize>,
+}
+
+impl MotionSequencer {
+    pub fn new() -> Self {
+        Self {
+            param: DEFAULT_PARAM,
+            steps: [MotionStep::default(); MAX_STEPS],
+            step: None,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.step = None;
+    }
+
+    pub fn param(&self) -> Smoothed {
+        self.param
+    }
+
+    /// Copies `sequence`, steps it does not have keep their default.
+    pub fn load(&mut self, sequence: &MotionSequence) {
+        self.param = sequence.param();
+        for (idx, step) in self.steps.iter_mut().enumerate() {
+            *step = sequence.steps.get(idx).copied().unwrap_or_default();
+        }
+    }
+
+    /// Copies the step values back into `sequence`, after recording.
+    pub fn store(&self, sequence: &mut MotionSequence) {
+        for (stored, step) in sequence.steps.iter_mut().zip(&self.steps) {
+            stored.value = step.value;
+        }
+    }
+
+    /// Step and position within it, in `0..1`, at transport position `beats` for steps
+    /// `step_beats` quarter notes long looping every `length` steps.
+    pub fn position(beats: f64, step_beats: f64, length: usize) -> (usize, f32) {
+        let steps = (beats / step_beats).max(0.0);
+        let step = steps.floor();
+        (step as usize % length.max(1), (steps - step) as f32)
+    }
+
+    /// Moves to `step` and returns whether that is a different step than before.
+    pub fn enter(&mut self, step: usize) -> bool {
+        self.step.replace(step) != Some(step)
+    }
+
+    /// Normalized value at `phase` through `step` of a `length` step loop.
+    pub fn value(&self, step: usize, phase: f32, length: usize) -> f32 {
+        let current = self.steps[step % MAX_STEPS];
+        if !current.glide {
+            return current.value;
+        }
+        let next = self.steps[(step + 1) % length.clamp(1, MAX_STEPS)];
+        current.value + (next.value - current.value) * phase
+    }
+
+    /// Overwrites `step`'s value, keeping its glide.
+    pub fn record(&mut self, step: usize, value: f32) {
+        self.steps[step % MAX_STEPS].value = value.clamp(0.0, 1.0);
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::VecDeque;
+
+// --- YIN SETTINGS ---
+const THRESHOLD: f32 = 0.15;
+const MAX_FREQ: f32 = 1500.0;
+/// Frames quieter than this (mean square, roughly -60 dBFS) are treated as unvoiced.
+const MIN_ENERGY: f32 = 1e-6;
+/// Rate the input is decimated to before detection, so a frame covers the same time and the
+/// detectable range stays the same at every sample rate.
+const DETECTION_RATE: f32 = 48000.0;
+
+/// Monophonic YIN pitch detector. All buffers are allocated up front so `detect()` can run on the
+/// audio thread.
+pub struct PitchDetector {
+    /// The latest decimated input, oldest first.
+    history: VecDeque<f32>,
+    frame: Vec<f32>,
+    /// Cumulative mean normalized difference function, indexed by lag.
+    cmnd: Vec<f32>,
+    /// Mean square of the last analysed frame.
+    energy: f32,
+    /// Input samples averaged into one decimated sample.
+    factor: usize,
+    sum: f32,
+    count: usize,
+    sample_rate: f32,
+}
+
+impl PitchDetector {
+    pub fn new(frame_size: usize) -> Self {
+        Self {
+            history: VecDeque::from(vec![0.0; frame_size]),
+            frame: vec![0.0; frame_size],
+            cmnd: vec![1.0; frame_size / 2],
+            energy: 0.0,
+            factor: 1,
+            sum: 0.0,
+            count: 0,
+            sample_rate: DETECTION_RATE,
+        }
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.factor = ((sample_rate / DETECTION_RATE).round() as usize).max(1);
+        self.sample_rate = sample_rate;
+        self.reset();
+    }
+
+    pub fn reset(&mut self) {
+        self.history.iter_mut().for_each(|x| *x = 0.0);
+        self.sum = 0.0;
+        self.count = 0;
+    }
+
+    /// Feeds the next input sample. Averaging each run of samples before dropping all but one
+    /// keeps most of what would fold down out of the decimated signal.
+    pub fn push(&mut self, sample: f32) {
+        self.sum += sample;
+        self.count += 1;
+        if self.count == self.factor {
+            self.history.pop_front();
+            self.history.push_back(self.sum / self.factor as f32);
+            self.sum = 0.0;
+            self.count = 0;
+        }
+    }
+
+    pub fn energy(&self) -> f32 {
+        self.energy
+    }
+
+    /// Returns the fundamental of the latest pushed input in Hz, or `None` for silent or unpitched
+    /// frames. The longest detectable period is half the frame size at `DETECTION_RATE`.
+    pub fn detect(&mut self) -> Option<f32> {
+        for (dst, src) in self.frame.iter_mut().zip(&self.history) {
+            *dst = *src;
+        }
+        let sample_rate = self.sample_rate / self.factor as f32;
+
+        self.energy = self.frame.iter().map(|x| x * x).sum::<f32>() / self.frame.len() as f32;
+        if self.energy < MIN_ENERGY {
+            return None;
+        }
+
+        let half = self.cmnd.len();
+        let mut running_sum = 0.0;
+        self.cmnd[0] = 1.0;
+        for tau in 1..half {
+            let diff: f32 = self.frame[..half]
+                .iter()
+                .zip(&self.frame[tau..tau + half])
+                .map(|(a, b)| (a - b) * (a - b))
+                .sum();
+            running_sum += diff;
+            self.cmnd[tau] = if running_sum > 0.0 {
+                diff * tau as f32 / running_sum
+            } else {
+                1.0
+            };
+        }
+
+        // First dip below the threshold, then walk down to the bottom of that dip
+        let min_tau = ((sample_rate / MAX_FREQ) as usize).max(2);
+        let mut tau = (min_tau..half - 1).find(|&tau| self.cmnd[tau] < THRESHOLD)?;
+        while tau + 1 < half - 1 && self.cmnd[tau + 1] < self.cmnd[tau] {
+            tau += 1;
+        }
+
+        let (a, b, c) = (self.cmnd[tau - 1], self.cmnd[tau], self.cmnd[tau + 1]);
+        let denom = a - 2.0 * b + c;
+        let offset = if denom.abs() > f32::EPSILON {
+            0.5 * (a - c) / denom
+        } else {
+            0.0
+        };
+
+        Some(sample_rate / (tau as f32 + offset))
+    }
+}
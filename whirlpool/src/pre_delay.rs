@@ -0,0 +1,47 @@
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+
+/// Longest harmony pre-delay, in seconds.
+pub const MAX_PRE_DELAY: f32 = 0.5;
+
+/// Delays half spectra by whole frames, so the harmony voice can enter after the source.
+pub struct SpectralDelay {
+    /// Ring of past frames, one after the other.
+    frames: Vec<Complex<f32>>,
+    bins: usize,
+    /// Frame the next push writes to.
+    write: usize,
+}
+
+impl SpectralDelay {
+    pub fn new(bins: usize) -> Self {
+        Self {
+            frames: vec![Complex::zero(); bins],
+            bins,
+            write: 0,
+        }
+    }
+
+    /// Makes room for delays of up to `max_frames` frames. This allocates and clears the delay.
+    pub fn set_max_frames(&mut self, max_frames: usize) {
+        self.frames = vec![Complex::zero(); (max_frames + 1) * self.bins];
+        self.write = 0;
+    }
+
+    pub fn reset(&mut self) {
+        self.frames.fill(Complex::zero());
+        self.write = 0;
+    }
+
+    /// Stores `spectrum` and returns the frame pushed `delay` frames before it, clamped to the
+    /// longest delay there is room for.
+    pub fn push(&mut self, spectrum: &[Complex<f32>], delay: usize) -> &[Complex<f32>] {
+        let len = self.frames.len() / self.bins;
+        let start = self.write * self.bins;
+        self.frames[start..start + self.bins].copy_from_slice(&spectrum[..self.bins]);
+
+        let read = (self.write + len - delay.min(len - 1)) % len;
+        self.write = (self.write + 1) % len;
+        &self.frames[read * self.bins..(read + 1) * self.bins]
+    }
+}
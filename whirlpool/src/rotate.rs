@@ -0,0 +1,46 @@
+use rustfft::num_complex::Complex;
+use std::f32::consts::TAU;
+
+/// Circularly shifts spectra by whole bins. Content pushed past Nyquist wraps around to DC, so
+/// unlike the ratio shift every partial moves by the same distance in Hz, like a ring modulator
+/// whose sidebands fold back.
+pub struct Rotator {
+    /// Unrotated copy of the spectrum.
+    scratch: Vec<Complex<f32>>,
+    /// Phase offset added to every rotated bin, advanced by every frame.
+    phase: f32,
+}
+
+impl Rotator {
+    pub fn new(bins: usize) -> Self {
+        Self {
+            scratch: vec![Complex::default(); bins],
+            phase: 0.0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+
+    /// Moves the content of every bin of the positive-frequency half `spectrum` up by `bins`, or
+    /// down when negative. `hop_ratio` is the hop size over the FFT size.
+    pub fn process(&mut self, spectrum: &mut [Complex<f32>], bins: isize, hop_ratio: f32) {
+        let len = spectrum.len().min(self.scratch.len());
+        let offset = bins.rem_euclid(len as isize) as usize;
+        if offset == 0 {
+            self.phase = 0.0;
+            return;
+        }
+
+        // A bin's phase turns by its frequency times the hop between frames. Moved to another
+        // bin it has to turn at that bin's rate, or overlapping frames cancel each other out
+        self.phase = (self.phase + TAU * offset as f32 * hop_ratio).rem_euclid(TAU);
+        let rotation = Complex::from_polar(1.0, self.phase);
+        let scratch = &mut self.scratch[..len];
+        scratch.copy_from_slice(&spectrum[..len]);
+        for (i, bin) in scratch.iter().enumerate() {
+            spectrum[(i + offset) % len] = bin * rotation;
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use nih_plug::prelude::*;
+
+#[cfg(feature = "editor")]
+pub const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// Every pitch class enabled, used as the default custom mask.
+pub const CHROMATIC_MASK: u16 = 0x0fff;
+
+#[derive(Enum, Debug, Clone, Copy, PartialEq)]
+pub enum Scale {
+    Off,
+    Major,
+    Minor,
+    Dorian,
+    Custom,
+}
+
+#[derive(Enum, Debug, Clone, Copy, PartialEq)]
+pub enum Key {
+    C,
+    #[name = "C#"]
+    CSharp,
+    D,
+    #[name = "D#"]
+    DSharp,
+    E,
+    F,
+    #[name = "F#"]
+    FSharp,
+    G,
+    #[name = "G#"]
+    GSharp,
+    A,
+    #[name = "A#"]
+    ASharp,
+    B,
+}
+
+impl Scale {
+    fn intervals(self) -> &'static [u8] {
+        match self {
+            Scale::Off | Scale::Custom => &[],
+            Scale::Major => &[0, 2, 4, 5, 7, 9, 11],
+            Scale::Minor => &[0, 2, 3, 5, 7, 8, 10],
+            Scale::Dorian => &[0, 2, 3, 5, 7, 9, 10],
+        }
+    }
+
+    /// Returns the allowed pitch classes as a 12-bit mask with C in bit 0. The custom mask is
+    /// already absolute, so the key is ignored for it. `Off` yields an empty mask.
+    pub fn mask(self, key: Key, custom_mask: u16) -> u16 {
+        match self {
+            Scale::Off => 0,
+            Scale::Custom => custom_mask & CHROMATIC_MASK,
+            _ => {
+                let root = key.to_index();
+                self.intervals().iter().fold(0, |mask, interval| {
+                    mask | 1 << ((root + *interval as usize) % 12)
+                })
+            }
+        }
+    }
+}
+
+pub fn freq_to_note(freq: f32) -> f32 {
+    69.0 + 12.0 * (freq / 440.0).log2()
+}
+
+pub fn note_to_freq(note: f32) -> f32 {
+    440.0 * 2f32.powf((note - 69.0) / 12.0)
+}
+
+/// Snaps `fundamental * ratio` to the nearest note allowed by `mask` and returns the ratio that
+/// lands on it. The ratio is passed through unchanged when the mask is empty or no fundamental
+/// was detected.
+pub fn quantize_ratio(fundamental: Option<f32>, ratio: f32, mask: u16) -> f32 {
+    let fundamental = match fundamental {
+        Some(f) if f > 0.0 && mask != 0 => f,
+        _ => return ratio,
+    };
+
+    let target = freq_to_note(fundamental * ratio);
+    let base = target.round() as i32;
+    let nearest = (base - 6..=base + 6)
+        .filter(|note| mask & (1 << note.rem_euclid(12)) != 0)
+        .min_by(|a, b| {
+            let da = (*a as f32 - target).abs();
+            let db = (*b as f32 - target).abs();
+            da.total_cmp(&db)
+        });
+
+    match nearest {
+        Some(note) => note_to_freq(note as f32) / fundamental,
+        None => ratio,
+    }
+}
@@ -0,0 +1,82 @@
+use nih_plug::prelude::*;
+use rustfft::num_complex::Complex;
+use std::f32::consts::TAU;
+
+/// Intervals the shimmer layers above the frozen spectrum.
+#[derive(Enum, Debug, Clone, Copy, PartialEq)]
+pub enum ShimmerInterval {
+    Off,
+    Octave,
+    Fifth,
+    #[name = "Octave + Fifth"]
+    Both,
+}
+
+impl ShimmerInterval {
+    /// Frequency ratios of the layered copies.
+    fn ratios(self) -> &'static [f32] {
+        match self {
+            ShimmerInterval::Off => &[],
+            ShimmerInterval::Octave => &[2.0],
+            ShimmerInterval::Fifth => &[1.5],
+            ShimmerInterval::Both => &[2.0, 1.5],
+        }
+    }
+}
+
+/// Adds transposed copies of the frozen magnitudes on top of the frozen spectrum, like the
+/// pitch-shifted feedback of a shimmer reverb. The copies get phases of their own that turn at
+/// their bins' centre frequencies, so they ring as steadily as the freeze itself.
+pub struct Shimmer {
+    /// Magnitude of the layered copies per bin.
+    mags: Vec<f32>,
+    phases: Vec<f32>,
+}
+
+impl Shimmer {
+    pub fn new(bins: usize) -> Self {
+        Self {
+            mags: vec![0.0; bins],
+            phases: vec![0.0; bins],
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.phases.iter_mut().for_each(|phase| *phase = 0.0);
+    }
+
+    /// Adds `interval`'s copies of `frozen_mags` at `level` to the positive-frequency half
+    /// `spectrum`. `hop_ratio` is the hop size over the FFT size.
+    pub fn process(
+        &mut self,
+        spectrum: &mut [Complex<f32>],
+        frozen_mags: &[f32],
+        interval: ShimmerInterval,
+        level: f32,
+        hop_ratio: f32,
+    ) {
+        if interval == ShimmerInterval::Off || level <= 0.0 {
+            return;
+        }
+
+        let len = spectrum.len().min(self.mags.len());
+        self.mags.iter_mut().for_each(|mag| *mag = 0.0);
+        for ratio in interval.ratios() {
+            for (i, mag) in frozen_mags.iter().enumerate() {
+                let target = (i as f32 * ratio).round() as usize;
+                if target >= len {
+                    break;
+                }
+                self.mags[target] += mag * level;
+            }
+        }
+
+        for (i, bin) in spectrum[..len].iter_mut().enumerate() {
+            let phase = (self.phases[i] + TAU * i as f32 * hop_ratio) % TAU;
+            self.phases[i] = phase;
+            if self.mags[i] > 0.0 {
+                *bin += Complex::from_polar(self.mags[i], phase);
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use nih_plug::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const MAX_SMOOTHING_MS: f32 = 2000.0;
+
+/// Parameters whose automation is smoothed by the plugin rather than by their own smoother, so
+/// the smoothing time can be changed at runtime.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Smoothed {
+    Harmonics,
+    Shift,
+    ShiftHz,
+    Blur,
+    Mix,
+    OutGain,
+    DelayTime,
+    DelayFeedback,
+}
+
+impl Smoothed {
+    pub const ALL: [Smoothed; 8] = [
+        Smoothed::Harmonics,
+        Smoothed::Shift,
+        Smoothed::ShiftHz,
+        Smoothed::Blur,
+        Smoothed::Mix,
+        Smoothed::OutGain,
+        Smoothed::DelayTime,
+        Smoothed::DelayFeedback,
+    ];
+
+    /// The parameter ID, used as the key for per-parameter overrides.
+    pub fn id(self) -> &'static str {
+        match self {
+            Smoothed::Harmonics => "harmonics",
+            Smoothed::Shift => "shift",
+            Smoothed::ShiftHz => "shift_hz",
+            Smoothed::Blur => "blur",
+            Smoothed::Mix => "mix",
+            Smoothed::OutGain => "output_gain",
+            Smoothed::DelayTime => "delay_time",
+            Smoothed::DelayFeedback => "delay_feedback",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Smoothed> {
+        Smoothed::ALL.into_iter().find(|param| param.id() == id)
+    }
+}
+
+/// Automation smoothing times set in the editor's settings tab.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SmoothingTimes {
+    /// Milliseconds for every parameter without an override.
+    pub global_ms: f32,
+    /// Milliseconds per parameter ID.
+    pub overrides: BTreeMap<String, f32>,
+}
+
+impl Default for SmoothingTimes {
+    fn default() -> Self {
+        // The spectral parameters only take effect once per hop, a longer glide hides the steps
+        let overrides = [
+            Smoothed::Harmonics,
+            Smoothed::Shift,
+            Smoothed::ShiftHz,
+            Smoothed::Blur,
+        ]
+        .into_iter()
+        .map(|param| (param.id().to_owned(), 50.0))
+        .collect();
+
+        Self {
+            global_ms: 20.0,
+            overrides,
+        }
+    }
+}
+
+impl SmoothingTimes {
+    pub fn time_ms(&self, param: Smoothed) -> f32 {
+        self.overrides
+            .get(param.id())
+            .copied()
+            .unwrap_or(self.global_ms)
+            .clamp(0.0, MAX_SMOOTHING_MS)
+    }
+
+    pub fn style(&self, param: Smoothed) -> SmoothingStyle {
+        SmoothingStyle::Linear(self.time_ms(param))
+    }
+}
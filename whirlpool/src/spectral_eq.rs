@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+
+// --- CURVE RANGES ---
+pub const MIN_FREQ: f32 = 20.0;
+pub const MAX_FREQ: f32 = 20000.0;
+pub const MIN_GAIN_DB: f32 = -24.0;
+pub const MAX_GAIN_DB: f32 = 24.0;
+
+/// A single breakpoint on the user-drawn gain curve.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EqPoint {
+    pub freq: f32,
+    pub gain_db: f32,
+}
+
+/// Spectral gain curve drawn in the editor. Breakpoints are kept sorted by frequency and the gain
+/// between them is interpolated linearly over a log-frequency axis. An empty curve is flat.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpectralEqCurve {
+    pub points: Vec<EqPoint>,
+}
+
+impl SpectralEqCurve {
+    /// Inserts a breakpoint and returns its index after sorting.
+    pub fn insert(&mut self, point: EqPoint) -> usize {
+        let point = clamp_point(point);
+        let idx = self.points.partition_point(|p| p.freq < point.freq);
+        self.points.insert(idx, point);
+        idx
+    }
+
+    /// Moves a breakpoint without letting it cross its neighbours, so indices stay stable while
+    /// the editor is dragging it.
+    pub fn move_point(&mut self, idx: usize, point: EqPoint) {
+        if idx >= self.points.len() {
+            return;
+        }
+
+        let mut point = clamp_point(point);
+        if idx > 0 {
+            point.freq = point.freq.max(self.points[idx - 1].freq);
+        }
+        if idx + 1 < self.points.len() {
+            point.freq = point.freq.min(self.points[idx + 1].freq);
+        }
+        self.points[idx] = point;
+    }
+
+    pub fn remove(&mut self, idx: usize) {
+        if idx < self.points.len() {
+            self.points.remove(idx);
+        }
+    }
+
+    pub fn gain_db_at(&self, freq: f32) -> f32 {
+        let (first, last) = match (self.points.first(), self.points.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return 0.0,
+        };
+        if freq <= first.freq {
+            return first.gain_db;
+        }
+        if freq >= last.freq {
+            return last.gain_db;
+        }
+
+        let idx = self.points.partition_point(|p| p.freq <= freq);
+        let (a, b) = (self.points[idx - 1], self.points[idx]);
+        let span = (b.freq / a.freq).ln();
+        if span <= f32::EPSILON {
+            return b.gain_db;
+        }
+        let t = (freq / a.freq).ln() / span;
+        a.gain_db + (b.gain_db - a.gain_db) * t
+    }
+
+    /// Renders the curve into one linear gain per FFT bin. Does not allocate, so it is safe to
+    /// call from the audio thread.
+    pub fn fill_bin_gains(&self, gains: &mut [f32], fft_size: usize, sample_rate: f32) {
+        let bin_hz = sample_rate / fft_size as f32;
+        for (i, gain) in gains.iter_mut().enumerate() {
+            let freq = (i as f32 * bin_hz).max(MIN_FREQ);
+            *gain = 10f32.powf(self.gain_db_at(freq) / 20.0);
+        }
+    }
+}
+
+fn clamp_point(point: EqPoint) -> EqPoint {
+    EqPoint {
+        freq: point.freq.clamp(MIN_FREQ, MAX_FREQ),
+        gain_db: point.gain_db.clamp(MIN_GAIN_DB, MAX_GAIN_DB),
+    }
+}
+
+/// Maps a frequency to `0..=1` on the log axis used by the editor.
+#[cfg(feature = "editor")]
+pub fn freq_to_unit(freq: f32) -> f32 {
+    (freq / MIN_FREQ).log10() / (MAX_FREQ / MIN_FREQ).log10()
+}
+
+#[cfg(feature = "editor")]
+pub fn unit_to_freq(unit: f32) -> f32 {
+    MIN_FREQ * (MAX_FREQ / MIN_FREQ).powf(unit.clamp(0.0, 1.0))
+}
@@ -0,0 +1,201 @@
+use crate::fft_size::rebin;
+use rustfft::num_complex::Complex;
+use serde::{Deserialize, Serialize};
+
+/// Seconds of input averaged into the noise floor when learning.
+pub const LEARN_TIME: f32 = 2.0;
+/// Time for a closed bin's gain to fall most of the way to the reduction, in seconds. Opening is
+/// instant.
+pub const RELEASE_TIME: f32 = 0.020;
+/// Weight of the previous frame in a bin's level. The level a bin is judged by is averaged over
+/// time and its neighbours, so the random peaks of the noise itself do not open the gate.
+const LEVEL_SMOOTHING: f32 = 0.5;
+
+/// Noise floor learned from noise-only input, saved with the plugin state.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NoiseProfile {
+    /// Bins per channel, zero until something was learned.
+    pub bins: usize,
+    /// Mean magnitude per bin, one channel after the other.
+    pub floors: Vec<f32>,
+    /// Sample rate the floors were learned at, zero for profiles saved before it was recorded.
+    #[serde(default)]
+    pub sample_rate: f32,
+}
+
+impl NoiseProfile {
+    /// Reserves room for `num_channels` floors of `bins` bins, so storing them never allocates.
+    pub fn reserve(&mut self, num_channels: usize, bins: usize) {
+        self.floors
+            .reserve((num_channels * bins).saturating_sub(self.floors.len()));
+    }
+
+    /// Replaces the profile with one floor per channel, learned at `sample_rate`.
+    pub fn store<'a>(
+        &mut self,
+        bins: usize,
+        sample_rate: f32,
+        floors: impl Iterator<Item = &'a [f32]>,
+    ) {
+        self.bins = bins;
+        self.sample_rate = sample_rate;
+        self.floors.clear();
+        for floor in floors {
+            self.floors.extend_from_slice(floor);
+        }
+    }
+
+    /// Moves the floors onto the bins of the same FFT at `sample_rate`, so every floor stays at
+    /// the frequency it was learned at. Bins past the old Nyquist frequency take the highest
+    /// learned floor. Profiles without a recorded rate are taken as learned at `sample_rate`.
+    pub fn resample(&mut self, sample_rate: f32) {
+        if self.bins > 0 && self.sample_rate > 0.0 && self.sample_rate != sample_rate {
+            let (bins, scale) = (self.bins, sample_rate / self.sample_rate);
+            self.floors = self
+                .floors
+                .chunks_exact(bins)
+                .flat_map(|floor| {
+                    (0..bins).map(move |i| {
+                        let position = i as f32 * scale;
+                        let below = (position as usize).min(bins - 1);
+                        let above = (below + 1).min(bins - 1);
+                        let t = (position - below as f32).min(1.0);
+                        floor[below] + (floor[above] - floor[below]) * t
+                    })
+                })
+                .collect();
+        }
+        self.sample_rate = sample_rate;
+    }
+
+    /// The floor for channel `channel`. Channels past the saved ones reuse the last floor.
+    pub fn floor(&self, channel: usize) -> Option<&[f32]> {
+        if self.bins == 0 {
+            return None;
+        }
+        let mut floors = self.floors.chunks_exact(self.bins);
+        floors.clone().nth(channel).or(floors.next_back())
+    }
+}
+
+/// How the gate treats bins around the learned floor, fixed for one frame.
+#[derive(Clone, Copy)]
+pub struct GateSettings {
+    /// Linear ratio a bin needs over its floor to open.
+    pub threshold: f32,
+    /// Linear gain of a closed bin.
+    pub reduction: f32,
+    /// Per-frame decay coefficient of a closing bin's gain.
+    pub release: f32,
+}
+
+/// Per-bin downward expander against a learned noise floor. With nothing learned every bin stays
+/// open, so the gate does nothing.
+pub struct SpectralGate {
+    floor: Vec<f32>,
+    /// Smoothed magnitude per bin the gate opens on.
+    levels: Vec<f32>,
+    gains: Vec<f32>,
+    /// Running sum of the magnitudes while learning.
+    sum: Vec<f32>,
+    learned_frames: usize,
+    frames_left: usize,
+}
+
+impl SpectralGate {
+    pub fn new(bins: usize) -> Self {
+        Self {
+            floor: vec![0.0; bins],
+            levels: vec![0.0; bins],
+            gains: vec![1.0; bins],
+            sum: vec![0.0; bins],
+            learned_frames: 0,
+            frames_left: 0,
+        }
+    }
+
+    /// Opens every bin again. The learned floor is kept.
+    pub fn reset(&mut self) {
+        self.levels.fill(0.0);
+        self.gains.fill(1.0);
+    }
+
+    /// Averages the next `frames` frames into a new floor, replacing the current one once done.
+    pub fn start_learning(&mut self, frames: usize) {
+        self.sum.fill(0.0);
+        self.learned_frames = 0;
+        self.frames_left = frames.max(1);
+    }
+
+    pub fn is_learning(&self) -> bool {
+        self.frames_left > 0
+    }
+
+    pub fn floor(&self) -> &[f32] {
+        &self.floor
+    }
+
+    /// Replaces the floor, or clears it when `floor` is missing. A floor learned at another FFT
+    /// size is moved onto this gate's bins.
+    pub fn set_floor(&mut self, floor: Option<&[f32]>) {
+        match floor {
+            Some(floor) if floor.len() == self.floor.len() => self.floor.copy_from_slice(floor),
+            // Noise magnitudes grow with the root of the frame length
+            Some(floor) if !floor.is_empty() => {
+                let scale = (self.floor.len() as f32 / floor.len() as f32).sqrt();
+                rebin(floor.len(), |i| floor[i], &mut self.floor, scale);
+            }
+            _ => self.floor.fill(0.0),
+        }
+    }
+
+    /// Adds the positive-frequency half `spectrum` to the floor being learned.
+    pub fn learn(&mut self, spectrum: &[Complex<f32>]) {
+        if self.frames_left == 0 {
+            return;
+        }
+
+        for (sum, bin) in self.sum.iter_mut().zip(spectrum) {
+            *sum += bin.norm();
+        }
+        self.learned_frames += 1;
+        self.frames_left -= 1;
+        if self.frames_left == 0 {
+            let norm = 1.0 / self.learned_frames as f32;
+            for (floor, sum) in self.floor.iter_mut().zip(&self.sum) {
+                *floor = sum * norm;
+            }
+        }
+    }
+
+    /// Attenuates the bins of the positive-frequency half `spectrum` that do not stand out from
+    /// the floor.
+    pub fn apply(&mut self, spectrum: &mut [Complex<f32>], settings: GateSettings) {
+        let bins = spectrum.len().min(self.levels.len());
+        for (i, level) in self.levels[..bins].iter_mut().enumerate() {
+            let neighbours = &spectrum[i.saturating_sub(1)..(i + 2).min(bins)];
+            let mean =
+                neighbours.iter().map(|bin| bin.norm()).sum::<f32>() / neighbours.len() as f32;
+            *level = *level * LEVEL_SMOOTHING + mean * (1.0 - LEVEL_SMOOTHING);
+        }
+
+        for (((bin, gain), floor), level) in spectrum
+            .iter_mut()
+            .zip(&mut self.gains)
+            .zip(&self.floor)
+            .zip(&self.levels)
+        {
+            let target = if *level > floor * settings.threshold {
+                1.0
+            } else {
+                settings.reduction
+            };
+            *gain = if target > *gain {
+                target
+            } else {
+                target + (*gain - target) * settings.release
+            };
+            *bin *= *gain;
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use std::f32::consts::FRAC_PI_2;
+use std::ops::Range;
+
+/// Most output channels of any layout.
+pub const MAX_OUTPUT_CHANNELS: usize = 6;
+
+/// Multichannel layouts beyond stereo. The front pair carries the stereo output as is, the rear
+/// pair plays the grain cloud placed around the listener.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SurroundLayout {
+    /// Left, right, rear left, rear right.
+    Quad,
+    /// Left, right, center, LFE, surround left, surround right.
+    FiveOne,
+}
+
+impl SurroundLayout {
+    /// The layout with `outputs` channels, `None` for stereo and anything else.
+    pub fn from_outputs(outputs: usize) -> Option<Self> {
+        match outputs {
+            4 => Some(Self::Quad),
+            6 => Some(Self::FiveOne),
+            _ => None,
+        }
+    }
+
+    /// Channels the left and right channel's grains go to at the back.
+    pub fn rear(self) -> [usize; 2] {
+        match self {
+            Self::Quad => [2, 3],
+            Self::FiveOne => [4, 5],
+        }
+    }
+
+    /// Channels left silent, the cloud has no place in the center or the LFE.
+    pub fn silent(self) -> Range<usize> {
+        match self {
+            Self::Quad => 0..0,
+            Self::FiveOne => 2..4,
+        }
+    }
+}
+
+/// Equal power gain of a grain `depth` in `0..=1` from the front to the back, in the rear
+/// speakers.
+pub fn rear_gain(depth: f32) -> f32 {
+    (depth * FRAC_PI_2).sin()
+}
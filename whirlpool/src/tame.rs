@@ -0,0 +1,56 @@
+use nih_plug::prelude::*;
+use rustfft::num_complex::Complex;
+
+/// Headroom over the median a bin keeps with the least taming, in dB.
+const MAX_HEADROOM: f32 = 48.0;
+/// Headroom over the median a bin keeps at full `Tame`, in dB.
+const MIN_HEADROOM: f32 = 12.0;
+/// Median below which a frame is treated as silent and left alone.
+const SILENCE: f32 = 1e-9;
+
+/// Caps every bin at a ceiling over the frame's median magnitude, so single bins that build up
+/// through feedback or stacked harmonies cannot ring out above the rest of the wash.
+pub struct SpectralTamer {
+    /// Scratch copy of the magnitudes the median is picked from.
+    magnitudes: Vec<f32>,
+}
+
+impl SpectralTamer {
+    pub fn new(bins: usize) -> Self {
+        Self {
+            magnitudes: Vec::with_capacity(bins),
+        }
+    }
+
+    /// Linear ceiling over the median for an `amount` in `0..=1`, `None` at zero.
+    pub fn ceiling(amount: f32) -> Option<f32> {
+        (amount > 0.0)
+            .then(|| util::db_to_gain(MAX_HEADROOM + (MIN_HEADROOM - MAX_HEADROOM) * amount))
+    }
+
+    /// Scales the bins of the positive-frequency half `spectrum` above `ceiling` times the
+    /// median magnitude down to it, keeping their phases.
+    pub fn process(&mut self, spectrum: &mut [Complex<f32>], ceiling: f32) {
+        self.magnitudes.clear();
+        self.magnitudes
+            .extend(spectrum.iter().map(|bin| bin.norm()));
+        if self.magnitudes.is_empty() {
+            return;
+        }
+        let middle = self.magnitudes.len() / 2;
+        let (_, median, _) = self
+            .magnitudes
+            .select_nth_unstable_by(middle, f32::total_cmp);
+        if *median <= SILENCE {
+            return;
+        }
+
+        let limit = *median * ceiling;
+        for bin in spectrum.iter_mut() {
+            let magnitude = bin.norm();
+            if magnitude > limit {
+                *bin *= limit / magnitude;
+            }
+        }
+    }
+}
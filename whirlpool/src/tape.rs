@@ -0,0 +1,56 @@
+use crate::lfo::{Lfo, LfoShape};
+
+/// Deepest wow, in seconds of delay time.
+pub const MAX_WOW: f32 = 0.004;
+/// Deepest flutter, in seconds of delay time.
+pub const MAX_FLUTTER: f32 = 0.0005;
+/// Rate of the wow in Hz, a slowly drifting capstan.
+const WOW_RATE: f32 = 0.7;
+/// Rate of the flutter in Hz.
+const FLUTTER_RATE: f32 = 7.5;
+/// Drive at full saturation. Small signals keep their level, louder ones are squashed towards
+/// `1 / MAX_DRIVE`.
+const MAX_DRIVE: f32 = 10.0;
+
+/// Tape-style speed variations of the delay's read heads: a slow random wow and a fast flutter.
+pub struct TapeWobble {
+    wow: Lfo,
+    flutter: Lfo,
+}
+
+impl TapeWobble {
+    pub fn new() -> Self {
+        Self {
+            wow: Lfo::new(),
+            flutter: Lfo::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.wow.reset();
+        self.flutter.reset();
+    }
+
+    /// Advances by one sample and returns the extra delay in samples, with `wow` and `flutter`
+    /// amounts in `0..=1`.
+    pub fn next(&mut self, wow: f32, flutter: f32, sample_rate: f32) -> f32 {
+        let wow = wow * MAX_WOW * self.wow.next(LfoShape::Random, WOW_RATE / sample_rate);
+        let flutter = flutter
+            * MAX_FLUTTER
+            * self
+                .flutter
+                .next(LfoShape::Sine, FLUTTER_RATE / sample_rate);
+        (wow + flutter) * sample_rate
+    }
+}
+
+/// Soft clips the delay's feedback like an overdriven tape, from clean at an `amount` of zero
+/// to heavily compressed at one.
+pub fn saturate(input: f32, amount: f32) -> f32 {
+    if amount <= 0.0 {
+        return input;
+    }
+    let drive = 1.0 + (MAX_DRIVE - 1.0) * amount;
+    let saturated = (input * drive).tanh() / drive;
+    input + (saturated - input) * amount
+}
@@ -0,0 +1,203 @@
+use nih_plug::prelude::*;
+
+/// Taps further apart than this, in seconds, start a new measurement.
+#[cfg(feature = "editor")]
+const TAP_TIMEOUT: f64 = 2.0;
+/// The tapped tempo is averaged over this many most recent taps.
+#[cfg(feature = "editor")]
+const MAX_TAPS: usize = 5;
+/// Longest random delay of a humanized event, in seconds.
+pub const MAX_HUMANIZE: f32 = 0.050;
+
+/// What the host reported about its transport for one block.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HostTime {
+    pub tempo: Option<f64>,
+    pub pos_beats: Option<f64>,
+    pub playing: bool,
+}
+
+/// Tempo and position for one block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClockState {
+    pub tempo: f64,
+    /// Position in quarter notes at the start of the block.
+    pub pos_beats: f64,
+    pub playing: bool,
+    /// Whether this came from the host rather than the internal transport.
+    pub from_host: bool,
+}
+
+/// Transport used when the host does not provide a tempo, as in standalone builds. Stopping it
+/// rewinds to the start, like a drum machine.
+pub struct InternalClock {
+    pos_beats: f64,
+}
+
+impl InternalClock {
+    pub fn new() -> Self {
+        Self { pos_beats: 0.0 }
+    }
+
+    pub fn reset(&mut self) {
+        self.pos_beats = 0.0;
+    }
+
+    /// Resolves the clock for a block of `num_samples`, preferring the host's tempo and falling
+    /// back to the internal transport running at `bpm`.
+    pub fn advance(
+        &mut self,
+        host: HostTime,
+        bpm: f64,
+        running: bool,
+        num_samples: usize,
+        sample_rate: f32,
+    ) -> ClockState {
+        if let Some(tempo) = host.tempo {
+            return ClockState {
+                tempo,
+                pos_beats: host.pos_beats.unwrap_or(0.0),
+                playing: host.playing,
+                from_host: true,
+            };
+        }
+
+        if !running {
+            self.pos_beats = 0.0;
+        }
+        let state = ClockState {
+            tempo: bpm,
+            pos_beats: self.pos_beats,
+            playing: running,
+            from_host: false,
+        };
+        if running {
+            self.pos_beats += num_samples as f64 / sample_rate as f64 * bpm / 60.0;
+        }
+        state
+    }
+}
+
+/// Note length of tempo-synced events.
+#[derive(Enum, Debug, Clone, Copy, PartialEq)]
+pub enum NoteDivision {
+    #[name = "1/4"]
+    Quarter,
+    #[name = "1/8"]
+    Eighth,
+    #[name = "1/8T"]
+    EighthTriplet,
+    #[name = "1/16"]
+    Sixteenth,
+    #[name = "1/16T"]
+    SixteenthTriplet,
+    #[name = "1/32"]
+    ThirtySecond,
+}
+
+impl NoteDivision {
+    /// Length in quarter notes.
+    pub fn beats(self) -> f64 {
+        match self {
+            NoteDivision::Quarter => 1.0,
+            NoteDivision::Eighth => 0.5,
+            NoteDivision::EighthTriplet => 1.0 / 3.0,
+            NoteDivision::Sixteenth => 0.25,
+            NoteDivision::SixteenthTriplet => 1.0 / 6.0,
+            NoteDivision::ThirtySecond => 0.125,
+        }
+    }
+}
+
+/// Fires on the steps of a tempo grid, following the transport one tick at a time. Every second
+/// step can be swung late, and every step can be humanized by a random delay.
+pub struct SyncGrid {
+    /// Step the transport was in at the last tick, `None` after a reset.
+    step: Option<i64>,
+    /// Ticks until the humanized current step fires, `None` once it has.
+    pending: Option<usize>,
+    rng_state: u32,
+}
+
+impl SyncGrid {
+    pub fn new() -> Self {
+        Self {
+            step: None,
+            pending: None,
+            rng_state: 1,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.step = None;
+        self.pending = None;
+    }
+
+    /// Moves to transport position `beats` and returns whether a step fires at this tick. Steps
+    /// are `length` quarter notes long, `swing` from zero to one delays every second step by up
+    /// to half a step, and `humanize` delays every step by a random number of ticks up to that
+    /// many.
+    pub fn tick(&mut self, beats: f64, length: f64, swing: f64, humanize: f32) -> bool {
+        let pair = (beats / (2.0 * length)).floor();
+        let offbeat = beats - pair * 2.0 * length >= length * (1.0 + swing * 0.5);
+        let step = pair as i64 * 2 + offbeat as i64;
+        if self.step.replace(step) != Some(step) {
+            let delay = if humanize > 0.0 {
+                self.next_random() * humanize
+            } else {
+                0.0
+            };
+            self.pending = Some(delay as usize);
+        }
+
+        match self.pending {
+            Some(0) => {
+                self.pending = None;
+                true
+            }
+            Some(ticks) => {
+                self.pending = Some(ticks - 1);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Uniform in `0..1`.
+    fn next_random(&mut self) -> f32 {
+        self.rng_state ^= self.rng_state << 13;
+        self.rng_state ^= self.rng_state >> 17;
+        self.rng_state ^= self.rng_state << 5;
+        self.rng_state as f32 / u32::MAX as f32
+    }
+}
+
+/// Derives a tempo from the intervals between button presses.
+#[cfg(feature = "editor")]
+#[derive(Default)]
+pub struct TapTempo {
+    taps: Vec<f64>,
+}
+
+#[cfg(feature = "editor")]
+impl TapTempo {
+    /// Registers a tap at `time` seconds and returns the tempo over the recent taps, or `None`
+    /// until there are at least two.
+    pub fn tap(&mut self, time: f64) -> Option<f64> {
+        if self
+            .taps
+            .last()
+            .is_some_and(|last| time - last > TAP_TIMEOUT)
+        {
+            self.taps.clear();
+        }
+        self.taps.push(time);
+        if self.taps.len() > MAX_TAPS {
+            self.taps.remove(0);
+        }
+
+        let intervals = self.taps.len() - 1;
+        let span = self.taps[intervals] - self.taps[0];
+        (intervals > 0 && span > 0.0).then(|| 60.0 * intervals as f64 / span)
+    }
+}
@@ -0,0 +1,89 @@
+use nih_plug::prelude::*;
+use rustfft::num_complex::Complex;
+
+/// Bins on each side used to estimate the local noise floor around a candidate peak.
+const FLOOR_RADIUS: usize = 8;
+/// Bins on each side of a peak shifted along with it in `HarmonyBins::Peaks`, the Hann window's
+/// main lobe and the skirt next to it.
+const PEAK_RADIUS: usize = 2;
+
+/// Which part of the spectrum the harmonizer and blur act on. The other part passes through.
+#[derive(Enum, Debug, Clone, Copy, PartialEq)]
+pub enum TonalSplit {
+    All,
+    Tonal,
+    Noisy,
+}
+
+/// Which bins feed the harmony voice.
+#[derive(Enum, Debug, Clone, Copy, PartialEq)]
+pub enum HarmonyBins {
+    All,
+    /// Only the strongest peaks of every frame and the bins around them, so the partials of
+    /// polyphonic material are shifted without the smear in between.
+    Peaks,
+}
+
+/// Marks every bin that belongs to a sinusoidal peak standing at least `threshold` (linear
+/// magnitude ratio) above the mean of its neighbourhood. The main lobe of the Hann window spans
+/// the peak bin and one bin on either side, so those are marked as well.
+pub fn find_tonal_bins(spectrum: &[Complex<f32>], threshold: f32, tonal: &mut [bool]) {
+    let len = tonal.len().min(spectrum.len());
+    tonal.fill(false);
+    if len < 3 {
+        return;
+    }
+
+    for i in 1..len - 1 {
+        let mag = spectrum[i].norm();
+        if mag <= spectrum[i - 1].norm() || mag < spectrum[i + 1].norm() {
+            continue;
+        }
+
+        let lo = i.saturating_sub(FLOOR_RADIUS);
+        let hi = (i + FLOOR_RADIUS).min(len - 1);
+        let floor =
+            spectrum[lo..=hi].iter().map(|bin| bin.norm()).sum::<f32>() / (hi - lo + 1) as f32;
+        if mag > floor * threshold {
+            tonal[i - 1..=i + 1].fill(true);
+        }
+    }
+}
+
+/// Marks the `count` strongest local maxima among the `allowed` bins of `spectrum`, and
+/// `PEAK_RADIUS` bins on either side of each, in `peaks`. `candidates` is scratch space that
+/// keeps its capacity between frames.
+pub fn find_strongest_peaks(
+    spectrum: &[Complex<f32>],
+    allowed: &[bool],
+    count: usize,
+    candidates: &mut Vec<(f32, usize)>,
+    peaks: &mut [bool],
+) {
+    let len = peaks.len().min(spectrum.len()).min(allowed.len());
+    peaks.fill(false);
+    candidates.clear();
+    if len < 3 || count == 0 {
+        return;
+    }
+
+    for i in 1..len - 1 {
+        let mag = spectrum[i].norm_sqr();
+        if allowed[i]
+            && mag > 0.0
+            && mag > spectrum[i - 1].norm_sqr()
+            && mag >= spectrum[i + 1].norm_sqr()
+        {
+            candidates.push((mag, i));
+        }
+    }
+    if candidates.len() > count {
+        candidates.select_nth_unstable_by(count - 1, |a, b| b.0.total_cmp(&a.0));
+        candidates.truncate(count);
+    }
+    for &(_, i) in candidates.iter() {
+        let lo = i.saturating_sub(PEAK_RADIUS);
+        let hi = (i + PEAK_RADIUS).min(len - 1);
+        peaks[lo..=hi].fill(true);
+    }
+}
@@ -0,0 +1,70 @@
+// --- DETECTOR TIMING (seconds) ---
+const FAST_RELEASE: f32 = 0.010;
+const SLOW_AVERAGE: f32 = 0.150;
+const HOLDOFF: f32 = 0.050;
+/// Envelopes below roughly -50 dBFS never trigger.
+const MIN_LEVEL: f32 = 0.003;
+
+/// Onset detector comparing a fast peak envelope against a slow average. Fires once per transient
+/// and then ignores the input for a short hold-off period.
+pub struct TransientDetector {
+    fast: f32,
+    slow: f32,
+    fast_coeff: f32,
+    slow_coeff: f32,
+    holdoff_samples: usize,
+    holdoff_left: usize,
+}
+
+impl TransientDetector {
+    pub fn new(sample_rate: f32) -> Self {
+        let mut detector = Self {
+            fast: 0.0,
+            slow: 0.0,
+            fast_coeff: 0.0,
+            slow_coeff: 0.0,
+            holdoff_samples: 0,
+            holdoff_left: 0,
+        };
+        detector.set_sample_rate(sample_rate);
+        detector
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.fast_coeff = (-1.0 / (FAST_RELEASE * sample_rate)).exp();
+        self.slow_coeff = (-1.0 / (SLOW_AVERAGE * sample_rate)).exp();
+        self.holdoff_samples = (HOLDOFF * sample_rate) as usize;
+    }
+
+    /// Ignores the input for `samples` after every onset instead of the default hold-off.
+    pub fn set_holdoff(&mut self, samples: usize) {
+        self.holdoff_samples = samples;
+    }
+
+    pub fn reset(&mut self) {
+        self.fast = 0.0;
+        self.slow = 0.0;
+        self.holdoff_left = 0;
+    }
+
+    /// Feeds one sample and returns `true` on an onset. `sensitivity` in `0..=1` lowers the
+    /// fast/slow ratio needed to fire from 4x down to 2x.
+    pub fn process(&mut self, input: f32, sensitivity: f32) -> bool {
+        let level = input.abs();
+        self.fast = level.max(self.fast * self.fast_coeff);
+        self.slow = self.slow * self.slow_coeff + level * (1.0 - self.slow_coeff);
+
+        if self.holdoff_left > 0 {
+            self.holdoff_left -= 1;
+            return false;
+        }
+
+        let ratio = 4.0 - 2.0 * sensitivity.clamp(0.0, 1.0);
+        if self.fast > MIN_LEVEL && self.fast > self.slow * ratio {
+            self.holdoff_left = self.holdoff_samples;
+            true
+        } else {
+            false
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use nih_plug::util;
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::ops::Range;
+
+/// Oversampling factor of the peak measurement, as in ITU-R BS.1770.
+const PHASES: usize = 4;
+const TAPS: usize = 12;
+/// Input samples the interpolated points lag behind the newest sample.
+const INTERP_DELAY: usize = TAPS / 2;
+/// Samples the limiter sees ahead of the gain it applies.
+const LOOKAHEAD: usize = 64;
+/// Extra latency while the limiter is on.
+pub const LIMITER_LATENCY: usize = LOOKAHEAD - 1 + INTERP_DELAY;
+const RELEASE_MS: f32 = 50.0;
+
+type Coefficients = [[f32; TAPS]; PHASES];
+
+/// Hann-windowed sinc interpolation at `PHASES` points between two input samples. Phase zero is
+/// the input sample itself.
+fn coefficients() -> Coefficients {
+    let mut coeffs = [[0.0; TAPS]; PHASES];
+    for (phase, taps) in coeffs.iter_mut().enumerate() {
+        let delay = INTERP_DELAY as f32 - phase as f32 / PHASES as f32;
+        for (tap, coeff) in taps.iter_mut().enumerate() {
+            let x = tap as f32 - delay;
+            let sinc = if x == 0.0 {
+                1.0
+            } else {
+                (PI * x).sin() / (PI * x)
+            };
+            let window = 0.5 + 0.5 * (PI * x / INTERP_DELAY as f32).cos();
+            *coeff = if x.abs() < INTERP_DELAY as f32 {
+                sinc * window
+            } else {
+                0.0
+            };
+        }
+        // Unity gain at DC for every phase
+        let sum: f32 = taps.iter().sum();
+        taps.iter_mut().for_each(|coeff| *coeff /= sum);
+    }
+    coeffs
+}
+
+/// One channel's oversampled peak detector.
+#[derive(Clone)]
+struct Interpolator {
+    /// Newest sample first.
+    history: [f32; TAPS],
+}
+
+impl Interpolator {
+    fn new() -> Self {
+        Self {
+            history: [0.0; TAPS],
+        }
+    }
+
+    /// Adds `sample` and returns the largest absolute value between the samples `INTERP_DELAY`
+    /// and `INTERP_DELAY - 1` back.
+    fn push(&mut self, sample: f32, coeffs: &Coefficients) -> f32 {
+        self.history.copy_within(0..TAPS - 1, 1);
+        self.history[0] = sample;
+        coeffs
+            .iter()
+            .map(|taps| {
+                taps.iter()
+                    .zip(&self.history)
+                    .map(|(coeff, x)| coeff * x)
+                    .sum::<f32>()
+                    .abs()
+            })
+            .fold(0.0, f32::max)
+    }
+}
+
+/// Inter-sample peak level of the output, latched until the editor clears it.
+pub struct TruePeakMeter {
+    coeffs: Coefficients,
+    detectors: Vec<Interpolator>,
+}
+
+impl TruePeakMeter {
+    pub fn new(num_channels: usize) -> Self {
+        Self {
+            coeffs: coefficients(),
+            detectors: vec![Interpolator::new(); num_channels],
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.detectors.fill(Interpolator::new());
+    }
+
+    /// The highest true peak in `range` of `channels`, as linear gain.
+    pub fn measure(&mut self, channels: &[&mut [f32]], range: Range<usize>) -> f32 {
+        let mut peak = 0.0f32;
+        for (detector, channel) in self.detectors.iter_mut().zip(channels) {
+            for &sample in &channel[range.clone()] {
+                peak = peak.max(detector.push(sample, &self.coeffs));
+            }
+        }
+        peak
+    }
+}
+
+/// Stereo-linked lookahead limiter that keeps the oversampled peaks below a ceiling. The gain
+/// reaches its minimum over a `LOOKAHEAD` long ramp that ends where the peak leaves the delay.
+pub struct TruePeakLimiter {
+    coeffs: Coefficients,
+    detectors: Vec<Interpolator>,
+    delays: Vec<VecDeque<f32>>,
+    /// Gain each of the last `LOOKAHEAD + 1` samples needs, newest last.
+    required: VecDeque<f32>,
+    /// Minimum of `required` for the last `LOOKAHEAD` samples, averaged into the ramp.
+    held: VecDeque<f32>,
+    gain: f32,
+    release: f32,
+}
+
+impl TruePeakLimiter {
+    pub fn new(num_channels: usize) -> Self {
+        let mut limiter = Self {
+            coeffs: coefficients(),
+            detectors: vec![Interpolator::new(); num_channels],
+            delays: vec![VecDeque::from(vec![0.0; LIMITER_LATENCY]); num_channels],
+            required: VecDeque::from(vec![1.0; LOOKAHEAD + 1]),
+            held: VecDeque::from(vec![1.0; LOOKAHEAD]),
+            gain: 1.0,
+            release: 0.0,
+        };
+        limiter.set_sample_rate(44100.0);
+        limiter
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.release = 1.0 - (-1.0 / (RELEASE_MS / 1000.0 * sample_rate)).exp();
+    }
+
+    pub fn reset(&mut self) {
+        self.detectors.fill(Interpolator::new());
+        for delay in self.delays.iter_mut() {
+            delay.iter_mut().for_each(|x| *x = 0.0);
+        }
+        self.required.iter_mut().for_each(|x| *x = 1.0);
+        self.held.iter_mut().for_each(|x| *x = 1.0);
+        self.gain = 1.0;
+    }
+
+    /// Limits `range` of `channels` in place to `ceiling_db` dBTP. `bypass` holds the bypass fade
+    /// for every sample in the range, a bypassed sample is only delayed.
+    pub fn process(
+        &mut self,
+        channels: &mut [&mut [f32]],
+        range: Range<usize>,
+        ceiling_db: f32,
+        bypass: &[f32],
+    ) {
+        let ceiling = util::db_to_gain(ceiling_db);
+        for (i, bypass) in range.zip(bypass) {
+            let mut peak = 0.0f32;
+            for (detector, channel) in self.detectors.iter_mut().zip(channels.iter()) {
+                peak = peak.max(detector.push(channel[i], &self.coeffs));
+            }
+
+            self.required.pop_front();
+            self.required.push_back((ceiling / peak).min(1.0));
+            let held = self.required.iter().copied().fold(1.0, f32::min);
+            self.held.pop_front();
+            self.held.push_back(held);
+            let target = self.held.iter().sum::<f32>() / LOOKAHEAD as f32;
+            self.gain = if target < self.gain {
+                target
+            } else {
+                self.gain + (target - self.gain) * self.release
+            };
+
+            let gain = self.gain + (1.0 - self.gain) * bypass;
+            for (delay, channel) in self.delays.iter_mut().zip(channels.iter_mut()) {
+                delay.push_back(channel[i]);
+                channel[i] = delay.pop_front().unwrap_or(0.0) * gain;
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{balance, FFT_SIZE, HOP_SIZE};
+use std::f32::consts::TAU;
+
+/// Most copies of the harmony voice.
+pub const MAX_VOICES: usize = 4;
+/// Detune of the outermost copies either way, in cents.
+const DETUNE: f32 = 12.0;
+/// Frames that overlap at any one sample.
+pub const OVERLAP: usize = FFT_SIZE / HOP_SIZE;
+
+/// The copies of the harmony voice one channel renders, each with its own detune and level.
+#[derive(Clone, Copy)]
+pub struct Unison {
+    count: usize,
+    ratios: [f32; MAX_VOICES],
+    gains: [f32; MAX_VOICES],
+    /// Index of the frame being rendered, modulo `OVERLAP`.
+    frame: usize,
+}
+
+impl Unison {
+    /// The harmony voice on its own.
+    pub const OFF: Self = Self {
+        count: 1,
+        ratios: [1.0; MAX_VOICES],
+        gains: [1.0; MAX_VOICES],
+        frame: 0,
+    };
+
+    /// Spreads `count` copies evenly across the detune range and, by `width`, across the stereo
+    /// field, lowest on the left. `channel` is the channel that renders them, `None` for mono.
+    pub fn new(count: usize, width: f32, channel: Option<usize>) -> Self {
+        let count = count.clamp(1, MAX_VOICES);
+        let mut unison = Self { count, ..Self::OFF };
+        if count == 1 {
+            return unison;
+        }
+
+        // Keeps the loudness of uncorrelated copies the same as the single voice
+        let level = (count as f32).sqrt().recip();
+        let copies = unison.ratios.iter_mut().zip(&mut unison.gains).take(count);
+        for (k, (ratio, gain)) in copies.enumerate() {
+            let position = k as f32 / (count - 1) as f32 * 2.0 - 1.0;
+            *ratio = (position * DETUNE / 1200.0).exp2();
+            *gain = level * channel.map_or(1.0, |channel| balance(position * width)[channel]);
+        }
+        unison
+    }
+
+    /// The same copies, rendered in frame number `frame`.
+    pub fn at_frame(self, frame: usize) -> Self {
+        Self {
+            frame: frame % OVERLAP,
+            ..self
+        }
+    }
+
+    /// The frequency ratio and level of every copy.
+    pub fn voices(&self) -> impl Iterator<Item = (f32, f32)> + '_ {
+        self.ratios
+            .iter()
+            .copied()
+            .zip(self.gains.iter().copied())
+            .take(self.count)
+    }
+
+    /// Phase to add to a copy moved from bin `source` to bin `target`. A bin's phase turns by
+    /// its index times the hop ratio every frame, and detuned copies rarely move by a multiple
+    /// of the overlap, so without this their overlapping frames cancel out. The single voice
+    /// keeps its phase untouched.
+    pub fn phase(&self, source: usize, target: usize) -> f32 {
+        if self.count == 1 {
+            return 0.0;
+        }
+        self.moved_phase(source, target)
+    }
+
+    /// Like `phase`, but for the single voice too, for copies moved by something else as well.
+    pub fn moved_phase(&self, source: usize, target: usize) -> f32 {
+        let moved = target as isize - source as isize;
+        let turn = (moved * (self.frame * HOP_SIZE) as isize).rem_euclid(FFT_SIZE as isize);
+        TAU * turn as f32 / FFT_SIZE as f32
+    }
+}
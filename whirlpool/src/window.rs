@@ -0,0 +1,107 @@
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+
+/// Seconds a window change takes to glide from the old shape to the new one.
+const GLIDE_TIME: f32 = 0.100;
+
+/// Analysis and synthesis window of the STFT.
+#[derive(Enum, Debug, Clone, Copy, PartialEq)]
+pub enum AnalysisWindow {
+    Hann,
+    /// Lower close-in sidelobes, for slightly sharper peaks.
+    Hamming,
+    /// Wider main lobe but much lower leakage, for clean isolation of strong partials.
+    Blackman,
+    #[name = "Blackman-Harris"]
+    BlackmanHarris,
+}
+
+impl AnalysisWindow {
+    /// Sample `i` of a window `len + 1` samples long.
+    fn value(self, i: f32, len: f32) -> f32 {
+        let cos = |k: f32| (2.0 * PI * k * i / len).cos();
+        match self {
+            AnalysisWindow::Hann => 0.5 * (1.0 - cos(1.0)),
+            AnalysisWindow::Hamming => 0.54 - 0.46 * cos(1.0),
+            AnalysisWindow::Blackman => 0.42 - 0.5 * cos(1.0) + 0.08 * cos(2.0),
+            AnalysisWindow::BlackmanHarris => {
+                0.35875 - 0.48829 * cos(1.0) + 0.14128 * cos(2.0) - 0.01168 * cos(3.0)
+            }
+        }
+    }
+
+    /// Fills `window` with this shape, scaled so that applied on analysis and synthesis it
+    /// overlap-adds to the same level as Hann.
+    pub fn fill(self, window: &mut [f32]) {
+        let len = window.len() as f32 - 1.0;
+        let mut hann_sum = 0.0;
+        let mut sum = 0.0;
+        for (i, w) in window.iter_mut().enumerate() {
+            let hann = AnalysisWindow::Hann.value(i as f32, len);
+            *w = self.value(i as f32, len);
+            hann_sum += hann * hann;
+            sum += *w * *w;
+        }
+
+        let gain = (hann_sum / sum).sqrt();
+        window.iter_mut().for_each(|w| *w *= gain);
+    }
+}
+
+/// Moves the STFT window to a new shape over a few frames. Consecutive frames then use nearly
+/// identical windows, so the overlap-add stays smooth while the shape changes and switching
+/// never clicks.
+pub struct WindowGlide {
+    shape: AnalysisWindow,
+    from: Vec<f32>,
+    to: Vec<f32>,
+    /// Position in the glide from `from` to `to`, one when there is nothing to do.
+    progress: f32,
+    hop: usize,
+}
+
+impl WindowGlide {
+    /// A glide resting at `shape`, which `window` is filled with.
+    pub fn new(shape: AnalysisWindow, window: &mut [f32], hop: usize) -> Self {
+        shape.fill(window);
+        Self {
+            shape,
+            from: window.to_vec(),
+            to: window.to_vec(),
+            progress: 1.0,
+            hop,
+        }
+    }
+
+    /// Starts gliding from the current `window` to `shape`, unless that is already the target.
+    pub fn set_shape(&mut self, shape: AnalysisWindow, window: &[f32]) {
+        if shape == self.shape {
+            return;
+        }
+        self.shape = shape;
+        self.from.copy_from_slice(window);
+        shape.fill(&mut self.to);
+        self.progress = 0.0;
+    }
+
+    /// Jumps to the end of a running glide.
+    pub fn finish(&mut self, window: &mut [f32]) {
+        window.copy_from_slice(&self.to);
+        self.progress = 1.0;
+    }
+
+    /// Moves one hop further along the glide and writes the window for the next frame. Returns
+    /// whether the glide ended with this hop.
+    pub fn advance(&mut self, window: &mut [f32], sample_rate: f32) -> bool {
+        if self.progress >= 1.0 {
+            return false;
+        }
+
+        self.progress = (self.progress + self.hop as f32 / (GLIDE_TIME * sample_rate)).min(1.0);
+        let t = self.progress;
+        for ((w, from), to) in window.iter_mut().zip(&self.from).zip(&self.to) {
+            *w = from + (to - from) * t;
+        }
+        self.progress >= 1.0
+    }
+}
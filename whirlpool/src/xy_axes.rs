@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+use crate::smoothing::Smoothed;
+
+/// Blur on X and Harmonics on Y.
+const DEFAULT_PARAMS: [Smoothed; 2] = [Smoothed::Blur, Smoothed::Harmonics];
+
+/// One axis of the editor's XY pad.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct XyAxis {
+    /// ID of the [`Smoothed`] parameter the axis controls.
+    pub param: String,
+    /// MIDI CC learned for the axis.
+    pub cc: Option<u8>,
+}
+
+/// The parameters on the XY pad's X and Y axes. Like the morph presets they are stored by
+/// parameter ID, so the assignment survives parameters being added or reordered.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct XyAxes {
+    pub axes: [XyAxis; 2],
+}
+
+impl Default for XyAxes {
+    fn default() -> Self {
+        Self {
+            axes: DEFAULT_PARAMS.map(|param| XyAxis {
+                param: param.id().to_owned(),
+                cc: None,
+            }),
+        }
+    }
+}
+
+impl XyAxes {
+    /// The parameter on `axis`, 0 for X and 1 for Y. Unknown IDs fall back to the default.
+    pub fn param(&self, axis: usize) -> Smoothed {
+        Smoothed::from_id(&self.axes[axis].param).unwrap_or(DEFAULT_PARAMS[axis])
+    }
+
+    /// Assigns `cc` to `axis`, taking it away from the other axis.
+    pub fn learn(&mut self, axis: usize, cc: u8) {
+        for other in self.axes.iter_mut() {
+            if other.cc == Some(cc) {
+                other.cc = None;
+            }
+        }
+        self.axes[axis].cc = Some(cc);
+    }
+}
@@ -0,0 +1,43 @@
+//! Checks that the spectral average sustains a partial after its source stops.
+
+mod common;
+
+use common::{float_param, rms, BLOCK_SIZE, SAMPLE_RATE};
+use std::f32::consts::PI;
+use whirlpool::WhirlpoolParams;
+
+/// Output RMS over the half second after a half second sine burst has passed the latency.
+fn tail_rms(average: f32) -> f32 {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        average: float_param("Average", average, 0.0, 1.0),
+        ..WhirlpoolParams::default()
+    };
+
+    let burst = SAMPLE_RATE as usize / 2;
+    let input: Vec<f32> = (0..2 * burst)
+        .map(|i| {
+            let sine = (2.0 * PI * 440.0 * i as f32 / SAMPLE_RATE).sin();
+            if i < burst {
+                0.25 * sine
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    let (mut plugin, latency) = common::plugin_with_latency(params);
+    let output = common::render(&mut plugin, &[input.clone(), input], BLOCK_SIZE);
+
+    // Leaves out the last frames that still overlap the burst
+    let tail = &output[0][burst + latency + 1024..];
+    rms(tail)
+}
+
+#[test]
+fn average_sustains_after_the_source_stops() {
+    let dry = tail_rms(0.0);
+    let washed = tail_rms(1.0);
+    assert!(dry < 1e-4, "{dry} left without averaging");
+    assert!(washed > 0.02, "only {washed} sustained with averaging");
+}
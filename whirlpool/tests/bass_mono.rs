@@ -0,0 +1,52 @@
+//! Checks that bass mono removes the stereo difference below the crossover and keeps it above.
+
+mod common;
+
+use common::{float_param, rms, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::WhirlpoolParams;
+
+/// Level of the side signal left over from an out of phase sine at `freq`, relative to the input.
+fn remaining_side(freq: f32) -> f32 {
+    let params = WhirlpoolParams {
+        mix: float_param("Dry/Wet", 0.0, 0.0, 1.0),
+        bass_mono: BoolParam::new("Bass Mono", true),
+        ..WhirlpoolParams::default()
+    };
+
+    let len = SAMPLE_RATE as usize;
+    let left: Vec<f32> = (0..len)
+        .map(|i| 0.5 * (2.0 * PI * freq * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let right: Vec<f32> = left.iter().map(|x| -x).collect();
+    let output = common::render(
+        &mut common::plugin(params),
+        &[left.clone(), right],
+        BLOCK_SIZE,
+    );
+
+    // Skip the latency and the filters settling
+    let tail = len / 2;
+    let side: Vec<f32> = output[0][tail..]
+        .iter()
+        .zip(&output[1][tail..])
+        .map(|(l, r)| (l - r) * 0.5)
+        .collect();
+    rms(&side) / rms(&left[tail..])
+}
+
+#[test]
+fn lows_are_mono() {
+    let side = remaining_side(40.0);
+    assert!(side < 0.1, "{side} of the side signal is left at 40 Hz");
+}
+
+#[test]
+fn highs_stay_stereo() {
+    let side = remaining_side(2000.0);
+    assert!(
+        side > 0.95,
+        "only {side} of the side signal is left at 2 kHz"
+    );
+}
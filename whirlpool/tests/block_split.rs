@@ -0,0 +1,145 @@
+//! With sample-accurate automation the wrapper splits host buffers wherever a parameter changes,
+//! so the output must not depend on where the block boundaries fall, and a change must take
+//! effect on the sample it arrives on.
+
+mod common;
+
+use common::float_param;
+use nih_plug::prelude::*;
+use whirlpool::WhirlpoolParams;
+
+/// Single samples, sizes that straddle the hop in both directions, and a whole host buffer.
+const SPLIT_SIZES: &[usize] = &[1, 17, 64, 512, 4096];
+
+fn params() -> WhirlpoolParams {
+    WhirlpoolParams {
+        harmonics: float_param("Harmonics", 1.0, 0.0, 1.0),
+        shift: float_param("Shift", 1.5, 0.5, 2.0),
+        blur: float_param("Blur", 0.4, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        grain_feedback: BoolParam::new("Grain Feedback", true),
+        delay_time: float_param("Delay Time", 80.0, 50.0, 2000.0),
+        ..WhirlpoolParams::default()
+    }
+}
+
+/// The grain delay with everything that keeps state across segments: modulated read heads,
+/// several grains, saturation, cross-feedback and a modulated rotation.
+fn delay_params() -> WhirlpoolParams {
+    WhirlpoolParams {
+        rotate_mod_depth: IntParam::new("Rotate Mod", 12, IntRange::Linear { min: 0, max: 512 }),
+        pre_delay: float_param("Pre-Delay", 40.0, 0.0, 500.0),
+        grain_voices: IntParam::new("Grain Voices", 4, IntRange::Linear { min: 2, max: 8 }),
+        delay_mod_depth: float_param("Mod Depth", 3.0, 0.0, 20.0),
+        delay_wow: float_param("Wow", 0.5, 0.0, 1.0),
+        delay_flutter: float_param("Flutter", 0.5, 0.0, 1.0),
+        delay_saturation: float_param("Saturation", 0.6, 0.0, 1.0),
+        delay_cross: float_param("Cross Feedback", 0.7, 0.0, 1.0),
+        ..params()
+    }
+}
+
+fn assert_independent_of_block_size(params: fn() -> WhirlpoolParams) {
+    let input = common::read_wav(&common::fixture_path("fixtures/input.wav"));
+    let reference = common::render(&mut common::plugin(params()), &input, SPLIT_SIZES[0]);
+
+    for &block_size in &SPLIT_SIZES[1..] {
+        let output = common::render(&mut common::plugin(params()), &input, block_size);
+        for (ch, (output, reference)) in output.iter().zip(&reference).enumerate() {
+            let mismatch = output
+                .iter()
+                .zip(reference)
+                .position(|(a, b)| a.to_bits() != b.to_bits());
+            assert!(
+                mismatch.is_none(),
+                "channel {ch}: sample {mismatch:?} differs between {} and {block_size} sample \
+                 blocks",
+                SPLIT_SIZES[0]
+            );
+        }
+    }
+}
+
+/// Where Dry/Wet is moved to fully wet: 300 samples into the third of the host's 1024 sample
+/// buffers, well past the latency so the dry and wet signals both sound.
+const CHANGE_AT: usize = 2 * 1024 + 300;
+const HOST_BUFFER: usize = 1024;
+
+/// Dry/Wet starting fully dry, on the XY pad's X axis with CC 1 learned for it.
+fn cc_params() -> WhirlpoolParams {
+    let params = WhirlpoolParams {
+        mix: float_param("Dry/Wet", 0.0, 0.0, 1.0),
+        ..params()
+    };
+    {
+        let mut axes = params.xy_axes.write().unwrap();
+        axes.axes[0].param = "mix".to_owned();
+        axes.learn(0, 1);
+    }
+    params
+}
+
+fn to_wet(timing: u32) -> NoteEvent<()> {
+    NoteEvent::MidiCC {
+        timing,
+        channel: 0,
+        cc: 1,
+        value: 1.0,
+    }
+}
+
+/// Index of the first sample where `a` and `b` differ in any channel.
+fn first_difference(a: &[Vec<f32>], b: &[Vec<f32>]) -> Option<usize> {
+    a.iter()
+        .zip(b)
+        .filter_map(|(a, b)| {
+            a.iter()
+                .zip(b)
+                .position(|(x, y)| x.to_bits() != y.to_bits())
+        })
+        .min()
+}
+
+#[test]
+fn mid_buffer_change_ramps_from_its_sample() {
+    let input = common::read_wav(&common::fixture_path("fixtures/input.wav"));
+    let unchanged = common::render(&mut common::plugin(cc_params()), &input, HOST_BUFFER);
+    let mid_buffer = common::render_notes(
+        &mut common::plugin(cc_params()),
+        &input,
+        HOST_BUFFER,
+        &[to_wet(CHANGE_AT as u32)],
+    );
+    assert_eq!(
+        first_difference(&unchanged, &mid_buffer),
+        Some(CHANGE_AT),
+        "the ramp did not start on the sample the change arrived on"
+    );
+
+    // The same change at the start of a host buffer that ends right before it
+    let mut plugin = common::plugin(cc_params());
+    let (before, after): (Vec<_>, Vec<_>) = input
+        .iter()
+        .map(|channel| (channel[..CHANGE_AT].to_vec(), channel[CHANGE_AT..].to_vec()))
+        .unzip();
+    let mut on_boundary = common::render(&mut plugin, &before, HOST_BUFFER);
+    let rest = common::render_notes(&mut plugin, &after, HOST_BUFFER, &[to_wet(0)]);
+    for (channel, rest) in on_boundary.iter_mut().zip(rest) {
+        channel.extend(rest);
+    }
+    assert_eq!(
+        first_difference(&mid_buffer, &on_boundary),
+        None,
+        "a change inside a buffer differs from the same change on a buffer boundary"
+    );
+}
+
+#[test]
+fn output_does_not_depend_on_block_splits() {
+    assert_independent_of_block_size(params);
+}
+
+#[test]
+fn delay_does_not_depend_on_block_splits() {
+    assert_independent_of_block_size(delay_params);
+}
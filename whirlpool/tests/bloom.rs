@@ -0,0 +1,78 @@
+//! Checks that the bloom swells the wet signal in after onsets and fades it out after the input.
+
+mod common;
+
+use common::{float_param, rms, BLOCK_SIZE, SAMPLE_RATE};
+use std::f32::consts::PI;
+use whirlpool::WhirlpoolParams;
+
+/// Wet output of a sine burst from `start` to `end` samples into a second and a half.
+fn render(params: WhirlpoolParams, start: usize, end: usize) -> (Vec<f32>, usize) {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        ..params
+    };
+    let input: Vec<f32> = (0..3 * SAMPLE_RATE as usize / 2)
+        .map(|i| {
+            let sine = (2.0 * PI * 440.0 * i as f32 / SAMPLE_RATE).sin();
+            if (start..end).contains(&i) {
+                0.25 * sine
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    let (mut plugin, latency) = common::plugin_with_latency(params);
+    let output = common::render(&mut plugin, &[input.clone(), input], BLOCK_SIZE);
+    (output[0].clone(), latency)
+}
+
+fn bloom(attack: f32, release: f32) -> WhirlpoolParams {
+    WhirlpoolParams {
+        bloom_attack: float_param("Bloom Attack", attack, 0.0, 2000.0),
+        bloom_release: float_param("Bloom Release", release, 0.0, 5000.0),
+        ..WhirlpoolParams::default()
+    }
+}
+
+#[test]
+fn attack_swells_in_after_an_onset() {
+    let onset = SAMPLE_RATE as usize / 4;
+    let (plain, latency) = render(WhirlpoolParams::default(), onset, usize::MAX);
+    let (bloomed, _) = render(bloom(800.0, 0.0), onset, usize::MAX);
+    let window = SAMPLE_RATE as usize / 10;
+    // Past the frames still overlapping the silence before the onset
+    let early = onset + latency + 1024;
+    let early = rms(&bloomed[early..][..window]) / rms(&plain[early..][..window]);
+    let late = onset + latency + SAMPLE_RATE as usize;
+    let late = rms(&bloomed[late..][..window]) / rms(&plain[late..][..window]);
+    assert!(
+        early < 0.3,
+        "the wet was already at {early} of its level right after the onset"
+    );
+    assert!(
+        (late - 1.0).abs() < 0.01,
+        "the wet only swelled to {late} of its level"
+    );
+}
+
+#[test]
+fn release_fades_out_after_the_input() {
+    let end = SAMPLE_RATE as usize / 2;
+    // The average keeps the wet ringing after the input stops
+    let ringing = |release| WhirlpoolParams {
+        average: float_param("Average", 1.0, 0.0, 1.0),
+        ..bloom(0.0, release)
+    };
+    let (held, latency) = render(ringing(0.0), 0, end);
+    let (released, _) = render(ringing(100.0), 0, end);
+    let tail = end + latency + SAMPLE_RATE as usize / 2;
+    let held = rms(&held[tail..]);
+    let released = rms(&released[tail..]);
+    assert!(held > 0.01, "only {held} rang on without a release");
+    assert!(
+        released < held * 0.01,
+        "{released} left of {held} after the release"
+    );
+}
@@ -0,0 +1,58 @@
+//! Checks that the blur hold keeps the random phases between refreshes, so a steady input stays
+//! steady instead of turning into a shimmer.
+
+mod common;
+
+use common::{float_param, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::WhirlpoolParams;
+
+const HOP_SIZE: usize = 256;
+/// Hops rendered while the plugin settles, well clear of the refresh at the first frame.
+const SETTLE_HOPS: usize = 20;
+/// Hops compared, ending before a hold of 64 frames refreshes again.
+const COMPARED_HOPS: usize = 30;
+
+/// Largest difference between the output and itself one hop later, relative to the peak. A sine
+/// repeating every hop makes identical frames, so this is zero when the phases are held.
+fn hop_to_hop_change(blur_hold: i32) -> f32 {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        blur: float_param("Blur", 1.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        blur_hold: IntParam::new("Blur Hold", blur_hold, IntRange::Linear { min: 1, max: 64 }),
+        ..WhirlpoolParams::default()
+    };
+
+    let len = (SETTLE_HOPS + COMPARED_HOPS + 1) * HOP_SIZE;
+    let freq = SAMPLE_RATE * 8.0 / HOP_SIZE as f32;
+    let sine: Vec<f32> = (0..len)
+        .map(|i| 0.5 * (2.0 * PI * freq * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let output = common::render(
+        &mut common::plugin(params),
+        &[sine.clone(), sine],
+        BLOCK_SIZE,
+    );
+
+    let compared = &output[0][SETTLE_HOPS * HOP_SIZE..];
+    let peak = compared.iter().fold(0.0f32, |peak, x| peak.max(x.abs()));
+    let change = compared
+        .iter()
+        .zip(&compared[HOP_SIZE..])
+        .fold(0.0f32, |change, (a, b)| change.max((a - b).abs()));
+    change / peak
+}
+
+#[test]
+fn held_phases_repeat_every_frame() {
+    let change = hop_to_hop_change(64);
+    assert!(change < 1e-3, "held frames still change by {change}");
+}
+
+#[test]
+fn phases_change_every_frame_without_hold() {
+    let change = hop_to_hop_change(1);
+    assert!(change > 0.1, "frames only change by {change}");
+}
@@ -0,0 +1,61 @@
+//! Checks that a longer grain buffer lets a slow scan reach further back before it wraps.
+
+mod common;
+
+use common::{amplitude, float_param, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use std::sync::atomic::AtomicU8;
+use std::sync::Arc;
+use whirlpool::WhirlpoolParams;
+
+const BIN_HZ: f32 = SAMPLE_RATE / 1024.0;
+const TONE: f32 = 40.0 * BIN_HZ;
+
+/// Level of the partials within four bins of `freq`, which the grains' random phases spread
+/// out.
+fn band(samples: &[f32], freq: f32) -> f32 {
+    (-8..=8)
+        .map(|k| amplitude(samples, freq + k as f32 * BIN_HZ / 2.0).powi(2))
+        .sum::<f32>()
+        .sqrt()
+}
+
+/// Level of the tone two seconds after a second of it gave way to silence, with the grains
+/// scanning at a quarter speed through a buffer of `seconds`. Little feedback, so the tone
+/// recorded again by grains that still reached it fades fast.
+fn tail(seconds: u8) -> f32 {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        grain_feedback: BoolParam::new("Grain Feedback", true),
+        delay_feedback: float_param("Delay Feedback", 0.1, 0.0, 0.95),
+        stretch: float_param("Stretch", 0.25, 0.25, 4.0),
+        buffer_length: Arc::new(AtomicU8::new(seconds)),
+        ..WhirlpoolParams::default()
+    };
+
+    let second = SAMPLE_RATE as usize;
+    let input: Vec<f32> = (0..4 * second)
+        .map(|i| {
+            if i < second {
+                0.1 * (2.0 * PI * TONE * i as f32 / SAMPLE_RATE).sin()
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    let mut plugin = common::plugin(params);
+    let output = common::render(&mut plugin, &[input.clone(), input], BLOCK_SIZE);
+    band(&output[0][3 * second..][..16 * 1024], TONE)
+}
+
+#[test]
+fn long_buffer_keeps_the_past_within_reach() {
+    let long = tail(20);
+    let short = tail(2);
+    assert!(
+        long > short * 4.0,
+        "{long} with a 20 s buffer against {short} with 2 s"
+    );
+}
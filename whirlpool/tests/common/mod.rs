@@ -0,0 +1,246 @@
+//! Shared helpers for driving Whirlpool offline in the integration tests.
+
+#![allow(dead_code)]
+
+use nih_plug::prelude::*;
+use std::cell::Cell;
+use std::f32::consts::PI;
+use std::path::{Path, PathBuf};
+use whirlpool::{BufferTask, Whirlpool, WhirlpoolParams};
+
+pub const SAMPLE_RATE: f32 = 44100.0;
+pub const BLOCK_SIZE: usize = 512;
+pub const MAX_BLOCK_SIZE: usize = 4096;
+
+#[derive(Default)]
+struct TestInitContext {
+    latency: Cell<u32>,
+}
+
+impl InitContext<Whirlpool> for TestInitContext {
+    fn plugin_api(&self) -> PluginApi {
+        PluginApi::Clap
+    }
+
+    fn execute(&self, _task: BufferTask) {}
+
+    fn set_latency_samples(&self, samples: u32) {
+        self.latency.set(samples);
+    }
+
+    fn set_current_voice_capacity(&self, _capacity: u32) {}
+}
+
+/// Creates an initialized and reset plugin, the same state a host leaves it in before the first
+/// `process()` call.
+pub fn plugin(params: WhirlpoolParams) -> Whirlpool {
+    plugin_with_latency(params).0
+}
+
+/// Like [`plugin()`], also returning the latency the plugin reported to the host.
+pub fn plugin_with_latency(params: WhirlpoolParams) -> (Whirlpool, usize) {
+    plugin_at(params, SAMPLE_RATE)
+}
+
+/// Like [`plugin_with_latency()`], initialized at `sample_rate`.
+pub fn plugin_at(params: WhirlpoolParams, sample_rate: f32) -> (Whirlpool, usize) {
+    plugin_with_layout(params, sample_rate, 0)
+}
+
+/// Like [`plugin_at()`], initialized with the `layout`th of the plugin's audio layouts.
+pub fn plugin_with_layout(
+    params: WhirlpoolParams,
+    sample_rate: f32,
+    layout: usize,
+) -> (Whirlpool, usize) {
+    let mut plugin = Whirlpool::with_params(params);
+    let latency = initialize(&mut plugin, sample_rate, layout);
+    (plugin, latency)
+}
+
+/// Initializes and resets `plugin` like [`plugin_with_layout()`], returning the latency it
+/// reported to the host.
+pub fn initialize(plugin: &mut Whirlpool, sample_rate: f32, layout: usize) -> usize {
+    let layout = <Whirlpool as Plugin>::AUDIO_IO_LAYOUTS[layout];
+    let buffer_config = BufferConfig {
+        sample_rate,
+        min_buffer_size: None,
+        max_buffer_size: MAX_BLOCK_SIZE as u32,
+        process_mode: ProcessMode::Offline,
+    };
+    let mut context = TestInitContext::default();
+    assert!(plugin.initialize(&layout, &buffer_config, &mut context));
+    plugin.reset();
+    context.latency.get() as usize
+}
+
+pub fn float_param(name: &str, value: f32, min: f32, max: f32) -> FloatParam {
+    FloatParam::new(name, value, FloatRange::Linear { min, max })
+}
+
+/// Root mean square level of `samples`.
+pub fn rms(samples: &[f32]) -> f32 {
+    (samples.iter().map(|x| x * x).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+/// Amplitude of the partial at `freq` in `samples`.
+pub fn amplitude(samples: &[f32], freq: f32) -> f32 {
+    amplitude_at(samples, freq, SAMPLE_RATE)
+}
+
+/// Amplitude of the partial at `freq` in `samples` recorded at `sample_rate`.
+pub fn amplitude_at(samples: &[f32], freq: f32, sample_rate: f32) -> f32 {
+    let (mut re, mut im) = (0.0, 0.0);
+    for (i, x) in samples.iter().enumerate() {
+        let phase = 2.0 * PI * freq * i as f32 / sample_rate;
+        re += x * phase.cos();
+        im += x * phase.sin();
+    }
+    2.0 * (re * re + im * im).sqrt() / samples.len() as f32
+}
+
+/// Deterministic white noise, so a failing case comes down to a seed instead of a huge sample
+/// vector.
+pub fn noise(seed: u32, amplitude: f32, len: usize) -> Vec<f32> {
+    let mut state = seed | 1;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            (state as f32 / u32::MAX as f32 * 2.0 - 1.0) * amplitude
+        })
+        .collect()
+}
+
+/// Renders `input` through `plugin` in host-sized blocks and returns the output channels.
+pub fn render(plugin: &mut Whirlpool, input: &[Vec<f32>], block_size: usize) -> Vec<Vec<f32>> {
+    render_notes(plugin, input, block_size, &[])
+}
+
+/// Like [`render()`], with `events` timed from the start of `input` and in order.
+pub fn render_notes(
+    plugin: &mut Whirlpool,
+    input: &[Vec<f32>],
+    block_size: usize,
+    events: &[NoteEvent<()>],
+) -> Vec<Vec<f32>> {
+    let mut output = input.to_vec();
+    let num_samples = output.first().map_or(0, Vec::len);
+
+    let mut start = 0;
+    while start < num_samples {
+        let end = (start + block_size).min(num_samples);
+        let mut block: Vec<&mut [f32]> = output
+            .iter_mut()
+            .map(|channel| &mut channel[start..end])
+            .collect();
+        let block_events: Vec<NoteEvent<()>> = events
+            .iter()
+            .filter(|event| (start..end).contains(&(event.timing() as usize)))
+            .map(|&event| retimed(event, (event.timing() as usize - start) as u32))
+            .collect();
+        plugin.render_with_notes(&mut block, None, &block_events);
+        start = end;
+    }
+
+    output
+}
+
+/// `event` moved to `timing`. Events the tests do not send are passed on as they are, with the
+/// timing they came with.
+fn retimed(event: NoteEvent<()>, timing: u32) -> NoteEvent<()> {
+    match event {
+        NoteEvent::NoteOn {
+            voice_id,
+            channel,
+            note,
+            velocity,
+            ..
+        } => NoteEvent::NoteOn {
+            timing,
+            voice_id,
+            channel,
+            note,
+            velocity,
+        },
+        NoteEvent::NoteOff {
+            voice_id,
+            channel,
+            note,
+            velocity,
+            ..
+        } => NoteEvent::NoteOff {
+            timing,
+            voice_id,
+            channel,
+            note,
+            velocity,
+        },
+        NoteEvent::MidiPitchBend { channel, value, .. } => NoteEvent::MidiPitchBend {
+            timing,
+            channel,
+            value,
+        },
+        NoteEvent::MidiCC {
+            channel, cc, value, ..
+        } => NoteEvent::MidiCC {
+            timing,
+            channel,
+            cc,
+            value,
+        },
+        _ => event,
+    }
+}
+
+pub fn fixture_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join(name)
+}
+
+/// Reads a WAV file as deinterleaved float channels.
+pub fn read_wav(path: &Path) -> Vec<Vec<f32>> {
+    let mut reader = hound::WavReader::open(path)
+        .unwrap_or_else(|err| panic!("Could not open '{}': {err}", path.display()));
+    let spec = reader.spec();
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().map(Result::unwrap).collect(),
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|sample| sample.unwrap() as f32 / scale)
+                .collect()
+        }
+    };
+
+    let num_channels = spec.channels as usize;
+    (0..num_channels)
+        .map(|ch| {
+            samples
+                .iter()
+                .skip(ch)
+                .step_by(num_channels)
+                .copied()
+                .collect()
+        })
+        .collect()
+}
+
+pub fn write_wav(path: &Path, channels: &[Vec<f32>]) {
+    let spec = hound::WavSpec {
+        channels: channels.len() as u16,
+        sample_rate: SAMPLE_RATE as u32,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let mut writer = hound::WavWriter::create(path, spec).unwrap();
+    for i in 0..channels[0].len() {
+        for channel in channels {
+            writer.write_sample(channel[i]).unwrap();
+        }
+    }
+    writer.finalize().unwrap();
+}
@@ -0,0 +1,47 @@
+//! Checks that drift slowly moves the level of a steady tone, which otherwise stays put.
+
+mod common;
+
+use common::{float_param, rms, BLOCK_SIZE, SAMPLE_RATE};
+use std::f32::consts::PI;
+use whirlpool::WhirlpoolParams;
+
+/// Ratio of the loudest to the quietest quarter second of a steady sine.
+fn level_swing(drift: f32) -> f32 {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        drift: float_param("Drift", drift, 0.0, 1.0),
+        ..WhirlpoolParams::default()
+    };
+
+    let len = 12 * SAMPLE_RATE as usize;
+    let input: Vec<f32> = (0..len)
+        .map(|i| 0.1 * (2.0 * PI * 1000.0 * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let output = common::render(
+        &mut common::plugin(params),
+        &[input.clone(), input],
+        BLOCK_SIZE,
+    );
+
+    let levels: Vec<f32> = output[0][SAMPLE_RATE as usize..]
+        .chunks(SAMPLE_RATE as usize / 4)
+        .map(rms)
+        .collect();
+    let max = levels.iter().copied().fold(0.0, f32::max);
+    let min = levels.iter().copied().fold(f32::INFINITY, f32::min);
+    max / min
+}
+
+#[test]
+fn steady_without_drift() {
+    let swing = level_swing(0.0);
+    assert!(swing < 1.01, "level swung by {swing}");
+}
+
+#[test]
+fn drift_moves_the_level() {
+    let swing = level_swing(1.0);
+    assert!(swing > 1.1, "level only swung by {swing}");
+}
@@ -0,0 +1,50 @@
+//! Checks that the dry tone controls shape the dry signal.
+
+mod common;
+
+use common::{float_param, rms, BLOCK_SIZE, SAMPLE_RATE};
+use std::f32::consts::PI;
+use whirlpool::WhirlpoolParams;
+
+/// RMS of the dry only output for a sine at `freq`, once the filters have settled.
+fn dry_rms(freq: f32, low_cut: f32, tilt: f32) -> f32 {
+    let params = WhirlpoolParams {
+        mix: float_param("Dry/Wet", 0.0, 0.0, 1.0),
+        dry_low_cut: float_param("Dry Low Cut", low_cut, 20.0, 2000.0),
+        dry_tilt: float_param("Dry Tilt", tilt, -6.0, 6.0),
+        ..WhirlpoolParams::default()
+    };
+
+    let len = SAMPLE_RATE as usize;
+    let input: Vec<f32> = (0..len)
+        .map(|i| 0.5 * (2.0 * PI * freq * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let output = common::render(
+        &mut common::plugin(params),
+        &[input.clone(), input],
+        BLOCK_SIZE,
+    );
+
+    let tail = &output[0][len / 2..];
+    rms(tail)
+}
+
+#[test]
+fn low_cut_thins_the_dry_signal() {
+    let open = dry_rms(50.0, 20.0, 0.0);
+    let cut = dry_rms(50.0, 1000.0, 0.0);
+    assert!(
+        cut < open * 0.01,
+        "{cut} of {open} passed a 1 kHz low cut at 50 Hz"
+    );
+}
+
+#[test]
+fn tilt_turns_around_the_pivot() {
+    let flat = [dry_rms(50.0, 20.0, 0.0), dry_rms(15000.0, 20.0, 0.0)];
+    let bright = [dry_rms(50.0, 20.0, 6.0), dry_rms(15000.0, 20.0, 6.0)];
+    let [low, high] = [bright[0] / flat[0], bright[1] / flat[1]];
+    // The one-pole split is gentle, so only check the ends move well past 3 dB either way
+    assert!(low < 0.7, "the lows only went down to {low}");
+    assert!(high > 1.4, "the highs only went up to {high}");
+}
@@ -0,0 +1,73 @@
+//! Checks that the Euclidean pattern stutters the spectrum on its hits and nowhere else, turned
+//! by the rotation.
+
+mod common;
+
+use common::{float_param, rms, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::{EuclidTarget, NoteDivision, WhirlpoolParams};
+
+/// A quarter note at the internal clock's default 120 BPM.
+const STEP: usize = SAMPLE_RATE as usize / 2;
+const STEPS: usize = 8;
+
+/// Level of every quarter note step, two hits in four stuttering a sine that only sounds
+/// through the last quarter of each step. Measured in the silence early in the step, where
+/// only a stutter still holds the sine.
+fn step_levels(rotation: i32) -> Vec<f32> {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        euclid: EnumParam::new("Euclid", EuclidTarget::Stutter),
+        euclid_steps: IntParam::new("Euclid Steps", 4, IntRange::Linear { min: 1, max: 32 }),
+        euclid_fills: IntParam::new("Euclid Fills", 2, IntRange::Linear { min: 0, max: 32 }),
+        euclid_rotation: IntParam::new(
+            "Euclid Rotation",
+            rotation,
+            IntRange::Linear { min: 0, max: 31 },
+        ),
+        euclid_division: EnumParam::new("Euclid Rate", NoteDivision::Quarter),
+        ..WhirlpoolParams::default()
+    };
+
+    let input: Vec<f32> = (0..STEPS * STEP)
+        .map(|i| {
+            let on = i % STEP >= STEP * 3 / 4;
+            on as u8 as f32 * 0.1 * (2.0 * PI * 440.0 * i as f32 / SAMPLE_RATE).sin()
+        })
+        .collect();
+    let mut plugin = common::plugin(params);
+    let output = common::render(&mut plugin, &[input.clone(), input], BLOCK_SIZE).remove(0);
+    output
+        .chunks(STEP)
+        .map(|step| rms(&step[STEP * 7 / 20..STEP / 2]))
+        .collect()
+}
+
+#[test]
+fn hits_stutter_the_spectrum() {
+    let levels = step_levels(0);
+    // Nothing sounded before the first step to capture
+    for (idx, level) in levels.iter().enumerate().skip(2) {
+        if idx % 2 == 0 {
+            assert!(*level > 1e-3, "hit on step {idx} at {level}");
+        } else {
+            assert!(
+                *level < levels[idx - 1] * 0.1,
+                "no hit on step {idx} at {level} in {levels:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn rotation_moves_the_hits() {
+    let levels = step_levels(1);
+    for pair in levels.chunks(2) {
+        assert!(
+            pair[1] > pair[0] * 10.0,
+            "hits at {levels:?} are not on the odd steps"
+        );
+    }
+}
@@ -0,0 +1,135 @@
+//! Checks that every FFT size passes a tone like the largest one, that switching sizes while
+//! playing crossfades without a gap, and that shorter frames smear a blurred click less.
+
+mod common;
+
+use common::{float_param, rms, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::Arc;
+use whirlpool::WhirlpoolParams;
+
+/// On a bin at every size.
+const TONE: f32 = 40.0 * SAMPLE_RATE / 1024.0;
+
+/// Fully wet settings with nothing shifted, at `fft_size`.
+fn params(fft_size: &Arc<AtomicU16>, blur: f32) -> WhirlpoolParams {
+    WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        blur: float_param("Blur", blur, 0.0, 1.0),
+        fft_size: fft_size.clone(),
+        ..WhirlpoolParams::default()
+    }
+}
+
+fn tone(len: usize) -> Vec<f32> {
+    (0..len)
+        .map(|i| 0.1 * (2.0 * PI * TONE * i as f32 / SAMPLE_RATE).sin())
+        .collect()
+}
+
+/// One second of the tone through the plugin at `fft_size`, with the reported latency.
+fn rendered(fft_size: u16) -> (Vec<f32>, usize) {
+    let size = Arc::new(AtomicU16::new(fft_size));
+    let (mut plugin, latency) = common::plugin_with_latency(params(&size, 0.0));
+    let input = tone(SAMPLE_RATE as usize);
+    let output = common::render(&mut plugin, &[input.clone(), input], BLOCK_SIZE);
+    (output[0].clone(), latency)
+}
+
+/// Standard deviation of the energy of `samples` over time, around its centre.
+fn spread(samples: &[f32]) -> f32 {
+    let energy: f32 = samples.iter().map(|x| x * x).sum();
+    let centre = samples
+        .iter()
+        .enumerate()
+        .map(|(i, x)| i as f32 * x * x)
+        .sum::<f32>()
+        / energy;
+    let variance = samples
+        .iter()
+        .enumerate()
+        .map(|(i, x)| (i as f32 - centre).powi(2) * x * x)
+        .sum::<f32>()
+        / energy;
+    variance.sqrt()
+}
+
+/// Time spread of a blurred click at `fft_size`.
+fn smear(fft_size: u16) -> f32 {
+    let size = Arc::new(AtomicU16::new(fft_size));
+    let mut plugin = common::plugin(params(&size, 1.0));
+    let len = SAMPLE_RATE as usize;
+    let mut input = vec![0.0; len];
+    input[len / 2] = 1.0;
+    let output = common::render(&mut plugin, &[input.clone(), input], BLOCK_SIZE);
+    spread(&output[0])
+}
+
+#[test]
+fn every_size_passes_the_tone_in_time() {
+    let (largest, latency) = rendered(1024);
+    let steady = SAMPLE_RATE as usize / 2;
+    for fft_size in [256, 512] {
+        let (output, size_latency) = rendered(fft_size);
+        assert_eq!(size_latency, latency, "{fft_size} reported another latency");
+        let difference: Vec<f32> = output[steady..]
+            .iter()
+            .zip(&largest[steady..])
+            .map(|(a, b)| a - b)
+            .collect();
+        let level = rms(&largest[steady..]);
+        let error = rms(&difference);
+        assert!(
+            error < level * 0.01,
+            "{error} of difference at {fft_size} on a level of {level}"
+        );
+    }
+}
+
+#[test]
+fn switching_sizes_crossfades_without_a_gap() {
+    let (steady, _) = rendered(1024);
+    let size = Arc::new(AtomicU16::new(1024));
+    let mut plugin = common::plugin(params(&size, 0.0));
+    let input = tone(SAMPLE_RATE as usize);
+    let half = input.len() / 2;
+    let first = common::render(
+        &mut plugin,
+        &[input[..half].to_vec(), input[..half].to_vec()],
+        BLOCK_SIZE,
+    );
+    size.store(256, Ordering::Relaxed);
+    let second = common::render(
+        &mut plugin,
+        &[input[half..].to_vec(), input[half..].to_vec()],
+        BLOCK_SIZE,
+    );
+
+    let switched: Vec<f32> = first[0].iter().chain(&second[0]).copied().collect();
+    let level = rms(&steady[half..]);
+    for (block, (a, b)) in switched[half..]
+        .chunks(BLOCK_SIZE)
+        .zip(steady[half..].chunks(BLOCK_SIZE))
+        .enumerate()
+    {
+        let difference: Vec<f32> = a.iter().zip(b).map(|(a, b)| a - b).collect();
+        let error = rms(&difference);
+        assert!(
+            error < level * 0.02,
+            "{error} of difference in block {block} after the switch on a level of {level}"
+        );
+    }
+}
+
+#[test]
+fn short_frames_smear_a_blurred_click_less() {
+    let long = smear(1024);
+    let short = smear(256);
+    assert!(
+        short < long / 2.0,
+        "the click spread over {short} samples at 256 against {long} at 1024"
+    );
+}
@@ -0,0 +1,52 @@
+//! Checks that a freeze below full amount follows the input while a full one holds still.
+
+mod common;
+
+use common::{amplitude, float_param, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::WhirlpoolParams;
+
+const BIN_HZ: f32 = SAMPLE_RATE / 1024.0;
+
+/// Amplitudes of the first and the second tone at the end of a render frozen from the start,
+/// where the input changes from one to the other after half a second.
+fn frozen_tones(amount: f32) -> (f32, f32) {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        freeze: BoolParam::new("Freeze", true),
+        freeze_amount: float_param("Freeze Amount", amount, 0.0, 1.0),
+        ..WhirlpoolParams::default()
+    };
+
+    let len = 3 * SAMPLE_RATE as usize;
+    let [first, second] = [40.0 * BIN_HZ, 60.0 * BIN_HZ];
+    let input: Vec<f32> = (0..len)
+        .map(|i| {
+            let freq = if i < len / 6 { first } else { second };
+            0.1 * (2.0 * PI * freq * i as f32 / SAMPLE_RATE).sin()
+        })
+        .collect();
+    let output = common::render(
+        &mut common::plugin(params),
+        &[input.clone(), input],
+        BLOCK_SIZE,
+    );
+
+    let tail = &output[0][len - 20 * 1024..];
+    (amplitude(tail, first), amplitude(tail, second))
+}
+
+#[test]
+fn full_freeze_ignores_the_new_tone() {
+    let (_, second) = frozen_tones(1.0);
+    assert!(second < 0.005, "{second} of the new tone came through");
+}
+
+#[test]
+fn partial_freeze_follows_the_input() {
+    let (first, second) = frozen_tones(0.3);
+    assert!(first < 0.005, "{first} of the old tone is still held");
+    assert!(second > 0.05, "only {second} of the new tone came through");
+}
@@ -0,0 +1,61 @@
+//! Checks that a launched freeze bank slot keeps playing its captured spectrum whatever the input
+//! does afterwards.
+
+mod common;
+
+use common::{float_param, BLOCK_SIZE};
+use std::sync::atomic::Ordering;
+use whirlpool::WhirlpoolParams;
+
+/// Where the first slot is launched, on a block boundary.
+const LAUNCH_AT: usize = BLOCK_SIZE * 8;
+/// The slot captures the first frame after the launch, which ends a hop later.
+const CAPTURED_UNTIL: usize = LAUNCH_AT + 256;
+/// Long enough for the slot fade and the latency to pass.
+const SETTLE: usize = 4096;
+
+fn render_with_launch(input: &[Vec<f32>]) -> Vec<Vec<f32>> {
+    // The harmony voice follows the pitch detected on the live input, so leave it out
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        slot_fade: float_param("Slot Fade", 10.0, 10.0, 5000.0),
+        ..WhirlpoolParams::default()
+    };
+    let slot_launch = params.slot_launch.clone();
+    let mut plugin = common::plugin(params);
+
+    let head: Vec<Vec<f32>> = input.iter().map(|ch| ch[..LAUNCH_AT].to_vec()).collect();
+    let tail: Vec<Vec<f32>> = input.iter().map(|ch| ch[LAUNCH_AT..].to_vec()).collect();
+    let mut output = common::render(&mut plugin, &head, BLOCK_SIZE);
+    slot_launch.fetch_or(1, Ordering::AcqRel);
+    for (channel, rest) in output
+        .iter_mut()
+        .zip(common::render(&mut plugin, &tail, BLOCK_SIZE))
+    {
+        channel.extend(rest);
+    }
+
+    output
+}
+
+#[test]
+fn launched_slot_ignores_the_input() {
+    // Looped so there is input left to ignore once the slot has settled
+    let input: Vec<Vec<f32>> = common::read_wav(&common::fixture_path("fixtures/input.wav"))
+        .iter()
+        .map(|channel| channel.repeat(2))
+        .collect();
+    let mut silenced = input.clone();
+    for channel in silenced.iter_mut() {
+        channel[CAPTURED_UNTIL..].fill(0.0);
+    }
+
+    let playing = render_with_launch(&input);
+    let held = render_with_launch(&silenced);
+    for (playing, held) in playing.iter().zip(&held) {
+        assert_eq!(playing[LAUNCH_AT + SETTLE..], held[LAUNCH_AT + SETTLE..]);
+        let energy: f32 = held[LAUNCH_AT + SETTLE..].iter().map(|x| x * x).sum();
+        assert!(energy > 1e-3, "the slot plays silence");
+    }
+}
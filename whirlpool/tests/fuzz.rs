@@ -0,0 +1,63 @@
+//! Drives the plugin with random block sizes, channel counts, sample rates and parameter values,
+//! including the ends of every range, and checks that it never panics or produces non-finite
+//! output.
+
+mod common;
+
+use common::{noise, MAX_BLOCK_SIZE};
+use nih_plug::prelude::*;
+use proptest::prelude::*;
+use whirlpool::{Whirlpool, WhirlpoolParams};
+
+/// Normalized values with the two ends of the range being picked much more often.
+fn normalized() -> impl Strategy<Value = f32> {
+    prop_oneof![Just(0.0), Just(1.0), 0.0..=1.0f32]
+}
+
+/// A normalized value for every parameter, in the order of `param_map()`, so new parameters are
+/// fuzzed as soon as they are added.
+fn settings() -> impl Strategy<Value = Vec<f32>> {
+    let count = WhirlpoolParams::default().param_map().len();
+    prop::collection::vec(normalized(), count)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn process_stays_finite(
+        settings in settings(),
+        sample_rate in prop::sample::select(vec![22050.0f32, 44100.0, 48000.0, 96000.0, 192000.0]),
+        num_channels in 1..=4usize,
+        block_sizes in prop::collection::vec(1..=MAX_BLOCK_SIZE, 1..4),
+        amplitude in prop_oneof![Just(0.0f32), Just(1e-20), Just(1.0), Just(100.0)],
+        with_sidechain in any::<bool>(),
+        seed in any::<u32>(),
+    ) {
+        let mut plugin = Whirlpool::with_params(WhirlpoolParams::default());
+        for ((_, param, _), &value) in plugin.params().param_map().iter().zip(&settings) {
+            // SAFETY: The plugin holds on to the parameters for as long as it lives
+            unsafe { param.set_normalized_value(value) };
+        }
+        common::initialize(&mut plugin, sample_rate, 0);
+
+        for (block_idx, &block_size) in block_sizes.iter().enumerate() {
+            let block_seed = seed.wrapping_add(block_idx as u32 * 8);
+            let mut channels: Vec<Vec<f32>> = (0..num_channels)
+                .map(|ch| noise(block_seed.wrapping_add(ch as u32), amplitude, block_size))
+                .collect();
+            let mut sidechain: Vec<Vec<f32>> = (0..2)
+                .map(|ch| noise(!seed.wrapping_add(ch), amplitude, block_size))
+                .collect();
+
+            let mut main: Vec<&mut [f32]> = channels.iter_mut().map(Vec::as_mut_slice).collect();
+            let sidechain: Vec<&mut [f32]> = sidechain.iter_mut().map(Vec::as_mut_slice).collect();
+            plugin.render(&mut main, with_sidechain.then_some(sidechain.as_slice()));
+
+            for (ch, channel) in channels.iter().enumerate() {
+                let bad = channel.iter().position(|x| !x.is_finite());
+                prop_assert!(bad.is_none(), "channel {} sample {:?} is not finite", ch, bad);
+            }
+        }
+    }
+}
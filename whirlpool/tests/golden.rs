@@ -0,0 +1,72 @@
+//! Renders `tests/fixtures/input.wav` with a set of parameter presets and compares the result
+//! against the stored outputs in `tests/golden`. Run with `WHIRLPOOL_BLESS=1` to regenerate the
+//! golden files after an intentional change in sound.
+
+mod common;
+
+use common::{float_param, BLOCK_SIZE};
+use whirlpool::WhirlpoolParams;
+
+/// Allows for FFT backends and platforms rounding differently.
+const TOLERANCE: f32 = 1e-4;
+
+fn check_golden(name: &str, params: WhirlpoolParams) {
+    let input = common::read_wav(&common::fixture_path("fixtures/input.wav"));
+    let mut plugin = common::plugin(params);
+    let output = common::render(&mut plugin, &input, BLOCK_SIZE);
+
+    let golden_path = common::fixture_path(&format!("golden/{name}.wav"));
+    if std::env::var_os("WHIRLPOOL_BLESS").is_some() {
+        common::write_wav(&golden_path, &output);
+        return;
+    }
+
+    let golden = common::read_wav(&golden_path);
+    assert_eq!(output.len(), golden.len(), "{name}: channel count differs");
+    for (ch, (actual, expected)) in output.iter().zip(&golden).enumerate() {
+        assert_eq!(actual.len(), expected.len(), "{name}: length differs");
+        let (idx, error) = actual
+            .iter()
+            .zip(expected)
+            .map(|(a, b)| (a - b).abs())
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or_default();
+        assert!(
+            error <= TOLERANCE,
+            "{name}: channel {ch} deviates by {error} at sample {idx}"
+        );
+    }
+}
+
+#[test]
+fn golden_default() {
+    check_golden("default", WhirlpoolParams::default());
+}
+
+#[test]
+fn golden_octave_down() {
+    check_golden(
+        "octave_down",
+        WhirlpoolParams {
+            harmonics: float_param("Harmonics", 1.0, 0.0, 1.0),
+            shift: float_param("Shift", 0.5, 0.5, 2.0),
+            mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+            ..WhirlpoolParams::default()
+        },
+    );
+}
+
+#[test]
+fn golden_blurred_band() {
+    check_golden(
+        "blurred_band",
+        WhirlpoolParams {
+            blur: float_param("Blur", 0.7, 0.0, 1.0),
+            low_cut: float_param("Low Cut", 200.0, 20.0, 20000.0),
+            high_cut: float_param("High Cut", 4000.0, 20.0, 20000.0),
+            mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+            ..WhirlpoolParams::default()
+        },
+    );
+}
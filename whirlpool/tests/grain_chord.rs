@@ -0,0 +1,62 @@
+//! Checks that a grain chord plays the grains at its intervals, and only as far as its weight
+//! lets it.
+
+mod common;
+
+use common::{amplitude, float_param, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::{GrainChord, WhirlpoolParams};
+
+const BIN_HZ: f32 = SAMPLE_RATE / 1024.0;
+
+/// Level of the partials within four bins of `freq`, which the grains' random phases spread
+/// out.
+fn band(samples: &[f32], freq: f32) -> f32 {
+    (-8..=8)
+        .map(|k| amplitude(samples, freq + k as f32 * BIN_HZ / 2.0).powi(2))
+        .sum::<f32>()
+        .sqrt()
+}
+
+/// Output of a sine at `freq` with the grains fed back through `chord` at `weight`.
+fn play(freq: f32, chord: GrainChord, weight: f32) -> Vec<f32> {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        grain_feedback: BoolParam::new("Grain Feedback", true),
+        grain_chord: EnumParam::new("Grain Chord", chord),
+        chord_weight: float_param("Chord Weight", weight, 0.0, 1.0),
+        ..WhirlpoolParams::default()
+    };
+
+    let len = SAMPLE_RATE as usize;
+    let input: Vec<f32> = (0..len)
+        .map(|i| 0.05 * (2.0 * PI * freq * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let mut plugin = common::plugin(params);
+    let output = common::render(&mut plugin, &[input.clone(), input], BLOCK_SIZE);
+    output[0][len / 2..][..20 * 1024].to_vec()
+}
+
+#[test]
+fn fifth_plays_grains_a_fifth_up() {
+    let tone = 40.0 * BIN_HZ;
+    let fifth = band(&play(tone, GrainChord::Fifth, 0.5), 1.5 * tone);
+    let plain = band(&play(tone, GrainChord::Off, 0.5), 1.5 * tone);
+    assert!(
+        fifth > plain * 20.0,
+        "{fifth} a fifth up against {plain} without a chord"
+    );
+}
+
+#[test]
+fn no_weight_keeps_the_grains_at_the_root() {
+    let tone = 40.0 * BIN_HZ;
+    let none = band(&play(tone, GrainChord::Octave, 0.0), 2.0 * tone);
+    let full = band(&play(tone, GrainChord::Octave, 1.0), 2.0 * tone);
+    assert!(
+        full > none * 20.0,
+        "{full} an octave up at full weight against {none} at none"
+    );
+}
@@ -0,0 +1,111 @@
+//! Checks that Reverse plays the repeats backwards and that Tape Stop brings them to a halt,
+//! from their switches as well as from the macro notes.
+
+mod common;
+
+use common::{float_param, rms, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use whirlpool::WhirlpoolParams;
+
+/// Low enough that the sawtooth's jumps take up few of the samples.
+const SAW: f32 = 50.0;
+
+fn note(note: u8) -> NoteEvent<()> {
+    NoteEvent::NoteOn {
+        timing: 0,
+        voice_id: None,
+        channel: 0,
+        note,
+        velocity: 1.0,
+    }
+}
+
+/// Fully wet settings with the grains fed back, changed by `tweak`.
+fn params(tweak: impl FnOnce(WhirlpoolParams) -> WhirlpoolParams) -> WhirlpoolParams {
+    tweak(WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        grain_feedback: BoolParam::new("Grain Feedback", true),
+        delay_feedback: float_param("Delay Feedback", 0.3, 0.0, 0.95),
+        ..WhirlpoolParams::default()
+    })
+}
+
+/// The first 6000 samples of the repeats after half a second of `input` gave way to silence,
+/// with `events` played from the start.
+fn repeats(
+    params: WhirlpoolParams,
+    input: impl Fn(usize) -> f32,
+    events: &[NoteEvent<()>],
+) -> Vec<f32> {
+    let len = SAMPLE_RATE as usize;
+    let input: Vec<f32> = (0..len)
+        .map(|i| if i < len / 2 { input(i) } else { 0.0 })
+        .collect();
+    let mut plugin = common::plugin(params);
+    let output = common::render_notes(&mut plugin, &[input.clone(), input], BLOCK_SIZE, events);
+    // Past the spectral processor's own tail, well before the delay time runs out
+    output[0][len / 2 + 2048..][..6000].to_vec()
+}
+
+/// Share of the samples the repeats of a rising sawtooth rise on.
+fn rising(params: WhirlpoolParams) -> f32 {
+    let saw = |i: usize| 0.1 * ((SAW * i as f32 / SAMPLE_RATE).fract() * 2.0 - 1.0);
+    let output = repeats(params, saw, &[]);
+    let rises = output.windows(2).filter(|pair| pair[1] > pair[0]).count();
+    rises as f32 / (output.len() - 1) as f32
+}
+
+/// Level of the repeats of a steady tone.
+fn level(params: WhirlpoolParams, events: &[NoteEvent<()>]) -> f32 {
+    let tone = |i: usize| 0.1 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / SAMPLE_RATE).sin();
+    rms(&repeats(params, tone, events))
+}
+
+#[test]
+fn reverse_plays_the_repeats_backwards() {
+    let forward = rising(params(|params| params));
+    let reversed = rising(params(|params| WhirlpoolParams {
+        grain_reverse: BoolParam::new("Reverse", true),
+        ..params
+    }));
+    assert!(forward > 0.7, "only {forward} of the forward repeats rose");
+    assert!(reversed < 0.3, "{reversed} of the reversed repeats rose");
+}
+
+#[test]
+fn tape_stop_halts_the_repeats() {
+    let stop_time = |params: WhirlpoolParams| WhirlpoolParams {
+        tape_stop_time: float_param("Tape Stop Time", 50.0, 50.0, 5000.0),
+        ..params
+    };
+    let running = level(params(stop_time), &[]);
+    let stopped = level(
+        params(|params| WhirlpoolParams {
+            tape_stop: BoolParam::new("Tape Stop", true),
+            ..stop_time(params)
+        }),
+        &[],
+    );
+    assert!(
+        running > 0.005,
+        "the repeats only came through at {running}"
+    );
+    assert!(
+        stopped < running * 0.01,
+        "{stopped} of the repeats played on through the tape stop, against {running}"
+    );
+
+    // The note above the base note holds the tape stop too
+    let held = level(
+        params(|params| WhirlpoolParams {
+            macro_notes: BoolParam::new("Macro Notes", true),
+            ..stop_time(params)
+        }),
+        &[note(25)],
+    );
+    assert!(
+        held < running * 0.01,
+        "{held} of the repeats played on while the macro note held the tape stop"
+    );
+}
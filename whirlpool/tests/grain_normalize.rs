@@ -0,0 +1,67 @@
+//! Checks that normalized grains keep the cloud's level as the voice count rises, where plain
+//! grains scaled to the voices get quieter.
+
+mod common;
+
+use common::{float_param, rms, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use whirlpool::{GrainSource, WhirlpoolParams};
+
+/// Output RMS for a silent main input and noise on the sidechain, granulated by `voices`
+/// grains and fed back.
+fn output_rms(voices: i32, normalize: bool) -> f32 {
+    let mut plugin = common::plugin(WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        grain_feedback: BoolParam::new("Grain Feedback", true),
+        grain_source: EnumParam::new("Grain Source", GrainSource::Sidechain),
+        grain_voices: IntParam::new("Grain Voices", voices, IntRange::Linear { min: 2, max: 8 }),
+        grain_normalize: BoolParam::new("Grain Normalize", normalize),
+        ..WhirlpoolParams::default()
+    });
+
+    let len = 2 * SAMPLE_RATE as usize;
+    let mut rng_state = 1u32;
+    let noise: Vec<f32> = (0..len)
+        .map(|_| {
+            rng_state ^= rng_state << 13;
+            rng_state ^= rng_state >> 17;
+            rng_state ^= rng_state << 5;
+            0.25 * (rng_state as f32 / u32::MAX as f32 * 2.0 - 1.0)
+        })
+        .collect();
+    let mut sidechain = [noise.clone(), noise];
+    let mut output = vec![vec![0.0; len]; 2];
+    let mut start = 0;
+    while start < len {
+        let end = (start + BLOCK_SIZE).min(len);
+        let mut main: Vec<&mut [f32]> = output.iter_mut().map(|ch| &mut ch[start..end]).collect();
+        let sidechain: Vec<&mut [f32]> =
+            sidechain.iter_mut().map(|ch| &mut ch[start..end]).collect();
+        plugin.render(&mut main, Some(&sidechain));
+        start = end;
+    }
+
+    let tail = &output[0][len / 2..];
+    rms(tail)
+}
+
+#[test]
+fn normalized_level_holds_across_voice_counts() {
+    let sparse = output_rms(2, true);
+    let dense = output_rms(8, true);
+    let ratio = dense / sparse;
+    assert!(
+        (0.75..1.33).contains(&ratio),
+        "{dense} with eight voices against {sparse} with two"
+    );
+}
+
+#[test]
+fn plain_grains_get_quieter_with_more_voices() {
+    let sparse = output_rms(2, false);
+    let dense = output_rms(8, false);
+    assert!(
+        dense < sparse * 0.75,
+        "{dense} with eight voices against {sparse} with two"
+    );
+}
@@ -0,0 +1,47 @@
+//! Checks that the granular delay can record the sidechain instead of the main signal.
+
+mod common;
+
+use common::{rms, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::{GrainSource, WhirlpoolParams};
+
+/// Output RMS for a silent main input and a sine on the sidechain, with the grains fed back.
+fn output_rms(grain_source: GrainSource) -> f32 {
+    let mut plugin = common::plugin(WhirlpoolParams {
+        grain_feedback: BoolParam::new("Grain Feedback", true),
+        grain_source: EnumParam::new("Grain Source", grain_source),
+        ..WhirlpoolParams::default()
+    });
+
+    let len = SAMPLE_RATE as usize;
+    let tone: Vec<f32> = (0..len)
+        .map(|i| 0.25 * (2.0 * PI * 440.0 * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let mut sidechain = [tone.clone(), tone];
+    let mut output = vec![vec![0.0; len]; 2];
+    let mut start = 0;
+    while start < len {
+        let end = (start + BLOCK_SIZE).min(len);
+        let mut main: Vec<&mut [f32]> = output.iter_mut().map(|ch| &mut ch[start..end]).collect();
+        let sidechain: Vec<&mut [f32]> =
+            sidechain.iter_mut().map(|ch| &mut ch[start..end]).collect();
+        plugin.render(&mut main, Some(&sidechain));
+        start = end;
+    }
+
+    let tail = &output[0][len / 2..];
+    rms(tail)
+}
+
+#[test]
+fn grains_drawn_from_the_sidechain_reach_the_output() {
+    let main = output_rms(GrainSource::Main);
+    let sidechain = output_rms(GrainSource::Sidechain);
+    assert!(main < 1e-6, "{main} came out of a silent main input");
+    assert!(
+        sidechain > 0.01,
+        "only {sidechain} of the sidechain's grains came out"
+    );
+}
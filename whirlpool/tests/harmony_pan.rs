@@ -0,0 +1,50 @@
+//! Checks that panning the harmony voice moves it without touching the source.
+
+mod common;
+
+use common::{amplitude, float_param, BLOCK_SIZE, SAMPLE_RATE};
+use std::f32::consts::PI;
+use whirlpool::WhirlpoolParams;
+
+const BIN_HZ: f32 = SAMPLE_RATE / 1024.0;
+
+#[test]
+fn hard_right_harmony_leaves_the_left_channel() {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 1.0, 0.0, 1.0),
+        harmony_pan: float_param("Harmony Pan", 1.0, -1.0, 1.0),
+        shift: float_param("Shift", 1.0, 0.5, 2.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        ..WhirlpoolParams::default()
+    };
+
+    let len = SAMPLE_RATE as usize;
+    let source = 40.0 * BIN_HZ;
+    let input: Vec<f32> = (0..len)
+        .map(|i| 0.05 * (2.0 * PI * source * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let output = common::render(
+        &mut common::plugin(params),
+        &[input.clone(), input],
+        BLOCK_SIZE,
+    );
+
+    // Whole FFT lengths hold whole cycles of both partials, so neither leaks into the other
+    let tail = len / 2..len / 2 + 20 * 1024;
+    let [left, right] = [&output[0][tail.clone()], &output[1][tail]];
+    // A shift of one adds an octave
+    let harmony = 2.0 * source;
+    assert!(
+        amplitude(left, harmony) < 0.002,
+        "the harmony stayed on the left"
+    );
+    assert!(
+        amplitude(right, harmony) > 0.02,
+        "the harmony is missing on the right"
+    );
+    let sources = [amplitude(left, source), amplitude(right, source)];
+    assert!(
+        (sources[0] - sources[1]).abs() < 0.002,
+        "the source moved too: {sources:?}"
+    );
+}
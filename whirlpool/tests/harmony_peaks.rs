@@ -0,0 +1,55 @@
+//! Checks that Peaks mode only shifts the strongest peaks into the harmony voice.
+
+mod common;
+
+use common::{amplitude, float_param, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::{HarmonyBins, WhirlpoolParams};
+
+const BIN_HZ: f32 = SAMPLE_RATE / 1024.0;
+/// The loud partial and a quieter one below it, both on bins so their octaves are too.
+const LOUD: f32 = 100.0 * BIN_HZ;
+const QUIET: f32 = 40.0 * BIN_HZ;
+
+/// The octaves above the loud and the quiet partial.
+fn octaves(harmony_bins: HarmonyBins) -> [f32; 2] {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 1.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        harmony_bins: EnumParam::new("Harmony Bins", harmony_bins),
+        peak_count: IntParam::new("Peak Count", 1, IntRange::Linear { min: 1, max: 32 }),
+        ..WhirlpoolParams::default()
+    };
+
+    let len = SAMPLE_RATE as usize;
+    let input: Vec<f32> = (0..len)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE;
+            0.05 * (2.0 * PI * LOUD * t).sin() + 0.02 * (2.0 * PI * QUIET * t).sin()
+        })
+        .collect();
+    let mut plugin = common::plugin(params);
+    let output = common::render(&mut plugin, &[input.clone(), input], BLOCK_SIZE);
+    let output = &output[0][len / 2..][..20 * 1024];
+    [
+        amplitude(output, 2.0 * LOUD),
+        amplitude(output, 2.0 * QUIET),
+    ]
+}
+
+#[test]
+fn peaks_mode_shifts_only_the_strongest_peaks() {
+    let [loud, quiet] = octaves(HarmonyBins::All);
+    assert!(
+        loud > 0.01 && quiet > 0.005,
+        "every bin only gave {loud} and {quiet}"
+    );
+
+    let [loud, quiet] = octaves(HarmonyBins::Peaks);
+    assert!(
+        loud > 0.01,
+        "the strongest peak only came through at {loud}"
+    );
+    assert!(quiet < 1e-4, "{quiet} of the weaker peak came through");
+}
@@ -0,0 +1,54 @@
+//! Checks that a pre-blur harmony stays focused while the source is blurred.
+
+mod common;
+
+use common::{amplitude, float_param, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::{HarmonySource, WhirlpoolParams};
+
+const BIN_HZ: f32 = SAMPLE_RATE / 1024.0;
+
+/// Amplitudes of the source and of its octave with full blur.
+fn blurred(source: HarmonySource) -> (f32, f32) {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 1.0, 0.0, 1.0),
+        shift: float_param("Shift", 1.0, 0.5, 2.0),
+        blur: float_param("Blur", 1.0, 0.0, 1.0),
+        harmony_source: EnumParam::new("Harmony Source", source),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        ..WhirlpoolParams::default()
+    };
+
+    let len = SAMPLE_RATE as usize;
+    let freq = 40.0 * BIN_HZ;
+    let input: Vec<f32> = (0..len)
+        .map(|i| 0.05 * (2.0 * PI * freq * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let output = common::render(
+        &mut common::plugin(params),
+        &[input.clone(), input],
+        BLOCK_SIZE,
+    );
+
+    let tail = &output[0][len / 2..len / 2 + 20 * 1024];
+    (amplitude(tail, freq), amplitude(tail, 2.0 * freq))
+}
+
+#[test]
+fn pre_blur_harmony_stays_focused() {
+    let (pre_source, pre_harmony) = blurred(HarmonySource::PreBlur);
+    let (post_source, post_harmony) = blurred(HarmonySource::PostBlur);
+    assert!(
+        pre_harmony > 0.03,
+        "only {pre_harmony} of the pre-blur harmony is left"
+    );
+    assert!(
+        post_harmony < 0.01,
+        "{post_harmony} of the post-blur harmony stayed focused"
+    );
+    assert!(
+        (pre_source - post_source).abs() < 0.001,
+        "the source changed too: {pre_source} and {post_source}"
+    );
+}
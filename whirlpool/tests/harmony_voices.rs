@@ -0,0 +1,109 @@
+//! Checks that every harmony voice follows its own level, pan and shift.
+
+mod common;
+
+use common::{amplitude, float_param, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::{HarmonyVoiceParams, ShiftMode, WhirlpoolParams};
+
+const BIN_HZ: f32 = SAMPLE_RATE / 1024.0;
+/// The source tone, on a bin whose fifth and octaves land on bins too.
+const TONE: f32 = 100.0 * BIN_HZ;
+const FIFTH: f32 = 150.0 * BIN_HZ;
+const OCTAVE: f32 = 200.0 * BIN_HZ;
+
+fn note_on(note: u8) -> NoteEvent<()> {
+    NoteEvent::NoteOn {
+        timing: 0,
+        voice_id: None,
+        channel: 0,
+        note,
+        velocity: 1.0,
+    }
+}
+
+/// Both channels of the second half of a second of `TONE` with a fifth and an octave held, the
+/// voice at `idx` set up by `voice`.
+fn play(idx: usize, voice: HarmonyVoiceParams) -> [Vec<f32>; 2] {
+    let mut voice = Some(voice);
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 1.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        shift_mode: EnumParam::new("Shift Mode", ShiftMode::Midi),
+        harmony_voices: std::array::from_fn(|i| {
+            if i == idx {
+                voice.take().unwrap()
+            } else {
+                HarmonyVoiceParams::new(i)
+            }
+        }),
+        ..WhirlpoolParams::default()
+    };
+
+    let len = SAMPLE_RATE as usize;
+    let input: Vec<f32> = (0..len)
+        .map(|i| 0.05 * (2.0 * PI * TONE * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let mut plugin = common::plugin(params);
+    let notes = [note_on(67), note_on(72)];
+    let output = common::render_notes(&mut plugin, &[input.clone(), input], BLOCK_SIZE, &notes);
+    let tail = |channel: &[f32]| channel[len / 2..][..20 * 1024].to_vec();
+    [tail(&output[0]), tail(&output[1])]
+}
+
+#[test]
+fn a_silent_voice_leaves_the_others() {
+    let voice = HarmonyVoiceParams {
+        level: float_param("Voice 2 Level", 0.0, 0.0, 1.0),
+        ..HarmonyVoiceParams::new(1)
+    };
+    let [left, _] = play(1, voice);
+    let fifth = amplitude(&left, FIFTH);
+    let octave = amplitude(&left, OCTAVE);
+    assert!(fifth > 0.01, "the fifth only came through at {fifth}");
+    assert!(
+        octave < 0.002,
+        "the silenced octave came through at {octave}"
+    );
+}
+
+#[test]
+fn a_panned_voice_leaves_the_other_channel() {
+    let voice = HarmonyVoiceParams {
+        pan: float_param("Voice 1 Pan", 1.0, -1.0, 1.0),
+        ..HarmonyVoiceParams::new(0)
+    };
+    let [left, right] = play(0, voice);
+    assert!(
+        amplitude(&left, FIFTH) < 0.002,
+        "the fifth stayed on the left"
+    );
+    assert!(
+        amplitude(&right, FIFTH) > 0.01,
+        "the fifth is missing on the right"
+    );
+    let octaves = [amplitude(&left, OCTAVE), amplitude(&right, OCTAVE)];
+    assert!(
+        (octaves[0] - octaves[1]).abs() < 0.002,
+        "the octave moved too: {octaves:?}"
+    );
+}
+
+#[test]
+fn a_shifted_voice_moves_alone() {
+    let voice = HarmonyVoiceParams {
+        shift: float_param("Voice 2 Shift", 12.0, -12.0, 12.0),
+        ..HarmonyVoiceParams::new(1)
+    };
+    let [left, _] = play(1, voice);
+    let fifth = amplitude(&left, FIFTH);
+    let octave = amplitude(&left, OCTAVE);
+    let double_octave = amplitude(&left, 2.0 * OCTAVE);
+    assert!(fifth > 0.01, "the fifth only came through at {fifth}");
+    assert!(octave < 0.002, "{octave} was left on the octave");
+    assert!(
+        double_octave > 0.01,
+        "the double octave only came through at {double_octave}"
+    );
+}
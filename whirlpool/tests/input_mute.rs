@@ -0,0 +1,46 @@
+//! Checks that muting the input takes the dry signal out of the mix while the wet path keeps
+//! being fed.
+
+mod common;
+
+use common::{float_param, BLOCK_SIZE};
+use nih_plug::prelude::*;
+use whirlpool::WhirlpoolParams;
+
+fn render(mix: f32, input_mute: bool) -> Vec<Vec<f32>> {
+    let params = WhirlpoolParams {
+        mix: float_param("Dry/Wet", mix, 0.0, 1.0),
+        input_mute: BoolParam::new("Input Mute", input_mute),
+        ..WhirlpoolParams::default()
+    };
+    let input = common::read_wav(&common::fixture_path("fixtures/input.wav"));
+    common::render(&mut common::plugin(params), &input, BLOCK_SIZE)
+}
+
+#[test]
+fn muted_dry_only_mix_is_silent() {
+    for channel in render(0.0, true) {
+        assert!(
+            channel.iter().all(|&x| x == 0.0),
+            "the dry signal leaked through"
+        );
+    }
+}
+
+#[test]
+fn muted_input_still_feeds_the_wet_signal() {
+    let muted = render(0.5, true);
+    let wet = render(1.0, false);
+    for (muted, wet) in muted.iter().zip(&wet) {
+        assert!(
+            wet.iter().any(|&x| x.abs() > 1e-3),
+            "the wet signal is silent"
+        );
+        for (idx, (muted, wet)) in muted.iter().zip(wet).enumerate() {
+            assert!(
+                (muted - wet * 0.5).abs() < 1e-6,
+                "sample {idx}: {muted} is not half the wet signal {wet}"
+            );
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//! Checks that the spectral mirror reflects a partial around the center frequency.
+
+mod common;
+
+use common::{amplitude, float_param, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::WhirlpoolParams;
+
+const BIN_HZ: f32 = SAMPLE_RATE / 1024.0;
+
+/// Amplitudes at the source and at its reflection of a sine nine bins below the center.
+fn mirrored(wet: f32) -> (f32, f32) {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        invert: BoolParam::new("Invert", true),
+        // An odd axis turns the mirrored phases by half a cycle per hop, which only adds up
+        // across overlapping frames when the mirror compensates for it
+        invert_center: float_param("Invert Center", 49.0 * BIN_HZ, 20.0, 20000.0),
+        invert_wet: float_param("Invert Wet", wet, 0.0, 1.0),
+        ..WhirlpoolParams::default()
+    };
+
+    let len = SAMPLE_RATE as usize;
+    let source = 40.0 * BIN_HZ;
+    let input: Vec<f32> = (0..len)
+        .map(|i| 0.25 * (2.0 * PI * source * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let output = common::render(
+        &mut common::plugin(params),
+        &[input.clone(), input],
+        BLOCK_SIZE,
+    );
+
+    let tail = &output[0][len / 2..];
+    (amplitude(tail, source), amplitude(tail, 58.0 * BIN_HZ))
+}
+
+#[test]
+fn partials_are_reflected_around_the_center() {
+    let (left, reflected) = mirrored(1.0);
+    assert!(
+        reflected > 0.2,
+        "only {reflected} arrived at the reflection"
+    );
+    assert!(left < 0.01, "{left} stayed at the source frequency");
+}
+
+#[test]
+fn wet_blends_in_the_reflection() {
+    let (source, reflected) = mirrored(0.5);
+    assert!(
+        source > 0.1 && reflected > 0.1,
+        "half wet keeps {source} of the source and {reflected} of the reflection"
+    );
+}
@@ -0,0 +1,66 @@
+//! Checks that the jitter distribution and bias move where the grains' echoes of a click land
+//! behind the delay time.
+
+mod common;
+
+use common::{float_param, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use whirlpool::{JitterDistribution, WhirlpoolParams};
+
+const DELAY_MS: f32 = 200.0;
+/// Samples between the clicks, long enough for each click's first echo to stand alone.
+const CLICK_SPACING: usize = 24000;
+const CLICKS: usize = 8;
+
+/// Average time in milliseconds the first echo of a click arrives behind the delay time,
+/// weighted by the echo's energy.
+fn echo_time(distribution: JitterDistribution, bias: f32) -> f32 {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        grain_feedback: BoolParam::new("Grain Feedback", true),
+        delay_time: float_param("Delay Time", DELAY_MS, 50.0, 2000.0),
+        jitter_distribution: EnumParam::new("Distribution", distribution),
+        jitter_bias: float_param("Jitter Bias", bias, -1.0, 1.0),
+        ..WhirlpoolParams::default()
+    };
+
+    let len = CLICK_SPACING * (CLICKS + 1);
+    let mut input = vec![0.0; len];
+    for click in 1..=CLICKS {
+        input[click * CLICK_SPACING] = 0.5;
+    }
+    let (mut plugin, latency) = common::plugin_with_latency(params);
+    let output = common::render(&mut plugin, &[input.clone(), input], BLOCK_SIZE);
+
+    let behind = latency + (DELAY_MS / 1000.0 * SAMPLE_RATE) as usize;
+    let lead = (0.005 * SAMPLE_RATE) as usize;
+    let window = (0.03 * SAMPLE_RATE) as usize;
+    let (mut weighted, mut energy) = (0.0, 0.0);
+    for click in 1..=CLICKS {
+        let start = click * CLICK_SPACING + behind - lead;
+        for (offset, x) in output[0][start..][..window].iter().enumerate() {
+            weighted += (offset as f32 - lead as f32) * x * x;
+            energy += x * x;
+        }
+    }
+    assert!(energy > 1e-6, "no echo arrived");
+    weighted / energy / SAMPLE_RATE * 1000.0
+}
+
+#[test]
+fn exponential_pulls_the_echoes_towards_the_delay_time() {
+    let uniform = echo_time(JitterDistribution::Uniform, 0.0);
+    let exponential = echo_time(JitterDistribution::Exponential, 0.0);
+    assert!(
+        exponential < uniform - 1.5,
+        "echoes at {exponential} ms against {uniform} ms uniformly"
+    );
+}
+
+#[test]
+fn bias_pushes_the_echoes_back() {
+    let early = echo_time(JitterDistribution::Gaussian, -1.0);
+    let late = echo_time(JitterDistribution::Gaussian, 1.0);
+    assert!(late > early + 6.0, "echoes at {late} ms against {early} ms");
+}
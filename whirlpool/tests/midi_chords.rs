@@ -0,0 +1,73 @@
+//! Checks that MIDI shift mode plays a harmony voice for every held note.
+
+mod common;
+
+use common::{amplitude, float_param, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::{ShiftMode, WhirlpoolParams};
+
+const BIN_HZ: f32 = SAMPLE_RATE / 1024.0;
+/// The source tone, on a bin whose fifth and octave land on bins too.
+const TONE: f32 = 100.0 * BIN_HZ;
+
+fn note(timing: usize, note: u8, on: bool) -> NoteEvent<()> {
+    let timing = timing as u32;
+    if on {
+        NoteEvent::NoteOn {
+            timing,
+            voice_id: None,
+            channel: 0,
+            note,
+            velocity: 1.0,
+        }
+    } else {
+        NoteEvent::NoteOff {
+            timing,
+            voice_id: None,
+            channel: 0,
+            note,
+            velocity: 0.0,
+        }
+    }
+}
+
+/// The second half of a second of `TONE` played with `events`.
+fn play(events: &[NoteEvent<()>]) -> Vec<f32> {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 1.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        shift_mode: EnumParam::new("Shift Mode", ShiftMode::Midi),
+        ..WhirlpoolParams::default()
+    };
+
+    let len = SAMPLE_RATE as usize;
+    let input: Vec<f32> = (0..len)
+        .map(|i| 0.05 * (2.0 * PI * TONE * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let mut plugin = common::plugin(params);
+    let output = common::render_notes(&mut plugin, &[input.clone(), input], BLOCK_SIZE, events);
+    output[0][len / 2..][..20 * 1024].to_vec()
+}
+
+#[test]
+fn held_notes_play_a_chord() {
+    // A fifth and an octave over the root at middle C
+    let output = play(&[note(0, 67, true), note(0, 72, true)]);
+    let fifth = amplitude(&output, 150.0 * BIN_HZ);
+    let octave = amplitude(&output, 200.0 * BIN_HZ);
+    assert!(fifth > 0.01, "the fifth only came through at {fifth}");
+    assert!(octave > 0.01, "the octave only came through at {octave}");
+}
+
+#[test]
+fn released_notes_stop_their_voices() {
+    let held = play(&[note(0, 72, true)]);
+    let released = play(&[note(0, 72, true), note(4096, 72, false)]);
+    let held = amplitude(&held, 200.0 * BIN_HZ);
+    let released = amplitude(&released, 200.0 * BIN_HZ);
+    assert!(
+        released < held * 0.01,
+        "{released} left of {held} after the release"
+    );
+}
@@ -0,0 +1,123 @@
+//! Checks the pitch wheel and the sustain pedal in the MIDI modes.
+
+mod common;
+
+use common::{amplitude, float_param, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::{FreezeTrigger, ShiftMode, WhirlpoolParams};
+
+const BIN_HZ: f32 = SAMPLE_RATE / 1024.0;
+/// The source tone, on a bin whose octave lands on a bin too.
+const TONE: f32 = 100.0 * BIN_HZ;
+const OCTAVE: f32 = 2.0 * TONE;
+
+fn note_on(timing: u32, note: u8) -> NoteEvent<()> {
+    NoteEvent::NoteOn {
+        timing,
+        voice_id: None,
+        channel: 0,
+        note,
+        velocity: 1.0,
+    }
+}
+
+fn note_off(timing: u32, note: u8) -> NoteEvent<()> {
+    NoteEvent::NoteOff {
+        timing,
+        voice_id: None,
+        channel: 0,
+        note,
+        velocity: 0.0,
+    }
+}
+
+fn pedal(timing: u32, down: bool) -> NoteEvent<()> {
+    NoteEvent::MidiCC {
+        timing,
+        channel: 0,
+        cc: 64,
+        value: if down { 1.0 } else { 0.0 },
+    }
+}
+
+/// The second half of a second of `TONE`, cut off after `tone_len` samples, played with
+/// `events`.
+fn play(params: WhirlpoolParams, tone_len: usize, events: &[NoteEvent<()>]) -> Vec<f32> {
+    let len = SAMPLE_RATE as usize;
+    let input: Vec<f32> = (0..len)
+        .map(|i| 0.05 * (2.0 * PI * TONE * i as f32 / SAMPLE_RATE).sin())
+        .enumerate()
+        .map(|(i, x)| if i < tone_len { x } else { 0.0 })
+        .collect();
+    let mut plugin = common::plugin(params);
+    let output = common::render_notes(&mut plugin, &[input.clone(), input], BLOCK_SIZE, events);
+    output[0][len / 2..][..20 * 1024].to_vec()
+}
+
+fn midi_params() -> WhirlpoolParams {
+    WhirlpoolParams {
+        harmonics: float_param("Harmonics", 1.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        shift_mode: EnumParam::new("Shift Mode", ShiftMode::Midi),
+        bend_range: IntParam::new("Bend Range", 12, IntRange::Linear { min: 0, max: 24 }),
+        ..WhirlpoolParams::default()
+    }
+}
+
+#[test]
+fn pitch_wheel_bends_the_harmony_voice() {
+    let len = SAMPLE_RATE as usize;
+    let bend = NoteEvent::MidiPitchBend {
+        timing: 0,
+        channel: 0,
+        value: 1.0,
+    };
+    let straight = play(midi_params(), len, &[note_on(0, 60)]);
+    let bent = play(midi_params(), len, &[note_on(0, 60), bend]);
+    let straight = amplitude(&straight, OCTAVE);
+    let bent = amplitude(&bent, OCTAVE);
+    assert!(bent > 0.01, "a full bend only reached the octave at {bent}");
+    assert!(
+        straight < bent * 0.01,
+        "{straight} at the octave without a bend"
+    );
+}
+
+#[test]
+fn sustain_pedal_holds_released_notes() {
+    let len = SAMPLE_RATE as usize;
+    let held = [note_on(0, 72), pedal(0, true), note_off(4096, 72)];
+    let lifted = [
+        note_on(0, 72),
+        pedal(0, true),
+        note_off(4096, 72),
+        pedal(8192, false),
+    ];
+    let held = amplitude(&play(midi_params(), len, &held), OCTAVE);
+    let lifted = amplitude(&play(midi_params(), len, &lifted), OCTAVE);
+    assert!(held > 0.01, "the pedal only held the note at {held}");
+    assert!(
+        lifted < held * 0.01,
+        "{lifted} left of {held} after lifting the pedal"
+    );
+}
+
+#[test]
+fn sustain_pedal_holds_a_note_triggered_freeze() {
+    let params = || WhirlpoolParams {
+        freeze_trigger: EnumParam::new("Freeze Trigger", FreezeTrigger::MidiNote),
+        ..WhirlpoolParams::default()
+    };
+    // The tone stops well before the measured half, only a held freeze keeps it going
+    let tone_len = SAMPLE_RATE as usize / 4;
+    let frozen = play(params(), tone_len, &[pedal(4096, true)]);
+    let released = play(params(), tone_len, &[pedal(4096, true), pedal(8192, false)]);
+    let frozen = amplitude(&frozen, TONE);
+    let released = amplitude(&released, TONE);
+    assert!(frozen > 0.01, "the pedal only held the freeze at {frozen}");
+    assert!(
+        released < frozen * 0.01,
+        "{released} left of {frozen} after lifting the pedal"
+    );
+}
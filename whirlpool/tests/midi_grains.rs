@@ -0,0 +1,65 @@
+//! Checks that notes start grains at their velocity's level and, with key tracking, at their
+//! pitch.
+
+mod common;
+
+use common::{amplitude, float_param, rms, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::{GrainKeyTrack, WhirlpoolParams};
+
+const BIN_HZ: f32 = SAMPLE_RATE / 1024.0;
+/// Samples between the notes, longer than a grain.
+const NOTE_SPACING: usize = 4096;
+
+/// Output of a sine at `freq` with a grain played by `note` at `velocity` every
+/// `NOTE_SPACING` samples, the grains fed back once.
+fn play(freq: f32, note: u8, velocity: f32, key_track: GrainKeyTrack) -> Vec<f32> {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        grain_feedback: BoolParam::new("Grain Feedback", true),
+        midi_grains: BoolParam::new("MIDI Grains", true),
+        grain_key_track: EnumParam::new("Key Track", key_track),
+        ..WhirlpoolParams::default()
+    };
+
+    let len = SAMPLE_RATE as usize;
+    let input: Vec<f32> = (0..len)
+        .map(|i| 0.05 * (2.0 * PI * freq * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let events: Vec<NoteEvent<()>> = (0..len)
+        .step_by(NOTE_SPACING)
+        .map(|timing| NoteEvent::NoteOn {
+            timing: timing as u32,
+            voice_id: None,
+            channel: 0,
+            note,
+            velocity,
+        })
+        .collect();
+    let mut plugin = common::plugin(params);
+    let output = common::render_notes(&mut plugin, &[input.clone(), input], BLOCK_SIZE, &events);
+    output[0][len / 2..][..20 * 1024].to_vec()
+}
+
+#[test]
+fn velocity_sets_the_grain_level() {
+    let dry = rms(&play(440.0, 60, 0.0, GrainKeyTrack::Off));
+    let soft = rms(&play(440.0, 60, 0.25, GrainKeyTrack::Off)) - dry;
+    let loud = rms(&play(440.0, 60, 1.0, GrainKeyTrack::Off)) - dry;
+    assert!(loud > 1e-3, "the grains only added {loud}");
+    assert!(soft < loud * 0.5, "a soft note added {soft} against {loud}");
+}
+
+#[test]
+fn key_tracked_pitch_transposes_the_grains() {
+    let tone = 40.0 * BIN_HZ;
+    let output = play(tone, 72, 1.0, GrainKeyTrack::Pitch);
+    let octave_up = amplitude(&output, 2.0 * tone);
+    let plain = amplitude(&play(tone, 72, 1.0, GrainKeyTrack::Off), 2.0 * tone);
+    assert!(
+        octave_up > plain * 10.0,
+        "{octave_up} an octave up against {plain} untracked"
+    );
+}
@@ -0,0 +1,39 @@
+//! Checks that the Morph parameter lands on the stored presets and leaves excluded parameters
+//! alone.
+
+mod common;
+
+use common::{float_param, BLOCK_SIZE};
+use whirlpool::WhirlpoolParams;
+
+fn params(shift: f32, mix: f32, morph: f32) -> WhirlpoolParams {
+    WhirlpoolParams {
+        harmonics: float_param("Harmonics", 1.0, 0.0, 1.0),
+        shift: float_param("Shift", shift, 0.5, 2.0),
+        mix: float_param("Dry/Wet", mix, 0.0, 1.0),
+        morph: float_param("Morph", morph, 0.0, 1.0),
+        ..WhirlpoolParams::default()
+    }
+}
+
+fn render(params: WhirlpoolParams) -> Vec<Vec<f32>> {
+    let input = common::read_wav(&common::fixture_path("fixtures/input.wav"));
+    let mut plugin = common::plugin(params);
+    common::render(&mut plugin, &input, BLOCK_SIZE)
+}
+
+#[test]
+fn morph_reaches_preset_b() {
+    let morphed = params(1.0, 1.0, 1.0);
+    {
+        let mut presets = morphed.morph_presets.write().unwrap();
+        // Shift 1.0 and 0.5, mix 0.0 and 0.5
+        presets.a.insert(String::from("shift"), 1.0 / 3.0);
+        presets.b.insert(String::from("shift"), 0.0);
+        presets.a.insert(String::from("mix"), 0.0);
+        presets.b.insert(String::from("mix"), 0.5);
+        presets.excluded.insert(String::from("mix"));
+    }
+
+    assert_eq!(render(morphed), render(params(0.5, 1.0, 1.0)));
+}
@@ -0,0 +1,100 @@
+//! Checks that the motion sequencer steps its parameter along with the transport, glides where
+//! asked and records the parameter's own value into the steps it passes.
+
+mod common;
+
+use common::{float_param, rms, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::{NoteDivision, WhirlpoolParams};
+
+/// A quarter note at the internal clock's default 120 BPM.
+const STEP: usize = SAMPLE_RATE as usize / 2;
+
+/// Quarter note motion steps on the output volume, set to `volume` on its own.
+fn params(volume: f32, record: bool) -> WhirlpoolParams {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        out_gain: float_param("Volume", volume, 0.0, 2.0),
+        motion: BoolParam::new("Motion", true),
+        motion_division: EnumParam::new("Motion Rate", NoteDivision::Quarter),
+        motion_record: BoolParam::new("Motion Record", record),
+        ..WhirlpoolParams::default()
+    };
+    params.motion_sequence.write().unwrap().param = String::from("output_gain");
+    params
+}
+
+/// Output of a steady sine over `steps` motion steps.
+fn render(params: WhirlpoolParams, steps: usize) -> Vec<f32> {
+    let input: Vec<f32> = (0..steps * STEP)
+        .map(|i| 0.05 * (2.0 * PI * 440.0 * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let mut plugin = common::plugin(params);
+    common::render(&mut plugin, &[input.clone(), input], BLOCK_SIZE).remove(0)
+}
+
+#[test]
+fn steps_set_the_parameter() {
+    let params = params(1.0, false);
+    {
+        let mut sequence = params.motion_sequence.write().unwrap();
+        for (idx, step) in sequence.steps.iter_mut().enumerate() {
+            // A volume of 0.5 and 1.5
+            step.value = if idx % 2 == 0 { 0.25 } else { 0.75 };
+        }
+    }
+
+    let output = render(params, 4);
+    let levels: Vec<f32> = output
+        .chunks(STEP)
+        .map(|step| rms(&step[STEP / 4..STEP * 3 / 4]))
+        .collect();
+    for pair in levels.windows(2) {
+        let ratio = pair[0].max(pair[1]) / pair[0].min(pair[1]);
+        assert!(
+            (ratio - 3.0).abs() < 0.3,
+            "steps at {levels:?} instead of alternating by a factor of three"
+        );
+    }
+    assert!(levels[1] > levels[0], "steps at {levels:?} start high");
+}
+
+#[test]
+fn glide_ramps_into_the_next_step() {
+    let params = params(1.0, false);
+    {
+        let mut sequence = params.motion_sequence.write().unwrap();
+        sequence.steps[0].value = 0.0;
+        sequence.steps[0].glide = true;
+        sequence.steps[1].value = 1.0;
+    }
+
+    let output = render(params, 1);
+    let quarters: Vec<f32> = output.chunks(STEP / 4).map(rms).collect();
+    assert!(
+        quarters
+            .windows(2)
+            .skip(1)
+            .all(|pair| pair[1] > pair[0] * 1.2),
+        "levels {quarters:?} do not rise through the step"
+    );
+}
+
+#[test]
+fn record_writes_the_parameters_own_value() {
+    let params = params(1.5, true);
+    let sequence = params.motion_sequence.clone();
+
+    render(params, 3);
+    let sequence = sequence.read().unwrap();
+    for (idx, step) in sequence.steps.iter().enumerate() {
+        let expected = if idx < 3 { 0.75 } else { 0.5 };
+        assert!(
+            (step.value - expected).abs() < 1e-6,
+            "step {idx} holds {} instead of {expected}",
+            step.value
+        );
+    }
+}
@@ -0,0 +1,61 @@
+//! The dry path must stay bit-exact: with the mix at zero, or with the plugin bypassed, the
+//! output is the input delayed by exactly the reported latency.
+
+mod common;
+
+use common::float_param;
+use nih_plug::prelude::*;
+use whirlpool::{InputPad, WhirlpoolParams};
+
+/// Odd sizes make sure nothing depends on blocks lining up with the hop size.
+const BLOCK_SIZES: &[usize] = &[1, 64, 441, 512, 4096];
+
+fn assert_nulls(params: impl Fn() -> WhirlpoolParams) {
+    let input = common::read_wav(&common::fixture_path("fixtures/input.wav"));
+
+    for &block_size in BLOCK_SIZES {
+        let (mut plugin, latency) = common::plugin_with_latency(params());
+        assert!(latency > 0, "the spectral path always adds latency");
+        let output = common::render(&mut plugin, &input, block_size);
+
+        for (ch, (output, input)) in output.iter().zip(&input).enumerate() {
+            assert!(
+                output[..latency].iter().all(|&x| x == 0.0),
+                "channel {ch}: output before the latency is not silent (block size {block_size})"
+            );
+            for (idx, (out, dry)) in output[latency..].iter().zip(input).enumerate() {
+                assert_eq!(
+                    out.to_bits(),
+                    dry.to_bits(),
+                    "channel {ch}: sample {idx} differs from the delayed input \
+                     (block size {block_size})"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn zero_mix_nulls_against_input() {
+    assert_nulls(|| WhirlpoolParams {
+        mix: float_param("Dry/Wet", 0.0, 0.0, 1.0),
+        ..WhirlpoolParams::default()
+    });
+}
+
+#[test]
+fn bypass_nulls_against_input() {
+    // Settings that would change the sound as much as possible if any of it leaked through
+    assert_nulls(|| WhirlpoolParams {
+        bypass: BoolParam::new("Bypass", true).make_bypass(),
+        input_pad: EnumParam::new("Input Pad", InputPad::Plus6),
+        harmonics: float_param("Harmonics", 1.0, 0.0, 1.0),
+        shift: float_param("Shift", 0.5, 0.5, 2.0),
+        blur: float_param("Blur", 1.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        out_gain: float_param("Volume", 2.0, 0.0, 2.0),
+        dry_low_cut: float_param("Dry Low Cut", 2000.0, 20.0, 2000.0),
+        dry_tilt: float_param("Dry Tilt", 6.0, -6.0, 6.0),
+        ..WhirlpoolParams::default()
+    });
+}
@@ -0,0 +1,61 @@
+//! Checks that the pitch detector reaches the same low notes at every sample rate, by snapping the
+//! harmony of a low tone onto the scale.
+
+mod common;
+
+use common::{amplitude_at, float_param, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::{Key, Scale, WhirlpoolParams};
+
+/// A2, a period longer than half a frame at 96 kHz and above.
+const TONE: f32 = 110.0;
+/// A ratio of 2.1, landing between A3 and A#3.
+const SHIFT: f32 = 1.1;
+/// Where the harmony lands unsnapped.
+const FREE: f32 = TONE * (1.0 + SHIFT);
+/// A3, the nearest note of C major.
+const SNAPPED: f32 = 220.0;
+
+/// The second half of a second of `TONE` at `sample_rate`, the harmony snapped to `scale`.
+fn harmony(scale: Scale, sample_rate: f32) -> Vec<f32> {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 1.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        shift: float_param("Shift", SHIFT, 0.5, 2.0),
+        scale: EnumParam::new("Scale", scale),
+        key: EnumParam::new("Key", Key::C),
+        ..WhirlpoolParams::default()
+    };
+    let (mut plugin, _) = common::plugin_at(params, sample_rate);
+    let len = sample_rate as usize;
+    let input: Vec<f32> = (0..len)
+        .map(|i| 0.1 * (2.0 * PI * TONE * i as f32 / sample_rate).sin())
+        .collect();
+    let output = common::render(&mut plugin, &[input.clone(), input], BLOCK_SIZE);
+    output[0][len / 2..].to_vec()
+}
+
+#[test]
+fn low_notes_are_detected_at_every_rate() {
+    for sample_rate in [SAMPLE_RATE, 96000.0, 192000.0] {
+        let free = harmony(Scale::Off, sample_rate);
+        let free_level = amplitude_at(&free, FREE, sample_rate);
+        assert!(
+            free_level > 0.01,
+            "the harmony only came through at {free_level} at {sample_rate} Hz"
+        );
+
+        let snapped = harmony(Scale::Major, sample_rate);
+        let on_scale = amplitude_at(&snapped, SNAPPED, sample_rate);
+        let off_scale = amplitude_at(&snapped, FREE, sample_rate);
+        assert!(
+            on_scale > 0.01,
+            "the harmony missed the scale at {sample_rate} Hz, {on_scale} on A3"
+        );
+        assert!(
+            off_scale < on_scale * 0.2,
+            "{off_scale} of the harmony stayed off the scale at {sample_rate} Hz"
+        );
+    }
+}
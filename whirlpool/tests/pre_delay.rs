@@ -0,0 +1,44 @@
+//! Checks that the pre-delay holds back the harmony voice but not the source.
+
+mod common;
+
+use common::{float_param, rms, BLOCK_SIZE, SAMPLE_RATE};
+use std::f32::consts::PI;
+use whirlpool::WhirlpoolParams;
+
+/// The first 200 ms of a note's wet signal.
+fn note_start(harmonics: f32, pre_delay_ms: f32) -> Vec<f32> {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", harmonics, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        pre_delay: float_param("Pre-Delay", pre_delay_ms, 0.0, 500.0),
+        ..WhirlpoolParams::default()
+    };
+    let (mut plugin, latency) = common::plugin_with_latency(params);
+
+    let len = SAMPLE_RATE as usize;
+    let note: Vec<f32> = (0..len)
+        .map(|i| 0.25 * (2.0 * PI * 440.0 * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let output = common::render(&mut plugin, &[note.clone(), note], BLOCK_SIZE);
+    output[0][latency..latency + (0.2 * SAMPLE_RATE) as usize].to_vec()
+}
+
+#[test]
+fn harmony_enters_after_the_pre_delay() {
+    let source = note_start(0.0, 0.0);
+    // The harmony voice is what the harmonics add on top of the source
+    let harmony = |pre_delay_ms| {
+        let output = note_start(1.0, pre_delay_ms);
+        let voice: Vec<f32> = output.iter().zip(&source).map(|(x, s)| x - s).collect();
+        rms(&voice)
+    };
+
+    let direct = harmony(0.0);
+    let delayed = harmony(400.0);
+    assert!(direct > 0.01, "no harmony without pre-delay: {direct}");
+    assert!(
+        delayed < direct * 0.01,
+        "the pre-delayed harmony is already at {delayed} of {direct}"
+    );
+}
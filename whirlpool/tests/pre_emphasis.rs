@@ -0,0 +1,172 @@
+//! Checks that the pre-emphasis steers the choice of peaks towards the upper partials of a dark
+//! tone and darkens the harmony voice shifted up from them, while unshifted audio and a learned
+//! gate profile are left as they are.
+
+mod common;
+
+use common::{amplitude, float_param, noise, rms, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use whirlpool::{HarmonyBins, WhirlpoolParams};
+
+const BIN_HZ: f32 = SAMPLE_RATE / 1024.0;
+/// A loud low partial and a quieter high one, both on bins so their octaves are too.
+const LOW: f32 = 10.0 * BIN_HZ;
+const HIGH: f32 = 100.0 * BIN_HZ;
+
+/// One second of a dark tone, the high partial at less than half the low one's level.
+fn dark_tone() -> Vec<f32> {
+    (0..SAMPLE_RATE as usize)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE;
+            0.05 * (2.0 * PI * LOW * t).sin() + 0.02 * (2.0 * PI * HIGH * t).sin()
+        })
+        .collect()
+}
+
+/// Fully wet output of the dark tone passed through unshifted, with or without the emphasis.
+fn unshifted(pre_emphasis: bool) -> Vec<f32> {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        pre_emphasis: BoolParam::new("Pre-Emphasis", pre_emphasis),
+        ..WhirlpoolParams::default()
+    };
+    let input = dark_tone();
+    let len = input.len();
+    let mut plugin = common::plugin(params);
+    let output = common::render(&mut plugin, &[input.clone(), input], BLOCK_SIZE);
+    output[0][len / 2..].to_vec()
+}
+
+/// The high partial and the octave above it when every bin is shifted an octave up.
+fn shifted(pre_emphasis: bool) -> [f32; 2] {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 1.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        pre_emphasis: BoolParam::new("Pre-Emphasis", pre_emphasis),
+        ..WhirlpoolParams::default()
+    };
+    let input = dark_tone();
+    let len = input.len();
+    let mut plugin = common::plugin(params);
+    let output = common::render(&mut plugin, &[input.clone(), input], BLOCK_SIZE);
+    let output = &output[0][len / 2..][..20 * 1024];
+    [amplitude(output, HIGH), amplitude(output, 2.0 * HIGH)]
+}
+
+/// The octaves above the low and the high partial when only the strongest peak is shifted.
+fn octaves(pre_emphasis: bool) -> [f32; 2] {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 1.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        harmony_bins: EnumParam::new("Harmony Bins", HarmonyBins::Peaks),
+        peak_count: IntParam::new("Peak Count", 1, IntRange::Linear { min: 1, max: 32 }),
+        pre_emphasis: BoolParam::new("Pre-Emphasis", pre_emphasis),
+        ..WhirlpoolParams::default()
+    };
+    let input = dark_tone();
+    let len = input.len();
+    let mut plugin = common::plugin(params);
+    let output = common::render(&mut plugin, &[input.clone(), input], BLOCK_SIZE);
+    let output = &output[0][len / 2..][..20 * 1024];
+    [amplitude(output, 2.0 * LOW), amplitude(output, 2.0 * HIGH)]
+}
+
+/// Level of the low partial through the spectral gate, with a noise profile learned with the
+/// emphasis at `learned_with` and the tone played with it at `played_with`.
+fn gated(learned_with: bool, played_with: bool) -> f32 {
+    let learning = WhirlpoolParams {
+        gate: BoolParam::new("Spectral Gate", true),
+        gate_learn: Arc::new(AtomicBool::new(true)),
+        pre_emphasis: BoolParam::new("Pre-Emphasis", learned_with),
+        ..WhirlpoolParams::default()
+    };
+    let profile = learning.gate_profile.clone();
+    let len = 4 * SAMPLE_RATE as usize;
+    common::render(
+        &mut common::plugin(learning),
+        &[noise(1, 0.01, len), noise(2, 0.01, len)],
+        BLOCK_SIZE,
+    );
+    assert!(
+        profile.read().unwrap().bins > 0,
+        "no noise profile was saved"
+    );
+
+    let playing = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        gate: BoolParam::new("Spectral Gate", true),
+        pre_emphasis: BoolParam::new("Pre-Emphasis", played_with),
+        ..WhirlpoolParams::default()
+    };
+    *playing.gate_profile.write().unwrap() = profile.read().unwrap().clone();
+    let len = SAMPLE_RATE as usize;
+    let input: Vec<f32> = noise(3, 0.01, len)
+        .iter()
+        .enumerate()
+        .map(|(i, noise)| noise + 0.05 * (2.0 * PI * LOW * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let mut plugin = common::plugin(playing);
+    let output = common::render(&mut plugin, &[input.clone(), input], BLOCK_SIZE);
+    amplitude(&output[0][len / 2..][..20 * 1024], LOW)
+}
+
+#[test]
+fn audio_is_left_as_is() {
+    let plain = unshifted(false);
+    let emphasized = unshifted(true);
+    let difference: Vec<f32> = plain.iter().zip(&emphasized).map(|(a, b)| a - b).collect();
+    let level = rms(&plain);
+    let error = rms(&difference);
+    assert!(
+        error < level * 0.01,
+        "{error} of difference on a level of {level}"
+    );
+}
+
+#[test]
+fn emphasis_picks_the_upper_partial_of_a_dark_tone() {
+    let [low, high] = octaves(false);
+    assert!(low > 0.01, "the loudest peak only came through at {low}");
+    assert!(high < 1e-4, "{high} of the quieter peak came through");
+
+    let [low, high] = octaves(true);
+    assert!(
+        high > 0.005,
+        "the emphasized peak only came through at {high}"
+    );
+    assert!(low < 1e-4, "{low} of the low peak came through");
+}
+
+#[test]
+fn harmony_keeps_the_tilt_of_its_source() {
+    let [source, octave] = shifted(false);
+    let [emphasized_source, emphasized_octave] = shifted(true);
+    assert!(
+        (emphasized_source - source).abs() < source * 0.02,
+        "the source came through at {emphasized_source} against {source} without"
+    );
+    // The emphasis weighs the partial at about half of what it weighs the octave
+    let tilt = emphasized_octave / octave;
+    assert!(
+        (tilt - 0.5).abs() < 0.05,
+        "the octave came through at {tilt} of its level without"
+    );
+}
+
+#[test]
+fn learned_gate_profile_holds_across_the_switch() {
+    for learned_with in [false, true] {
+        let kept = gated(learned_with, learned_with);
+        let switched = gated(learned_with, !learned_with);
+        assert!(kept > 0.025, "the gate left only {kept} of the tone");
+        assert!(
+            (switched - kept).abs() < kept * 0.05,
+            "{switched} of the tone after switching against {kept} without"
+        );
+    }
+}
@@ -0,0 +1,56 @@
+//! Checks that a transport jump keeps a live freeze playing, where a full reset drops it.
+
+mod common;
+
+use common::{amplitude, float_param, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::{FreezeTrigger, Whirlpool, WhirlpoolParams};
+
+const BIN_HZ: f32 = SAMPLE_RATE / 1024.0;
+const TONE: f32 = 40.0 * BIN_HZ;
+
+/// Level of the frozen tone in silence after `jump` ran on a plugin that captured the tone with
+/// a MIDI note.
+fn frozen_after(jump: fn(&mut Whirlpool)) -> f32 {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        freeze: BoolParam::new("Freeze", true),
+        freeze_trigger: EnumParam::new("Freeze Trigger", FreezeTrigger::MidiNote),
+        ..WhirlpoolParams::default()
+    };
+    let mut plugin = common::plugin(params);
+
+    let len = SAMPLE_RATE as usize;
+    let tone: Vec<f32> = (0..len)
+        .map(|i| 0.1 * (2.0 * PI * TONE * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let capture = NoteEvent::NoteOn {
+        timing: len as u32 / 2,
+        voice_id: None,
+        channel: 0,
+        note: 60,
+        velocity: 1.0,
+    };
+    common::render_notes(&mut plugin, &[tone.clone(), tone], BLOCK_SIZE, &[capture]);
+
+    jump(&mut plugin);
+    let output = common::render(&mut plugin, &[vec![0.0; len], vec![0.0; len]], BLOCK_SIZE);
+    amplitude(&output[0][len / 2..][..20 * 1024], TONE)
+}
+
+#[test]
+fn relocating_keeps_the_freeze() {
+    let relocated = frozen_after(Whirlpool::relocate);
+    assert!(
+        relocated > 0.02,
+        "only {relocated} of the frozen tone was left after the jump"
+    );
+}
+
+#[test]
+fn resetting_drops_the_freeze() {
+    let reset = frozen_after(|plugin| plugin.reset());
+    assert!(reset < 1e-4, "{reset} of the frozen tone survived a reset");
+}
@@ -0,0 +1,46 @@
+//! Checks that rotation moves a partial by whole bins and keeps it coherent across frames.
+
+mod common;
+
+use common::{amplitude, float_param, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::WhirlpoolParams;
+
+const BIN_HZ: f32 = SAMPLE_RATE / 1024.0;
+
+#[test]
+fn rotation_moves_a_partial_by_whole_bins() {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        // Six bins turn the phase by one and a half cycles per hop, which only adds up across
+        // overlapping frames when the rotation compensates for it
+        rotate: IntParam::new(
+            "Rotate",
+            6,
+            IntRange::Linear {
+                min: -512,
+                max: 512,
+            },
+        ),
+        ..WhirlpoolParams::default()
+    };
+
+    let len = SAMPLE_RATE as usize;
+    let source = 40.0 * BIN_HZ;
+    let input: Vec<f32> = (0..len)
+        .map(|i| 0.25 * (2.0 * PI * source * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let output = common::render(
+        &mut common::plugin(params),
+        &[input.clone(), input],
+        BLOCK_SIZE,
+    );
+
+    let tail = &output[0][len / 2..];
+    let moved = amplitude(tail, 46.0 * BIN_HZ);
+    let left = amplitude(tail, source);
+    assert!(moved > 0.2, "only {moved} arrived six bins up");
+    assert!(left < 0.01, "{left} stayed at the source frequency");
+}
@@ -0,0 +1,87 @@
+//! Checks that a saved freeze comes back with the plugin state instead of starting silent.
+
+mod common;
+
+use common::{amplitude_at, float_param, rms, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::{Whirlpool, WhirlpoolParams};
+
+const BIN_HZ: f32 = SAMPLE_RATE / 1024.0;
+
+fn params(save_freeze: bool) -> WhirlpoolParams {
+    WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        freeze: BoolParam::new("Freeze", true),
+        save_freeze: BoolParam::new("Save Freeze", save_freeze),
+        ..WhirlpoolParams::default()
+    }
+}
+
+/// Freezes a tone at `freq`, then reopens the saved state at `sample_rate` in a new instance.
+fn reopened_plugin(save_freeze: bool, freq: f32, sample_rate: f32) -> Whirlpool {
+    let len = SAMPLE_RATE as usize;
+    let tone: Vec<f32> = (0..len)
+        .map(|i| 0.25 * (2.0 * PI * freq * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    // A freeze on from the start captures the silence before the input, so let it follow the
+    // tone the whole way
+    let first = WhirlpoolParams {
+        freeze_amount: float_param("Freeze Amount", 0.0, 0.0, 1.0),
+        ..params(save_freeze)
+    };
+    let snapshot = first.freeze_snapshot.clone();
+    common::render(
+        &mut common::plugin(first),
+        &[tone.clone(), tone],
+        BLOCK_SIZE,
+    );
+
+    let reopened = params(save_freeze);
+    *reopened.freeze_snapshot.write().unwrap() = snapshot.read().unwrap().clone();
+    common::plugin_at(reopened, sample_rate).0
+}
+
+/// Like [`reopened_plugin()`], fed with silence and returning a stretch of what it plays.
+fn reopened(save_freeze: bool, freq: f32, sample_rate: f32) -> Vec<f32> {
+    let mut plugin = reopened_plugin(save_freeze, freq, sample_rate);
+    let len = sample_rate as usize;
+    let output = common::render(&mut plugin, &[vec![0.0; len], vec![0.0; len]], BLOCK_SIZE);
+    output[0][len / 2..][..20 * 1024].to_vec()
+}
+
+#[test]
+fn saved_freeze_plays_after_reopening() {
+    let saved = rms(&reopened(true, 440.0, SAMPLE_RATE));
+    let unsaved = rms(&reopened(false, 440.0, SAMPLE_RATE));
+    assert!(saved > 0.01, "only {saved} came back from the saved freeze");
+    assert!(unsaved < 1e-6, "{unsaved} played without saving the freeze");
+}
+
+#[test]
+fn saved_freeze_keeps_its_pitch_at_another_rate() {
+    let tone = 100.0 * BIN_HZ;
+    let sample_rate = 2.0 * SAMPLE_RATE;
+    let output = reopened(true, tone, sample_rate);
+    let kept = amplitude_at(&output, tone, sample_rate);
+    let moved = amplitude_at(&output, 2.0 * tone, sample_rate);
+    assert!(kept > 0.05, "only {kept} came back at the frozen pitch");
+    assert!(
+        moved < kept * 0.1,
+        "{moved} moved up an octave against {kept}"
+    );
+}
+
+#[test]
+fn saved_freeze_survives_a_reset() {
+    // Hosts reset the plugin when the transport loops or seeks, the silence playing afterwards
+    // must not be captured over the saved drone
+    let mut plugin = reopened_plugin(true, 440.0, SAMPLE_RATE);
+    let len = SAMPLE_RATE as usize;
+    let silence = [vec![0.0; len / 2], vec![0.0; len / 2]];
+    common::render(&mut plugin, &silence, BLOCK_SIZE);
+    plugin.reset();
+    let output = common::render(&mut plugin, &silence, BLOCK_SIZE);
+    let level = rms(&output[0][len / 4..]);
+    assert!(level > 0.01, "only {level} was left of the saved freeze");
+}
@@ -0,0 +1,62 @@
+//! Checks that the shimmer layers its intervals over a frozen tone, and nothing without them.
+
+mod common;
+
+use common::{amplitude, float_param, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::{ShimmerInterval, WhirlpoolParams};
+
+const BIN_HZ: f32 = SAMPLE_RATE / 1024.0;
+const TONE: f32 = 40.0 * BIN_HZ;
+
+/// Amplitudes of the octave and the fifth above a frozen tone. The freeze follows the input
+/// quickly, as it starts out capturing the silence before the tone.
+fn layers(interval: ShimmerInterval) -> (f32, f32) {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        freeze: BoolParam::new("Freeze", true),
+        freeze_amount: float_param("Freeze Amount", 0.2, 0.0, 1.0),
+        shimmer: EnumParam::new("Shimmer", interval),
+        shimmer_level: float_param("Shimmer Level", 0.5, 0.0, 1.0),
+        ..WhirlpoolParams::default()
+    };
+
+    let len = 2 * SAMPLE_RATE as usize;
+    let input: Vec<f32> = (0..len)
+        .map(|i| 0.1 * (2.0 * PI * TONE * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let output = common::render(
+        &mut common::plugin(params),
+        &[input.clone(), input],
+        BLOCK_SIZE,
+    );
+
+    let tail = &output[0][len - 20 * 1024..];
+    (amplitude(tail, TONE * 2.0), amplitude(tail, TONE * 1.5))
+}
+
+#[test]
+fn off_adds_nothing() {
+    let (octave, fifth) = layers(ShimmerInterval::Off);
+    assert!(octave < 0.002, "octave at {octave}");
+    assert!(fifth < 0.002, "fifth at {fifth}");
+}
+
+#[test]
+fn intervals_layer_their_copies() {
+    let (octave, fifth) = layers(ShimmerInterval::Octave);
+    assert!(octave > 0.03, "octave at {octave}");
+    assert!(fifth < octave * 0.2, "fifth at {fifth} against {octave}");
+
+    let (octave, fifth) = layers(ShimmerInterval::Fifth);
+    assert!(fifth > 0.03, "fifth at {fifth}");
+    assert!(octave < fifth * 0.2, "octave at {octave} against {fifth}");
+
+    let (octave, fifth) = layers(ShimmerInterval::Both);
+    assert!(
+        octave > 0.03 && fifth > 0.03,
+        "octave at {octave}, fifth at {fifth}"
+    );
+}
@@ -0,0 +1,44 @@
+//! Checks that the sidechain listen mode outputs the mono sidechain the detectors see.
+
+mod common;
+
+use common::BLOCK_SIZE;
+use nih_plug::prelude::*;
+use whirlpool::WhirlpoolParams;
+
+#[test]
+fn listen_outputs_the_mono_sidechain() {
+    let input = common::read_wav(&common::fixture_path("fixtures/input.wav"));
+    let mut plugin = common::plugin(WhirlpoolParams {
+        sidechain_listen: BoolParam::new("Sidechain Listen", true),
+        ..WhirlpoolParams::default()
+    });
+
+    // The fixture's channels reversed as the sidechain, so the mono sum is easy to predict
+    let mut sidechain: Vec<Vec<f32>> = input.iter().rev().cloned().collect();
+    let mut output = input.clone();
+    let num_samples = output[0].len();
+    let mut start = 0;
+    while start < num_samples {
+        let end = (start + BLOCK_SIZE).min(num_samples);
+        let mut main: Vec<&mut [f32]> = output.iter_mut().map(|ch| &mut ch[start..end]).collect();
+        let sidechain: Vec<&mut [f32]> =
+            sidechain.iter_mut().map(|ch| &mut ch[start..end]).collect();
+        plugin.render(&mut main, Some(&sidechain));
+        start = end;
+    }
+
+    for channel in &output {
+        for (idx, (out, (left, right))) in channel
+            .iter()
+            .zip(input[0].iter().zip(&input[1]))
+            .enumerate()
+        {
+            let expected = (left + right) / 2.0;
+            assert!(
+                (out - expected).abs() < 1e-6,
+                "sample {idx}: {out} is not the sidechain's {expected}"
+            );
+        }
+    }
+}
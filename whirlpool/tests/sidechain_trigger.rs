@@ -0,0 +1,69 @@
+//! Checks that hits on the sidechain spawn grains, and that the trigger hold skips hits that
+//! follow too closely.
+
+mod common;
+
+use common::{float_param, rms, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::WhirlpoolParams;
+
+/// Level of a sine with the grains fed back once, the sidechain clicking every `spacing`
+/// samples, or never for `None`.
+fn level(spacing: Option<usize>, hold_ms: f32) -> f32 {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        grain_feedback: BoolParam::new("Grain Feedback", true),
+        sidechain_grains: BoolParam::new("Sidechain Grains", true),
+        trigger_hold: float_param("Trigger Hold", hold_ms, 10.0, 1000.0),
+        ..WhirlpoolParams::default()
+    };
+
+    let len = SAMPLE_RATE as usize * 2;
+    let mut output: Vec<Vec<f32>> = vec![
+        (0..len)
+            .map(|i| 0.05 * (2.0 * PI * 440.0 * i as f32 / SAMPLE_RATE).sin())
+            .collect();
+        2
+    ];
+    let mut sidechain = vec![vec![0.0; len]; 2];
+    if let Some(spacing) = spacing {
+        for channel in sidechain.iter_mut() {
+            for click in channel.iter_mut().step_by(spacing).skip(1) {
+                *click = 0.5;
+            }
+        }
+    }
+
+    let mut plugin = common::plugin(params);
+    let mut start = 0;
+    while start < len {
+        let end = (start + BLOCK_SIZE).min(len);
+        let mut main: Vec<&mut [f32]> = output.iter_mut().map(|ch| &mut ch[start..end]).collect();
+        let sidechain: Vec<&mut [f32]> =
+            sidechain.iter_mut().map(|ch| &mut ch[start..end]).collect();
+        plugin.render(&mut main, Some(&sidechain));
+        start = end;
+    }
+    rms(&output[0][len / 2..])
+}
+
+#[test]
+fn hits_spawn_grains() {
+    let silent = level(None, 50.0);
+    let hits = level(Some(4096), 50.0);
+    assert!(
+        hits > silent + 1e-3,
+        "hits played at {hits} against {silent} without"
+    );
+}
+
+#[test]
+fn hold_skips_close_hits() {
+    let silent = level(None, 10.0);
+    let every = level(Some(2400), 10.0) - silent;
+    let held = level(Some(2400), 500.0) - silent;
+    assert!(every > 1e-3, "hits only added {every}");
+    assert!(held < every * 0.6, "held hits added {held} against {every}");
+}
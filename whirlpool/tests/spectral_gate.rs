@@ -0,0 +1,70 @@
+//! Checks that the spectral gate learns a noise floor and then pulls that noise down.
+
+mod common;
+
+use common::{float_param, noise, rms, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use whirlpool::WhirlpoolParams;
+
+/// Longer than the learning time, with a second of gated noise at the end.
+const NOISE_SECONDS: f32 = 4.0;
+
+#[test]
+fn learned_noise_is_gated() {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        gate: BoolParam::new("Spectral Gate", true),
+        gate_learn: Arc::new(AtomicBool::new(true)),
+        ..WhirlpoolParams::default()
+    };
+    let profile = params.gate_profile.clone();
+
+    let len = (NOISE_SECONDS * SAMPLE_RATE) as usize;
+    let input = vec![noise(1, 0.1, len), noise(2, 0.1, len)];
+    let output = common::render(&mut common::plugin(params), &input, BLOCK_SIZE);
+
+    assert!(
+        profile.read().unwrap().bins > 0,
+        "no noise profile was saved"
+    );
+    let tail = len - SAMPLE_RATE as usize;
+    for (output, input) in output.iter().zip(&input) {
+        let reduction = rms(&output[tail..]) / rms(&input[tail..]);
+        assert!(reduction < 0.25, "the noise is only reduced to {reduction}");
+    }
+}
+
+/// Restores a profile learned at `SAMPLE_RATE` into a plugin running at `sample_rate` and
+/// checks bin `i` ends up with the floor `expected(i)`.
+fn assert_resampled(sample_rate: f32, expected: impl Fn(usize) -> f32) {
+    let bins = 512;
+    let params = WhirlpoolParams::default();
+    {
+        // A ramp over the bins, so every floor's value tells the bin it was learned at
+        let mut profile = params.gate_profile.write().unwrap();
+        profile.bins = bins;
+        profile.floors = (0..bins).map(|i| i as f32).collect();
+        profile.sample_rate = SAMPLE_RATE;
+    }
+    let profile = params.gate_profile.clone();
+    common::plugin_at(params, sample_rate);
+
+    let profile = profile.read().unwrap();
+    assert_eq!(profile.sample_rate, sample_rate);
+    for (i, floor) in profile.floor(0).unwrap().iter().enumerate() {
+        assert!(
+            (floor - expected(i)).abs() < 1e-3,
+            "bin {i} at {sample_rate} Hz holds the floor {floor} instead of {}",
+            expected(i)
+        );
+    }
+}
+
+#[test]
+fn restored_profile_follows_the_sample_rate() {
+    assert_resampled(2.0 * SAMPLE_RATE, |i| (2 * i).min(511) as f32);
+    assert_resampled(SAMPLE_RATE / 2.0, |i| i as f32 / 2.0);
+}
@@ -0,0 +1,58 @@
+//! Checks that a slow scan keeps the grains reading a tone after the input has moved on, where
+//! real time scanning lets the tone die away with the feedback.
+
+mod common;
+
+use common::{amplitude, float_param, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::WhirlpoolParams;
+
+const BIN_HZ: f32 = SAMPLE_RATE / 1024.0;
+const TONE: f32 = 40.0 * BIN_HZ;
+
+/// Level of the partials within four bins of `freq`, which the grains' random phases spread
+/// out.
+fn band(samples: &[f32], freq: f32) -> f32 {
+    (-8..=8)
+        .map(|k| amplitude(samples, freq + k as f32 * BIN_HZ / 2.0).powi(2))
+        .sum::<f32>()
+        .sqrt()
+}
+
+/// Level of the tone half a second after a second of it gave way to silence, with the grains
+/// fed back and scanning at `stretch`.
+fn tail(stretch: f32) -> f32 {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        grain_feedback: BoolParam::new("Grain Feedback", true),
+        delay_feedback: float_param("Delay Feedback", 0.3, 0.0, 0.95),
+        stretch: float_param("Stretch", stretch, 0.25, 4.0),
+        ..WhirlpoolParams::default()
+    };
+
+    let len = 2 * SAMPLE_RATE as usize;
+    let input: Vec<f32> = (0..len)
+        .map(|i| {
+            if i < len / 2 {
+                0.1 * (2.0 * PI * TONE * i as f32 / SAMPLE_RATE).sin()
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    let mut plugin = common::plugin(params);
+    let output = common::render(&mut plugin, &[input.clone(), input], BLOCK_SIZE);
+    band(&output[0][len / 2 + len / 4..][..16 * 1024], TONE)
+}
+
+#[test]
+fn slow_scan_keeps_reading_the_past() {
+    let stretched = tail(0.25);
+    let real_time = tail(1.0);
+    assert!(
+        stretched > real_time * 4.0,
+        "{stretched} at a quarter speed against {real_time} in real time"
+    );
+}
@@ -0,0 +1,106 @@
+//! Checks the quad and 5.1 layouts: the front pair plays what stereo would, the rear pair the
+//! grains placed towards the back and the center and LFE nothing.
+
+mod common;
+
+use common::{float_param, rms, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::{Scale, WhirlpoolParams};
+
+const STEREO: usize = 0;
+const QUAD: usize = 1;
+const FIVE_ONE: usize = 2;
+
+/// Output of the `layout`th layout with the grains fed back and placed up to `spread` towards
+/// the back.
+fn play(layout: usize, spread: f32) -> Vec<Vec<f32>> {
+    let params = WhirlpoolParams {
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        grain_feedback: BoolParam::new("Grain Feedback", true),
+        surround_spread: float_param("Surround Spread", spread, 0.0, 1.0),
+        ..WhirlpoolParams::default()
+    };
+    play_sine(params, layout, 0.05)
+}
+
+/// Output of a sine of `amplitude` in the stereo input of the `layout`th layout. The channels
+/// that only exist on the output side start out silent, as a host leaves them.
+fn play_sine(params: WhirlpoolParams, layout: usize, amplitude: f32) -> Vec<Vec<f32>> {
+    let outputs = <whirlpool::Whirlpool as Plugin>::AUDIO_IO_LAYOUTS[layout]
+        .main_output_channels
+        .map_or(0, |channels| channels.get() as usize);
+
+    let len = SAMPLE_RATE as usize;
+    let input: Vec<f32> = (0..len)
+        .map(|i| amplitude * (2.0 * PI * 440.0 * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let mut channels = vec![vec![0.0; len]; outputs];
+    channels[0].clone_from(&input);
+    channels[1] = input;
+    let (mut plugin, _) = common::plugin_with_layout(params, SAMPLE_RATE, layout);
+    common::render(&mut plugin, &channels, BLOCK_SIZE)
+}
+
+/// Largest difference between the front pair of `output` and `stereo`.
+fn front_difference(stereo: &[Vec<f32>], output: &[Vec<f32>]) -> f32 {
+    stereo
+        .iter()
+        .zip(output)
+        .flat_map(|(stereo, output)| stereo.iter().zip(output).map(|(a, b)| (a - b).abs()))
+        .fold(0.0, f32::max)
+}
+
+#[test]
+fn front_pair_matches_stereo() {
+    let stereo = play(STEREO, 1.0);
+    let diff = front_difference(&stereo, &play(QUAD, 0.0));
+    assert!(diff < 1e-6, "the front pair differs by {diff}");
+}
+
+#[test]
+fn spread_moves_grains_to_the_rear() {
+    let front = play(QUAD, 0.0);
+    let spread = play(QUAD, 1.0);
+    for rear in 2..4 {
+        assert!(
+            rms(&front[rear]) < 1e-6,
+            "rear channel {rear} plays without spread"
+        );
+        let level = rms(&spread[rear][SAMPLE_RATE as usize / 2..]);
+        assert!(level > 1e-3, "rear channel {rear} only reached {level}");
+    }
+}
+
+#[test]
+fn five_one_leaves_center_and_lfe_silent() {
+    let output = play(FIVE_ONE, 1.0);
+    for (channel, samples) in output.iter().enumerate().take(4).skip(2) {
+        let level = rms(samples);
+        assert!(level == 0.0, "channel {channel} carries {level}");
+    }
+    for (rear, samples) in output.iter().enumerate().skip(4) {
+        let level = rms(&samples[SAMPLE_RATE as usize / 2..]);
+        assert!(level > 1e-3, "surround channel {rear} only reached {level}");
+    }
+}
+
+#[test]
+fn analysis_hears_the_same_input_in_every_layout() {
+    // Just loud enough for the pitch detector and for the bloom to count the input as playing,
+    // averaged with the silent output-only channels it would be neither
+    let params = || WhirlpoolParams {
+        scale: EnumParam::new("Scale", Scale::Major),
+        bloom_release: float_param("Bloom Release", 100.0, 0.0, 5000.0),
+        ..WhirlpoolParams::default()
+    };
+    let stereo = play_sine(params(), STEREO, 0.002);
+    assert!(
+        rms(&stereo[0][SAMPLE_RATE as usize / 2..]) > 1e-4,
+        "the stereo output faded out"
+    );
+    for layout in [QUAD, FIVE_ONE] {
+        let diff = front_difference(&stereo, &play_sine(params(), layout, 0.002));
+        assert!(diff < 1e-6, "layout {layout} differs from stereo by {diff}");
+    }
+}
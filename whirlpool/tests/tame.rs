@@ -0,0 +1,48 @@
+//! Checks that taming holds a ringing bin back towards the rest of the frame.
+
+mod common;
+
+use common::{amplitude, float_param, BLOCK_SIZE, SAMPLE_RATE};
+use std::f32::consts::PI;
+use whirlpool::WhirlpoolParams;
+
+const BIN_HZ: f32 = SAMPLE_RATE / 1024.0;
+
+/// Wet levels of a loud tone and of the quiet noise bed around it.
+fn levels(tame: f32) -> (f32, f32) {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 0.0, 0.0, 1.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        tame: float_param("Tame", tame, 0.0, 1.0),
+        ..WhirlpoolParams::default()
+    };
+
+    let tone = 100.0 * BIN_HZ;
+    let mut seed = 1u32;
+    let input: Vec<f32> = (0..SAMPLE_RATE as usize)
+        .map(|i| {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let noise = (seed >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0;
+            0.05 * (2.0 * PI * tone * i as f32 / SAMPLE_RATE).sin() + 0.001 * noise
+        })
+        .collect();
+    let output = common::render(
+        &mut common::plugin(params),
+        &[input.clone(), input],
+        BLOCK_SIZE,
+    );
+
+    let window = &output[0][output[0].len() / 2..][..20 * 1024];
+    let rms = common::rms(window);
+    let tone_level = amplitude(window, tone);
+    (tone_level, rms)
+}
+
+#[test]
+fn tame_holds_back_a_ringing_bin() {
+    let (open, _) = levels(0.0);
+    let (tamed, rms) = levels(1.0);
+    assert!(open > 0.04, "the tone only came through at {open}");
+    assert!(tamed < open * 0.5, "the tone kept {tamed} of {open}");
+    assert!(rms > 1e-4, "the noise bed went quiet at {rms}");
+}
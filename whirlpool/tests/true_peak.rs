@@ -0,0 +1,51 @@
+//! Checks that the true peak limiter holds its ceiling and reports its lookahead as latency.
+
+mod common;
+
+use common::{float_param, BLOCK_SIZE};
+use nih_plug::prelude::*;
+use whirlpool::WhirlpoolParams;
+
+const CEILING_DB: f32 = -1.0;
+
+fn params(tp_limit: bool) -> WhirlpoolParams {
+    // Dry only and boosted, so the output goes well over full scale
+    WhirlpoolParams {
+        mix: float_param("Dry/Wet", 0.0, 0.0, 1.0),
+        out_gain: float_param("Volume", 2.0, 0.0, 2.0),
+        tp_limit: BoolParam::new("True Peak Limit", tp_limit),
+        tp_ceiling: float_param("TP Ceiling", CEILING_DB, -12.0, 0.0),
+        ..WhirlpoolParams::default()
+    }
+}
+
+#[test]
+fn limiter_holds_the_ceiling() {
+    let input: Vec<Vec<f32>> = common::read_wav(&common::fixture_path("fixtures/input.wav"))
+        .iter()
+        .map(|channel| channel.iter().map(|x| x * 4.0).collect())
+        .collect();
+
+    let unlimited = common::render(&mut common::plugin(params(false)), &input, BLOCK_SIZE);
+    let peak = |output: &[Vec<f32>]| {
+        output
+            .iter()
+            .flatten()
+            .fold(0.0f32, |peak, x| peak.max(x.abs()))
+    };
+    assert!(
+        peak(&unlimited) > 1.0,
+        "the input does not exceed the ceiling"
+    );
+
+    let limited = common::render(&mut common::plugin(params(true)), &input, BLOCK_SIZE);
+    assert!(peak(&limited) <= util::db_to_gain(CEILING_DB) * 1.001);
+    assert!(peak(&limited) > util::db_to_gain(CEILING_DB - 3.0));
+}
+
+#[test]
+fn limiter_adds_latency() {
+    let (_, latency) = common::plugin_with_latency(params(false));
+    let (_, limited_latency) = common::plugin_with_latency(params(true));
+    assert!(limited_latency > latency);
+}
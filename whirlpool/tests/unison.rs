@@ -0,0 +1,55 @@
+//! Checks that the unison copies are detuned around the harmony voice and spread across the
+//! stereo field.
+
+mod common;
+
+use common::{amplitude, float_param, BLOCK_SIZE, SAMPLE_RATE};
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use whirlpool::WhirlpoolParams;
+
+const BIN_HZ: f32 = SAMPLE_RATE / 1024.0;
+
+#[test]
+fn wide_unison_puts_the_lower_copy_left_and_the_upper_one_right() {
+    let params = WhirlpoolParams {
+        harmonics: float_param("Harmonics", 1.0, 0.0, 1.0),
+        shift: float_param("Shift", 1.0, 0.5, 2.0),
+        mix: float_param("Dry/Wet", 1.0, 0.0, 1.0),
+        unison: IntParam::new("Unison", 2, IntRange::Linear { min: 1, max: 4 }),
+        unison_width: float_param("Unison Width", 1.0, 0.0, 1.0),
+        ..WhirlpoolParams::default()
+    };
+
+    let len = SAMPLE_RATE as usize;
+    let source = 100.0 * BIN_HZ;
+    let input: Vec<f32> = (0..len)
+        .map(|i| 0.05 * (2.0 * PI * source * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let output = common::render(
+        &mut common::plugin(params),
+        &[input.clone(), input],
+        BLOCK_SIZE,
+    );
+
+    // The octave lands on bin 200, the copies twelve cents either side of it on 199 and 201
+    let tail = len / 2..len / 2 + 20 * 1024;
+    let [left, right] = [&output[0][tail.clone()], &output[1][tail]];
+    let [lower, upper] = [199.0 * BIN_HZ, 201.0 * BIN_HZ];
+    assert!(
+        amplitude(left, lower) > 0.02,
+        "the lower copy is missing on the left"
+    );
+    assert!(
+        amplitude(right, upper) > 0.02,
+        "the upper copy is missing on the right"
+    );
+    assert!(
+        amplitude(left, upper) < 0.002,
+        "the upper copy leaked to the left"
+    );
+    assert!(
+        amplitude(right, lower) < 0.002,
+        "the lower copy leaked to the right"
+    );
+}
@@ -0,0 +1,75 @@
+use anyhow::{bail, Context};
+use std::path::Path;
+use std::process::Command;
+
+use crate::util::{copy_path, package_version, run};
+
+/// An Audio Unit shell built around one of the workspace's CLAP bundles.
+struct AuTarget {
+    package: &'static str,
+    /// Bundle name, must match the package's entry in `bundler.toml`.
+    name: &'static str,
+    bundle_id: &'static str,
+    /// Four-character AU subtype, unique per plugin under the manufacturer code.
+    subtype: &'static str,
+}
+
+const MANUFACTURER_NAME: &str = "Antigravity";
+const MANUFACTURER_CODE: &str = "Antg";
+
+const AU_TARGETS: &[AuTarget] = &[AuTarget {
+    package: "whirlpool",
+    name: "Whirlpool",
+    bundle_id: "com.antigravity.whirlpool.auv2",
+    subtype: "Whpl",
+}];
+
+/// `cargo xtask bundle-au [--release] [nih_plug_xtask bundle flags]`
+///
+/// Bundles the CLAP for every AU target and wraps it in an AUv2 `.component` with clap-wrapper
+/// (see `au/CMakeLists.txt`). The components end up next to the other bundles in
+/// `target/bundled`.
+pub fn bundle_au(args: Vec<String>) -> nih_plug_xtask::Result<()> {
+    if !cfg!(target_os = "macos") {
+        bail!("Audio Units can only be built on macOS");
+    }
+    nih_plug_xtask::chdir_workspace_root()?;
+
+    for target in AU_TARGETS {
+        let bundle_args = ["bundle", target.package]
+            .into_iter()
+            .map(String::from)
+            .chain(args.iter().cloned());
+        nih_plug_xtask::main_with_args("xtask", bundle_args)?;
+        let version = package_version(target.package)?;
+
+        let clap = Path::new("target/bundled")
+            .join(format!("{}.clap", target.name))
+            .canonicalize()
+            .with_context(|| format!("Could not find the CLAP bundle for {}", target.name))?;
+        let build_dir = Path::new("target/au-build").join(target.package);
+
+        run(Command::new("cmake")
+            .args(["-S", "au", "-B"])
+            .arg(&build_dir)
+            .arg("-DCMAKE_BUILD_TYPE=Release")
+            .arg(format!("-DAU_CLAP_PATH={}", clap.display()))
+            .arg(format!("-DAU_OUTPUT_NAME={}", target.name))
+            .arg(format!("-DAU_BUNDLE_ID={}", target.bundle_id))
+            .arg(format!("-DAU_BUNDLE_VERSION={version}"))
+            .arg(format!("-DAU_MANUFACTURER_NAME={MANUFACTURER_NAME}"))
+            .arg(format!("-DAU_MANUFACTURER_CODE={MANUFACTURER_CODE}"))
+            .arg(format!("-DAU_SUBTYPE_CODE={}", target.subtype)))?;
+        run(Command::new("cmake")
+            .arg("--build")
+            .arg(&build_dir)
+            .args(["--config", "Release"]))?;
+
+        let component = format!("{}.component", target.name);
+        let destination = Path::new("target/bundled").join(&component);
+        copy_path(&build_dir.join("out").join(&component), &destination)?;
+        eprintln!("Created an AU bundle at '{}'", destination.display());
+    }
+
+    Ok(())
+}
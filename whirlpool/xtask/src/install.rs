@@ -0,0 +1,89 @@
+use anyhow::{bail, Context};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::util::{copy_path, run};
+use crate::PLUGINS;
+
+/// Plugin formats produced by `nih_plug_xtask` bundling, by file extension.
+const FORMATS: &[&str] = &["clap", "vst3"];
+
+/// `cargo xtask install [--universal] [--no-sign] [nih_plug_xtask bundle flags]`
+///
+/// Bundles every workspace plugin, signs the bundles on macOS and copies them into the current
+/// user's CLAP and VST3 folders. `--universal` builds x86_64 + arm64 binaries on macOS. The
+/// signing identity is read from `CODESIGN_IDENTITY` and defaults to ad-hoc signing.
+pub fn install(args: Vec<String>) -> nih_plug_xtask::Result<()> {
+    let universal = args.iter().any(|arg| arg == "--universal");
+    let sign = cfg!(target_os = "macos") && !args.iter().any(|arg| arg == "--no-sign");
+    let bundle_flags: Vec<String> = args
+        .into_iter()
+        .filter(|arg| arg != "--universal" && arg != "--no-sign")
+        .collect();
+    if universal && !cfg!(target_os = "macos") {
+        bail!("--universal is only supported on macOS");
+    }
+    nih_plug_xtask::chdir_workspace_root()?;
+
+    let command = if universal {
+        "bundle-universal"
+    } else {
+        "bundle"
+    };
+    for (package, _) in PLUGINS {
+        let bundle_args = [command, package]
+            .into_iter()
+            .map(String::from)
+            .chain(bundle_flags.iter().cloned());
+        nih_plug_xtask::main_with_args("xtask", bundle_args)?;
+    }
+
+    for (_, name) in PLUGINS {
+        for format in FORMATS {
+            let bundle = Path::new("target/bundled").join(format!("{name}.{format}"));
+            if !bundle.exists() {
+                bail!("'{}' was not created by the bundler", bundle.display());
+            }
+            if sign {
+                codesign(&bundle)?;
+            }
+
+            let destination = user_plugin_dir(format)?.join(bundle.file_name().unwrap());
+            std::fs::create_dir_all(destination.parent().unwrap())?;
+            copy_path(&bundle, &destination)?;
+            eprintln!("Installed '{}'", destination.display());
+        }
+    }
+
+    Ok(())
+}
+
+fn codesign(bundle: &Path) -> nih_plug_xtask::Result<()> {
+    let identity = env::var("CODESIGN_IDENTITY").unwrap_or_else(|_| String::from("-"));
+    let mut command = Command::new("codesign");
+    command.args(["--force", "--deep", "--options", "runtime"]);
+    // Ad-hoc signatures cannot be timestamped, asking for one fails the signing
+    if identity != "-" {
+        command.arg("--timestamp");
+    }
+    run(command.args(["--sign", &identity]).arg(bundle))
+}
+
+/// The per-user plugin folder for `format`, following each format's platform conventions.
+fn user_plugin_dir(format: &str) -> nih_plug_xtask::Result<PathBuf> {
+    let folder = format.to_uppercase();
+    if cfg!(target_os = "windows") {
+        let local_app_data = env::var("LOCALAPPDATA").context("LOCALAPPDATA is not set")?;
+        Ok(Path::new(&local_app_data)
+            .join("Programs/Common")
+            .join(folder))
+    } else {
+        let home = env::var("HOME").context("HOME is not set")?;
+        if cfg!(target_os = "macos") {
+            Ok(Path::new(&home).join("Library/Audio/Plug-Ins").join(folder))
+        } else {
+            Ok(Path::new(&home).join(format!(".{format}")))
+        }
+    }
+}
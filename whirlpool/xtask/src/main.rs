@@ -0,0 +1,15 @@
+mod au;
+mod install;
+mod util;
+
+/// Every plugin package in the workspace with its bundle name from `bundler.toml`.
+const PLUGINS: &[(&str, &str)] = &[("whirlpool", "Whirlpool")];
+
+fn main() -> nih_plug_xtask::Result<()> {
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        Some("bundle-au") => au::bundle_au(args.collect()),
+        Some("install") => install::install(args.collect()),
+        _ => nih_plug_xtask::main(),
+    }
+}
@@ -0,0 +1,59 @@
+use anyhow::{bail, Context};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+pub fn run(command: &mut Command) -> nih_plug_xtask::Result<()> {
+    let status = command
+        .status()
+        .with_context(|| format!("Could not run {command:?}"))?;
+    if !status.success() {
+        bail!("{command:?} failed with {status}");
+    }
+
+    Ok(())
+}
+
+/// The version of the workspace's `package`, as `cargo metadata` reports it.
+pub fn package_version(package: &str) -> nih_plug_xtask::Result<String> {
+    let output = Command::new(std::env::var("CARGO").unwrap_or_else(|_| String::from("cargo")))
+        .args(["metadata", "--format-version", "1", "--no-deps"])
+        .output()
+        .context("Could not run cargo metadata")?;
+    if !output.status.success() {
+        bail!("cargo metadata failed with {}", output.status);
+    }
+    let metadata: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .context("Could not parse the output of cargo metadata")?;
+    metadata["packages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|candidate| candidate["name"] == package)
+        .and_then(|candidate| candidate["version"].as_str())
+        .map(String::from)
+        .with_context(|| format!("cargo metadata does not list {package}"))
+}
+
+/// Copies a file or a bundle directory, replacing whatever is at `to`.
+pub fn copy_path(from: &Path, to: &Path) -> nih_plug_xtask::Result<()> {
+    if to.is_dir() {
+        fs::remove_dir_all(to)?;
+    } else if to.exists() {
+        fs::remove_file(to)?;
+    }
+
+    if from.is_dir() {
+        fs::create_dir_all(to)?;
+        let entries =
+            fs::read_dir(from).with_context(|| format!("Could not read '{}'", from.display()))?;
+        for entry in entries {
+            let entry = entry?;
+            copy_path(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    } else {
+        fs::copy(from, to).with_context(|| format!("Could not copy '{}'", from.display()))?;
+    }
+
+    Ok(())
+}